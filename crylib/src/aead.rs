@@ -1,3 +1,4 @@
+pub mod ccm;
 pub mod chacha;
 pub mod gcm;
 
@@ -19,9 +20,11 @@ impl core::fmt::Display for BadData {
 
 impl core::error::Error for BadData {}
 
-pub trait Aead {
-    fn encrypt_inline(&self, msg: &mut [u8], add_data: &[u8], iv: &[u8; IV_SIZE])
-        -> [u8; TAG_SIZE];
+/// An authenticated encryption algorithm with associated data.
+///
+/// `T_LEN` is the length of the tag in bytes.
+pub trait Aead<const T_LEN: usize = TAG_SIZE> {
+    fn encrypt_inline(&self, msg: &mut [u8], add_data: &[u8], iv: &[u8; IV_SIZE]) -> [u8; T_LEN];
 
     fn encrypt(
         &self,
@@ -29,8 +32,8 @@ pub trait Aead {
         plain_text: &[u8],
         add_data: &[u8],
         iv: &[u8; IV_SIZE],
-    ) -> [u8; TAG_SIZE] {
-        buf[..plain_text.len()].copy_from_slice(&plain_text);
+    ) -> [u8; T_LEN] {
+        buf[..plain_text.len()].copy_from_slice(plain_text);
         self.encrypt_inline(&mut buf[..plain_text.len()], add_data, iv)
     }
 
@@ -39,7 +42,7 @@ pub trait Aead {
         msg: &mut [u8],
         add_data: &[u8],
         iv: &[u8; IV_SIZE],
-        tag: &[u8; TAG_SIZE],
+        tag: &[u8; T_LEN],
     ) -> Result<(), BadData>;
 
     fn decrypt(
@@ -48,9 +51,9 @@ pub trait Aead {
         cipher_text: &[u8],
         add_data: &[u8],
         iv: &[u8; IV_SIZE],
-        tag: &[u8; TAG_SIZE],
+        tag: &[u8; T_LEN],
     ) -> Result<(), BadData> {
-        buf[..cipher_text.len()].copy_from_slice(&cipher_text);
+        buf[..cipher_text.len()].copy_from_slice(cipher_text);
         self.decrypt_inline(&mut buf[..cipher_text.len()], add_data, iv, tag)
     }
 }
@@ -0,0 +1,356 @@
+//! [`Counter with CBC-MAC`] for AES
+//!
+//! This module implements counter with CBC-MAC (CCM) mode for AES, with a 12-byte nonce.
+//! The tag length is chosen with the `T_LEN` parameter of [`Ccm`], which defaults to 16 bytes.
+//!
+//! # Examples
+//!
+//! ```
+//! use crylib::aead::ccm::Ccm;
+//! use crylib::aead::gcm::Aes128;
+//! use crylib::aead::Aead;
+//!
+//! let key = [0x42; 16];
+//! let init_vector = [0x24; 12];
+//!
+//! // CCM_8 uses an 8-byte tag
+//! let cipher = Ccm::<Aes128, 8>::new(key);
+//!
+//! let mut msg = *b"Top secret message";
+//! let tag = cipher.encrypt_inline(&mut msg, b"Public information", &init_vector);
+//! assert_ne!(&msg, b"Top secret message");
+//!
+//! cipher
+//!     .decrypt_inline(&mut msg, b"Public information", &init_vector, &tag)
+//!     .expect("Our message has been modified!");
+//! assert_eq!(&msg, b"Top secret message");
+//! ```
+//!
+//! [`Counter with CBC-MAC`]: https://en.wikipedia.org/wiki/CCM_mode
+
+use super::gcm::{AesCipher, BLOCK_SIZE};
+use super::{Aead, BadData, IV_SIZE, TAG_SIZE};
+
+/// A type that allows for authenticated encryption and decryption in CCM via AES.
+///
+/// `T_LEN` is the length of the tag in bytes. It must be an even number from 4 to 16.
+pub struct Ccm<C: AesCipher, const T_LEN: usize = TAG_SIZE> {
+    cipher: C,
+}
+
+impl<C: AesCipher, const T_LEN: usize> Aead<T_LEN> for Ccm<C, T_LEN> {
+    /// Encrypts `msg` inline and returns the tag.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if `msg` is `2^24` bytes or longer.
+    fn encrypt_inline(&self, msg: &mut [u8], add_data: &[u8], iv: &[u8; IV_SIZE]) -> [u8; T_LEN] {
+        assert!(msg.len() <= Self::MAX_MSG_LEN);
+        let tag = self.cbc_mac(msg, add_data, iv);
+        self.xor_key_stream(msg, iv);
+        tag
+    }
+
+    fn decrypt_inline(
+        &self,
+        msg: &mut [u8],
+        add_data: &[u8],
+        iv: &[u8; IV_SIZE],
+        tag: &[u8; T_LEN],
+    ) -> Result<(), BadData> {
+        if msg.len() > Self::MAX_MSG_LEN {
+            return Err(BadData);
+        }
+        self.xor_key_stream(msg, iv);
+        let gen_tag = self.cbc_mac(msg, add_data, iv);
+
+        // compare in constant time
+        let diff = tag
+            .iter()
+            .zip(gen_tag)
+            .fold(0, |diff, (x, y)| diff | (x ^ y));
+        if diff != 0 {
+            // leave the message as it was
+            self.xor_key_stream(msg, iv);
+            return Err(BadData);
+        }
+        Ok(())
+    }
+}
+
+impl<C: AesCipher, const T_LEN: usize> Ccm<C, T_LEN> {
+    /// The size of the message length field in the first block.
+    const LEN_SIZE: usize = BLOCK_SIZE - 1 - IV_SIZE;
+    const MAX_MSG_LEN: usize = (1 << (8 * Self::LEN_SIZE)) - 1;
+
+    /// Construct a new [`Ccm`] cipher.
+    pub fn new(key: C::Key) -> Self {
+        const { assert!(T_LEN >= 4 && T_LEN <= TAG_SIZE && T_LEN.is_multiple_of(2)) };
+        Self {
+            cipher: C::new(key),
+        }
+    }
+
+    /// Returns a block with `flags`, `iv`, and `value` in the length field.
+    fn block_with(flags: u8, iv: &[u8; IV_SIZE], value: usize) -> [u8; BLOCK_SIZE] {
+        let mut block = [0; BLOCK_SIZE];
+        block[0] = flags;
+        block[1..][..IV_SIZE].copy_from_slice(iv);
+        block[1 + IV_SIZE..]
+            .copy_from_slice(&value.to_be_bytes()[size_of::<usize>() - Self::LEN_SIZE..]);
+        block
+    }
+
+    /// Returns the `counter`th counter block.
+    fn counter_block(iv: &[u8; IV_SIZE], counter: usize) -> [u8; BLOCK_SIZE] {
+        Self::block_with((Self::LEN_SIZE - 1) as u8, iv, counter)
+    }
+
+    /// Encrypts or decrypts `data` in counter mode, starting from the first counter block.
+    fn xor_key_stream(&self, data: &mut [u8], iv: &[u8; IV_SIZE]) {
+        for (counter, block) in data.chunks_mut(BLOCK_SIZE).enumerate() {
+            let stream = self.cipher.encrypt(&Self::counter_block(iv, counter + 1));
+
+            for (data_byte, stream_byte) in block.iter_mut().zip(stream) {
+                *data_byte ^= stream_byte;
+            }
+        }
+    }
+
+    /// Calculates the encrypted CBC-MAC of `plain_text` and `add_data`.
+    fn cbc_mac(&self, plain_text: &[u8], add_data: &[u8], iv: &[u8; IV_SIZE]) -> [u8; T_LEN] {
+        let flags = ((!add_data.is_empty() as u8) << 6)
+            | (((T_LEN - 2) / 2) as u8) << 3
+            | (Self::LEN_SIZE - 1) as u8;
+        let mut mac = CbcMac::new(&self.cipher, &Self::block_with(flags, iv, plain_text.len()));
+
+        if !add_data.is_empty() {
+            // the length of the additional data is encoded with as few bytes as possible
+            if add_data.len() < 0xff00 {
+                mac.update(&(add_data.len() as u16).to_be_bytes());
+            } else {
+                mac.update(&[0xff, 0xfe]);
+                mac.update(&(add_data.len() as u32).to_be_bytes());
+            }
+            mac.update(add_data);
+            mac.pad();
+        }
+        mac.update(plain_text);
+        mac.pad();
+
+        let mut tag = [0; T_LEN];
+        let stream = self.cipher.encrypt(&Self::counter_block(iv, 0));
+        for ((tag_byte, mac_byte), stream_byte) in tag.iter_mut().zip(mac.state).zip(stream) {
+            *tag_byte = mac_byte ^ stream_byte;
+        }
+        tag
+    }
+}
+
+/// The running state of a CBC-MAC.
+struct CbcMac<'a, C: AesCipher> {
+    cipher: &'a C,
+    state: [u8; BLOCK_SIZE],
+    /// The number of bytes XORed into the state since it was last encrypted.
+    pos: usize,
+}
+
+impl<'a, C: AesCipher> CbcMac<'a, C> {
+    fn new(cipher: &'a C, first_block: &[u8; BLOCK_SIZE]) -> Self {
+        Self {
+            cipher,
+            state: cipher.encrypt(first_block),
+            pos: 0,
+        }
+    }
+
+    fn update(&mut self, data: &[u8]) {
+        for &byte in data {
+            self.state[self.pos] ^= byte;
+            self.pos += 1;
+            if self.pos == BLOCK_SIZE {
+                self.cipher.encrypt_inline(&mut self.state);
+                self.pos = 0;
+            }
+        }
+    }
+
+    /// Pads the current block with zeros.
+    fn pad(&mut self) {
+        if self.pos != 0 {
+            self.cipher.encrypt_inline(&mut self.state);
+            self.pos = 0;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Ccm;
+    use crate::aead::gcm::Aes128;
+    use crate::aead::Aead;
+
+    #[test]
+    fn encrypt() {
+        let key = [
+            0xfe, 0xff, 0xe9, 0x92, 0x86, 0x65, 0x73, 0x1c, 0x6d, 0x6a, 0x8f, 0x94, 0x67, 0x30,
+            0x83, 0x08,
+        ];
+        let init_vector = [
+            0xca, 0xfe, 0xba, 0xbe, 0xfa, 0xce, 0xdb, 0xad, 0xde, 0xca, 0xf8, 0x88,
+        ];
+        let add_data = [
+            0xfe, 0xed, 0xfa, 0xce, 0xde, 0xad, 0xbe, 0xef, 0xfe, 0xed, 0xfa, 0xce, 0xde, 0xad,
+            0xbe, 0xef, 0xab, 0xad, 0xda, 0xd2,
+        ];
+        let mut plain_text = [
+            0xd9, 0x31, 0x32, 0x25, 0xf8, 0x84, 0x06, 0xe5, 0xa5, 0x59, 0x09, 0xc5, 0xaf, 0xf5,
+            0x26, 0x9a, 0x86, 0xa7, 0xa9, 0x53, 0x15, 0x34, 0xf7, 0xda, 0x2e, 0x4c, 0x30, 0x3d,
+            0x8a, 0x31, 0x8a, 0x72, 0x1c, 0x3c, 0x0c, 0x95, 0x95, 0x68, 0x09, 0x53, 0x2f, 0xcf,
+            0x0e, 0x24, 0x49, 0xa6, 0xb5, 0x25, 0xb1, 0x6a, 0xed, 0xf5, 0xaa, 0x0d, 0xe6, 0x57,
+            0xba, 0x63, 0x7b, 0x39,
+        ];
+        let tag = [
+            0x02, 0x38, 0xc3, 0x26, 0xb4, 0x7b, 0x34, 0xf7, 0x8f, 0x65, 0x9e, 0x75, 0x10, 0x96,
+            0xcd, 0x22,
+        ];
+        let cipher_text = [
+            0x08, 0x93, 0xe9, 0x4b, 0x91, 0x48, 0x80, 0x1a, 0xf0, 0xf7, 0x34, 0x26, 0xab, 0xb0,
+            0x0e, 0x3c, 0xa4, 0x9b, 0xf0, 0x9d, 0x79, 0xa2, 0x01, 0x27, 0xa7, 0xeb, 0x19, 0x26,
+            0xfa, 0x89, 0x05, 0x37, 0x87, 0xff, 0x02, 0xd0, 0x7d, 0x71, 0x81, 0x3b, 0x88, 0x5b,
+            0x85, 0xe7, 0xf9, 0x6c, 0x4e, 0xed, 0xf4, 0x20, 0xdb, 0x12, 0x6a, 0x04, 0x51, 0xce,
+            0x13, 0xbd, 0x41, 0xba,
+        ];
+        let cipher = Ccm::<Aes128>::new(key);
+        assert_eq!(
+            tag,
+            cipher.encrypt_inline(&mut plain_text, &add_data, &init_vector)
+        );
+        assert_eq!(plain_text, cipher_text);
+    }
+
+    #[test]
+    fn decrypt() {
+        let key = [
+            0xfe, 0xff, 0xe9, 0x92, 0x86, 0x65, 0x73, 0x1c, 0x6d, 0x6a, 0x8f, 0x94, 0x67, 0x30,
+            0x83, 0x08,
+        ];
+        let init_vector = [
+            0xca, 0xfe, 0xba, 0xbe, 0xfa, 0xce, 0xdb, 0xad, 0xde, 0xca, 0xf8, 0x88,
+        ];
+        let add_data = [
+            0xfe, 0xed, 0xfa, 0xce, 0xde, 0xad, 0xbe, 0xef, 0xfe, 0xed, 0xfa, 0xce, 0xde, 0xad,
+            0xbe, 0xef, 0xab, 0xad, 0xda, 0xd2,
+        ];
+        let plain_text = [
+            0xd9, 0x31, 0x32, 0x25, 0xf8, 0x84, 0x06, 0xe5, 0xa5, 0x59, 0x09, 0xc5, 0xaf, 0xf5,
+            0x26, 0x9a, 0x86, 0xa7, 0xa9, 0x53, 0x15, 0x34, 0xf7, 0xda, 0x2e, 0x4c, 0x30, 0x3d,
+            0x8a, 0x31, 0x8a, 0x72, 0x1c, 0x3c, 0x0c, 0x95, 0x95, 0x68, 0x09, 0x53, 0x2f, 0xcf,
+            0x0e, 0x24, 0x49, 0xa6, 0xb5, 0x25, 0xb1, 0x6a, 0xed, 0xf5, 0xaa, 0x0d, 0xe6, 0x57,
+            0xba, 0x63, 0x7b, 0x39,
+        ];
+        let tag = [
+            0x02, 0x38, 0xc3, 0x26, 0xb4, 0x7b, 0x34, 0xf7, 0x8f, 0x65, 0x9e, 0x75, 0x10, 0x96,
+            0xcd, 0x22,
+        ];
+        let mut cipher_text = [
+            0x08, 0x93, 0xe9, 0x4b, 0x91, 0x48, 0x80, 0x1a, 0xf0, 0xf7, 0x34, 0x26, 0xab, 0xb0,
+            0x0e, 0x3c, 0xa4, 0x9b, 0xf0, 0x9d, 0x79, 0xa2, 0x01, 0x27, 0xa7, 0xeb, 0x19, 0x26,
+            0xfa, 0x89, 0x05, 0x37, 0x87, 0xff, 0x02, 0xd0, 0x7d, 0x71, 0x81, 0x3b, 0x88, 0x5b,
+            0x85, 0xe7, 0xf9, 0x6c, 0x4e, 0xed, 0xf4, 0x20, 0xdb, 0x12, 0x6a, 0x04, 0x51, 0xce,
+            0x13, 0xbd, 0x41, 0xba,
+        ];
+        let cipher = Ccm::<Aes128>::new(key);
+        cipher
+            .decrypt_inline(&mut cipher_text, &add_data, &init_vector, &tag)
+            .unwrap();
+        assert_eq!(plain_text, cipher_text);
+    }
+
+    #[test]
+    fn bad_tag() {
+        let key = [
+            0xfe, 0xff, 0xe9, 0x92, 0x86, 0x65, 0x73, 0x1c, 0x6d, 0x6a, 0x8f, 0x94, 0x67, 0x30,
+            0x83, 0x08,
+        ];
+        let init_vector = [
+            0xca, 0xfe, 0xba, 0xbe, 0xfa, 0xce, 0xdb, 0xad, 0xde, 0xca, 0xf8, 0x88,
+        ];
+        let add_data = [
+            0xfe, 0xed, 0xfa, 0xce, 0xde, 0xad, 0xbe, 0xef, 0xfe, 0xed, 0xfa, 0xce, 0xde, 0xad,
+            0xbe, 0xef, 0xab, 0xad, 0xda, 0xd2,
+        ];
+        let mut tag = [
+            0x02, 0x38, 0xc3, 0x26, 0xb4, 0x7b, 0x34, 0xf7, 0x8f, 0x65, 0x9e, 0x75, 0x10, 0x96,
+            0xcd, 0x22,
+        ];
+        tag[0] ^= 1;
+        let cipher_text = [
+            0x08, 0x93, 0xe9, 0x4b, 0x91, 0x48, 0x80, 0x1a, 0xf0, 0xf7, 0x34, 0x26, 0xab, 0xb0,
+            0x0e, 0x3c, 0xa4, 0x9b, 0xf0, 0x9d, 0x79, 0xa2, 0x01, 0x27, 0xa7, 0xeb, 0x19, 0x26,
+            0xfa, 0x89, 0x05, 0x37, 0x87, 0xff, 0x02, 0xd0, 0x7d, 0x71, 0x81, 0x3b, 0x88, 0x5b,
+            0x85, 0xe7, 0xf9, 0x6c, 0x4e, 0xed, 0xf4, 0x20, 0xdb, 0x12, 0x6a, 0x04, 0x51, 0xce,
+            0x13, 0xbd, 0x41, 0xba,
+        ];
+        let mut msg = cipher_text;
+        let cipher = Ccm::<Aes128>::new(key);
+        assert!(cipher
+            .decrypt_inline(&mut msg, &add_data, &init_vector, &tag)
+            .is_err());
+        assert_eq!(msg, cipher_text);
+    }
+
+    #[test]
+    fn long_add_data() {
+        let key = [
+            0xfe, 0xff, 0xe9, 0x92, 0x86, 0x65, 0x73, 0x1c, 0x6d, 0x6a, 0x8f, 0x94, 0x67, 0x30,
+            0x83, 0x08,
+        ];
+        let init_vector = [
+            0xca, 0xfe, 0xba, 0xbe, 0xfa, 0xce, 0xdb, 0xad, 0xde, 0xca, 0xf8, 0x88,
+        ];
+        let add_data: [u8; 70000] = core::array::from_fn(|i| i as u8);
+        let mut plain_text = [
+            0xd9, 0x31, 0x32, 0x25, 0xf8, 0x84, 0x06, 0xe5, 0xa5, 0x59, 0x09, 0xc5, 0xaf, 0xf5,
+            0x26, 0x9a, 0x86, 0xa7, 0xa9, 0x53, 0x15, 0x34, 0xf7, 0xda, 0x2e, 0x4c, 0x30, 0x3d,
+            0x8a, 0x31, 0x8a, 0x72, 0x1c, 0x3c, 0x0c, 0x95, 0x95, 0x68, 0x09, 0x53, 0x2f, 0xcf,
+            0x0e, 0x24, 0x49, 0xa6, 0xb5, 0x25, 0xb1, 0x6a, 0xed, 0xf5, 0xaa, 0x0d, 0xe6, 0x57,
+            0xba, 0x63, 0x7b, 0x39,
+        ];
+        let tag = [
+            0x2d, 0x58, 0xaa, 0x82, 0x19, 0x2f, 0x48, 0x10, 0x3e, 0x34, 0x87, 0x6c, 0x5d, 0xef,
+            0x68, 0x97,
+        ];
+        let cipher = Ccm::<Aes128>::new(key);
+        assert_eq!(
+            tag,
+            cipher.encrypt_inline(&mut plain_text, &add_data, &init_vector)
+        );
+    }
+
+    #[test]
+    fn ccm_8() {
+        // from NIST SP 800-38C, example 3
+        let key: [u8; 16] = core::array::from_fn(|i| i as u8 + 0x40);
+        let init_vector: [u8; 12] = core::array::from_fn(|i| i as u8 + 0x10);
+        let add_data: [u8; 20] = core::array::from_fn(|i| i as u8);
+        let mut msg: [u8; 24] = core::array::from_fn(|i| i as u8 + 0x20);
+        let plain_text = msg;
+        let tag = [0x48, 0x43, 0x92, 0xfb, 0xc1, 0xb0, 0x99, 0x51];
+        let cipher_text = [
+            0xe3, 0xb2, 0x01, 0xa9, 0xf5, 0xb7, 0x1a, 0x7a, 0x9b, 0x1c, 0xea, 0xec, 0xcd, 0x97,
+            0xe7, 0x0b, 0x61, 0x76, 0xaa, 0xd9, 0xa4, 0x42, 0x8a, 0xa5,
+        ];
+        let cipher = Ccm::<Aes128, 8>::new(key);
+        assert_eq!(
+            tag,
+            cipher.encrypt_inline(&mut msg, &add_data, &init_vector)
+        );
+        assert_eq!(msg, cipher_text);
+
+        cipher
+            .decrypt_inline(&mut msg, &add_data, &init_vector, &tag)
+            .unwrap();
+        assert_eq!(msg, plain_text);
+    }
+}
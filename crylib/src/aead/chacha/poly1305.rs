@@ -59,7 +59,7 @@ impl Poly1305 {
         for block in blocks {
             self.update(block.try_into().unwrap());
         }
-        if remainder.len() > 0 {
+        if !remainder.is_empty() {
             let mut last_block = [0; 16];
             last_block[..remainder.len()].copy_from_slice(remainder);
             self.update(&last_block);
@@ -74,7 +74,7 @@ impl Poly1305 {
         for block in blocks {
             self.update(block.try_into().unwrap());
         }
-        if remainder.len() > 0 {
+        if !remainder.is_empty() {
             let mut last_block = [0; 16];
             last_block[..remainder.len()].copy_from_slice(remainder);
 
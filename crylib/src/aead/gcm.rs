@@ -51,7 +51,6 @@
 //!
 //! [`Gallois/Counter Mode`]: https://en.wikipedia.org/wiki/Galois/Counter_Mode
 mod aes;
-use core::u128;
 
 pub use aes::*;
 
@@ -157,13 +156,11 @@ impl<C: aes::AesCipher> Gcm<C> {
             add_block(&mut tag, block.try_into().unwrap(), self.h);
         }
 
-        let last_block = {
+        if !remainder.is_empty() {
             let mut last_block = [0; aes::BLOCK_SIZE];
             last_block[..remainder.len()].copy_from_slice(remainder);
-            last_block
-        };
-
-        add_block(&mut tag, last_block, self.h);
+            add_block(&mut tag, last_block, self.h);
+        }
 
         // TODO: use `array_chunks` once stabilized
         let blocks = cipher_text.chunks_exact(aes::BLOCK_SIZE);
@@ -173,15 +170,13 @@ impl<C: aes::AesCipher> Gcm<C> {
             add_block(&mut tag, block.try_into().unwrap(), self.h);
         }
 
-        let last_block = {
+        if !remainder.is_empty() {
             let mut last_block = [0; aes::BLOCK_SIZE];
             last_block[..remainder.len()].copy_from_slice(remainder);
-            last_block
-        };
-
-        add_block(&mut tag, last_block, self.h);
+            add_block(&mut tag, last_block, self.h);
+        }
 
-        tag ^= ((add_data.len() as u128 * 8) << 64) | cipher_text.len() as u128 * 8;
+        tag ^= ((add_data.len() as u128 * 8) << 64) | (cipher_text.len() as u128 * 8);
         tag = gf_2to128_mul(tag, self.h);
 
         let encrypted_iv = u128::from_be_bytes(self.cipher.encrypt(counter));
@@ -334,6 +329,37 @@ mod tests {
         assert_eq!(plain_text, cipher_text);
     }
 
+    #[test]
+    fn encrypt_block_aligned() {
+        let key = [
+            0xfe, 0xff, 0xe9, 0x92, 0x86, 0x65, 0x73, 0x1c, 0x6d, 0x6a, 0x8f, 0x94, 0x67, 0x30,
+            0x83, 0x08,
+        ];
+        let cipher = Gcm::<Aes128>::new(key);
+
+        let init_vector = [
+            0xca, 0xfe, 0xba, 0xbe, 0xfa, 0xce, 0xdb, 0xad, 0xde, 0xca, 0xf8, 0x88,
+        ];
+
+        let mut plain_text: [u8; 32] = core::array::from_fn(|i| i as u8);
+        let add_data: [u8; 16] = core::array::from_fn(|i| i as u8 + 100);
+
+        let tag = [
+            0x20, 0xb5, 0xd2, 0x9b, 0x6e, 0x2d, 0x6a, 0x45, 0x97, 0x84, 0x5e, 0x32, 0xbb, 0x93,
+            0xe6, 0x3a,
+        ];
+        let cipher_text = [
+            0x9b, 0xb3, 0x2e, 0xe4, 0xdd, 0xf6, 0x74, 0xc6, 0xe6, 0x22, 0x22, 0x79, 0x27, 0x28,
+            0xfc, 0x09, 0x75, 0x1c, 0x9a, 0x6f, 0x2d, 0x23, 0x45, 0x2d, 0x03, 0x94, 0x54, 0x05,
+            0xbf, 0x80, 0x35, 0x43,
+        ];
+        assert_eq!(
+            tag,
+            cipher.encrypt_inline(&mut plain_text, &add_data, &init_vector)
+        );
+        assert_eq!(plain_text, cipher_text);
+    }
+
     #[test]
     fn decrypt() {
         let key = [
@@ -271,7 +271,9 @@ macro_rules! impl_big_int {
 
 impl_big_int!(3);
 impl_big_int!(4);
+impl_big_int!(6);
 impl_big_int!(8);
+impl_big_int!(9);
 
 impl<const N: usize> PartialOrd for BigInt<N> {
     fn lt(&self, other: &Self) -> bool {
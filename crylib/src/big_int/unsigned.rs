@@ -844,6 +844,11 @@ impl_non_generic!(4);
 impl_non_generic!(8);
 impl_non_generic!(5);
 impl_non_generic!(6);
+impl_non_generic!(7);
+impl_non_generic!(9);
+impl_non_generic!(10);
+impl_non_generic!(12);
+impl_non_generic!(18);
 
 impl<const N: usize> Ord for UBigInt<N> {
     // TODO: make this constant-time?
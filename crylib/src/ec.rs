@@ -1,27 +1,37 @@
 //! Elliptic curve cryptography.
 pub mod ecdsa;
+pub mod ed25519;
 mod point;
 mod secp256r1;
+mod secp384r1;
+mod secp521r1;
+pub mod x25519;
 
 pub use point::affine::AffinePoint;
 pub use point::projective::ProjectivePoint;
 pub use secp256r1::Secp256r1;
+pub use secp384r1::Secp384r1;
+pub use secp521r1::Secp521r1;
 
 use crate::finite_field::{FieldElement, FiniteField};
 
 /// A trait for describining an elliptic curve over a finite field in Weierstrass form.
 ///
 /// The curve is defined by the equation `Y^2 = X^3 + A*X + B`.
-// TODO: make this generic over any size N once const generic operators are stabilized.
-pub trait EllipticCurve: FiniteField<4> {
+///
+/// `N` is the number of 64-bit limbs in each field element. Point arithmetic is only implemented
+/// for the sizes used by the curves in this module.
+// TODO: implement point arithmetic for any size N once const generic operators are stabilized.
+pub trait EllipticCurve<const N: usize>: FiniteField<N> {
     /// The generator point used for elliptic-curve cryptography.
-    const BASE_POINT: ProjectivePoint<Self>;
+    const BASE_POINT: ProjectivePoint<N, Self>;
 
     /// The linear-term coefficient of the curve.
-    const A: FieldElement<4, Self>;
+    const A: FieldElement<N, Self>;
 
     /// The constant-term coefficient of the curve.
-    const B: FieldElement<4, Self>;
+    const B: FieldElement<N, Self>;
 
-    type Order: FiniteField<4>;
+    /// The field of scalars, whose modulus is the order of [`Self::BASE_POINT`].
+    type Order: FiniteField<N>;
 }
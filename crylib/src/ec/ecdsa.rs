@@ -1,19 +1,206 @@
 //! The Elliptic Curve Digital Signature Algorithm.
 
+use core::cell::RefCell;
+
 use super::{EllipticCurve, ProjectivePoint};
 use crate::big_int::UBigInt;
 use crate::finite_field::{FieldElement, FiniteField};
+use crate::hash::{BlockHasher, BufHasher};
+use crate::hmac::Hmac;
 
 #[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
-pub struct Signature<C: FiniteField<4>> {
-    r: FieldElement<4, C>,
-    s: FieldElement<4, C>,
+pub struct Signature<const N: usize, C: FiniteField<N>> {
+    r: FieldElement<N, C>,
+    s: FieldElement<N, C>,
 }
 
-impl<C: FiniteField<4>> Signature<C> {
-    pub const fn new(r: FieldElement<4, C>, s: FieldElement<4, C>) -> Self {
+impl<const N: usize, C: FiniteField<N>> Signature<N, C> {
+    pub const fn new(r: FieldElement<N, C>, s: FieldElement<N, C>) -> Self {
         Self { r, s }
     }
+
+    /// Returns the `r` component of the signature.
+    pub const fn r(&self) -> &FieldElement<N, C> {
+        &self.r
+    }
+
+    /// Returns the `s` component of the signature.
+    pub const fn s(&self) -> &FieldElement<N, C> {
+        &self.s
+    }
+
+    /// Returns the length of the fixed-width encoding, `r || s`.
+    ///
+    /// Each component is as long as the order of the curve, in bytes.
+    pub fn raw_len() -> usize {
+        2 * scalar_len::<N, C>()
+    }
+
+    /// Writes the fixed-width encoding, `r || s`, to `buf` and returns its length.
+    ///
+    /// Each component is a big-endian integer as long as the order of the curve.
+    ///
+    /// # Panics
+    /// Panics if `buf` is shorter than [`Self::raw_len()`].
+    pub fn encode_raw(&self, buf: &mut [u8]) -> usize {
+        let len = scalar_len::<N, C>();
+        write_be(&self.r, &mut buf[..len]);
+        write_be(&self.s, &mut buf[len..2 * len]);
+        2 * len
+    }
+
+    /// Decodes the fixed-width encoding, `r || s`.
+    ///
+    /// Both components must be in the range `[1, n - 1]`, where `n` is the order of the curve.
+    pub fn decode_raw(raw: &[u8]) -> Result<Self, InvalidEncoding> {
+        if raw.len() != Self::raw_len() {
+            return Err(InvalidEncoding);
+        }
+        let (r, s) = raw.split_at(scalar_len::<N, C>());
+        Ok(Self::new(scalar_from_be(r)?, scalar_from_be(s)?))
+    }
+
+    /// Returns the maximum length of the DER encoding.
+    pub fn der_max_len() -> usize {
+        // a leading zero may be needed to keep each integer positive
+        let int_len = 2 + scalar_len::<N, C>() + 1;
+        let seq_len = 2 * int_len;
+        match seq_len < 0x80 {
+            true => 2 + seq_len,
+            false => 3 + seq_len,
+        }
+    }
+
+    /// Writes the DER encoding, `SEQUENCE { r INTEGER, s INTEGER }`, to `buf` and returns its
+    /// length.
+    ///
+    /// # Panics
+    /// Panics if `buf` is shorter than [`Self::der_max_len()`].
+    pub fn encode_der(&self, buf: &mut [u8]) -> usize {
+        // the extra byte is either part of the integer or a leading zero that keeps it positive
+        let r_len = self.r.count_bits() / 8 + 1;
+        let s_len = self.s.count_bits() / 8 + 1;
+        let seq_len = 2 + r_len + 2 + s_len;
+
+        buf[0] = DER_SEQUENCE;
+        let mut pos = match seq_len < 0x80 {
+            true => {
+                buf[1] = seq_len as u8;
+                2
+            },
+            false => {
+                buf[1] = 0x81;
+                buf[2] = seq_len as u8;
+                3
+            },
+        };
+        for (int, len) in [(&self.r, r_len), (&self.s, s_len)] {
+            buf[pos] = DER_INTEGER;
+            buf[pos + 1] = len as u8;
+            write_be(int, &mut buf[pos + 2..][..len]);
+            pos += 2 + len;
+        }
+        pos
+    }
+
+    /// Decodes the DER encoding, `SEQUENCE { r INTEGER, s INTEGER }`.
+    ///
+    /// Only the distinguished encoding is accepted: lengths and integers must be minimal, and there
+    /// must be no trailing data. Both components must be in the range `[1, n - 1]`, where `n` is
+    /// the order of the curve.
+    pub fn decode_der(der: &[u8]) -> Result<Self, InvalidEncoding> {
+        let (seq, rest) = read_der(der, DER_SEQUENCE)?;
+        if !rest.is_empty() {
+            return Err(InvalidEncoding);
+        }
+        let (r, seq) = read_der(seq, DER_INTEGER)?;
+        let (s, seq) = read_der(seq, DER_INTEGER)?;
+        if !seq.is_empty() {
+            return Err(InvalidEncoding);
+        }
+        Ok(Self::new(der_int_to_scalar(r)?, der_int_to_scalar(s)?))
+    }
+}
+
+/// The error that is returned when an encoded signature is malformed or out of range.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Default)]
+pub struct InvalidEncoding;
+
+impl core::fmt::Display for InvalidEncoding {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str("the signature encoding is not valid")
+    }
+}
+
+impl core::error::Error for InvalidEncoding {}
+
+const DER_INTEGER: u8 = 0x02;
+const DER_SEQUENCE: u8 = 0x30;
+
+/// Returns the number of bytes in the order of the curve.
+fn scalar_len<const N: usize, C: FiniteField<N>>() -> usize {
+    C::MODULUS.count_bits().div_ceil(8)
+}
+
+/// Writes `int` to `buf` as a big-endian integer that is exactly as long as `buf`.
+///
+/// Bytes beyond the size of `int` are set to zero.
+fn write_be<const N: usize>(int: &UBigInt<N>, buf: &mut [u8]) {
+    for (i, byte) in buf.iter_mut().rev().enumerate() {
+        *byte = int
+            .0
+            .get(i / size_of::<u64>())
+            .map_or(0, |digit| (digit >> (i % size_of::<u64>() * 8)) as u8);
+    }
+}
+
+/// Parses a big-endian scalar in the range `[1, n - 1]`.
+fn scalar_from_be<const N: usize, C: FiniteField<N>>(
+    bytes: &[u8],
+) -> Result<FieldElement<N, C>, InvalidEncoding> {
+    if bytes.len() > N * size_of::<u64>() {
+        return Err(InvalidEncoding);
+    }
+    let mut int = UBigInt::ZERO;
+    for (i, &byte) in bytes.iter().rev().enumerate() {
+        int.set_byte(i, byte);
+    }
+    match FieldElement::try_new(int) {
+        Ok(scalar) if scalar != FieldElement::ZERO => Ok(scalar),
+        _ => Err(InvalidEncoding),
+    }
+}
+
+/// Reads a DER element with tag `tag`, returning its contents and the remaining input.
+///
+/// Signatures are never long enough to need more than one length byte.
+fn read_der(der: &[u8], tag: u8) -> Result<(&[u8], &[u8]), InvalidEncoding> {
+    let (len, rest) = match der {
+        [t, len, rest @ ..] if *t == tag && *len < 0x80 => (*len as usize, rest),
+        // the long form is only allowed for lengths that don't fit in the short form
+        [t, 0x81, len, rest @ ..] if *t == tag && *len >= 0x80 => (*len as usize, rest),
+        _ => return Err(InvalidEncoding),
+    };
+    if rest.len() < len {
+        return Err(InvalidEncoding);
+    }
+    Ok(rest.split_at(len))
+}
+
+/// Parses the contents of a DER INTEGER as a scalar in the range `[1, n - 1]`.
+fn der_int_to_scalar<const N: usize, C: FiniteField<N>>(
+    int: &[u8],
+) -> Result<FieldElement<N, C>, InvalidEncoding> {
+    let magnitude = match int {
+        [] => return Err(InvalidEncoding),
+        // negative
+        [first, ..] if first & 0x80 != 0 => return Err(InvalidEncoding),
+        // a leading zero is only allowed when the next byte would make the integer negative
+        [0, second, ..] if second & 0x80 == 0 => return Err(InvalidEncoding),
+        [0, rest @ ..] => rest,
+        _ => int,
+    };
+    scalar_from_be(magnitude)
 }
 
 /// The value that represents a valid signature.
@@ -38,6 +225,110 @@ impl core::fmt::Display for InvalidSig {
 
 impl core::error::Error for InvalidSig {}
 
+/// ECDSA over an [`EllipticCurve`] whose field elements have `N` limbs.
+///
+/// This is implemented for every curve with a supported size. In most cases, it's easier to use
+/// [`sign`] and [`verify_signature`].
+// TODO: remove this once const generic operators are stabilized.
+pub trait Ecdsa<const N: usize>: EllipticCurve<N> {
+    /// See [`sign`].
+    fn sign<H: AsRef<[u8]>>(
+        msg: &[u8],
+        priv_key: &FieldElement<N, Self::Order>,
+        hash_func: impl FnOnce(&[u8]) -> H,
+        random_num_gen: impl Fn() -> FieldElement<N, Self::Order>,
+    ) -> Signature<N, Self::Order>;
+
+    /// See [`verify_signature`].
+    fn verify_signature<H: AsRef<[u8]>>(
+        msg: &[u8],
+        pub_key: &ProjectivePoint<N, Self>,
+        hash_func: impl FnOnce(&[u8]) -> H,
+        sig: &Signature<N, Self::Order>,
+    ) -> Result<ValidSig, InvalidSig>;
+}
+
+macro_rules! impl_ecdsa {
+    ($n:literal) => {
+        impl<C: EllipticCurve<$n>> Ecdsa<$n> for C {
+            fn sign<H: AsRef<[u8]>>(
+                msg: &[u8],
+                priv_key: &FieldElement<$n, C::Order>,
+                hash_func: impl FnOnce(&[u8]) -> H,
+                random_num_gen: impl Fn() -> FieldElement<$n, C::Order>,
+            ) -> Signature<$n, C::Order> {
+                let hash = hash_to_int::<$n, C::Order>(hash_func(msg).as_ref());
+                let hash = FieldElement::<$n, C::Order>::new(hash);
+
+                loop {
+                    let secret_num = random_num_gen();
+                    let mut inverse = secret_num.inverse();
+
+                    let Some(new_point) = C::BASE_POINT.mul_scalar(&secret_num).as_affine() else {
+                        continue;
+                    };
+
+                    let r = new_point.x_ref().convert();
+
+                    inverse.mul_assign(&hash.add(&r.mul(priv_key)));
+
+                    if r != FieldElement::ZERO && inverse != FieldElement::ZERO {
+                        return Signature::new(r, inverse);
+                    }
+                }
+            }
+
+            fn verify_signature<H: AsRef<[u8]>>(
+                msg: &[u8],
+                pub_key: &ProjectivePoint<$n, C>,
+                hash_func: impl FnOnce(&[u8]) -> H,
+                sig: &Signature<$n, C::Order>,
+            ) -> Result<ValidSig, InvalidSig> {
+                let hash = hash_to_int::<$n, C::Order>(hash_func(msg).as_ref());
+                let hash = FieldElement::<$n, C::Order>::new(hash);
+                let inverse = sig.s.inverse();
+
+                let u = hash.mul(&inverse);
+                let v = sig.r.mul(&inverse);
+
+                let r = match C::BASE_POINT
+                    .mul_scalar(&u)
+                    .add(&pub_key.mul_scalar(&v))
+                    .as_affine()
+                {
+                    Some(point) => point.x_ref().convert(),
+                    None => return Err(InvalidSig),
+                };
+
+                match r == sig.r {
+                    true => Ok(ValidSig),
+                    false => Err(InvalidSig),
+                }
+            }
+        }
+    };
+}
+
+impl_ecdsa!(4);
+impl_ecdsa!(6);
+impl_ecdsa!(9);
+
+/// Converts a hash to an integer, keeping only as many leftmost bits as the order of `O` has.
+///
+/// The result isn't reduced.
+fn hash_to_int<const N: usize, O: FiniteField<N>>(hash: &[u8]) -> UBigInt<N> {
+    let order_bits = O::MODULUS.count_bits();
+    let hash = &hash[..core::cmp::min(hash.len(), order_bits.div_ceil(8))];
+
+    let mut int = UBigInt::ZERO;
+    for (i, &byte) in hash.iter().rev().enumerate() {
+        int.set_byte(i, byte);
+    }
+    // at most 7 bits are left over
+    int.shift_right_assign((hash.len() * 8).saturating_sub(order_bits) as u64);
+    int
+}
+
 /// Creates a unique signature for `msg`.
 ///
 /// Both parties must use the same `hash_func` (such as SHA-256) for signing and signature authentication.
@@ -45,74 +336,177 @@ impl core::error::Error for InvalidSig {}
 ///
 /// DO NOT SHARE THE PRIVATE KEY. The security of this algorithm depends on the secrecy of
 /// `priv_key`.
-pub fn sign<C: EllipticCurve>(
+pub fn sign<const N: usize, C: Ecdsa<N>, H: AsRef<[u8]>>(
     msg: &[u8],
-    priv_key: &FieldElement<4, C::Order>,
-    hash_func: impl FnOnce(&[u8]) -> [u8; 32],
-    random_num_gen: impl Fn() -> FieldElement<4, C::Order>,
-) -> Signature<C::Order> {
-    let mut hash: FieldElement<4, C::Order> =
-        FieldElement::<4, _>::new(UBigInt::<4>::from_be_bytes(hash_func(msg)));
-
-    loop {
-        let secret_num = random_num_gen();
-        let mut inverse = secret_num.inverse();
-
-        let Some(new_point) = C::BASE_POINT.mul_scalar(&secret_num).as_affine() else {
-            continue;
-        };
+    priv_key: &FieldElement<N, C::Order>,
+    hash_func: impl FnOnce(&[u8]) -> H,
+    random_num_gen: impl Fn() -> FieldElement<N, C::Order>,
+) -> Signature<N, C::Order> {
+    C::sign(msg, priv_key, hash_func, random_num_gen)
+}
+
+/// Creates a signature for `msg` with a deterministic nonce, as described in
+/// [RFC 6979](https://www.rfc-editor.org/rfc/rfc6979).
+///
+/// The nonce is derived from `priv_key` and the hash of `msg` with HMAC, using the same hash
+/// function `H` that is used for the signature. Signing the same message with the same key
+/// always produces the same signature, so no random number generator is needed.
+///
+/// DO NOT SHARE THE PRIVATE KEY. The security of this algorithm depends on the secrecy of
+/// `priv_key`.
+pub fn sign_deterministic<
+    const N: usize,
+    C: Ecdsa<N>,
+    const H_LEN: usize,
+    const B_LEN: usize,
+    H: BlockHasher<H_LEN, B_LEN>,
+>(
+    msg: &[u8],
+    priv_key: &FieldElement<N, C::Order>,
+) -> Signature<N, C::Order> {
+    sign_hedged::<N, C, H_LEN, B_LEN, H>(msg, priv_key, &[])
+}
+
+/// Creates a signature for `msg` with a hedged nonce.
+///
+/// This is the same as [`sign_deterministic`], but `extra_entropy` is mixed into the nonce as the
+/// additional data described in RFC 6979 section 3.6. If `extra_entropy` is random, signatures
+/// are randomized, but the nonce stays secret even if the random number generator is weak.
+///
+/// DO NOT SHARE THE PRIVATE KEY. The security of this algorithm depends on the secrecy of
+/// `priv_key`.
+pub fn sign_hedged<
+    const N: usize,
+    C: Ecdsa<N>,
+    const H_LEN: usize,
+    const B_LEN: usize,
+    H: BlockHasher<H_LEN, B_LEN>,
+>(
+    msg: &[u8],
+    priv_key: &FieldElement<N, C::Order>,
+    extra_entropy: &[u8],
+) -> Signature<N, C::Order> {
+    let hash = H::hash(msg);
+    let nonces = RefCell::new(NonceGen::<N, C::Order, H_LEN, B_LEN, H>::new(
+        priv_key,
+        &hash,
+        extra_entropy,
+    ));
+    C::sign(msg, priv_key, |_| hash, || nonces.borrow_mut().next())
+}
+
+/// The largest number of bytes in a scalar of a supported curve.
+const MAX_SCALAR_LEN: usize = 9 * size_of::<u64>();
+
+/// Generates the nonces of RFC 6979 section 3.2.
+struct NonceGen<const N: usize, O, const H_LEN: usize, const B_LEN: usize, H>
+where
+    O: FiniteField<N>,
+    H: BlockHasher<H_LEN, B_LEN>,
+{
+    k: [u8; H_LEN],
+    v: [u8; H_LEN],
+    /// Whether or not a nonce has already been returned.
+    used: bool,
+    _marker: core::marker::PhantomData<(O, H)>,
+}
+
+impl<const N: usize, O, const H_LEN: usize, const B_LEN: usize, H> NonceGen<N, O, H_LEN, B_LEN, H>
+where
+    O: FiniteField<N>,
+    H: BlockHasher<H_LEN, B_LEN>,
+{
+    fn new(priv_key: &FieldElement<N, O>, hash: &[u8; H_LEN], extra_entropy: &[u8]) -> Self {
+        let len = scalar_len::<N, O>();
+        debug_assert!(len <= MAX_SCALAR_LEN);
 
-        let r = new_point.x_ref().convert();
+        let mut key = [0; MAX_SCALAR_LEN];
+        write_be(priv_key, &mut key[..len]);
+        let key = &key[..len];
 
-        hash.add_assign(&r.mul(priv_key));
-        inverse.mul_assign(&hash);
+        // bits2octets: the hash is at most one modulus too large after truncation
+        let mut hash_int = hash_to_int::<N, O>(hash);
+        if hash_int >= O::MODULUS {
+            hash_int.sub_assign(&O::MODULUS);
+        }
+        let mut hash = [0; MAX_SCALAR_LEN];
+        write_be(&hash_int, &mut hash[..len]);
+        let hash = &hash[..len];
 
-        if r != FieldElement::ZERO && inverse != FieldElement::ZERO {
-            return Signature::new(r, inverse);
+        let mut k = [0; H_LEN];
+        let mut v = [1; H_LEN];
+        k = hmac::<H_LEN, B_LEN, H>(&k, &[&v, &[0], key, hash, extra_entropy]);
+        v = hmac::<H_LEN, B_LEN, H>(&k, &[&v]);
+        k = hmac::<H_LEN, B_LEN, H>(&k, &[&v, &[1], key, hash, extra_entropy]);
+        v = hmac::<H_LEN, B_LEN, H>(&k, &[&v]);
+        Self {
+            k,
+            v,
+            used: false,
+            _marker: core::marker::PhantomData,
+        }
+    }
+
+    /// Returns the next nonce in the range `[1, n - 1]`.
+    fn next(&mut self) -> FieldElement<N, O> {
+        let len = scalar_len::<N, O>();
+        loop {
+            // a previous nonce was rejected, so the state must be updated
+            if self.used {
+                self.k = hmac::<H_LEN, B_LEN, H>(&self.k, &[&self.v, &[0]]);
+                self.v = hmac::<H_LEN, B_LEN, H>(&self.k, &[&self.v]);
+            }
+            self.used = true;
+
+            let mut t = [0; MAX_SCALAR_LEN];
+            for chunk in t[..len].chunks_mut(H_LEN) {
+                self.v = hmac::<H_LEN, B_LEN, H>(&self.k, &[&self.v]);
+                chunk.copy_from_slice(&self.v[..chunk.len()]);
+            }
+
+            match FieldElement::try_new(hash_to_int::<N, O>(&t[..len])) {
+                Ok(nonce) if nonce != FieldElement::ZERO => return nonce,
+                _ => continue,
+            }
         }
     }
 }
 
+/// Returns the HMAC of the concatenation of `parts`.
+fn hmac<const H_LEN: usize, const B_LEN: usize, H: BlockHasher<H_LEN, B_LEN>>(
+    key: &[u8],
+    parts: &[&[u8]],
+) -> [u8; H_LEN] {
+    let mut hmac = Hmac::<H_LEN, B_LEN, BufHasher<H_LEN, B_LEN, H>>::new(key);
+    for part in parts {
+        hmac.update_with(part);
+    }
+    hmac.finish()
+}
+
 /// Verifies the authenticity of `sig` using the signer's public key.
-pub fn verify_signature<C: EllipticCurve>(
+pub fn verify_signature<const N: usize, C: Ecdsa<N>, H: AsRef<[u8]>>(
     msg: &[u8],
-    pub_key: &ProjectivePoint<C>,
-    hash_func: impl FnOnce(&[u8]) -> [u8; 32],
-    sig: &Signature<C::Order>,
+    pub_key: &ProjectivePoint<N, C>,
+    hash_func: impl FnOnce(&[u8]) -> H,
+    sig: &Signature<N, C::Order>,
 ) -> Result<ValidSig, InvalidSig> {
-    let hash: FieldElement<4, C::Order> =
-        FieldElement::<4, _>::new(UBigInt::<4>::from_be_bytes(hash_func(msg)));
-    let inverse = sig.s.inverse();
-
-    let u = hash.mul(&inverse);
-    let v = sig.r.mul(&inverse);
-
-    let r = match C::BASE_POINT
-        .mul_scalar(&u)
-        .add(&pub_key.mul_scalar(&v))
-        .as_affine()
-    {
-        Some(point) => point.x_ref().convert(),
-        None => return Err(InvalidSig),
-    };
-
-    match r == sig.r {
-        true => Ok(ValidSig),
-        false => Err(InvalidSig),
-    }
+    C::verify_signature(msg, pub_key, hash_func, sig)
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::ec::Secp256r1;
+    use crate::ec::{AffinePoint, EllipticCurve, Secp256r1, Secp384r1, Secp521r1};
 
     use super::FieldElement;
+    use super::InvalidEncoding;
     use super::InvalidSig;
     use super::ProjectivePoint;
     use super::Signature;
     use super::UBigInt;
     use super::ValidSig;
-    use crate::hash::{Hasher, Sha256};
+    use crate::finite_field::FiniteField;
+    use crate::hash::{Hasher, Sha256, Sha512};
 
     // test vectors from http://csrc.nist.gov/groups/STM/cavp/documents/dss/186-3ecdsatestvectors.zip
 
@@ -168,7 +562,7 @@ mod tests {
             ]))
         };
 
-        let generated_signature = super::sign::<Secp256r1>(
+        let generated_signature = super::sign::<4, Secp256r1, _>(
             msg,
             &priv_key,
             crate::hash::Sha256::hash,
@@ -226,7 +620,7 @@ mod tests {
         };
         let signature = Signature::new(r, s);
 
-        let generated_signature = super::sign::<Secp256r1>(
+        let generated_signature = super::sign::<4, Secp256r1, _>(
             msg,
             &priv_key,
             Sha256::hash,
@@ -388,4 +782,474 @@ mod tests {
             Err(InvalidSig)
         );
     }
+
+    #[test]
+    fn p384() {
+        let msg = b"sample";
+
+        let priv_key = unsafe {
+            FieldElement::new_unchecked(UBigInt([
+                0x65fd72f5cb685b95,
+                0x4e2bd3d44edc52b7,
+                0x0ccf34560c6eb677,
+                0x098493bc76293886,
+                0xee15cb827df23702,
+                0xba89efaf0518934b,
+            ]))
+        };
+
+        let pub_key = unsafe {
+            AffinePoint::<6, Secp384r1>::new_unchecked(
+                FieldElement::new_unchecked(UBigInt([
+                    0x2059afff74c94077,
+                    0xeda752a048a5df2a,
+                    0x2dd9567af46725e3,
+                    0x6e059cb07a33da32,
+                    0xcc1c9c1ac92e6c8c,
+                    0xaa66dbfd8bdb5595,
+                ])),
+                FieldElement::new_unchecked(UBigInt([
+                    0x01485e869ffc02da,
+                    0xa70aac03502dd60b,
+                    0xb1b8988d1e9583d9,
+                    0xe54146ae8df595a7,
+                    0x4a3ae28e27a5da86,
+                    0xba45006a069e66bc,
+                ])),
+            )
+        }
+        .as_projective();
+        assert_eq!(Secp384r1::BASE_POINT.mul_scalar(&priv_key), pub_key);
+
+        let nonce = unsafe {
+            FieldElement::new_unchecked(UBigInt([
+                0xcd36d4447f823cad,
+                0x98e62d0a495ac1c5,
+                0x0edd7ed81e6e2d8c,
+                0x62ffa5848d69f7a3,
+                0xbd1e42d56ef00f19,
+                0x27d9148f4df4a973,
+            ]))
+        };
+
+        let r = unsafe {
+            FieldElement::new_unchecked(UBigInt([
+                0xb261083346011f78,
+                0xd68d26443bf7e2ea,
+                0xeaf2776b52d40800,
+                0x91f49e684065400d,
+                0x57150a49e204ed1a,
+                0xcddf8dcee3cda83d,
+            ]))
+        };
+
+        let s = unsafe {
+            FieldElement::new_unchecked(UBigInt([
+                0xfb5622b0c861bf8c,
+                0x1e8517b4564fec96,
+                0xcdc140607c36e113,
+                0x74da321667c05d0f,
+                0xd70098625d8d571e,
+                0x2f358a4160a97c6b,
+            ]))
+        };
+        let signature = Signature::new(r, s);
+
+        // SHA-512 is longer than the order, so the hash is truncated
+        let generated_signature =
+            super::sign::<6, Secp384r1, _>(msg, &priv_key, Sha512::hash, || nonce);
+        assert_eq!(generated_signature, signature);
+
+        assert_eq!(
+            super::verify_signature(msg, &pub_key, Sha512::hash, &signature),
+            Ok(ValidSig)
+        );
+        assert_eq!(
+            super::verify_signature(&msg[1..], &pub_key, Sha512::hash, &signature),
+            Err(InvalidSig)
+        );
+    }
+
+    #[test]
+    fn p521() {
+        let msg = b"sample";
+
+        let priv_key = unsafe {
+            FieldElement::new_unchecked(UBigInt([
+                0x130f88f6c6da6247,
+                0x81cfdea2d60ee5df,
+                0x33d0d344283fdeb0,
+                0xc616123eb6d0450d,
+                0xe23b5d97f1d13737,
+                0xe2d5667e76e358f3,
+                0x1d6e57dd858cd675,
+                0xfebdbf1c517f8fe0,
+                0x0000000000000197,
+            ]))
+        };
+
+        let pub_key = unsafe {
+            AffinePoint::<9, Secp521r1>::new_unchecked(
+                FieldElement::new_unchecked(UBigInt([
+                    0x1ba85b95fb27724b,
+                    0x203099b3c791acff,
+                    0x57737babd800258b,
+                    0x7c3d3a8c76a0cae5,
+                    0x75452fdcb6afd311,
+                    0x43116f405c6e25e6,
+                    0x2ab2bb78acf0c7c7,
+                    0x2676ab33b6e5a4fe,
+                    0x000000000000001e,
+                ])),
+                FieldElement::new_unchecked(UBigInt([
+                    0xf6536600210e6630,
+                    0x5ea9f3e889f5ed6d,
+                    0xa6e75c15e5f3826d,
+                    0x3b0724a0645fa700,
+                    0x9dd11de3a784cc2c,
+                    0xae063e298f3338b7,
+                    0x13f74c9f39df9d73,
+                    0x47e90d470b9a6811,
+                    0x0000000000000094,
+                ])),
+            )
+        }
+        .as_projective();
+        assert_eq!(Secp521r1::BASE_POINT.mul_scalar(&priv_key), pub_key);
+
+        let nonce = unsafe {
+            FieldElement::new_unchecked(UBigInt([
+                0xb85be9f6e3ecae41,
+                0x20062a45f80e6452,
+                0x32cdb73762a6a41a,
+                0x1dd1137f6457ebf5,
+                0x4a08bdb6df236fd5,
+                0xa35f0b8fb21022ff,
+                0xaf0687fa80ccf2a7,
+                0x2cf9097388e89775,
+                0x00000000000001e2,
+            ]))
+        };
+
+        let r = unsafe {
+            FieldElement::new_unchecked(UBigInt([
+                0xa11868cca6a1e0da,
+                0x9fadadbae76870e2,
+                0x85f793ab6f8cedfb,
+                0x657dcce91549eb42,
+                0xeed4999a756c18dc,
+                0x4e84dbc6948c47d6,
+                0x7fe58fe0876576de,
+                0x1e1a0f523518b282,
+                0x00000000000000d0,
+            ]))
+        };
+
+        let s = unsafe {
+            FieldElement::new_unchecked(UBigInt([
+                0x39796408179778aa,
+                0xaef43623f06e935b,
+                0xe55f9f33da9f813c,
+                0xf0cce3638ab1ce90,
+                0xe34f48a8faf3fab1,
+                0xb98e7d5e099b1c2d,
+                0x05ed68d85e4d9e67,
+                0xcbc88deea1d36aa8,
+                0x000000000000009d,
+            ]))
+        };
+        let signature = Signature::new(r, s);
+
+        let generated_signature =
+            super::sign::<9, Secp521r1, _>(msg, &priv_key, Sha512::hash, || nonce);
+        assert_eq!(generated_signature, signature);
+
+        assert_eq!(
+            super::verify_signature(msg, &pub_key, Sha512::hash, &signature),
+            Ok(ValidSig)
+        );
+        assert_eq!(
+            super::verify_signature(&msg[1..], &pub_key, Sha512::hash, &signature),
+            Err(InvalidSig)
+        );
+    }
+
+    type P256Sig = Signature<4, <Secp256r1 as EllipticCurve<4>>::Order>;
+    type P521Sig = Signature<9, <Secp521r1 as EllipticCurve<9>>::Order>;
+
+    // the signature from the secp256r1 signing test
+    const DER_SIG: [u8; 72] = [
+        0x30, 0x46, 0x02, 0x21, 0x00, 0xf3, 0xac, 0x80, 0x61, 0xb5, 0x14, 0x79, 0x5b, 0x88, 0x43,
+        0xe3, 0xd6, 0x62, 0x95, 0x27, 0xed, 0x2a, 0xfd, 0x6b, 0x1f, 0x6a, 0x55, 0x5a, 0x7a, 0xca,
+        0xbb, 0x5e, 0x6f, 0x79, 0xc8, 0xc2, 0xac, 0x02, 0x21, 0x00, 0x8b, 0xf7, 0x78, 0x19, 0xca,
+        0x05, 0xa6, 0xb2, 0x78, 0x6c, 0x76, 0x26, 0x2b, 0xf7, 0x37, 0x1c, 0xef, 0x97, 0xb2, 0x18,
+        0xe9, 0x6f, 0x17, 0x5a, 0x3c, 0xcd, 0xda, 0x2a, 0xcc, 0x05, 0x89, 0x03,
+    ];
+
+    fn p256_sig() -> P256Sig {
+        let r = unsafe {
+            FieldElement::new_unchecked(UBigInt([
+                0xcabb5e6f79c8c2ac,
+                0x2afd6b1f6a555a7a,
+                0x8843e3d6629527ed,
+                0xf3ac8061b514795b,
+            ]))
+        };
+        let s = unsafe {
+            FieldElement::new_unchecked(UBigInt([
+                0x3ccdda2acc058903,
+                0xef97b218e96f175a,
+                0x786c76262bf7371c,
+                0x8bf77819ca05a6b2,
+            ]))
+        };
+        Signature::new(r, s)
+    }
+
+    #[test]
+    fn der() {
+        let sig = p256_sig();
+        let mut buf = [0; 80];
+        assert_eq!(P256Sig::der_max_len(), 72);
+        let len = sig.encode_der(&mut buf);
+        assert_eq!(buf[..len], DER_SIG);
+        assert_eq!(P256Sig::decode_der(&DER_SIG), Ok(sig));
+
+        // small integers don't need padding
+        let small = Signature::new(FieldElement::ONE, unsafe {
+            FieldElement::new_unchecked(UBigInt([0x80, 0, 0, 0]))
+        });
+        let len = small.encode_der(&mut buf);
+        assert_eq!(
+            buf[..len],
+            [0x30, 0x07, 0x02, 0x01, 0x01, 0x02, 0x02, 0x00, 0x80]
+        );
+        assert_eq!(P256Sig::decode_der(&buf[..len]), Ok(small));
+
+        // secp521r1 signatures need a long-form length
+        let max = <Secp521r1 as EllipticCurve<9>>::Order::MODULUS.sub(&UBigInt::ONE);
+        let big = Signature::new(unsafe { FieldElement::new_unchecked(max) }, unsafe {
+            FieldElement::new_unchecked(max)
+        });
+        let mut buf = [0; 160];
+        assert_eq!(P521Sig::der_max_len(), 141);
+        let len = big.encode_der(&mut buf);
+        assert_eq!(len, 3 + 2 * (2 + 66));
+        assert_eq!(buf[..5], [0x30, 0x81, 2 * (2 + 66), 0x02, 66]);
+        assert_eq!(P521Sig::decode_der(&buf[..len]), Ok(big));
+    }
+
+    #[test]
+    fn invalid_der() {
+        let mut der = DER_SIG;
+
+        // trailing data
+        let mut long = [0; 73];
+        long[..72].copy_from_slice(&der);
+        assert_eq!(P256Sig::decode_der(&long), Err(InvalidEncoding));
+
+        // truncated
+        assert_eq!(P256Sig::decode_der(&der[..71]), Err(InvalidEncoding));
+
+        // non-minimal length
+        assert_eq!(
+            P256Sig::decode_der(&[0x30, 0x81, 0x06, 0x02, 0x01, 0x01, 0x02, 0x01, 0x01]),
+            Err(InvalidEncoding)
+        );
+
+        // negative integer
+        assert_eq!(
+            P256Sig::decode_der(&[0x30, 0x06, 0x02, 0x01, 0x01, 0x02, 0x01, 0x81]),
+            Err(InvalidEncoding)
+        );
+
+        // non-minimal integer
+        assert_eq!(
+            P256Sig::decode_der(&[0x30, 0x07, 0x02, 0x01, 0x01, 0x02, 0x02, 0x00, 0x01]),
+            Err(InvalidEncoding)
+        );
+
+        // zero
+        assert_eq!(
+            P256Sig::decode_der(&[0x30, 0x06, 0x02, 0x01, 0x00, 0x02, 0x01, 0x01]),
+            Err(InvalidEncoding)
+        );
+
+        // `r` equal to the order
+        let order = <Secp256r1 as EllipticCurve<4>>::Order::MODULUS.to_be_bytes();
+        der[5..37].copy_from_slice(&order);
+        assert_eq!(P256Sig::decode_der(&der), Err(InvalidEncoding));
+
+        // wrong tag
+        der = DER_SIG;
+        der[0] = 0x31;
+        assert_eq!(P256Sig::decode_der(&der), Err(InvalidEncoding));
+    }
+
+    #[test]
+    fn raw() {
+        let sig = p256_sig();
+        let mut buf = [0; 64];
+        assert_eq!(P256Sig::raw_len(), 64);
+        assert_eq!(sig.encode_raw(&mut buf), 64);
+        assert_eq!(buf[..32], DER_SIG[5..37]);
+        assert_eq!(buf[32..], DER_SIG[40..]);
+        assert_eq!(P256Sig::decode_raw(&buf), Ok(sig));
+
+        assert_eq!(P256Sig::decode_raw(&buf[..63]), Err(InvalidEncoding));
+        assert_eq!(P256Sig::decode_raw(&[0; 64]), Err(InvalidEncoding));
+        let order = <Secp256r1 as EllipticCurve<4>>::Order::MODULUS.to_be_bytes();
+        buf[32..].copy_from_slice(&order);
+        assert_eq!(P256Sig::decode_raw(&buf), Err(InvalidEncoding));
+
+        // secp521r1 scalars are 66 bytes, not 72
+        assert_eq!(P521Sig::raw_len(), 132);
+    }
+
+    // test vectors from RFC 6979 section A.2.5
+
+    type P256Order = <Secp256r1 as EllipticCurve<4>>::Order;
+
+    fn p256_scalar(digits: [u64; 4]) -> FieldElement<4, P256Order> {
+        FieldElement::try_new(UBigInt(digits)).unwrap()
+    }
+
+    const RFC6979_KEY: [u64; 4] = [
+        0x7b8a622b120f6721,
+        0x4e50c3db36e89b12,
+        0x6b5c215767b1d693,
+        0xc9afa9d845ba7516,
+    ];
+
+    #[test]
+    fn deterministic() {
+        let priv_key = p256_scalar(RFC6979_KEY);
+        let sign_sha256 = |msg| {
+            super::sign_deterministic::<
+                4,
+                Secp256r1,
+                { Sha256::HASH_SIZE },
+                { Sha256::BLOCK_SIZE },
+                Sha256,
+            >(msg, &priv_key)
+        };
+        let sign_sha512 = |msg| {
+            super::sign_deterministic::<
+                4,
+                Secp256r1,
+                { Sha512::HASH_SIZE },
+                { Sha512::BLOCK_SIZE },
+                Sha512,
+            >(msg, &priv_key)
+        };
+
+        let expected = Signature::new(
+            p256_scalar([
+                0xc34d0ea84eaf3716,
+                0x9d2c877b56aaf991,
+                0x1140dd9cd45e81d6,
+                0xefd48b2aacb6a8fd,
+            ]),
+            p256_scalar([
+                0x4dc4ab2f843acda8,
+                0xf3e900dbb9aff406,
+                0xd436c7a1b6e29f65,
+                0xf7cb1c942d657c41,
+            ]),
+        );
+        assert_eq!(sign_sha256(b"sample"), expected);
+
+        let expected = Signature::new(
+            p256_scalar([
+                0x4f28d3b0b7d38367,
+                0xed3efcf6c5132b35,
+                0x71d881567b1ea663,
+                0xf1abb023518351cd,
+            ]),
+            p256_scalar([
+                0x0cc84250e46f0083,
+                0x5f267e60d3814b4c,
+                0xbd25926b49c64915,
+                0x019f4113742a2b14,
+            ]),
+        );
+        assert_eq!(sign_sha256(b"test"), expected);
+
+        let expected = Signature::new(
+            p256_scalar([
+                0x8d1097678eb97f00,
+                0x3fa109ec4568fd3f,
+                0x25488827e0495b0e,
+                0x8496a60b5e9b47c8,
+            ]),
+            p256_scalar([
+                0x6554f61fae3302fe,
+                0x49c028114f2460f9,
+                0xf9cb9edab740ea60,
+                0x2362ab1adbe2b8ad,
+            ]),
+        );
+        assert_eq!(sign_sha512(b"sample"), expected);
+
+        let expected = Signature::new(
+            p256_scalar([
+                0xfff1040ad1581a04,
+                0xcc35f46fa3c91816,
+                0x4788fd206c07cfa0,
+                0x461d93f31b654089,
+            ]),
+            p256_scalar([
+                0x7519e840d1194e55,
+                0x4ce5226e32dedae6,
+                0x7e72719c74820d30,
+                0x39af9f15de0db8d9,
+            ]),
+        );
+        assert_eq!(sign_sha512(b"test"), expected);
+    }
+
+    #[test]
+    fn hedged() {
+        let priv_key = p256_scalar(RFC6979_KEY);
+        let mut extra_entropy = [0; 32];
+        for (i, byte) in extra_entropy.iter_mut().enumerate() {
+            *byte = i as u8;
+        }
+        let sign = |extra_entropy: &[u8]| {
+            super::sign_hedged::<4, Secp256r1, { Sha256::HASH_SIZE }, { Sha256::BLOCK_SIZE }, Sha256>(
+                b"sample",
+                &priv_key,
+                extra_entropy,
+            )
+        };
+
+        let expected = Signature::new(
+            p256_scalar([
+                0x927810d7c4635d74,
+                0x43f988c40cbc4e23,
+                0x0881e195dae0665f,
+                0x25404cfdb1228f68,
+            ]),
+            p256_scalar([
+                0x53cb8754f1b819c3,
+                0x56db01a6e19c885b,
+                0x2fb16b5cf25d0d36,
+                0x8f076e7b9ea4bde9,
+            ]),
+        );
+        assert_eq!(sign(&extra_entropy), expected);
+
+        // no extra entropy is the same as deterministic signing
+        assert_eq!(
+            sign(&[]),
+            super::sign_deterministic::<
+                4,
+                Secp256r1,
+                { Sha256::HASH_SIZE },
+                { Sha256::BLOCK_SIZE },
+                Sha256,
+            >(b"sample", &priv_key)
+        );
+        assert_ne!(sign(&[]), expected);
+    }
 }
@@ -0,0 +1,465 @@
+//! Ed25519 signatures over the twisted Edwards form of Curve25519, as described in
+//! [RFC 8032](https://www.rfc-editor.org/rfc/rfc8032).
+//!
+//! Edwards curves aren't in Weierstrass form, so this doesn't implement
+//! [`EllipticCurve`](super::EllipticCurve).
+
+use super::ecdsa::{InvalidSig, ValidSig};
+use super::x25519::{cswap, Curve25519};
+use crate::big_int::UBigInt;
+use crate::finite_field::{FieldElement, FiniteField};
+use crate::hash::{BufHasher, Hasher, Sha512};
+
+/// The size of Ed25519 private keys (seeds) and public keys in bytes.
+pub const KEY_SIZE: usize = 32;
+
+/// The size of Ed25519 signatures in bytes.
+pub const SIG_SIZE: usize = 64;
+
+/// The field of scalars, whose modulus is the order of the base point.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug, Default, Hash)]
+struct Order;
+// SAFETY: `Self::MODULUS` (2^252 + 27742317777372353535851937790883648493) is prime.
+unsafe impl FiniteField<4> for Order {
+    const MODULUS: UBigInt<4> = UBigInt([
+        0x5812631a5cf5d3ed,
+        0x14def9dea2f79cd6,
+        0x0000000000000000,
+        0x1000000000000000,
+    ]);
+}
+
+type Fe = FieldElement<4, Curve25519>;
+type Scalar = FieldElement<4, Order>;
+
+/// The curve constant `d = -121665 / 121666`.
+// SAFETY: the value is less than the modulus.
+const D: Fe = unsafe {
+    Fe::new_unchecked(UBigInt([
+        0x75eb4dca135978a3,
+        0x00700a4d4141d8ab,
+        0x8cc740797779e898,
+        0x52036cee2b6ffe73,
+    ]))
+};
+
+/// `2 * d`.
+// SAFETY: the value is less than the modulus.
+const D2: Fe = unsafe {
+    Fe::new_unchecked(UBigInt([
+        0xebd69b9426b2f159,
+        0x00e0149a8283b156,
+        0x198e80f2eef3d130,
+        0x2406d9dc56dffce7,
+    ]))
+};
+
+/// A square root of -1.
+// SAFETY: the value is less than the modulus.
+const SQRT_M1: Fe = unsafe {
+    Fe::new_unchecked(UBigInt([
+        0xc4ee1b274a0ea0b0,
+        0x2f431806ad2fe478,
+        0x2b4d00993dfbd7a7,
+        0x2b8324804fc1df0b,
+    ]))
+};
+
+/// `(p - 5) / 8`, which is used to calculate square roots.
+const SQRT_EXP: UBigInt<4> = UBigInt([
+    0xfffffffffffffffd,
+    0xffffffffffffffff,
+    0xffffffffffffffff,
+    0x0fffffffffffffff,
+]);
+
+/// A point in extended homogeneous coordinates, where `x = X / Z`, `y = Y / Z`, and `x * y = T / Z`.
+#[derive(Clone, Copy, Debug)]
+struct EdwardsPoint {
+    x: Fe,
+    y: Fe,
+    z: Fe,
+    t: Fe,
+}
+
+impl EdwardsPoint {
+    const IDENTITY: Self = Self {
+        x: Fe::ZERO,
+        y: Fe::ONE,
+        z: Fe::ONE,
+        t: Fe::ZERO,
+    };
+
+    // SAFETY: all coordinates are less than the modulus.
+    const BASE_POINT: Self = unsafe {
+        Self {
+            x: Fe::new_unchecked(UBigInt([
+                0xc9562d608f25d51a,
+                0x692cc7609525a7b2,
+                0xc0a4e231fdd6dc5c,
+                0x216936d3cd6e53fe,
+            ])),
+            y: Fe::new_unchecked(UBigInt([
+                0x6666666666666658,
+                0x6666666666666666,
+                0x6666666666666666,
+                0x6666666666666666,
+            ])),
+            z: Fe::ONE,
+            t: Fe::new_unchecked(UBigInt([
+                0x6dde8ab3a5b7dda3,
+                0x20f09f80775152f5,
+                0x66ea4e8e64abe37d,
+                0x67875f0fd78b7665,
+            ])),
+        }
+    };
+
+    /// Decodes a point as described in RFC 8032 section 5.1.3.
+    ///
+    /// Returns `None` if `bytes` isn't the canonical encoding of a point on the curve.
+    fn decode(bytes: &[u8; KEY_SIZE]) -> Option<Self> {
+        let mut y = *bytes;
+        let x_0 = y[KEY_SIZE - 1] >> 7 == 1;
+        y[KEY_SIZE - 1] &= 0b0111_1111;
+        let y = Fe::try_new(UBigInt::<4>::from_le_bytes(y)).ok()?;
+
+        let y_2 = y.sqr();
+        let u = y_2.sub(&Fe::ONE);
+        let v = D.mul(&y_2).add(&Fe::ONE);
+
+        let v_3 = v.sqr().mul(&v);
+        let v_7 = v_3.sqr().mul(&v);
+        let mut x = u.mul(&v_3).mul(&pow(&u.mul(&v_7), &SQRT_EXP));
+
+        let v_x_2 = v.mul(&x.sqr());
+        if v_x_2 == u.neg() {
+            x.mul_assign(&SQRT_M1);
+        } else if v_x_2 != u {
+            return None;
+        }
+
+        if x == Fe::ZERO && x_0 {
+            return None;
+        }
+        if x.get_bit(0) != x_0 {
+            x.neg_assign();
+        }
+
+        Some(Self {
+            x,
+            y,
+            z: Fe::ONE,
+            t: x.mul(&y),
+        })
+    }
+
+    /// Encodes the point as described in RFC 8032 section 5.1.2.
+    fn encode(&self) -> [u8; KEY_SIZE] {
+        let z_inv = self.z.inverse();
+        let x = self.x.mul(&z_inv);
+        let y = self.y.mul(&z_inv);
+
+        let mut bytes = y.into_inner().to_le_bytes();
+        bytes[KEY_SIZE - 1] |= (x.get_bit(0) as u8) << 7;
+        bytes
+    }
+
+    /// Returns `self + rhs`.
+    ///
+    /// The formula is complete, so it works for any pair of points, including doubling.
+    fn add(&self, rhs: &Self) -> Self {
+        let a = self.y.sub(&self.x).mul(&rhs.y.sub(&rhs.x));
+        let b = self.y.add(&self.x).mul(&rhs.y.add(&rhs.x));
+        let c = self.t.mul(&D2).mul(&rhs.t);
+        let d = self.z.double().mul(&rhs.z);
+        let e = b.sub(&a);
+        let f = d.sub(&c);
+        let g = d.add(&c);
+        let h = b.add(&a);
+        Self {
+            x: e.mul(&f),
+            y: g.mul(&h),
+            z: f.mul(&g),
+            t: e.mul(&h),
+        }
+    }
+
+    /// Returns `self + self`.
+    fn double(&self) -> Self {
+        let a = self.x.sqr();
+        let b = self.y.sqr();
+        let c = self.z.sqr().double();
+        let h = a.add(&b);
+        let e = h.sub(&self.x.add(&self.y).sqr());
+        let g = a.sub(&b);
+        let f = c.add(&g);
+        Self {
+            x: e.mul(&f),
+            y: g.mul(&h),
+            z: f.mul(&g),
+            t: e.mul(&h),
+        }
+    }
+
+    /// Returns `self * scalar`.
+    ///
+    /// # Constant-timedness
+    /// The Montgomery ladder is constant-time.
+    fn mul_scalar(&self, scalar: &Scalar) -> Self {
+        let mut r_0 = Self::IDENTITY;
+        let mut r_1 = *self;
+        let mut swap = false;
+
+        // scalars are less than 2^253
+        for bit in (0..253).rev() {
+            let k_t = scalar.get_bit(bit);
+            swap ^= k_t;
+            r_0.cswap(swap, &mut r_1);
+            swap = k_t;
+
+            r_1 = r_0.add(&r_1);
+            r_0 = r_0.double();
+        }
+        r_0.cswap(swap, &mut r_1);
+        r_0
+    }
+
+    /// Swaps `self` and `other` if `swap` is `true`.
+    fn cswap(&mut self, swap: bool, other: &mut Self) {
+        cswap(swap, &mut self.x, &mut other.x);
+        cswap(swap, &mut self.y, &mut other.y);
+        cswap(swap, &mut self.z, &mut other.z);
+        cswap(swap, &mut self.t, &mut other.t);
+    }
+
+    /// Returns whether `self` and `other` represent the same point.
+    fn equals(&self, other: &Self) -> bool {
+        self.x.mul(&other.z) == other.x.mul(&self.z) && self.y.mul(&other.z) == other.y.mul(&self.z)
+    }
+}
+
+/// Returns `base` raised to the power of `exp`.
+fn pow(base: &Fe, exp: &UBigInt<4>) -> Fe {
+    let mut result = Fe::ONE;
+    for bit in (0..exp.count_bits()).rev() {
+        result.sqr_assign();
+        if exp.get_bit(bit) {
+            result.mul_assign(base);
+        }
+    }
+    result
+}
+
+/// Hashes the concatenation of `parts` with SHA-512 and reduces the result modulo the group order.
+fn hash_to_scalar(parts: &[&[u8]]) -> Scalar {
+    let mut hasher = BufHasher::<{ Sha512::HASH_SIZE }, { Sha512::BLOCK_SIZE }, Sha512>::new();
+    for part in parts {
+        hasher.update_with(part);
+    }
+    let hash = UBigInt::<8>::from_le_bytes(hasher.finish());
+    let reduced = hash.div(&Order::MODULUS.resize()).1.resize();
+    // SAFETY: the value was reduced modulo `Order::MODULUS`.
+    unsafe { Scalar::new_unchecked(reduced) }
+}
+
+/// An Ed25519 private key.
+#[derive(Clone)]
+pub struct SigningKey {
+    scalar: Scalar,
+    prefix: [u8; 32],
+    public_key: [u8; KEY_SIZE],
+}
+
+impl SigningKey {
+    /// Expands a 32-byte private key, as described in RFC 8032 section 5.1.5.
+    ///
+    /// To generate a new key, `seed` must be 32 bytes from a cryptographically secure random
+    /// number generator.
+    pub fn from_seed(seed: &[u8; KEY_SIZE]) -> Self {
+        let hash = Sha512::hash(seed);
+
+        let mut scalar: [u8; 32] = hash[..32].try_into().unwrap();
+        scalar[0] &= 0b1111_1000;
+        scalar[31] &= 0b0111_1111;
+        scalar[31] |= 0b0100_0000;
+        let scalar = Scalar::new(UBigInt::<4>::from_le_bytes(scalar));
+
+        let public_key = EdwardsPoint::BASE_POINT.mul_scalar(&scalar).encode();
+        Self {
+            scalar,
+            prefix: hash[32..].try_into().unwrap(),
+            public_key,
+        }
+    }
+
+    /// Returns the encoded public key.
+    pub const fn public_key(&self) -> &[u8; KEY_SIZE] {
+        &self.public_key
+    }
+
+    /// Signs `msg`, as described in RFC 8032 section 5.1.6.
+    ///
+    /// Signing is deterministic, so no random numbers are needed.
+    pub fn sign(&self, msg: &[u8]) -> [u8; SIG_SIZE] {
+        let r = hash_to_scalar(&[&self.prefix, msg]);
+        let big_r = EdwardsPoint::BASE_POINT.mul_scalar(&r).encode();
+
+        let k = hash_to_scalar(&[&big_r, &self.public_key, msg]);
+        let s = r.add(&k.mul(&self.scalar));
+
+        let mut sig = [0; SIG_SIZE];
+        sig[..32].copy_from_slice(&big_r);
+        sig[32..].copy_from_slice(&s.into_inner().to_le_bytes());
+        sig
+    }
+}
+
+impl core::fmt::Debug for SigningKey {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("SigningKey")
+            .field("public_key", &self.public_key)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Verifies an Ed25519 signature, as described in RFC 8032 section 5.1.7.
+///
+/// Non-canonical encodings of points and scalars are rejected.
+pub fn verify_signature(
+    pub_key: &[u8; KEY_SIZE],
+    msg: &[u8],
+    sig: &[u8; SIG_SIZE],
+) -> Result<ValidSig, InvalidSig> {
+    let big_a = EdwardsPoint::decode(pub_key).ok_or(InvalidSig)?;
+    let big_r_bytes: &[u8; 32] = sig[..32].try_into().unwrap();
+    let big_r = EdwardsPoint::decode(big_r_bytes).ok_or(InvalidSig)?;
+    let s = Scalar::try_new(UBigInt::<4>::from_le_bytes(sig[32..].try_into().unwrap()))
+        .map_err(|_| InvalidSig)?;
+
+    let k = hash_to_scalar(&[big_r_bytes, pub_key, msg]);
+
+    let lhs = EdwardsPoint::BASE_POINT.mul_scalar(&s);
+    let rhs = big_r.add(&big_a.mul_scalar(&k));
+    match lhs.equals(&rhs) {
+        true => Ok(ValidSig),
+        false => Err(InvalidSig),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{verify_signature, Order, SigningKey, KEY_SIZE, SIG_SIZE};
+    use crate::ec::ecdsa::{InvalidSig, ValidSig};
+    use crate::finite_field::FiniteField;
+
+    // test vectors from RFC 8032 section 7.1
+
+    const SEED_1: [u8; KEY_SIZE] = [
+        0x9d, 0x61, 0xb1, 0x9d, 0xef, 0xfd, 0x5a, 0x60, 0xba, 0x84, 0x4a, 0xf4, 0x92, 0xec, 0x2c,
+        0xc4, 0x44, 0x49, 0xc5, 0x69, 0x7b, 0x32, 0x69, 0x19, 0x70, 0x3b, 0xac, 0x03, 0x1c, 0xae,
+        0x7f, 0x60,
+    ];
+    const PUB_1: [u8; KEY_SIZE] = [
+        0xd7, 0x5a, 0x98, 0x01, 0x82, 0xb1, 0x0a, 0xb7, 0xd5, 0x4b, 0xfe, 0xd3, 0xc9, 0x64, 0x07,
+        0x3a, 0x0e, 0xe1, 0x72, 0xf3, 0xda, 0xa6, 0x23, 0x25, 0xaf, 0x02, 0x1a, 0x68, 0xf7, 0x07,
+        0x51, 0x1a,
+    ];
+    const SIG_1: [u8; SIG_SIZE] = [
+        0xe5, 0x56, 0x43, 0x00, 0xc3, 0x60, 0xac, 0x72, 0x90, 0x86, 0xe2, 0xcc, 0x80, 0x6e, 0x82,
+        0x8a, 0x84, 0x87, 0x7f, 0x1e, 0xb8, 0xe5, 0xd9, 0x74, 0xd8, 0x73, 0xe0, 0x65, 0x22, 0x49,
+        0x01, 0x55, 0x5f, 0xb8, 0x82, 0x15, 0x90, 0xa3, 0x3b, 0xac, 0xc6, 0x1e, 0x39, 0x70, 0x1c,
+        0xf9, 0xb4, 0x6b, 0xd2, 0x5b, 0xf5, 0xf0, 0x59, 0x5b, 0xbe, 0x24, 0x65, 0x51, 0x41, 0x43,
+        0x8e, 0x7a, 0x10, 0x0b,
+    ];
+
+    const SEED_2: [u8; KEY_SIZE] = [
+        0x4c, 0xcd, 0x08, 0x9b, 0x28, 0xff, 0x96, 0xda, 0x9d, 0xb6, 0xc3, 0x46, 0xec, 0x11, 0x4e,
+        0x0f, 0x5b, 0x8a, 0x31, 0x9f, 0x35, 0xab, 0xa6, 0x24, 0xda, 0x8c, 0xf6, 0xed, 0x4f, 0xb8,
+        0xa6, 0xfb,
+    ];
+    const PUB_2: [u8; KEY_SIZE] = [
+        0x3d, 0x40, 0x17, 0xc3, 0xe8, 0x43, 0x89, 0x5a, 0x92, 0xb7, 0x0a, 0xa7, 0x4d, 0x1b, 0x7e,
+        0xbc, 0x9c, 0x98, 0x2c, 0xcf, 0x2e, 0xc4, 0x96, 0x8c, 0xc0, 0xcd, 0x55, 0xf1, 0x2a, 0xf4,
+        0x66, 0x0c,
+    ];
+    const MSG_2: [u8; 1] = [0x72];
+    const SIG_2: [u8; SIG_SIZE] = [
+        0x92, 0xa0, 0x09, 0xa9, 0xf0, 0xd4, 0xca, 0xb8, 0x72, 0x0e, 0x82, 0x0b, 0x5f, 0x64, 0x25,
+        0x40, 0xa2, 0xb2, 0x7b, 0x54, 0x16, 0x50, 0x3f, 0x8f, 0xb3, 0x76, 0x22, 0x23, 0xeb, 0xdb,
+        0x69, 0xda, 0x08, 0x5a, 0xc1, 0xe4, 0x3e, 0x15, 0x99, 0x6e, 0x45, 0x8f, 0x36, 0x13, 0xd0,
+        0xf1, 0x1d, 0x8c, 0x38, 0x7b, 0x2e, 0xae, 0xb4, 0x30, 0x2a, 0xee, 0xb0, 0x0d, 0x29, 0x16,
+        0x12, 0xbb, 0x0c, 0x00,
+    ];
+
+    const SEED_3: [u8; KEY_SIZE] = [
+        0xc5, 0xaa, 0x8d, 0xf4, 0x3f, 0x9f, 0x83, 0x7b, 0xed, 0xb7, 0x44, 0x2f, 0x31, 0xdc, 0xb7,
+        0xb1, 0x66, 0xd3, 0x85, 0x35, 0x07, 0x6f, 0x09, 0x4b, 0x85, 0xce, 0x3a, 0x2e, 0x0b, 0x44,
+        0x58, 0xf7,
+    ];
+    const PUB_3: [u8; KEY_SIZE] = [
+        0xfc, 0x51, 0xcd, 0x8e, 0x62, 0x18, 0xa1, 0xa3, 0x8d, 0xa4, 0x7e, 0xd0, 0x02, 0x30, 0xf0,
+        0x58, 0x08, 0x16, 0xed, 0x13, 0xba, 0x33, 0x03, 0xac, 0x5d, 0xeb, 0x91, 0x15, 0x48, 0x90,
+        0x80, 0x25,
+    ];
+    const MSG_3: [u8; 2] = [0xaf, 0x82];
+    const SIG_3: [u8; SIG_SIZE] = [
+        0x62, 0x91, 0xd6, 0x57, 0xde, 0xec, 0x24, 0x02, 0x48, 0x27, 0xe6, 0x9c, 0x3a, 0xbe, 0x01,
+        0xa3, 0x0c, 0xe5, 0x48, 0xa2, 0x84, 0x74, 0x3a, 0x44, 0x5e, 0x36, 0x80, 0xd7, 0xdb, 0x5a,
+        0xc3, 0xac, 0x18, 0xff, 0x9b, 0x53, 0x8d, 0x16, 0xf2, 0x90, 0xae, 0x67, 0xf7, 0x60, 0x98,
+        0x4d, 0xc6, 0x59, 0x4a, 0x7c, 0x15, 0xe9, 0x71, 0x6e, 0xd2, 0x8d, 0xc0, 0x27, 0xbe, 0xce,
+        0xea, 0x1e, 0xc4, 0x0a,
+    ];
+
+    #[test]
+    fn keygen() {
+        assert_eq!(SigningKey::from_seed(&SEED_1).public_key(), &PUB_1);
+        assert_eq!(SigningKey::from_seed(&SEED_2).public_key(), &PUB_2);
+        assert_eq!(SigningKey::from_seed(&SEED_3).public_key(), &PUB_3);
+    }
+
+    #[test]
+    fn sign() {
+        assert_eq!(SigningKey::from_seed(&SEED_1).sign(&[]), SIG_1);
+        assert_eq!(SigningKey::from_seed(&SEED_2).sign(&MSG_2), SIG_2);
+        assert_eq!(SigningKey::from_seed(&SEED_3).sign(&MSG_3), SIG_3);
+    }
+
+    #[test]
+    fn verify() {
+        assert_eq!(verify_signature(&PUB_1, &[], &SIG_1), Ok(ValidSig));
+        assert_eq!(verify_signature(&PUB_2, &MSG_2, &SIG_2), Ok(ValidSig));
+        assert_eq!(verify_signature(&PUB_3, &MSG_3, &SIG_3), Ok(ValidSig));
+
+        assert_eq!(verify_signature(&PUB_2, &MSG_3, &SIG_2), Err(InvalidSig));
+        assert_eq!(verify_signature(&PUB_3, &MSG_2, &SIG_2), Err(InvalidSig));
+
+        let mut sig = SIG_3;
+        sig[5] ^= 1;
+        assert_eq!(verify_signature(&PUB_3, &MSG_3, &sig), Err(InvalidSig));
+    }
+
+    #[test]
+    fn non_canonical() {
+        // `S + L` verifies with a lax implementation
+        let mut sig = SIG_1;
+        let mut carry = 0;
+        for (byte, l) in sig[32..].iter_mut().zip(Order::MODULUS.to_le_bytes()) {
+            let sum = *byte as u16 + l as u16 + carry;
+            *byte = sum as u8;
+            carry = sum >> 8;
+        }
+        assert_eq!(verify_signature(&PUB_1, &[], &sig), Err(InvalidSig));
+
+        // y = p is the non-canonical encoding of y = 0
+        let mut pub_key = [0xff; KEY_SIZE];
+        pub_key[0] = 0xed;
+        pub_key[KEY_SIZE - 1] = 0x7f;
+        assert_eq!(verify_signature(&pub_key, &[], &SIG_1), Err(InvalidSig));
+
+        // y = 2 isn't on the curve
+        let mut pub_key = [0; KEY_SIZE];
+        pub_key[0] = 2;
+        assert_eq!(verify_signature(&pub_key, &[], &SIG_1), Err(InvalidSig));
+    }
+}
@@ -3,12 +3,12 @@ use crate::finite_field::FieldElement;
 use super::{super::EllipticCurve, AffineInfinity, ProjectivePoint};
 /// A point on an elliptic curve in affine representation.
 #[derive(Clone, Copy, Eq, PartialEq)]
-pub struct AffinePoint<C: EllipticCurve> {
-    x: FieldElement<4, C>,
-    y: FieldElement<4, C>,
+pub struct AffinePoint<const N: usize, C: EllipticCurve<N>> {
+    x: FieldElement<N, C>,
+    y: FieldElement<N, C>,
 }
 
-impl<C: EllipticCurve> core::fmt::Display for AffinePoint<C> {
+impl<const N: usize, C: EllipticCurve<N>> core::fmt::Display for AffinePoint<N, C> {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         f.write_str("(")?;
         core::fmt::Display::fmt(&self.x, f)?;
@@ -20,7 +20,7 @@ impl<C: EllipticCurve> core::fmt::Display for AffinePoint<C> {
     }
 }
 
-impl<C: EllipticCurve> core::fmt::Debug for AffinePoint<C> {
+impl<const N: usize, C: EllipticCurve<N>> core::fmt::Debug for AffinePoint<N, C> {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         f.write_str(concat!(stringify!(AffinePoint), " { x: "))?;
         core::fmt::Debug::fmt(&self.x, f)?;
@@ -32,43 +32,27 @@ impl<C: EllipticCurve> core::fmt::Debug for AffinePoint<C> {
     }
 }
 
-impl<C: EllipticCurve> AffinePoint<C> {
-    pub fn is_on_curve(x: &FieldElement<4, C>, y: &FieldElement<4, C>) -> bool {
-        let mut x_cube_ax_b = x.sqr();
-        x_cube_ax_b.mul_assign(x);
-
-        x_cube_ax_b.add_assign(&x.mul(&C::A));
-        x_cube_ax_b.add_assign(&C::B);
-        y.sqr() == x_cube_ax_b
-    }
-
-    pub fn new(x: FieldElement<4, C>, y: FieldElement<4, C>) -> Option<Self> {
-        match Self::is_on_curve(&x, &y) {
-            true => Some(Self { x, y }),
-            false => None,
-        }
-    }
-
+impl<const N: usize, C: EllipticCurve<N>> AffinePoint<N, C> {
     /// Returns the x-value of `self`.
-    pub fn x(&self) -> FieldElement<4, C> {
+    pub fn x(&self) -> FieldElement<N, C> {
         self.x
     }
 
-    pub fn x_ref(&self) -> &FieldElement<4, C> {
+    pub fn x_ref(&self) -> &FieldElement<N, C> {
         &self.x
     }
 
     /// Returns the y-value of `self`.
-    pub fn y(&self) -> FieldElement<4, C> {
+    pub fn y(&self) -> FieldElement<N, C> {
         self.y
     }
 
-    pub fn y_ref(&self) -> &FieldElement<4, C> {
+    pub fn y_ref(&self) -> &FieldElement<N, C> {
         &self.y
     }
 
     /// Converts `self` into its projective representation.
-    pub const fn as_projective(self) -> ProjectivePoint<C> {
+    pub const fn as_projective(self) -> ProjectivePoint<N, C> {
         // # SAFETY: The projective value is still on the curve.
         unsafe { ProjectivePoint::new_unchecked(self.x, self.y, FieldElement::ONE) }
     }
@@ -78,15 +62,10 @@ impl<C: EllipticCurve> AffinePoint<C> {
     /// # Safety
     /// The point must be on the curve. If the point isn't on the curve, it will result in
     /// undefined behavior.
-    pub const unsafe fn new_unchecked(x: FieldElement<4, C>, y: FieldElement<4, C>) -> Self {
+    pub const unsafe fn new_unchecked(x: FieldElement<N, C>, y: FieldElement<N, C>) -> Self {
         Self { x, y }
     }
 
-    pub fn add(&self, rhs: &Self) -> Self {
-        let slope = rhs.y.sub(&self.y).div(&rhs.x.sub(&self.x));
-        self.third_point_on_line(rhs, &slope)
-    }
-
     pub fn neg(&self) -> Self {
         Self {
             x: self.x,
@@ -97,39 +76,70 @@ impl<C: EllipticCurve> AffinePoint<C> {
     pub fn neg_assign(&mut self) {
         self.y.neg_assign();
     }
+}
 
-    pub fn double(&self) -> Self {
-        let slope = {
-            let mut slope = self.x.sqr();
-            slope.mul_digit_assign(3);
-            slope.add_assign(&C::A);
-            let tmp = self.y.double();
-            slope.div(&tmp)
-        };
-        self.third_point_on_line(self, &slope)
-    }
-
-    pub fn double_assign(&mut self) {
-        *self = self.double();
-    }
-
-    fn third_point_on_line(&self, other: &Self, slope: &FieldElement<4, C>) -> Self {
-        let mut x = slope.sqr();
-        x.sub_assign(&self.x);
-        x.sub_assign(&other.x);
+macro_rules! impl_affine_point {
+    ($n:literal) => {
+        impl<C: EllipticCurve<$n>> AffinePoint<$n, C> {
+            pub fn is_on_curve(x: &FieldElement<$n, C>, y: &FieldElement<$n, C>) -> bool {
+                let mut x_cube_ax_b = x.sqr();
+                x_cube_ax_b.mul_assign(x);
+
+                x_cube_ax_b.add_assign(&x.mul(&C::A));
+                x_cube_ax_b.add_assign(&C::B);
+                y.sqr() == x_cube_ax_b
+            }
+
+            pub fn new(x: FieldElement<$n, C>, y: FieldElement<$n, C>) -> Option<Self> {
+                match Self::is_on_curve(&x, &y) {
+                    true => Some(Self { x, y }),
+                    false => None,
+                }
+            }
+
+            pub fn add(&self, rhs: &Self) -> Self {
+                let slope = rhs.y.sub(&self.y).div(&rhs.x.sub(&self.x));
+                self.third_point_on_line(rhs, &slope)
+            }
+
+            pub fn double(&self) -> Self {
+                let slope = {
+                    let mut slope = self.x.sqr();
+                    slope.mul_digit_assign(3);
+                    slope.add_assign(&C::A);
+                    let tmp = self.y.double();
+                    slope.div(&tmp)
+                };
+                self.third_point_on_line(self, &slope)
+            }
+
+            pub fn double_assign(&mut self) {
+                *self = self.double();
+            }
+
+            fn third_point_on_line(&self, other: &Self, slope: &FieldElement<$n, C>) -> Self {
+                let mut x = slope.sqr();
+                x.sub_assign(&self.x);
+                x.sub_assign(&other.x);
+
+                let mut y = slope.mul(&self.x.sub(&x));
+                y.sub_assign(&self.y);
+                Self { x, y }
+            }
+        }
 
-        let mut y = slope.mul(&self.x.sub(&x));
-        y.sub_assign(&self.y);
-        Self { x, y }
-    }
+        impl<C: EllipticCurve<$n>> TryFrom<ProjectivePoint<$n, C>> for AffinePoint<$n, C> {
+            type Error = AffineInfinity;
+            fn try_from(value: ProjectivePoint<$n, C>) -> Result<AffinePoint<$n, C>, Self::Error> {
+                value.as_affine().ok_or(AffineInfinity)
+            }
+        }
+    };
 }
 
-impl<C: EllipticCurve> TryFrom<ProjectivePoint<C>> for AffinePoint<C> {
-    type Error = AffineInfinity;
-    fn try_from(value: ProjectivePoint<C>) -> Result<AffinePoint<C>, Self::Error> {
-        value.as_affine().ok_or(AffineInfinity)
-    }
-}
+impl_affine_point!(4);
+impl_affine_point!(6);
+impl_affine_point!(9);
 
 #[cfg(test)]
 mod tests {
@@ -144,7 +154,7 @@ mod tests {
         let base_point = Secp256r1::BASE_POINT
             .as_affine()
             .expect("BP is valid affine point");
-        assert!(AffinePoint::is_on_curve(
+        assert!(AffinePoint::<4, _>::is_on_curve(
             base_point.x_ref(),
             base_point.y_ref()
         ));
@@ -165,7 +175,7 @@ mod tests {
                 0x07775510db8ed040,
             ]))
         };
-        assert!(AffinePoint::is_on_curve(&x, &y));
+        assert!(AffinePoint::<4, _>::is_on_curve(&x, &y));
     }
 
     #[test]
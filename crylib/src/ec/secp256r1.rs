@@ -28,8 +28,8 @@ unsafe impl FiniteField<4> for P256Order {
     ]);
 }
 
-impl EllipticCurve for Secp256r1 {
-    const BASE_POINT: ProjectivePoint<Self> = unsafe {
+impl EllipticCurve<4> for Secp256r1 {
+    const BASE_POINT: ProjectivePoint<4, Self> = unsafe {
         AffinePoint::new_unchecked(
             FieldElement::new_unchecked(UBigInt([
                 0xf4a13945d898c296,
@@ -0,0 +1,81 @@
+use crate::big_int::UBigInt;
+use crate::finite_field::{FieldElement, FiniteField};
+
+use super::EllipticCurve;
+use super::{AffinePoint, ProjectivePoint};
+
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug, Default, Hash)]
+pub struct Secp384r1;
+// SAFETY: `Self::MODULUS` is prime.
+unsafe impl FiniteField<6> for Secp384r1 {
+    const MODULUS: UBigInt<6> = UBigInt([
+        0x00000000ffffffff,
+        0xffffffff00000000,
+        0xfffffffffffffffe,
+        0xffffffffffffffff,
+        0xffffffffffffffff,
+        0xffffffffffffffff,
+    ]);
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug, Default, Hash)]
+pub struct P384Order;
+// SAFETY: `Self::MODULUS` is prime.
+unsafe impl FiniteField<6> for P384Order {
+    const MODULUS: UBigInt<6> = UBigInt([
+        0xecec196accc52973,
+        0x581a0db248b0a77a,
+        0xc7634d81f4372ddf,
+        0xffffffffffffffff,
+        0xffffffffffffffff,
+        0xffffffffffffffff,
+    ]);
+}
+
+impl EllipticCurve<6> for Secp384r1 {
+    const BASE_POINT: ProjectivePoint<6, Self> = unsafe {
+        AffinePoint::new_unchecked(
+            FieldElement::new_unchecked(UBigInt([
+                0x3a545e3872760ab7,
+                0x5502f25dbf55296c,
+                0x59f741e082542a38,
+                0x6e1d3b628ba79b98,
+                0x8eb1c71ef320ad74,
+                0xaa87ca22be8b0537,
+            ])),
+            FieldElement::<6, Self>::new_unchecked(UBigInt([
+                0x7a431d7c90ea0e5f,
+                0x0a60b1ce1d7e819d,
+                0xe9da3113b5f0b8c0,
+                0xf8f41dbd289a147c,
+                0x5d9e98bf9292dc29,
+                0x3617de4a96262c6f,
+            ])),
+        )
+        .as_projective()
+    };
+
+    const A: FieldElement<6, Self> = unsafe {
+        FieldElement::new_unchecked(UBigInt([
+            0x00000000fffffffc,
+            0xffffffff00000000,
+            0xfffffffffffffffe,
+            0xffffffffffffffff,
+            0xffffffffffffffff,
+            0xffffffffffffffff,
+        ]))
+    };
+
+    const B: FieldElement<6, Self> = unsafe {
+        FieldElement::new_unchecked(UBigInt([
+            0x2a85c8edd3ec2aef,
+            0xc656398d8a2ed19d,
+            0x0314088f5013875a,
+            0x181d9c6efe814112,
+            0x988e056be3f82d19,
+            0xb3312fa7e23ee7e4,
+        ]))
+    };
+
+    type Order = P384Order;
+}
@@ -0,0 +1,99 @@
+use crate::big_int::UBigInt;
+use crate::finite_field::{FieldElement, FiniteField};
+
+use super::EllipticCurve;
+use super::{AffinePoint, ProjectivePoint};
+
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug, Default, Hash)]
+pub struct Secp521r1;
+// SAFETY: `Self::MODULUS` is prime.
+unsafe impl FiniteField<9> for Secp521r1 {
+    const MODULUS: UBigInt<9> = UBigInt([
+        0xffffffffffffffff,
+        0xffffffffffffffff,
+        0xffffffffffffffff,
+        0xffffffffffffffff,
+        0xffffffffffffffff,
+        0xffffffffffffffff,
+        0xffffffffffffffff,
+        0xffffffffffffffff,
+        0x00000000000001ff,
+    ]);
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug, Default, Hash)]
+pub struct P521Order;
+// SAFETY: `Self::MODULUS` is prime.
+unsafe impl FiniteField<9> for P521Order {
+    const MODULUS: UBigInt<9> = UBigInt([
+        0xbb6fb71e91386409,
+        0x3bb5c9b8899c47ae,
+        0x7fcc0148f709a5d0,
+        0x51868783bf2f966b,
+        0xfffffffffffffffa,
+        0xffffffffffffffff,
+        0xffffffffffffffff,
+        0xffffffffffffffff,
+        0x00000000000001ff,
+    ]);
+}
+
+impl EllipticCurve<9> for Secp521r1 {
+    const BASE_POINT: ProjectivePoint<9, Self> = unsafe {
+        AffinePoint::new_unchecked(
+            FieldElement::new_unchecked(UBigInt([
+                0xf97e7e31c2e5bd66,
+                0x3348b3c1856a429b,
+                0xfe1dc127a2ffa8de,
+                0xa14b5e77efe75928,
+                0xf828af606b4d3dba,
+                0x9c648139053fb521,
+                0x9e3ecb662395b442,
+                0x858e06b70404e9cd,
+                0x00000000000000c6,
+            ])),
+            FieldElement::<9, Self>::new_unchecked(UBigInt([
+                0x88be94769fd16650,
+                0x353c7086a272c240,
+                0xc550b9013fad0761,
+                0x97ee72995ef42640,
+                0x17afbd17273e662c,
+                0x98f54449579b4468,
+                0x5c8a5fb42c7d1bd9,
+                0x39296a789a3bc004,
+                0x0000000000000118,
+            ])),
+        )
+        .as_projective()
+    };
+
+    const A: FieldElement<9, Self> = unsafe {
+        FieldElement::new_unchecked(UBigInt([
+            0xfffffffffffffffc,
+            0xffffffffffffffff,
+            0xffffffffffffffff,
+            0xffffffffffffffff,
+            0xffffffffffffffff,
+            0xffffffffffffffff,
+            0xffffffffffffffff,
+            0xffffffffffffffff,
+            0x00000000000001ff,
+        ]))
+    };
+
+    const B: FieldElement<9, Self> = unsafe {
+        FieldElement::new_unchecked(UBigInt([
+            0xef451fd46b503f00,
+            0x3573df883d2c34f1,
+            0x1652c0bd3bb1bf07,
+            0x56193951ec7e937b,
+            0xb8b489918ef109e1,
+            0xa2da725b99b315f3,
+            0x929a21a0b68540ee,
+            0x953eb9618e1c9a1f,
+            0x0000000000000051,
+        ]))
+    };
+
+    type Order = P521Order;
+}
@@ -0,0 +1,205 @@
+//! X25519 Diffie-Hellman key exchange over Curve25519, as described in
+//! [RFC 7748](https://www.rfc-editor.org/rfc/rfc7748).
+//!
+//! Curve25519 is a Montgomery curve, so it doesn't implement [`EllipticCurve`](super::EllipticCurve).
+
+use crate::big_int::UBigInt;
+use crate::finite_field::{FieldElement, FiniteField};
+
+/// The field that Curve25519 is defined over.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug, Default, Hash)]
+pub struct Curve25519;
+// SAFETY: `Self::MODULUS` (2^255 - 19) is prime.
+unsafe impl FiniteField<4> for Curve25519 {
+    const MODULUS: UBigInt<4> = UBigInt([
+        0xffffffffffffffed,
+        0xffffffffffffffff,
+        0xffffffffffffffff,
+        0x7fffffffffffffff,
+    ]);
+}
+
+/// The size of X25519 private keys, public keys, and shared secrets in bytes.
+pub const KEY_SIZE: usize = 32;
+
+/// The u-coordinate of the base point.
+pub const BASE_POINT: [u8; KEY_SIZE] = {
+    let mut point = [0; KEY_SIZE];
+    point[0] = 9;
+    point
+};
+
+/// `(A - 2) / 4`, where `A` is the Curve25519 coefficient.
+const A24: u64 = 121665;
+
+/// Multiplies the point with u-coordinate `u` by `scalar`, returning the resulting u-coordinate.
+///
+/// This is the `X25519` function from RFC 7748. Both inputs and the output are little-endian.
+/// Use [`BASE_POINT`] as `u` to calculate a public key.
+///
+/// The result is all zeros if `u` is a point of small order, which callers performing key
+/// exchange should check for.
+///
+/// # Constant-timedness
+/// The Montgomery ladder is constant-time. The final inversion is not.
+pub fn x25519(scalar: &[u8; KEY_SIZE], u: &[u8; KEY_SIZE]) -> [u8; KEY_SIZE] {
+    let mut scalar = *scalar;
+    scalar[0] &= 0b1111_1000;
+    scalar[KEY_SIZE - 1] &= 0b0111_1111;
+    scalar[KEY_SIZE - 1] |= 0b0100_0000;
+    let scalar = UBigInt::<4>::from_le_bytes(scalar);
+
+    let mut u = *u;
+    // the most significant bit is ignored
+    u[KEY_SIZE - 1] &= 0b0111_1111;
+    // non-canonical values are reduced
+    let x_1 = FieldElement::<4, Curve25519>::new(UBigInt::<4>::from_le_bytes(u));
+
+    let mut x_2 = FieldElement::ONE;
+    let mut z_2 = FieldElement::ZERO;
+    let mut x_3 = x_1;
+    let mut z_3 = FieldElement::ONE;
+    let mut swap = false;
+
+    for bit in (0..255).rev() {
+        let k_t = scalar.get_bit(bit);
+        swap ^= k_t;
+        cswap(swap, &mut x_2, &mut x_3);
+        cswap(swap, &mut z_2, &mut z_3);
+        swap = k_t;
+
+        let a = x_2.add(&z_2);
+        let aa = a.sqr();
+        let b = x_2.sub(&z_2);
+        let bb = b.sqr();
+        let e = aa.sub(&bb);
+        let c = x_3.add(&z_3);
+        let d = x_3.sub(&z_3);
+        let da = d.mul(&a);
+        let cb = c.mul(&b);
+
+        x_3 = da.add(&cb).sqr();
+        z_3 = x_1.mul(&da.sub(&cb).sqr());
+        x_2 = aa.mul(&bb);
+        z_2 = e.mul(&aa.add(&e.mul_digit(A24)));
+    }
+    cswap(swap, &mut x_2, &mut x_3);
+    cswap(swap, &mut z_2, &mut z_3);
+
+    // `z_2` is only zero if `u` has small order, in which case the result is zero
+    if z_2 == FieldElement::ZERO {
+        return [0; KEY_SIZE];
+    }
+    x_2.mul(&z_2.inverse()).into_inner().to_le_bytes()
+}
+
+/// Swaps `a` and `b` if `swap` is `true`.
+///
+/// # Constant-timedness
+/// This is a constant-time operation.
+pub(super) fn cswap(
+    swap: bool,
+    a: &mut FieldElement<4, Curve25519>,
+    b: &mut FieldElement<4, Curve25519>,
+) {
+    let mask = a.xor(b).and_bool(swap);
+    // SAFETY: `a` and `b` are either swapped or left unchanged, so they stay in the field.
+    unsafe {
+        *a = FieldElement::new_unchecked(a.xor(&mask));
+        *b = FieldElement::new_unchecked(b.xor(&mask));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{x25519, BASE_POINT};
+
+    // test vectors from RFC 7748
+
+    #[test]
+    fn scalar_mul() {
+        let scalar = [
+            0xa5, 0x46, 0xe3, 0x6b, 0xf0, 0x52, 0x7c, 0x9d, 0x3b, 0x16, 0x15, 0x4b, 0x82, 0x46,
+            0x5e, 0xdd, 0x62, 0x14, 0x4c, 0x0a, 0xc1, 0xfc, 0x5a, 0x18, 0x50, 0x6a, 0x22, 0x44,
+            0xba, 0x44, 0x9a, 0xc4,
+        ];
+        let u = [
+            0xe6, 0xdb, 0x68, 0x67, 0x58, 0x30, 0x30, 0xdb, 0x35, 0x94, 0xc1, 0xa4, 0x24, 0xb1,
+            0x5f, 0x7c, 0x72, 0x66, 0x24, 0xec, 0x26, 0xb3, 0x35, 0x3b, 0x10, 0xa9, 0x03, 0xa6,
+            0xd0, 0xab, 0x1c, 0x4c,
+        ];
+        let output = [
+            0xc3, 0xda, 0x55, 0x37, 0x9d, 0xe9, 0xc6, 0x90, 0x8e, 0x94, 0xea, 0x4d, 0xf2, 0x8d,
+            0x08, 0x4f, 0x32, 0xec, 0xcf, 0x03, 0x49, 0x1c, 0x71, 0xf7, 0x54, 0xb4, 0x07, 0x55,
+            0x77, 0xa2, 0x85, 0x52,
+        ];
+        assert_eq!(x25519(&scalar, &u), output);
+
+        let scalar = [
+            0x4b, 0x66, 0xe9, 0xd4, 0xd1, 0xb4, 0x67, 0x3c, 0x5a, 0xd2, 0x26, 0x91, 0x95, 0x7d,
+            0x6a, 0xf5, 0xc1, 0x1b, 0x64, 0x21, 0xe0, 0xea, 0x01, 0xd4, 0x2c, 0xa4, 0x16, 0x9e,
+            0x79, 0x18, 0xba, 0x0d,
+        ];
+        let u = [
+            0xe5, 0x21, 0x0f, 0x12, 0x78, 0x68, 0x11, 0xd3, 0xf4, 0xb7, 0x95, 0x9d, 0x05, 0x38,
+            0xae, 0x2c, 0x31, 0xdb, 0xe7, 0x10, 0x6f, 0xc0, 0x3c, 0x3e, 0xfc, 0x4c, 0xd5, 0x49,
+            0xc7, 0x15, 0xa4, 0x93,
+        ];
+        let output = [
+            0x95, 0xcb, 0xde, 0x94, 0x76, 0xe8, 0x90, 0x7d, 0x7a, 0xad, 0xe4, 0x5c, 0xb4, 0xb8,
+            0x73, 0xf8, 0x8b, 0x59, 0x5a, 0x68, 0x79, 0x9f, 0xa1, 0x52, 0xe6, 0xf8, 0xf7, 0x64,
+            0x7a, 0xac, 0x79, 0x57,
+        ];
+        assert_eq!(x25519(&scalar, &u), output);
+    }
+
+    #[test]
+    fn iterated() {
+        let output = [
+            0x42, 0x2c, 0x8e, 0x7a, 0x62, 0x27, 0xd7, 0xbc, 0xa1, 0x35, 0x0b, 0x3e, 0x2b, 0xb7,
+            0x27, 0x9f, 0x78, 0x97, 0xb8, 0x7b, 0xb6, 0x85, 0x4b, 0x78, 0x3c, 0x60, 0xe8, 0x03,
+            0x11, 0xae, 0x30, 0x79,
+        ];
+        assert_eq!(x25519(&BASE_POINT, &BASE_POINT), output);
+    }
+
+    #[test]
+    fn key_exchange() {
+        let alice_priv = [
+            0x77, 0x07, 0x6d, 0x0a, 0x73, 0x18, 0xa5, 0x7d, 0x3c, 0x16, 0xc1, 0x72, 0x51, 0xb2,
+            0x66, 0x45, 0xdf, 0x4c, 0x2f, 0x87, 0xeb, 0xc0, 0x99, 0x2a, 0xb1, 0x77, 0xfb, 0xa5,
+            0x1d, 0xb9, 0x2c, 0x2a,
+        ];
+        let alice_pub = [
+            0x85, 0x20, 0xf0, 0x09, 0x89, 0x30, 0xa7, 0x54, 0x74, 0x8b, 0x7d, 0xdc, 0xb4, 0x3e,
+            0xf7, 0x5a, 0x0d, 0xbf, 0x3a, 0x0d, 0x26, 0x38, 0x1a, 0xf4, 0xeb, 0xa4, 0xa9, 0x8e,
+            0xaa, 0x9b, 0x4e, 0x6a,
+        ];
+        let bob_priv = [
+            0x5d, 0xab, 0x08, 0x7e, 0x62, 0x4a, 0x8a, 0x4b, 0x79, 0xe1, 0x7f, 0x8b, 0x83, 0x80,
+            0x0e, 0xe6, 0x6f, 0x3b, 0xb1, 0x29, 0x26, 0x18, 0xb6, 0xfd, 0x1c, 0x2f, 0x8b, 0x27,
+            0xff, 0x88, 0xe0, 0xeb,
+        ];
+        let bob_pub = [
+            0xde, 0x9e, 0xdb, 0x7d, 0x7b, 0x7d, 0xc1, 0xb4, 0xd3, 0x5b, 0x61, 0xc2, 0xec, 0xe4,
+            0x35, 0x37, 0x3f, 0x83, 0x43, 0xc8, 0x5b, 0x78, 0x67, 0x4d, 0xad, 0xfc, 0x7e, 0x14,
+            0x6f, 0x88, 0x2b, 0x4f,
+        ];
+        let shared_secret = [
+            0x4a, 0x5d, 0x9d, 0x5b, 0xa4, 0xce, 0x2d, 0xe1, 0x72, 0x8e, 0x3b, 0xf4, 0x80, 0x35,
+            0x0f, 0x25, 0xe0, 0x7e, 0x21, 0xc9, 0x47, 0xd1, 0x9e, 0x33, 0x76, 0xf0, 0x9b, 0x3c,
+            0x1e, 0x16, 0x17, 0x42,
+        ];
+
+        assert_eq!(x25519(&alice_priv, &BASE_POINT), alice_pub);
+        assert_eq!(x25519(&bob_priv, &BASE_POINT), bob_pub);
+        assert_eq!(x25519(&alice_priv, &bob_pub), shared_secret);
+        assert_eq!(x25519(&bob_priv, &alice_pub), shared_secret);
+    }
+
+    #[test]
+    fn small_order() {
+        let scalar = [1; 32];
+        assert_eq!(x25519(&scalar, &[0; 32]), [0; 32]);
+    }
+}
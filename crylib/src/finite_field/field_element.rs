@@ -204,7 +204,7 @@ macro_rules! impl_field_element {
 
             pub fn mul_digit(&self, digit: u64) -> Self {
                 let mut carry = 0;
-                let mut buf = UBigInt::<5>::ZERO;
+                let mut buf = UBigInt::<{ $n + 1 }>::ZERO;
                 for i in 0..$n {
                     (buf.0[i], carry) = crate::big_int::carry_mul(self.0 .0[i], digit, carry);
                 }
@@ -267,6 +267,8 @@ macro_rules! impl_field_element {
 
 impl_field_element!(3);
 impl_field_element!(4);
+impl_field_element!(6);
+impl_field_element!(9);
 
 impl<const N: usize, F: FiniteField<N>> TryFrom<UBigInt<N>> for FieldElement<N, F> {
     type Error = InputTooLargeError;
@@ -307,7 +309,7 @@ mod tests {
                 0x5d97c948e23c79c0,
                 0x89c9a8bb5116b562,
                 0xec57bfa67717cf1b,
-                0x840b25e463c7037A,
+                0x840b25e463c7037a,
             ]),
             PhantomData,
         );
@@ -354,7 +356,7 @@ mod tests {
                 0x5d97c948e23c79c0,
                 0x89c9a8bb5116b562,
                 0xec57bfa67717cf1b,
-                0x840b25e463c7037A,
+                0x840b25e463c7037a,
             ]),
             PhantomData,
         );
@@ -5,7 +5,7 @@ mod sha512;
 
 pub use buf_hasher::BufHasher;
 pub use sha256::Sha256;
-pub use sha512::Sha512;
+pub use sha512::{Sha384, Sha512};
 
 pub trait Hasher<const H_LEN: usize> {
     fn new() -> Self;
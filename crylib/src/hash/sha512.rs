@@ -1,4 +1,4 @@
-//! A software implementation of SHA-512.
+//! A software implementation of SHA-512 and SHA-384.
 
 use super::{BlockHasher, Hasher};
 
@@ -230,6 +230,61 @@ impl Sha512 {
     }
 }
 
+/// SHA-384, which is SHA-512 with a different initial state and a truncated output.
+#[derive(Clone)]
+pub struct Sha384 {
+    inner: Sha512,
+}
+
+impl Hasher<{ Sha384::HASH_SIZE }> for Sha384 {
+    fn new() -> Self {
+        Self {
+            inner: Sha512 {
+                state: [
+                    0xcbbb9d5dc1059ed8,
+                    0x629a292a367cd507,
+                    0x9159015a3070dd17,
+                    0x152fecd8f70e5939,
+                    0x67332667ffc00b31,
+                    0x8eb44a8768581511,
+                    0xdb0c2e0d64f98fa7,
+                    0x47b5481dbefa4fa4,
+                ],
+                len: 0,
+            },
+        }
+    }
+
+    fn finish_with(self, msg: &[u8]) -> [u8; Self::HASH_SIZE] {
+        truncate(self.inner.finish_with(msg))
+    }
+
+    fn finish(self) -> [u8; Self::HASH_SIZE] {
+        truncate(self.inner.finish())
+    }
+
+    fn hash(msg: &[u8]) -> [u8; Self::HASH_SIZE] {
+        let hasher = Self::new();
+        hasher.finish_with(msg)
+    }
+}
+
+impl BlockHasher<{ Self::HASH_SIZE }, { Self::BLOCK_SIZE }> for Sha384 {
+    fn update(&mut self, block: &[u8; Self::BLOCK_SIZE]) {
+        self.inner.update(block);
+    }
+}
+
+impl Sha384 {
+    pub const HASH_SIZE: usize = 48;
+    pub const BLOCK_SIZE: usize = Sha512::BLOCK_SIZE;
+}
+
+fn truncate(digest: [u8; Sha512::HASH_SIZE]) -> [u8; Sha384::HASH_SIZE] {
+    // we can safely unwrap because `Sha384::HASH_SIZE` is less than `Sha512::HASH_SIZE`
+    digest[..Sha384::HASH_SIZE].try_into().unwrap()
+}
+
 fn be_bytes_to_u64_array(bytes: &[u8; Sha512::BLOCK_SIZE]) -> [u64; Sha512::BLOCK_SIZE / 8] {
     // TODO: consider using uninitialized array
     let mut as_u64 = [64; Sha512::BLOCK_SIZE / 8];
@@ -337,4 +392,44 @@ mod tests {
         }
         assert_eq!(hasher.finish_with(remainder), digest);
     }
+
+    #[test]
+    fn sha384_hash() {
+        let digest = [
+            0xcb, 0x00, 0x75, 0x3f, 0x45, 0xa3, 0x5e, 0x8b, 0xb5, 0xa0, 0x3d, 0x69, 0x9a, 0xc6,
+            0x50, 0x07, 0x27, 0x2c, 0x32, 0xab, 0x0e, 0xde, 0xd1, 0x63, 0x1a, 0x8b, 0x60, 0x5a,
+            0x43, 0xff, 0x5b, 0xed, 0x80, 0x86, 0x07, 0x2b, 0xa1, 0xe7, 0xcc, 0x23, 0x58, 0xba,
+            0xec, 0xa1, 0x34, 0xc8, 0x25, 0xa7,
+        ];
+        assert_eq!(Sha384::hash(b"abc"), digest);
+
+        let msg = b"abcdefghbcdefghicdefghijdefghijkefghijklfghijklmghijklmnhijklmnoijklmnopjklmnopqklmnopqrlmnopqrsmnopqrstnopqrstu";
+        let digest = [
+            0x09, 0x33, 0x0c, 0x33, 0xf7, 0x11, 0x47, 0xe8, 0x3d, 0x19, 0x2f, 0xc7, 0x82, 0xcd,
+            0x1b, 0x47, 0x53, 0x11, 0x1b, 0x17, 0x3b, 0x3b, 0x05, 0xd2, 0x2f, 0xa0, 0x80, 0x86,
+            0xe3, 0xb0, 0xf7, 0x12, 0xfc, 0xc7, 0xc7, 0x1a, 0x55, 0x7e, 0x2d, 0xb9, 0x66, 0xc3,
+            0xe9, 0xfa, 0x91, 0x74, 0x60, 0x39,
+        ];
+        assert_eq!(Sha384::hash(msg), digest);
+    }
+
+    #[test]
+    fn sha384_update() {
+        let msg = [0x61; 300];
+        let digest = [
+            0x05, 0x41, 0xe3, 0x49, 0xb6, 0xd0, 0x67, 0x49, 0xa8, 0x52, 0xae, 0x0a, 0x68, 0xdb,
+            0x73, 0xf9, 0x86, 0x20, 0x87, 0x9d, 0xe6, 0xb5, 0xb3, 0x74, 0x3d, 0x7f, 0xca, 0x7d,
+            0xda, 0x0f, 0x81, 0xd8, 0x3b, 0x0c, 0xa5, 0x96, 0xf7, 0x10, 0x54, 0x2e, 0xd3, 0x43,
+            0xf1, 0xb4, 0x21, 0x4d, 0x50, 0x8c,
+        ];
+        let mut hasher = Sha384::new();
+
+        let blocks = msg.chunks_exact(Sha384::BLOCK_SIZE);
+        let remainder = blocks.remainder();
+
+        for block in blocks {
+            hasher.update(block.try_into().unwrap());
+        }
+        assert_eq!(hasher.finish_with(remainder), digest);
+    }
 }
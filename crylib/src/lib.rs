@@ -14,3 +14,4 @@ pub mod finite_field;
 pub mod hash;
 pub mod hkdf;
 pub mod hmac;
+pub mod rsa;
@@ -0,0 +1,682 @@
+//! RSA signature verification.
+//!
+//! Only the public-key operation is supported, with moduli from [`MIN_BITS`] to [`MAX_BITS`]
+//! bits. Signatures can be verified with either RSASSA-PKCS1-v1_5 or RSASSA-PSS, as described in
+//! RFC 8017.
+
+use crate::big_int::UBigInt;
+use crate::ec::ecdsa::{InvalidSig, ValidSig};
+use crate::hash::{Hasher, Sha256, Sha384, Sha512};
+
+/// The size of the smallest supported modulus in bits.
+pub const MIN_BITS: usize = 2048;
+
+/// The size of the largest supported modulus in bits.
+pub const MAX_BITS: usize = 4096;
+
+/// The number of limbs needed to store the largest supported modulus.
+const LIMBS: usize = MAX_BITS / u64::BITS as usize;
+
+/// The size of the largest supported modulus in bytes.
+const MAX_LEN: usize = MAX_BITS / 8;
+
+/// The minimum length of the padding in RSASSA-PKCS1-v1_5.
+const MIN_PADDING_LEN: usize = 8;
+
+/// The size of the largest supported hash in bytes.
+const MAX_HASH_LEN: usize = 64;
+
+/// A hash function that can be used with RSA signatures.
+pub trait RsaHash<const H_LEN: usize>: Hasher<H_LEN> {
+    /// The DER-encoded DigestInfo of this hash function, without the digest itself.
+    ///
+    /// This is used by RSASSA-PKCS1-v1_5.
+    const DIGEST_INFO_PREFIX: &'static [u8];
+}
+
+impl RsaHash<{ Sha256::HASH_SIZE }> for Sha256 {
+    const DIGEST_INFO_PREFIX: &'static [u8] = &[
+        0x30, 0x31, 0x30, 0x0d, 0x06, 0x09, 0x60, 0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x02, 0x01,
+        0x05, 0x00, 0x04, 0x20,
+    ];
+}
+
+impl RsaHash<{ Sha384::HASH_SIZE }> for Sha384 {
+    const DIGEST_INFO_PREFIX: &'static [u8] = &[
+        0x30, 0x41, 0x30, 0x0d, 0x06, 0x09, 0x60, 0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x02, 0x02,
+        0x05, 0x00, 0x04, 0x30,
+    ];
+}
+
+impl RsaHash<{ Sha512::HASH_SIZE }> for Sha512 {
+    const DIGEST_INFO_PREFIX: &'static [u8] = &[
+        0x30, 0x51, 0x30, 0x0d, 0x06, 0x09, 0x60, 0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x02, 0x03,
+        0x05, 0x00, 0x04, 0x40,
+    ];
+}
+
+/// The error that is returned when an RSA public key is invalid or unsupported.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Default)]
+pub struct InvalidKey;
+
+impl core::fmt::Display for InvalidKey {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str("the key is invalid or unsupported")
+    }
+}
+
+impl core::error::Error for InvalidKey {}
+
+/// An RSA public key.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct PublicKey {
+    modulus: UBigInt<LIMBS>,
+    exponent: u64,
+    /// The size of the modulus in bits.
+    bits: usize,
+    /// `-modulus^-1 mod 2^64`, which is used for Montgomery multiplication.
+    mont_factor: u64,
+    /// `R^2 mod modulus`, where `R` is `2^MAX_BITS`.
+    r_squared: UBigInt<LIMBS>,
+}
+
+impl PublicKey {
+    /// Creates a public key from its big-endian modulus and public exponent.
+    ///
+    /// The modulus must be odd and between [`MIN_BITS`] and [`MAX_BITS`] bits long. The exponent
+    /// must be odd, at least 3, and fit in a `u64`.
+    pub fn new(modulus: &[u8], exponent: &[u8]) -> Result<Self, InvalidKey> {
+        let modulus = strip_leading_zeros(modulus);
+        let exponent = strip_leading_zeros(exponent);
+
+        let bits = match modulus.first() {
+            Some(first) => modulus.len() * 8 - first.leading_zeros() as usize,
+            None => 0,
+        };
+        if !(MIN_BITS..=MAX_BITS).contains(&bits) || modulus[modulus.len() - 1] & 1 == 0 {
+            return Err(InvalidKey);
+        }
+        if exponent.len() > size_of::<u64>() {
+            return Err(InvalidKey);
+        }
+        let exponent = exponent
+            .iter()
+            .fold(0, |exponent, &byte| (exponent << 8) | byte as u64);
+        if exponent < 3 || exponent & 1 == 0 {
+            return Err(InvalidKey);
+        }
+
+        let modulus = from_be_slice(modulus);
+
+        // Newton's method doubles the number of correct bits each iteration
+        let mut inverse: u64 = 1;
+        for _ in 0..6 {
+            inverse = inverse.wrapping_mul(2u64.wrapping_sub(modulus.0[0].wrapping_mul(inverse)));
+        }
+
+        let mut key = Self {
+            modulus,
+            exponent,
+            bits,
+            mont_factor: inverse.wrapping_neg(),
+            r_squared: UBigInt::ONE,
+        };
+        for _ in 0..2 * MAX_BITS {
+            key.r_squared = key.double_mod(&key.r_squared);
+        }
+        Ok(key)
+    }
+
+    /// The size of the modulus in bytes, which is also the size of a signature.
+    pub const fn modulus_len(&self) -> usize {
+        self.bits.div_ceil(8)
+    }
+
+    /// Verifies an RSASSA-PKCS1-v1_5 signature of `msg`.
+    pub fn verify_pkcs1_v15<const H_LEN: usize, H: RsaHash<H_LEN>>(
+        &self,
+        msg: &[u8],
+        sig: &[u8],
+    ) -> Result<ValidSig, InvalidSig> {
+        let buf = self.public_op(sig)?;
+        let encoded = &buf[MAX_LEN - self.modulus_len()..];
+
+        // EM = 0x00 || 0x01 || PS || 0x00 || DigestInfo
+        let info_len = H::DIGEST_INFO_PREFIX.len() + H_LEN;
+        if encoded.len() < info_len + MIN_PADDING_LEN + 3 {
+            return Err(InvalidSig);
+        }
+        let (header, rest) = encoded.split_at(2);
+        let (padding, rest) = rest.split_at(rest.len() - info_len - 1);
+        let (separator, info) = rest.split_at(1);
+        let (prefix, digest) = info.split_at(H::DIGEST_INFO_PREFIX.len());
+
+        match header == [0x00, 0x01]
+            && padding.iter().all(|&byte| byte == 0xff)
+            && separator == [0x00]
+            && prefix == H::DIGEST_INFO_PREFIX
+            && digest == H::hash(msg)
+        {
+            true => Ok(ValidSig),
+            false => Err(InvalidSig),
+        }
+    }
+
+    /// Verifies an RSASSA-PSS signature of `msg`, using MGF1 with the same hash function.
+    ///
+    /// The salt must be as long as the hash, which is required by TLS 1.3 and by most uses of
+    /// PSS in certificates.
+    pub fn verify_pss<const H_LEN: usize, H: RsaHash<H_LEN>>(
+        &self,
+        msg: &[u8],
+        sig: &[u8],
+    ) -> Result<ValidSig, InvalidSig> {
+        let salt_len = H_LEN;
+        let buf = self.public_op(sig)?;
+        let em_bits = self.bits - 1;
+        let em_len = em_bits.div_ceil(8);
+
+        // the encoded message is one bit shorter than the modulus
+        let (zeros, encoded) =
+            buf[MAX_LEN - self.modulus_len()..].split_at(self.modulus_len() - em_len);
+        if zeros.iter().any(|&byte| byte != 0) || em_len < H_LEN + salt_len + 2 {
+            return Err(InvalidSig);
+        }
+
+        // EM = maskedDB || H || 0xbc
+        let (masked_db, rest) = encoded.split_at(em_len - H_LEN - 1);
+        let (hash, trailer) = rest.split_at(H_LEN);
+        let unused_bits = 8 * em_len - em_bits;
+        if trailer != [0xbc] || masked_db[0] & !(0xff >> unused_bits) != 0 {
+            return Err(InvalidSig);
+        }
+
+        let mut db_buf = [0; MAX_LEN];
+        let db = &mut db_buf[..masked_db.len()];
+        mgf1::<H_LEN, H>(hash, db);
+        for (db_byte, masked_byte) in db.iter_mut().zip(masked_db) {
+            *db_byte ^= masked_byte;
+        }
+        db[0] &= 0xff >> unused_bits;
+
+        // DB = PS || 0x01 || salt
+        let (padding, rest) = db.split_at(db.len() - salt_len - 1);
+        let (separator, salt) = rest.split_at(1);
+        if padding.iter().any(|&byte| byte != 0) || separator != [0x01] {
+            return Err(InvalidSig);
+        }
+
+        // M' = (0x00 * 8) || mHash || salt
+        let mut m_prime = [0; 8 + 2 * MAX_HASH_LEN];
+        m_prime[8..][..H_LEN].copy_from_slice(&H::hash(msg));
+        m_prime[8 + H_LEN..][..salt_len].copy_from_slice(salt);
+        match H::hash(&m_prime[..8 + H_LEN + salt_len]) == *hash {
+            true => Ok(ValidSig),
+            false => Err(InvalidSig),
+        }
+    }
+
+    /// Raises `sig` to the public exponent, returning the big-endian result.
+    ///
+    /// Only the last [`PublicKey::modulus_len`] bytes of the result are used.
+    fn public_op(&self, sig: &[u8]) -> Result<[u8; MAX_LEN], InvalidSig> {
+        if sig.len() != self.modulus_len() {
+            return Err(InvalidSig);
+        }
+        let sig = from_be_slice(sig);
+        if sig >= self.modulus {
+            return Err(InvalidSig);
+        }
+
+        let base = self.mont_mul(&sig, &self.r_squared);
+        let mut result = base;
+        for bit in (0..u64::BITS - 1 - self.exponent.leading_zeros()).rev() {
+            result = self.mont_mul(&result, &result);
+            if self.exponent >> bit & 1 == 1 {
+                result = self.mont_mul(&result, &base);
+            }
+        }
+        let result = self.mont_mul(&result, &UBigInt::ONE);
+
+        let mut buf = [0; MAX_LEN];
+        for (chunk, limb) in buf.rchunks_exact_mut(size_of::<u64>()).zip(result.0) {
+            chunk.copy_from_slice(&limb.to_be_bytes());
+        }
+        Ok(buf)
+    }
+
+    /// Returns `2 * int mod modulus`.
+    ///
+    /// `int` must be less than the modulus.
+    fn double_mod(&self, int: &UBigInt<LIMBS>) -> UBigInt<LIMBS> {
+        let (double, overflowed) = int.overflowing_add(int);
+        match overflowed || double >= self.modulus {
+            true => double.sub(&self.modulus),
+            false => double,
+        }
+    }
+
+    /// Returns `lhs * rhs * R^-1 mod modulus`, using Montgomery multiplication.
+    ///
+    /// Both inputs must be less than the modulus.
+    fn mont_mul(&self, lhs: &UBigInt<LIMBS>, rhs: &UBigInt<LIMBS>) -> UBigInt<LIMBS> {
+        // the two extra limbs hold the carries
+        let mut buf = [0u64; LIMBS + 2];
+        for &rhs_limb in &rhs.0 {
+            let mut carry = 0;
+            for (buf_limb, &lhs_limb) in buf.iter_mut().zip(&lhs.0) {
+                (*buf_limb, carry) = mul_add(lhs_limb, rhs_limb, *buf_limb, carry);
+            }
+            let overflowed;
+            (buf[LIMBS], overflowed) = buf[LIMBS].overflowing_add(carry);
+            buf[LIMBS + 1] = overflowed as u64;
+
+            // add a multiple of the modulus that makes the lowest limb zero, then shift it out
+            let factor = buf[0].wrapping_mul(self.mont_factor);
+            let (_, mut carry) = mul_add(factor, self.modulus.0[0], buf[0], 0);
+            for i in 1..LIMBS {
+                (buf[i - 1], carry) = mul_add(factor, self.modulus.0[i], buf[i], carry);
+            }
+            let overflowed;
+            (buf[LIMBS - 1], overflowed) = buf[LIMBS].overflowing_add(carry);
+            buf[LIMBS] = buf[LIMBS + 1] + overflowed as u64;
+        }
+
+        // the result is less than twice the modulus
+        let mut result = UBigInt::<LIMBS>::ZERO;
+        result.0.copy_from_slice(&buf[..LIMBS]);
+        match buf[LIMBS] != 0 || result >= self.modulus {
+            true => result.sub(&self.modulus),
+            false => result,
+        }
+    }
+}
+
+/// Returns `x * y + add + carry`, split into its low and high limbs.
+const fn mul_add(x: u64, y: u64, add: u64, carry: u64) -> (u64, u64) {
+    let result = x as u128 * y as u128 + add as u128 + carry as u128;
+    (result as u64, (result >> 64) as u64)
+}
+
+const fn strip_leading_zeros(mut bytes: &[u8]) -> &[u8] {
+    while let [0, rest @ ..] = bytes {
+        bytes = rest;
+    }
+    bytes
+}
+
+/// Converts a big-endian integer of at most [`MAX_LEN`] bytes to a [`UBigInt`].
+fn from_be_slice(bytes: &[u8]) -> UBigInt<LIMBS> {
+    let mut buf = [0; MAX_LEN];
+    buf[MAX_LEN - bytes.len()..].copy_from_slice(bytes);
+
+    let mut int = UBigInt::ZERO;
+    for (chunk, limb) in buf.rchunks_exact(size_of::<u64>()).zip(int.0.iter_mut()) {
+        *limb = u64::from_be_bytes(chunk.try_into().unwrap());
+    }
+    int
+}
+
+/// Fills `mask` using the mask generation function MGF1.
+fn mgf1<const H_LEN: usize, H: Hasher<H_LEN>>(seed: &[u8], mask: &mut [u8]) {
+    let mut input = [0; MAX_HASH_LEN + size_of::<u32>()];
+    input[..seed.len()].copy_from_slice(seed);
+    for (counter, chunk) in mask.chunks_mut(H_LEN).enumerate() {
+        input[seed.len()..][..size_of::<u32>()].copy_from_slice(&(counter as u32).to_be_bytes());
+        let hash = H::hash(&input[..seed.len() + size_of::<u32>()]);
+        chunk.copy_from_slice(&hash[..chunk.len()]);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{InvalidKey, PublicKey};
+    use crate::ec::ecdsa::InvalidSig;
+    use crate::hash::{Sha256, Sha384, Sha512};
+
+    const MSG: &[u8] = b"sample message";
+    const EXPONENT: [u8; 3] = [0x01, 0x00, 0x01];
+
+    const MODULUS_2048: [u8; 256] = [
+        0xab, 0xe4, 0x7f, 0xb5, 0xcc, 0x43, 0xfd, 0xb2, 0xea, 0xe5, 0x9b, 0xe7, 0x25, 0x88, 0x9e,
+        0xf8, 0xc8, 0xd0, 0xef, 0x48, 0x95, 0xac, 0xc3, 0x4f, 0x55, 0x26, 0x37, 0x59, 0xe5, 0xe9,
+        0x39, 0x50, 0xd3, 0x2e, 0xe9, 0x88, 0xd7, 0x41, 0xe9, 0x66, 0x32, 0x47, 0xc0, 0x9d, 0xda,
+        0xd5, 0x9a, 0xb7, 0xcf, 0xbd, 0x95, 0x01, 0x5b, 0xa5, 0x3d, 0x09, 0x12, 0x78, 0x54, 0x51,
+        0x12, 0x5c, 0x60, 0x12, 0xe5, 0x5b, 0xff, 0x03, 0x77, 0x9f, 0x32, 0xd4, 0x13, 0xeb, 0xa7,
+        0xae, 0x35, 0x98, 0xc8, 0x41, 0x5c, 0x17, 0xae, 0x39, 0x5d, 0x26, 0xa3, 0x14, 0xcb, 0x98,
+        0x46, 0x35, 0x33, 0x5d, 0xa7, 0xb3, 0x23, 0xe9, 0xf1, 0x93, 0x7f, 0xae, 0x15, 0xe3, 0xae,
+        0xbc, 0x44, 0x9a, 0xb7, 0xde, 0x06, 0xca, 0xfd, 0x67, 0xa9, 0x12, 0xd0, 0x5e, 0xf2, 0x4c,
+        0xc2, 0xbe, 0x47, 0xae, 0x19, 0xcf, 0xc6, 0x9b, 0xde, 0x71, 0x5c, 0x20, 0xd3, 0xf6, 0xd3,
+        0x58, 0xde, 0x05, 0xef, 0x83, 0x8f, 0x3a, 0x66, 0x0d, 0x7a, 0xcf, 0x3c, 0xfd, 0xad, 0x66,
+        0x76, 0x73, 0xe1, 0xe9, 0xe3, 0xa2, 0xd7, 0xa2, 0xf3, 0xd4, 0xd2, 0x70, 0xaf, 0xa5, 0xdf,
+        0x47, 0x6c, 0xb3, 0xa3, 0x0e, 0xfe, 0x19, 0x3b, 0x6b, 0x2b, 0x1e, 0x0d, 0x43, 0x32, 0xb0,
+        0xb6, 0x72, 0xa5, 0xf2, 0x08, 0xd3, 0x2b, 0x90, 0xad, 0xb7, 0xc0, 0xda, 0xa6, 0xa4, 0x34,
+        0x77, 0x27, 0x52, 0x72, 0x83, 0x81, 0x2a, 0x6a, 0x26, 0x8b, 0x01, 0x98, 0xb7, 0x92, 0x15,
+        0x66, 0x48, 0x08, 0xed, 0xd8, 0x0b, 0xa7, 0x7a, 0xcf, 0x5f, 0x47, 0xfc, 0xdf, 0xf5, 0x40,
+        0x97, 0x5d, 0x99, 0x18, 0x71, 0xf1, 0xac, 0x23, 0xa1, 0x85, 0xed, 0x24, 0x6e, 0xe4, 0x40,
+        0xfe, 0x8f, 0x07, 0x65, 0x80, 0xc5, 0xf3, 0xca, 0x1f, 0xb7, 0xa2, 0xf8, 0x36, 0x8a, 0xb8,
+        0x83,
+    ];
+    const PKCS1_SHA256: [u8; 256] = [
+        0x5d, 0x49, 0xee, 0x4c, 0xf1, 0xe4, 0x58, 0x98, 0x9f, 0x06, 0xf9, 0x46, 0x34, 0x6d, 0xe3,
+        0x64, 0x20, 0xdf, 0x7e, 0x83, 0x28, 0x98, 0x25, 0xa2, 0xca, 0x54, 0xaa, 0xc8, 0x39, 0xe4,
+        0x87, 0xdb, 0xdf, 0x6d, 0xf7, 0xfe, 0x0a, 0xe6, 0x21, 0xf2, 0x99, 0x8a, 0x3c, 0xec, 0x9a,
+        0x16, 0x9c, 0x65, 0x45, 0x0d, 0x3c, 0x9c, 0x4c, 0xed, 0x85, 0x37, 0xde, 0xed, 0x05, 0x4c,
+        0xdd, 0x04, 0xdf, 0x6b, 0xcf, 0x50, 0xbc, 0xcc, 0xf0, 0x77, 0x85, 0x40, 0x5e, 0xbe, 0x32,
+        0x5b, 0x61, 0x98, 0x8e, 0x8f, 0x4a, 0x32, 0x39, 0x42, 0xae, 0x91, 0xcf, 0x35, 0xb7, 0xd2,
+        0x5d, 0x4d, 0x94, 0x18, 0x2d, 0x4b, 0x03, 0x4a, 0x59, 0x40, 0x93, 0xbb, 0x2d, 0x7f, 0x03,
+        0x1e, 0x82, 0x2f, 0xcb, 0x30, 0xbc, 0x19, 0x30, 0x09, 0xa6, 0x92, 0x0c, 0x45, 0xea, 0xe8,
+        0x5c, 0x99, 0xa5, 0xfe, 0xcd, 0x4f, 0xf8, 0xbe, 0x4c, 0x58, 0xf0, 0x82, 0xaf, 0xac, 0xd7,
+        0x74, 0x60, 0x76, 0x7d, 0x2a, 0x9c, 0x8e, 0x7b, 0x88, 0x25, 0xbc, 0xa1, 0x85, 0x76, 0xd9,
+        0x8c, 0x30, 0xbf, 0xce, 0x35, 0x29, 0xd1, 0x0c, 0x70, 0xae, 0x03, 0x49, 0xf5, 0xd2, 0x88,
+        0x61, 0x18, 0xbb, 0x16, 0x31, 0x40, 0xcc, 0x76, 0x3f, 0xe5, 0xfb, 0xd5, 0xc4, 0xb6, 0x3a,
+        0x26, 0xa0, 0x26, 0xbd, 0x3e, 0x92, 0xa4, 0xf9, 0x91, 0xd8, 0x5b, 0xa9, 0xc7, 0xc2, 0x0a,
+        0x70, 0xe3, 0xe8, 0x39, 0x64, 0x51, 0x52, 0x6d, 0x45, 0x5c, 0x7c, 0x0f, 0x9c, 0xe3, 0xff,
+        0xbf, 0xd0, 0x55, 0x18, 0x11, 0x93, 0x03, 0x3b, 0x9a, 0xad, 0x6c, 0x0c, 0x8f, 0xac, 0x01,
+        0xe1, 0xb7, 0x6b, 0xd1, 0x70, 0x22, 0x64, 0xae, 0x83, 0x33, 0xbe, 0xa3, 0x93, 0x50, 0x46,
+        0x69, 0x46, 0x40, 0x26, 0xb4, 0x60, 0x28, 0x4e, 0xe8, 0x44, 0xdd, 0xcd, 0xa4, 0x8c, 0x9c,
+        0xeb,
+    ];
+    const PSS_SHA256: [u8; 256] = [
+        0x6c, 0xf7, 0xa0, 0x48, 0x83, 0x10, 0x0e, 0xff, 0xe1, 0x79, 0x88, 0x40, 0xe2, 0xb1, 0x35,
+        0xb1, 0xd4, 0xc5, 0x41, 0xfe, 0xb7, 0xec, 0x2d, 0x9f, 0xc0, 0x92, 0x0c, 0x8c, 0xd4, 0xac,
+        0xfc, 0x7d, 0xc7, 0x26, 0x60, 0x7c, 0xf2, 0x32, 0x0e, 0xc0, 0xac, 0x8e, 0xf0, 0xfc, 0xd3,
+        0x2d, 0x36, 0x79, 0xfc, 0x2f, 0x7b, 0xed, 0x4e, 0x1d, 0x2a, 0x5c, 0x04, 0xd7, 0x20, 0x66,
+        0x88, 0xee, 0x7b, 0x7b, 0x44, 0x42, 0x50, 0xfe, 0x15, 0xb0, 0x0f, 0xf3, 0x98, 0x00, 0xba,
+        0x11, 0x21, 0xb0, 0xa8, 0x87, 0xf7, 0xc6, 0x7f, 0xc7, 0x42, 0x0c, 0x4f, 0x11, 0xb3, 0x1d,
+        0x6f, 0xed, 0x0c, 0xe4, 0xc0, 0x1c, 0x53, 0xbb, 0xd6, 0x8e, 0xd9, 0xfc, 0xd7, 0x65, 0xf5,
+        0x68, 0x80, 0x46, 0xc7, 0xd7, 0xd0, 0x76, 0xa3, 0x12, 0x5f, 0xd8, 0x72, 0x06, 0x0d, 0x3b,
+        0xe2, 0xbd, 0xa9, 0x72, 0x6f, 0x48, 0xe8, 0xee, 0x94, 0x8f, 0x1e, 0x6d, 0x1e, 0x21, 0xcb,
+        0xcb, 0xdf, 0xb7, 0x2e, 0xf9, 0x43, 0xa1, 0x8d, 0x6b, 0x66, 0xa9, 0xd5, 0x80, 0xdd, 0xf2,
+        0xe3, 0x69, 0xcf, 0x7a, 0xc8, 0x4c, 0x19, 0x56, 0x7a, 0x96, 0xb5, 0x12, 0x30, 0xd1, 0x23,
+        0x5b, 0xdc, 0x11, 0xdc, 0x96, 0x4d, 0xbf, 0x88, 0xea, 0x73, 0x1e, 0x62, 0xf3, 0x4d, 0x3a,
+        0xa8, 0x2d, 0xf1, 0x93, 0x4e, 0x67, 0x04, 0x13, 0x54, 0x4e, 0x73, 0x5c, 0xba, 0xb0, 0x75,
+        0x37, 0x5f, 0xd7, 0xac, 0xdb, 0x14, 0x00, 0x07, 0xd0, 0x6a, 0x70, 0xb6, 0x3b, 0xe6, 0xd4,
+        0x62, 0xfe, 0xc3, 0xab, 0x0c, 0xc6, 0x5e, 0x86, 0x42, 0x65, 0x4f, 0xf8, 0x25, 0xb9, 0x37,
+        0x33, 0xe5, 0x90, 0x90, 0x60, 0x3c, 0xff, 0x99, 0xa3, 0x40, 0x00, 0x56, 0x61, 0xa9, 0x47,
+        0x2b, 0x55, 0x5b, 0xec, 0xf7, 0x3e, 0xeb, 0xbc, 0x77, 0xba, 0x42, 0x85, 0x28, 0xd2, 0xc2,
+        0xf2,
+    ];
+    const PSS_SHA384: [u8; 256] = [
+        0x34, 0x06, 0x7a, 0x91, 0x2b, 0xcc, 0x86, 0xe2, 0x7c, 0x3f, 0xc8, 0x88, 0x17, 0xfa, 0x24,
+        0xbc, 0xef, 0xc8, 0x72, 0x2f, 0x43, 0xe5, 0x51, 0x32, 0xc5, 0xbe, 0xc2, 0x68, 0x49, 0xb6,
+        0x86, 0xb1, 0x0a, 0x2c, 0x39, 0x08, 0x66, 0xb8, 0x8d, 0xe7, 0x77, 0x2d, 0xdb, 0xde, 0x9e,
+        0x70, 0xac, 0xb5, 0x84, 0x3e, 0x34, 0x2e, 0x2e, 0x15, 0xce, 0x77, 0xdb, 0x39, 0x2b, 0x06,
+        0x8c, 0x60, 0x4b, 0x11, 0xcb, 0x9e, 0x6b, 0xe8, 0x1f, 0x78, 0xae, 0x9a, 0x18, 0xa7, 0x16,
+        0x80, 0xfe, 0x81, 0x24, 0xeb, 0x57, 0x7c, 0xf3, 0xf2, 0x26, 0x47, 0xb8, 0x5d, 0x26, 0x44,
+        0xf9, 0x68, 0x54, 0x8a, 0xab, 0x0d, 0x33, 0x0a, 0x37, 0x17, 0x3a, 0xc3, 0x82, 0xac, 0xe3,
+        0x48, 0x0d, 0xd7, 0x13, 0xad, 0x3a, 0xd7, 0xf0, 0x52, 0x70, 0x84, 0x5d, 0x3e, 0xfb, 0x9e,
+        0x2e, 0x59, 0xf7, 0xfb, 0x6e, 0x1e, 0x19, 0x71, 0x8f, 0x47, 0x44, 0xcb, 0x02, 0x07, 0x32,
+        0xed, 0x5b, 0xd4, 0xf5, 0x5b, 0xc8, 0x99, 0x27, 0xc7, 0x31, 0xa0, 0x7e, 0xce, 0x7b, 0x48,
+        0x42, 0x35, 0x6d, 0x50, 0xcd, 0x1a, 0x8b, 0x8c, 0xc8, 0x71, 0xb5, 0x5a, 0x3b, 0x1d, 0xa8,
+        0x06, 0x57, 0xab, 0x6b, 0x1e, 0x42, 0x7e, 0x20, 0xbd, 0x16, 0xa6, 0x79, 0x47, 0x4b, 0xab,
+        0xce, 0x6d, 0xbe, 0xb0, 0x85, 0x4a, 0x9f, 0x10, 0xc6, 0x9b, 0x63, 0x92, 0x09, 0xc5, 0xcc,
+        0xe3, 0x4c, 0x7f, 0xa1, 0xe5, 0x20, 0x77, 0x22, 0x97, 0xf9, 0x6a, 0xd2, 0xc2, 0x69, 0xe4,
+        0x91, 0xce, 0x25, 0xc5, 0x09, 0x27, 0xfb, 0xad, 0x4f, 0xd7, 0x5e, 0xcf, 0x21, 0xaf, 0x9e,
+        0x22, 0x45, 0x14, 0x21, 0x14, 0xa3, 0x8c, 0x4f, 0x67, 0x67, 0x99, 0xb4, 0x2b, 0x4b, 0x7e,
+        0xb0, 0x67, 0x74, 0xad, 0x59, 0x38, 0x27, 0x83, 0x5c, 0x98, 0xe8, 0x91, 0x4e, 0xb5, 0x35,
+        0x1a,
+    ];
+    const MODULUS_2049: [u8; 257] = [
+        0x01, 0xa1, 0xfa, 0xc6, 0x4a, 0x56, 0xb2, 0x25, 0x79, 0x49, 0x10, 0xcb, 0x19, 0xf0, 0xcb,
+        0x3a, 0x17, 0x39, 0xc2, 0x53, 0xfc, 0x3d, 0x26, 0x25, 0x86, 0x30, 0xef, 0x60, 0xed, 0x84,
+        0x35, 0x34, 0x09, 0x0b, 0x22, 0x54, 0x59, 0x4a, 0x5b, 0x30, 0xe0, 0x6b, 0xd1, 0x63, 0x32,
+        0x41, 0x40, 0xa4, 0xb3, 0x86, 0x18, 0xd6, 0x58, 0x82, 0x8f, 0x42, 0xdf, 0x5f, 0x02, 0x36,
+        0x3f, 0x01, 0x62, 0x73, 0x79, 0xc3, 0xc7, 0x7f, 0x75, 0x28, 0x42, 0x21, 0x14, 0x00, 0x29,
+        0x83, 0xe2, 0xb6, 0xd9, 0xe2, 0x89, 0x7c, 0x5b, 0xbe, 0x99, 0xfe, 0xf3, 0x20, 0xe3, 0xff,
+        0xdd, 0x8f, 0xb8, 0x4f, 0x23, 0x20, 0x8e, 0xab, 0xa6, 0xe6, 0xce, 0x80, 0x00, 0xf8, 0xb0,
+        0xc1, 0x0e, 0xe3, 0x07, 0x31, 0xb6, 0x63, 0xc8, 0x9e, 0x69, 0x3a, 0x0c, 0xff, 0xc3, 0x19,
+        0x6d, 0xcd, 0x9e, 0x89, 0x43, 0x44, 0xa8, 0x3f, 0x49, 0x06, 0x4e, 0x57, 0xad, 0xd2, 0x1a,
+        0xb0, 0xa3, 0x6c, 0x04, 0xad, 0x4c, 0x28, 0x3e, 0x2f, 0x1d, 0xec, 0x8f, 0xda, 0x6f, 0xcb,
+        0x27, 0x4e, 0x8f, 0xa6, 0xd2, 0x99, 0x7a, 0x35, 0x29, 0x1b, 0x0d, 0xe3, 0x4e, 0xb0, 0x3b,
+        0xbb, 0xd2, 0x08, 0x9e, 0x04, 0x43, 0x41, 0xd9, 0x65, 0x06, 0xc6, 0xad, 0x49, 0x16, 0x63,
+        0x11, 0x24, 0xb3, 0xa0, 0xee, 0x5c, 0x79, 0xce, 0x07, 0xce, 0xfe, 0x4d, 0x3b, 0x61, 0xf0,
+        0x75, 0xcb, 0x0a, 0x7f, 0xf6, 0x16, 0x3d, 0x64, 0x76, 0xb8, 0x18, 0x04, 0x46, 0xde, 0x10,
+        0x75, 0x06, 0x0c, 0xd9, 0x95, 0xbf, 0xc1, 0xb0, 0x14, 0xd5, 0xe1, 0x33, 0xc1, 0x08, 0xf2,
+        0x79, 0x6b, 0xc0, 0xa8, 0x57, 0x3f, 0x2c, 0xb2, 0x80, 0x97, 0xa4, 0xe9, 0x2a, 0x3e, 0x6f,
+        0x21, 0x51, 0x24, 0x28, 0xdb, 0xc8, 0x6f, 0xa7, 0x58, 0xf3, 0x6a, 0x82, 0x29, 0x53, 0xdf,
+        0xff, 0x37,
+    ];
+    const PSS_2049: [u8; 257] = [
+        0x00, 0x71, 0x8c, 0x47, 0x8a, 0x0c, 0x7b, 0x39, 0x02, 0xaf, 0xd4, 0xf9, 0x6d, 0x5c, 0x49,
+        0xa1, 0x99, 0x95, 0x68, 0xfd, 0x01, 0x32, 0x50, 0x54, 0x3e, 0x3a, 0x63, 0x50, 0x03, 0xaa,
+        0x87, 0x4e, 0x6e, 0x75, 0x11, 0x06, 0x0b, 0x29, 0x27, 0xf0, 0x0d, 0xb4, 0xdc, 0xe6, 0x21,
+        0x83, 0xa3, 0x29, 0x1f, 0xfd, 0x81, 0x93, 0xd8, 0x57, 0xa6, 0xea, 0x8b, 0x05, 0xe6, 0x0d,
+        0x1e, 0x30, 0xc5, 0x3e, 0x1a, 0xec, 0x32, 0x6e, 0x9b, 0xe0, 0x47, 0xd3, 0xf2, 0xbd, 0xdf,
+        0x2a, 0x05, 0x19, 0x0d, 0x2b, 0x7b, 0x00, 0xa7, 0x58, 0x88, 0x29, 0xed, 0x6e, 0x7c, 0x57,
+        0xec, 0x7e, 0xcf, 0xf2, 0xc4, 0x5f, 0xde, 0xcd, 0x64, 0xa1, 0x7a, 0xbc, 0x7d, 0xae, 0x67,
+        0x4b, 0x82, 0xda, 0x3d, 0x80, 0x6a, 0x70, 0x28, 0xa4, 0xea, 0x66, 0x9b, 0x29, 0xa1, 0x2c,
+        0xbc, 0x3d, 0xae, 0x4b, 0x38, 0xf4, 0xb3, 0xe3, 0xd4, 0x4f, 0xb4, 0xfa, 0x43, 0x94, 0xdb,
+        0x86, 0x02, 0x98, 0x50, 0x89, 0x28, 0x5e, 0xf6, 0xde, 0xff, 0x34, 0x75, 0xa6, 0x25, 0xbc,
+        0x19, 0x41, 0xde, 0x91, 0xf5, 0x1f, 0xc1, 0xba, 0xaa, 0xcb, 0x38, 0x84, 0xc7, 0x6f, 0x81,
+        0x3c, 0x19, 0x4c, 0x35, 0x92, 0xc4, 0xd8, 0x52, 0x54, 0xec, 0x49, 0xbd, 0xa0, 0x58, 0x44,
+        0x93, 0xca, 0x88, 0x02, 0xd1, 0xbd, 0x3c, 0x89, 0xb1, 0xc4, 0x09, 0x18, 0xb8, 0x57, 0xfa,
+        0xa5, 0xa6, 0x3a, 0x53, 0x8e, 0x44, 0x3d, 0x3e, 0x0b, 0x96, 0x3d, 0x14, 0x97, 0xf2, 0xa1,
+        0x55, 0x14, 0xa0, 0xe2, 0x1b, 0x3b, 0xc1, 0xbd, 0x39, 0x40, 0xfa, 0x63, 0xb9, 0xa3, 0x58,
+        0x4c, 0x47, 0x9c, 0xc4, 0x89, 0xc0, 0x1c, 0xc4, 0xe4, 0xad, 0xc7, 0x7f, 0xc5, 0x22, 0xa5,
+        0xdd, 0x3e, 0xd7, 0x3c, 0x93, 0xf7, 0xc9, 0xcc, 0x56, 0xfb, 0xd0, 0x80, 0xcd, 0x1f, 0x90,
+        0xfb, 0x52,
+    ];
+    const MODULUS_4096: [u8; 512] = [
+        0xbe, 0x52, 0xec, 0x2a, 0x19, 0x04, 0x4b, 0xe1, 0x16, 0x0f, 0xb1, 0x8b, 0x08, 0xdb, 0xb6,
+        0xfa, 0x00, 0x0b, 0x3d, 0xdf, 0x45, 0x5c, 0x7b, 0x53, 0x77, 0x1e, 0x29, 0x08, 0xbc, 0x59,
+        0x87, 0x09, 0x7f, 0x57, 0x25, 0x28, 0x00, 0x75, 0xee, 0x43, 0x6e, 0xa5, 0xbe, 0x5e, 0x9b,
+        0xb5, 0x14, 0x2f, 0x30, 0x74, 0xef, 0xb0, 0xc0, 0x21, 0x3d, 0xae, 0xfb, 0x0d, 0xde, 0x31,
+        0x20, 0x51, 0x00, 0xa5, 0x51, 0xc1, 0x4f, 0x0c, 0xfd, 0xee, 0x23, 0xf2, 0x22, 0x05, 0x61,
+        0xae, 0x0f, 0xf6, 0x57, 0x51, 0x9d, 0xad, 0x27, 0x92, 0x71, 0x69, 0x93, 0xe1, 0x6b, 0x94,
+        0x0f, 0xf5, 0x4e, 0x8e, 0x85, 0x7f, 0x7d, 0x3d, 0x7e, 0x65, 0xf8, 0xbf, 0xab, 0xc1, 0x07,
+        0xf5, 0xd4, 0x24, 0x3a, 0x5e, 0x4b, 0x1c, 0x36, 0x27, 0x5d, 0xc9, 0x53, 0x9c, 0x4a, 0x34,
+        0x3d, 0xd7, 0x88, 0x41, 0x02, 0x7c, 0xde, 0x5b, 0xcd, 0x02, 0x06, 0x2b, 0x23, 0x35, 0xf6,
+        0xf2, 0x85, 0x32, 0xf0, 0xdb, 0x42, 0x1d, 0xe7, 0x71, 0x93, 0xdb, 0xab, 0x26, 0x90, 0xbb,
+        0x47, 0x8f, 0xad, 0x7b, 0xe2, 0x27, 0x3e, 0x83, 0xca, 0x94, 0x3b, 0x99, 0x37, 0x08, 0xb3,
+        0x23, 0x66, 0x44, 0x44, 0x38, 0xbd, 0x0f, 0x88, 0x1d, 0xf1, 0xbe, 0x7c, 0x8f, 0xd6, 0xa3,
+        0x68, 0x11, 0xa0, 0xe2, 0xa3, 0x9b, 0x04, 0x36, 0x01, 0xee, 0xda, 0xd6, 0xfa, 0x61, 0x28,
+        0x34, 0xe5, 0x3a, 0xe3, 0x73, 0x2d, 0x89, 0x7c, 0xb7, 0x29, 0xa6, 0x51, 0x18, 0x39, 0x89,
+        0x3f, 0x89, 0xc1, 0x5f, 0xbb, 0x1e, 0x08, 0x28, 0xb4, 0x79, 0x8f, 0x39, 0x97, 0xc6, 0x1a,
+        0x94, 0x54, 0xdc, 0x69, 0x63, 0xd9, 0xdb, 0x56, 0x63, 0xe8, 0x61, 0x41, 0xaa, 0x5d, 0x3d,
+        0x92, 0x8e, 0x47, 0x87, 0x15, 0x46, 0x49, 0x04, 0x2e, 0xa1, 0x4e, 0x00, 0x99, 0x3d, 0xea,
+        0x17, 0x3a, 0xbb, 0x77, 0xcf, 0xaf, 0x0d, 0xdc, 0x7e, 0x6c, 0x0e, 0xbd, 0xd6, 0x45, 0x8a,
+        0x0f, 0x2d, 0xeb, 0xfb, 0xbf, 0x0a, 0xa2, 0xb9, 0x5c, 0x9c, 0xd1, 0x35, 0x8f, 0x21, 0x56,
+        0x29, 0x94, 0x9f, 0xfe, 0x84, 0xdb, 0xb4, 0x22, 0x83, 0x1f, 0xf0, 0x30, 0x50, 0x5f, 0x73,
+        0x1b, 0xc2, 0xd4, 0x92, 0xdd, 0xda, 0xb7, 0xa1, 0xbb, 0x14, 0x1f, 0x98, 0x09, 0xae, 0xde,
+        0x96, 0x05, 0x07, 0x42, 0x8e, 0x1f, 0x4b, 0xb1, 0x73, 0xfb, 0xd7, 0xfe, 0x5c, 0xbc, 0xc8,
+        0x1e, 0x16, 0x21, 0x10, 0x64, 0xa5, 0xc0, 0x69, 0x72, 0xe1, 0x43, 0x62, 0xea, 0xd9, 0x80,
+        0x26, 0x49, 0x46, 0x1c, 0x77, 0x17, 0x21, 0x53, 0x4d, 0x49, 0x71, 0x3e, 0x49, 0x51, 0xd7,
+        0x77, 0xab, 0x0c, 0xf4, 0x60, 0x52, 0x23, 0x6a, 0x59, 0x03, 0x91, 0x06, 0x61, 0x06, 0x3b,
+        0x30, 0x35, 0x57, 0x7b, 0x53, 0x1f, 0xed, 0x85, 0xd3, 0x9b, 0x23, 0x0d, 0xac, 0x65, 0xee,
+        0xb0, 0xa0, 0x4e, 0x32, 0x78, 0xa7, 0x27, 0xa6, 0x4b, 0xfd, 0x82, 0x4b, 0xb9, 0xb4, 0xc6,
+        0xaa, 0x2a, 0x99, 0x1e, 0x1d, 0xd3, 0x57, 0x65, 0xef, 0x01, 0x9b, 0xf9, 0x1d, 0xd6, 0x30,
+        0x93, 0xe3, 0xbd, 0x04, 0x47, 0x2e, 0x8e, 0x71, 0x8f, 0x47, 0x84, 0x1e, 0x53, 0x41, 0x8b,
+        0xeb, 0xd5, 0x6d, 0xe4, 0xbd, 0x2c, 0x54, 0x63, 0x33, 0xce, 0xc7, 0xd3, 0xad, 0xf5, 0x98,
+        0x74, 0xc3, 0xdb, 0x33, 0x61, 0x1f, 0x02, 0x95, 0xfa, 0xba, 0x35, 0xd7, 0x63, 0xec, 0x4e,
+        0x65, 0x29, 0xc7, 0x23, 0x5a, 0x65, 0xc8, 0x7f, 0xfc, 0x30, 0xf5, 0x4e, 0xfa, 0x07, 0xad,
+        0x1e, 0xb4, 0xdc, 0x2f, 0xb1, 0xdf, 0xe6, 0xae, 0xfc, 0x6f, 0x27, 0x0b, 0xf1, 0x0c, 0x64,
+        0x13, 0x23, 0x0c, 0xb4, 0x6d, 0x57, 0xbe, 0x49, 0x2b, 0x30, 0x7d, 0x1f, 0x96, 0x9b, 0x2a,
+        0xd9, 0x47,
+    ];
+    const PKCS1_SHA512_4096: [u8; 512] = [
+        0xa2, 0xc1, 0xbb, 0xa8, 0x6c, 0x9d, 0x64, 0x0f, 0x4e, 0x7d, 0x5f, 0x4b, 0xf3, 0x63, 0xf6,
+        0xf7, 0x2a, 0x16, 0xb4, 0x2e, 0xaf, 0x30, 0x93, 0x8e, 0x22, 0xb3, 0x62, 0x5b, 0xfe, 0x14,
+        0x3b, 0xca, 0xb3, 0xb8, 0x3a, 0xff, 0x45, 0x88, 0xa9, 0x85, 0xc2, 0xaf, 0xe4, 0x2f, 0xf7,
+        0x04, 0xed, 0x0a, 0xa8, 0x01, 0xf5, 0xa4, 0xb5, 0x8a, 0xb4, 0x98, 0x1a, 0x97, 0xe3, 0x4f,
+        0x33, 0xa9, 0x32, 0x42, 0x4c, 0x90, 0xfd, 0x04, 0x97, 0xc6, 0xb9, 0x42, 0x64, 0x3d, 0x14,
+        0x99, 0xdc, 0x43, 0xbd, 0x67, 0x52, 0xb6, 0xde, 0xfc, 0xa8, 0xab, 0xa8, 0xe4, 0x5c, 0xcc,
+        0x75, 0x4e, 0x84, 0x55, 0x46, 0x36, 0x3a, 0x49, 0xe3, 0x39, 0x4f, 0xad, 0x6e, 0xe5, 0xb4,
+        0xf4, 0xb2, 0x2c, 0x12, 0xe7, 0x1a, 0x07, 0x2c, 0xaa, 0x08, 0xfc, 0x9c, 0xf1, 0x4b, 0xcc,
+        0xf3, 0xbf, 0x0b, 0x24, 0xc3, 0x05, 0x19, 0x60, 0x4a, 0xbe, 0x88, 0x76, 0x16, 0xa0, 0x27,
+        0xd3, 0x08, 0x38, 0x9c, 0xf7, 0x35, 0xbb, 0x9d, 0xe6, 0x8f, 0x77, 0xfe, 0x0a, 0x0b, 0xc7,
+        0x1b, 0x04, 0x53, 0x84, 0xc1, 0x23, 0x7a, 0x02, 0x86, 0xb3, 0x82, 0xa6, 0x7c, 0x5b, 0x65,
+        0xbf, 0xaf, 0x64, 0x2d, 0xed, 0x4e, 0x72, 0xdb, 0x4d, 0x45, 0x99, 0xb8, 0x4c, 0x96, 0xe8,
+        0x7e, 0x98, 0x61, 0xb6, 0x9b, 0xba, 0xf1, 0x8c, 0x11, 0xb1, 0xc5, 0xcc, 0xdd, 0x7a, 0xc0,
+        0xdc, 0xaa, 0x78, 0x07, 0x40, 0x3d, 0x8e, 0x7f, 0xe1, 0x23, 0xda, 0xaf, 0xba, 0x7d, 0xf7,
+        0x12, 0xa9, 0x2a, 0x17, 0xf6, 0xd5, 0x97, 0x95, 0x26, 0xe9, 0x8d, 0xde, 0x47, 0x4a, 0x87,
+        0xf5, 0x3c, 0x1e, 0x8b, 0x76, 0x9d, 0xf2, 0xe8, 0xe6, 0x86, 0x3e, 0x62, 0x69, 0xd0, 0x59,
+        0xac, 0xfa, 0x21, 0x53, 0x04, 0x69, 0x9c, 0x4e, 0x5f, 0x0c, 0xf3, 0x07, 0x29, 0x17, 0x6e,
+        0xb3, 0x7c, 0xf7, 0x8c, 0x54, 0x2c, 0x6b, 0xfb, 0xd6, 0x43, 0xa7, 0xa3, 0x55, 0x38, 0xac,
+        0xfe, 0x24, 0x7a, 0x68, 0x1b, 0xae, 0xdd, 0x4c, 0x17, 0xa1, 0xca, 0x70, 0x0d, 0xf2, 0x0e,
+        0x46, 0x80, 0xea, 0x68, 0xcf, 0x99, 0x47, 0xdf, 0xcf, 0x32, 0x5f, 0xe8, 0xe2, 0x27, 0x74,
+        0xed, 0xb5, 0x09, 0x16, 0x79, 0xb7, 0xf6, 0x78, 0xa7, 0x94, 0xa0, 0x20, 0x27, 0x27, 0xb7,
+        0x5e, 0x37, 0xb4, 0x49, 0xa7, 0x5f, 0xbd, 0x49, 0x5b, 0x3b, 0x17, 0x77, 0xd4, 0x7d, 0xc2,
+        0xfd, 0xde, 0x7b, 0xad, 0x30, 0x01, 0x3a, 0x25, 0xbb, 0xd7, 0x3c, 0xe0, 0x8a, 0x8a, 0xe9,
+        0xc0, 0xea, 0xff, 0xa2, 0xe0, 0x1e, 0x59, 0x7e, 0x28, 0x8f, 0x1e, 0x36, 0x19, 0x01, 0x98,
+        0xfb, 0x50, 0x3d, 0x3e, 0x4f, 0x08, 0xd4, 0xaa, 0xf7, 0x8d, 0x21, 0xfa, 0x6e, 0x02, 0xe9,
+        0x02, 0xcc, 0x70, 0x4d, 0xb1, 0x7d, 0x25, 0xc4, 0x29, 0xc6, 0x39, 0xe0, 0xd1, 0x91, 0x94,
+        0x0c, 0x3f, 0x96, 0x70, 0x1c, 0xc9, 0x85, 0xfe, 0xf3, 0xee, 0x82, 0x82, 0x95, 0xf1, 0x07,
+        0x92, 0x4a, 0x34, 0xdb, 0x78, 0xf1, 0xb3, 0x36, 0xb9, 0x5f, 0xc7, 0x2f, 0x4c, 0x3a, 0xea,
+        0xb8, 0xf7, 0x38, 0x04, 0xba, 0x5d, 0xe5, 0x55, 0x6e, 0xbe, 0x84, 0xd9, 0x4f, 0xeb, 0x69,
+        0x42, 0x87, 0x85, 0x94, 0x2a, 0x62, 0xec, 0xf8, 0x36, 0xbc, 0xeb, 0xae, 0x9d, 0x90, 0x57,
+        0x17, 0x4c, 0x09, 0xcd, 0xa1, 0x66, 0xfd, 0x05, 0xfd, 0x55, 0x74, 0x92, 0x19, 0x41, 0x61,
+        0xce, 0xfc, 0xe3, 0xd1, 0xdf, 0x73, 0x4f, 0xda, 0x79, 0xda, 0xfa, 0x11, 0xb2, 0xfc, 0xea,
+        0x03, 0x4f, 0xe9, 0xce, 0x89, 0xcc, 0x94, 0xd1, 0x19, 0x4e, 0x9b, 0x0b, 0xe6, 0xe4, 0xc3,
+        0xa9, 0x05, 0x63, 0x76, 0x23, 0xf7, 0x25, 0xf9, 0x76, 0x75, 0x37, 0x7a, 0x9e, 0x42, 0x70,
+        0xc4, 0xeb,
+    ];
+    const PSS_SHA512_4096: [u8; 512] = [
+        0xa1, 0x75, 0xdf, 0x11, 0x46, 0xbb, 0x67, 0xa1, 0xb8, 0xd9, 0x5e, 0xb7, 0x97, 0x15, 0x7f,
+        0xb0, 0x7d, 0xc7, 0xcd, 0x82, 0x43, 0xb9, 0x5d, 0x3b, 0x9e, 0xc9, 0x71, 0x67, 0x33, 0x98,
+        0xf6, 0xdd, 0xd1, 0x66, 0xcc, 0x11, 0xca, 0x19, 0xaf, 0x24, 0x57, 0x7f, 0xea, 0x6d, 0xe9,
+        0xce, 0xd3, 0x13, 0x61, 0xd2, 0x1c, 0x44, 0x73, 0x21, 0x29, 0x10, 0x7e, 0x19, 0x6d, 0xf8,
+        0x45, 0xe1, 0xf2, 0xff, 0x35, 0x0e, 0x06, 0x87, 0x87, 0x38, 0xf3, 0x70, 0x63, 0x4c, 0x7c,
+        0xb8, 0xbb, 0xde, 0xe8, 0xd3, 0xe4, 0x88, 0x05, 0x30, 0xc3, 0x4e, 0x7f, 0xa2, 0x49, 0x36,
+        0xe2, 0x6d, 0x18, 0xa0, 0x5e, 0x45, 0x23, 0xf0, 0xb9, 0xb2, 0xd6, 0x38, 0x9a, 0xf5, 0xa0,
+        0x30, 0x57, 0x6e, 0x40, 0x37, 0x73, 0xcd, 0x9c, 0x29, 0x30, 0xec, 0x6d, 0x25, 0xe9, 0x7c,
+        0x2c, 0x25, 0x84, 0x95, 0xa3, 0x5e, 0xe1, 0xe8, 0x0b, 0xf0, 0x37, 0x8d, 0xd4, 0xc1, 0x2f,
+        0xcf, 0x7e, 0xb8, 0x59, 0xc0, 0xe0, 0x26, 0x6a, 0x3d, 0x05, 0x55, 0x28, 0x44, 0xf5, 0x99,
+        0xa9, 0xa8, 0xe0, 0x70, 0x16, 0x95, 0x05, 0x28, 0xe2, 0x40, 0x86, 0x05, 0xda, 0x8e, 0x0b,
+        0xca, 0x94, 0xfe, 0xb4, 0x0b, 0x55, 0x32, 0xd2, 0x2c, 0x2c, 0x46, 0xc3, 0xaf, 0xe1, 0xfe,
+        0x48, 0xa6, 0x5e, 0x54, 0x19, 0xe9, 0x57, 0xd5, 0x33, 0xae, 0x6e, 0x60, 0xda, 0x70, 0x1d,
+        0xa0, 0x38, 0x5b, 0xbf, 0xb4, 0x79, 0xf9, 0xa2, 0x24, 0x9e, 0x88, 0x06, 0x54, 0xa0, 0xe2,
+        0x17, 0x8e, 0xf5, 0x87, 0x97, 0x8b, 0x14, 0x00, 0x13, 0x86, 0xbb, 0x44, 0xb5, 0x3d, 0x37,
+        0xb4, 0x46, 0xcb, 0x44, 0xdf, 0xca, 0x71, 0xfc, 0x13, 0x33, 0x1f, 0x4e, 0x10, 0x3b, 0xb6,
+        0xe0, 0x97, 0x28, 0xbb, 0xe5, 0xdf, 0x55, 0xd7, 0x6b, 0x9c, 0x99, 0x35, 0xb6, 0x55, 0x85,
+        0x1e, 0xea, 0xa8, 0xa7, 0x63, 0xb9, 0x57, 0x27, 0x59, 0x49, 0xe7, 0x71, 0x62, 0x7c, 0xc4,
+        0xaf, 0x72, 0x75, 0x72, 0x5b, 0x9f, 0x92, 0x3a, 0x07, 0x90, 0x60, 0x0e, 0xb2, 0x4b, 0x3a,
+        0xf5, 0xa7, 0x72, 0x50, 0x06, 0x6b, 0xab, 0xd2, 0x25, 0x5a, 0xd2, 0x7a, 0x22, 0x12, 0x3c,
+        0x2d, 0x5f, 0x18, 0xfa, 0xd8, 0x2f, 0xe3, 0x6c, 0x4b, 0x22, 0xad, 0x51, 0xaa, 0x14, 0xf0,
+        0x9d, 0xd3, 0x48, 0x26, 0x12, 0xe4, 0xc1, 0x56, 0xa2, 0x21, 0xd2, 0xdc, 0xc7, 0x1c, 0x11,
+        0xfb, 0x32, 0x25, 0x19, 0xfc, 0x41, 0xcb, 0x3e, 0x53, 0x98, 0x1c, 0xc7, 0xbf, 0x30, 0xa2,
+        0xda, 0xd4, 0x9a, 0xee, 0x21, 0x1e, 0x84, 0x4e, 0xfd, 0x1f, 0x16, 0x64, 0xb3, 0x5b, 0x8b,
+        0x6b, 0x92, 0xa1, 0x8e, 0xdf, 0xe9, 0xbf, 0xc8, 0x4e, 0x89, 0xc6, 0xfd, 0x48, 0x7d, 0x5b,
+        0xf2, 0x41, 0x3d, 0x62, 0xd9, 0xf4, 0xbc, 0x47, 0x11, 0x48, 0xaa, 0xf2, 0x6b, 0x78, 0x83,
+        0xed, 0xbf, 0x69, 0x8b, 0xd2, 0x42, 0xef, 0xa7, 0xf7, 0x1a, 0x15, 0x3b, 0xeb, 0xab, 0xba,
+        0xbb, 0x12, 0xa4, 0x34, 0x93, 0xe4, 0xd4, 0x6b, 0x94, 0x4c, 0xa7, 0x5f, 0xa6, 0xea, 0x07,
+        0xa7, 0x11, 0x36, 0xc8, 0x83, 0xc4, 0xfb, 0x58, 0xec, 0xe9, 0x18, 0xda, 0xdd, 0x2f, 0x14,
+        0x6d, 0xbc, 0x5b, 0x56, 0x4e, 0xd6, 0x27, 0xbd, 0x71, 0xe1, 0x4e, 0x34, 0xf8, 0x7f, 0xe9,
+        0x12, 0xd8, 0xf2, 0x28, 0x3f, 0xde, 0x5b, 0x51, 0xd9, 0x94, 0x73, 0x8f, 0x7a, 0x4b, 0x30,
+        0xd7, 0x6e, 0xf8, 0x62, 0xc2, 0x68, 0xf2, 0xa2, 0x93, 0x16, 0x77, 0x20, 0xec, 0xef, 0xd4,
+        0x40, 0x9a, 0x93, 0xa0, 0xb9, 0xf7, 0xf0, 0xb0, 0xbd, 0x29, 0x0f, 0x58, 0xda, 0xef, 0x8b,
+        0x07, 0x3e, 0xbc, 0xd0, 0x1d, 0xb5, 0xea, 0xf9, 0xe9, 0x95, 0x4b, 0xb2, 0x31, 0xd8, 0xba,
+        0xc4, 0x47,
+    ];
+
+    #[test]
+    fn pkcs1_v15() {
+        let key = PublicKey::new(&MODULUS_2048, &EXPONENT).unwrap();
+        assert!(key
+            .verify_pkcs1_v15::<{ Sha256::HASH_SIZE }, Sha256>(MSG, &PKCS1_SHA256)
+            .is_ok());
+        assert_eq!(
+            key.verify_pkcs1_v15::<{ Sha256::HASH_SIZE }, Sha256>(b"other message", &PKCS1_SHA256),
+            Err(InvalidSig)
+        );
+        assert_eq!(
+            key.verify_pkcs1_v15::<{ Sha384::HASH_SIZE }, Sha384>(MSG, &PKCS1_SHA256),
+            Err(InvalidSig)
+        );
+        assert_eq!(
+            key.verify_pss::<{ Sha256::HASH_SIZE }, Sha256>(MSG, &PKCS1_SHA256),
+            Err(InvalidSig)
+        );
+
+        let mut bad_sig = PKCS1_SHA256;
+        bad_sig[100] ^= 1;
+        assert_eq!(
+            key.verify_pkcs1_v15::<{ Sha256::HASH_SIZE }, Sha256>(MSG, &bad_sig),
+            Err(InvalidSig)
+        );
+    }
+
+    #[test]
+    fn pss() {
+        let key = PublicKey::new(&MODULUS_2048, &EXPONENT).unwrap();
+        assert!(key
+            .verify_pss::<{ Sha256::HASH_SIZE }, Sha256>(MSG, &PSS_SHA256)
+            .is_ok());
+        assert!(key
+            .verify_pss::<{ Sha384::HASH_SIZE }, Sha384>(MSG, &PSS_SHA384)
+            .is_ok());
+        assert_eq!(
+            key.verify_pss::<{ Sha256::HASH_SIZE }, Sha256>(b"other message", &PSS_SHA256),
+            Err(InvalidSig)
+        );
+        assert_eq!(
+            key.verify_pss::<{ Sha384::HASH_SIZE }, Sha384>(MSG, &PSS_SHA256),
+            Err(InvalidSig)
+        );
+        assert_eq!(
+            key.verify_pkcs1_v15::<{ Sha256::HASH_SIZE }, Sha256>(MSG, &PSS_SHA256),
+            Err(InvalidSig)
+        );
+
+        let mut bad_sig = PSS_SHA256;
+        bad_sig[0] ^= 1;
+        assert_eq!(
+            key.verify_pss::<{ Sha256::HASH_SIZE }, Sha256>(MSG, &bad_sig),
+            Err(InvalidSig)
+        );
+    }
+
+    #[test]
+    fn odd_modulus_size() {
+        // the encoded message is a byte shorter than the modulus
+        let key = PublicKey::new(&MODULUS_2049, &EXPONENT).unwrap();
+        assert_eq!(key.modulus_len(), 257);
+        assert!(key
+            .verify_pss::<{ Sha256::HASH_SIZE }, Sha256>(MSG, &PSS_2049)
+            .is_ok());
+    }
+
+    #[test]
+    fn max_modulus_size() {
+        let key = PublicKey::new(&MODULUS_4096, &EXPONENT).unwrap();
+        assert!(key
+            .verify_pkcs1_v15::<{ Sha512::HASH_SIZE }, Sha512>(MSG, &PKCS1_SHA512_4096)
+            .is_ok());
+        assert!(key
+            .verify_pss::<{ Sha512::HASH_SIZE }, Sha512>(MSG, &PSS_SHA512_4096)
+            .is_ok());
+    }
+
+    #[test]
+    fn sig_out_of_range() {
+        let key = PublicKey::new(&MODULUS_2048, &EXPONENT).unwrap();
+        assert_eq!(
+            key.verify_pkcs1_v15::<{ Sha256::HASH_SIZE }, Sha256>(MSG, &MODULUS_2048),
+            Err(InvalidSig)
+        );
+        assert_eq!(
+            key.verify_pkcs1_v15::<{ Sha256::HASH_SIZE }, Sha256>(MSG, &PKCS1_SHA256[1..]),
+            Err(InvalidSig)
+        );
+    }
+
+    #[test]
+    fn invalid_key() {
+        let mut short_modulus = [0; 128];
+        short_modulus.copy_from_slice(&MODULUS_2048[..128]);
+        short_modulus[127] |= 1;
+        assert_eq!(PublicKey::new(&short_modulus, &EXPONENT), Err(InvalidKey));
+
+        let mut even_modulus = MODULUS_2048;
+        even_modulus[255] &= !1;
+        assert_eq!(PublicKey::new(&even_modulus, &EXPONENT), Err(InvalidKey));
+
+        assert_eq!(PublicKey::new(&MODULUS_2048, &[1]), Err(InvalidKey));
+        assert_eq!(PublicKey::new(&MODULUS_2048, &[4]), Err(InvalidKey));
+        assert_eq!(PublicKey::new(&MODULUS_2048, &[1; 9]), Err(InvalidKey));
+        // leading zeros are ignored
+        let mut padded_modulus = [0; 257];
+        padded_modulus[1..].copy_from_slice(&MODULUS_2048);
+        assert!(PublicKey::new(&padded_modulus, &[0, 0, 3]).is_ok());
+    }
+}
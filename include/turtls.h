@@ -3,6 +3,7 @@
 
 /* This file is autogenerated by cbindgen. Don't modify this manually. */
 
+#include <stdbool.h>
 #include <stddef.h>
 #include <stdint.h>
 
@@ -121,6 +122,29 @@ enum turtls_Alert
 typedef uint8_t turtls_Alert;
 #endif // __cplusplus
 
+/**
+ * Whether or not the server authenticates the client with a certificate (mutual TLS).
+ *
+ * Certificates aren't requested when resuming a session. Instead, the certificate the client
+ * authenticated with when the ticket was issued is used.
+ */
+enum turtls_ClientAuth {
+    /**
+     * No certificate is requested.
+     */
+    TURTLS_CLIENT_AUTH_NONE,
+    /**
+     * A certificate is requested, but the client may decline to send one.
+     *
+     * If the client sends a certificate, it must be valid.
+     */
+    TURTLS_CLIENT_AUTH_REQUEST,
+    /**
+     * A valid certificate is required.
+     */
+    TURTLS_CLIENT_AUTH_REQUIRE,
+};
+
 /**
  * The error that is returned when there is an error in the config.
  */
@@ -133,6 +157,40 @@ enum turtls_ConfigError {
      * One or more extensions is missing.
      */
     TURTLS_CONFIG_ERROR_MISSING_EXTENSIONS,
+    /**
+     * The certificate chain or private key is missing or invalid.
+     */
+    TURTLS_CONFIG_ERROR_MISSING_IDENTITY,
+    /**
+     * The client has trust anchors but no server name to verify the server's certificate with.
+     */
+    TURTLS_CONFIG_ERROR_MISSING_SERVER_NAME,
+    /**
+     * The application protocols are incorrectly encoded.
+     */
+    TURTLS_CONFIG_ERROR_INVALID_APP_PROTOS,
+    /**
+     * The record size limit is less than `64`.
+     */
+    TURTLS_CONFIG_ERROR_INVALID_RECORD_SIZE_LIMIT,
+};
+
+/**
+ * The status of the early data (0-RTT) of a connection.
+ */
+enum turtls_EarlyDataStatus {
+    /**
+     * No early data was sent.
+     */
+    TURTLS_EARLY_DATA_STATUS_NOT_SENT,
+    /**
+     * The early data was accepted by the server.
+     */
+    TURTLS_EARLY_DATA_STATUS_ACCEPTED,
+    /**
+     * The early data was rejected by the server and must be sent again if it is still needed.
+     */
+    TURTLS_EARLY_DATA_STATUS_REJECTED,
 };
 
 /**
@@ -142,10 +200,77 @@ enum turtls_ConfigError {
  */
 struct turtls_Connection;
 
+/**
+ * A certificate chain loaded into a config.
+ */
+struct turtls_IdentityStore;
+
+/**
+ * A set of trusted root certificates.
+ */
+struct turtls_TrustStore;
+
+/**
+ * The result of sending or reading application data.
+ *
+ * If a value other than `Ok`, `WantRead`, or `WantWrite` is returned, the connection is closed.
+ */
+enum turtls_DataResult_Tag {
+    /**
+     * Indicates that the operation was successful.
+     */
+    TURTLS_DATA_RESULT_OK,
+    /**
+     * Indicates that the peer sent an alert.
+     *
+     * `CloseNotify` indicates that the peer closed the connection.
+     */
+    TURTLS_DATA_RESULT_RECEIVED_ALERT,
+    /**
+     * Indicates that an alert was sent to the peer.
+     */
+    TURTLS_DATA_RESULT_SENT_ALERT,
+    /**
+     * Indicates that there was an error performing an IO operation.
+     */
+    TURTLS_DATA_RESULT_IO_ERROR,
+    /**
+     * Indicates that the record read took too long.
+     */
+    TURTLS_DATA_RESULT_TIMEOUT,
+    /**
+     * Indicates that there is no open connection.
+     */
+    TURTLS_DATA_RESULT_NOT_CONNECTED,
+    /**
+     * Indicates that no data could be read without blocking (non-blocking IO only).
+     */
+    TURTLS_DATA_RESULT_WANT_READ,
+    /**
+     * Indicates that the data was accepted, but not all of it could be written without blocking
+     * (non-blocking IO only).
+     *
+     * The rest is written by the next call to `turtls_send`, which may send no data.
+     */
+    TURTLS_DATA_RESULT_WANT_WRITE,
+};
+
+struct turtls_DataResult {
+    enum turtls_DataResult_Tag tag;
+    union {
+        struct {
+            turtls_Alert received_alert;
+        };
+        struct {
+            turtls_Alert sent_alert;
+        };
+    };
+};
+
 /**
  * The result of the handshake.
  *
- * If a value other than `Ok` is returned, the connection is closed.
+ * If a value other than `Ok`, `WantRead`, or `WantWrite` is returned, the connection is closed.
  */
 enum turtls_ShakeResult_Tag {
     /**
@@ -180,6 +305,19 @@ enum turtls_ShakeResult_Tag {
      * Indicates there was an error in the config struct.
      */
     TURTLS_SHAKE_RESULT_CONFIG_ERROR,
+    /**
+     * Indicates that the handshake is waiting for data from the peer (non-blocking IO only).
+     *
+     * The handshake is resumed by calling the handshake function again once data can be read.
+     */
+    TURTLS_SHAKE_RESULT_WANT_READ,
+    /**
+     * Indicates that the handshake is waiting to write data to the peer (non-blocking IO only).
+     *
+     * The handshake is resumed by calling the handshake function again once data can be
+     * written.
+     */
+    TURTLS_SHAKE_RESULT_WANT_WRITE,
 };
 
 struct turtls_ShakeResult {
@@ -209,6 +347,9 @@ struct turtls_Io {
      * `buf`: the buffer to write.
      * `amt`: the number of bytes to write.
      * `ctx`: contextual data (e.g. a file descriptor).
+     *
+     * This function must return a negative value on error, and the number of bytes written
+     * otherwise. If `Config::non_blocking` is set, it must return `0` instead of blocking.
      */
     ptrdiff_t (*write_fn)(const void *buf, size_t amt, const void *ctx);
     /**
@@ -238,6 +379,10 @@ struct turtls_Io {
 /**
  * The server name to send to the server or expect from the client.
  *
+ * Clients verify that the server's certificate was issued for this name. The name may also be
+ * an IP address, which is verified but not sent to the server. A client that has trust anchors
+ * must set a name, or the handshake fails with `ConfigError::MissingServerName`.
+ *
  * If no server name is to be sent or expected, set `name` to `NULL` and `len` to `0`.
  * By default, no name will be sent or expected.
  */
@@ -266,6 +411,48 @@ typedef uint16_t turtls_SigAlgs;
  * The Elliptic Curve Digital Signature Algorithm with curve Secp256r1 (NIST-P 256).
  */
 #define turtls_SigAlgs_ECDSA_SECP256R1 1
+/**
+ * The Elliptic Curve Digital Signature Algorithm with curve Secp384r1 (NIST-P 384).
+ */
+#define turtls_SigAlgs_ECDSA_SECP384R1 2
+/**
+ * The Elliptic Curve Digital Signature Algorithm with curve Secp521r1 (NIST-P 521).
+ */
+#define turtls_SigAlgs_ECDSA_SECP521R1 4
+/**
+ * RSASSA-PSS with SHA-256 and an rsaEncryption key.
+ */
+#define turtls_SigAlgs_RSA_PSS_RSAE_SHA256 8
+/**
+ * RSASSA-PSS with SHA-384 and an rsaEncryption key.
+ */
+#define turtls_SigAlgs_RSA_PSS_RSAE_SHA384 16
+/**
+ * RSASSA-PSS with SHA-512 and an rsaEncryption key.
+ */
+#define turtls_SigAlgs_RSA_PSS_RSAE_SHA512 32
+/**
+ * RSASSA-PKCS1-v1_5 with SHA-256.
+ *
+ * In TLS 1.3, this may only be used for signatures in certificates.
+ */
+#define turtls_SigAlgs_RSA_PKCS1_SHA256 64
+/**
+ * RSASSA-PKCS1-v1_5 with SHA-384.
+ *
+ * In TLS 1.3, this may only be used for signatures in certificates.
+ */
+#define turtls_SigAlgs_RSA_PKCS1_SHA384 128
+/**
+ * RSASSA-PKCS1-v1_5 with SHA-512.
+ *
+ * In TLS 1.3, this may only be used for signatures in certificates.
+ */
+#define turtls_SigAlgs_RSA_PKCS1_SHA512 256
+/**
+ * EdDSA with Ed25519.
+ */
+#define turtls_SigAlgs_ED25519 512
 
 /**
  * A list of curves to use for key exchange.
@@ -273,6 +460,76 @@ typedef uint16_t turtls_SigAlgs;
  * Use bit-OR to turn an option on and bit-NAND to turn an option off.
  */
 typedef uint16_t turtls_SupGroups;
+/**
+ * NIST-P 256.
+ *
+ * This is a reasonable default curve to enable.
+ */
+#define turtls_SupGroups_SECP256R1 1
+/**
+ * Curve25519 (X25519).
+ *
+ * This is the curve most peers prefer. You should probably leave it enabled.
+ */
+#define turtls_SupGroups_X25519 2
+/**
+ * NIST-P 384.
+ *
+ * Key exchange with this curve is much slower than with X25519 or NIST-P 256, so it isn't
+ * enabled by default.
+ */
+#define turtls_SupGroups_SECP384R1 4
+/**
+ * NIST-P 521.
+ *
+ * Key exchange with this curve is much slower than with X25519 or NIST-P 256, so it isn't
+ * enabled by default.
+ */
+#define turtls_SupGroups_SECP521R1 8
+
+/**
+ * The application protocols to negotiate with the peer (ALPN), such as `h2` or `http/1.1`.
+ *
+ * The protocols are listed in order of preference, and each one is prefixed with its length in
+ * a single byte. For example, `h2` and `http/1.1` are encoded as `"\x02h2\x08http/1.1"`.
+ *
+ * Clients offer these protocols to the server. Servers choose the first of these protocols that
+ * the client also offers, or let `select` choose if it isn't `NULL`. If a server can't choose a
+ * protocol, the handshake fails with `NoAppProtocol`. If the client doesn't offer any protocols,
+ * no protocol is negotiated.
+ *
+ * If no protocols are to be offered or accepted, set `protos` to `NULL` and `len` to `0`.
+ * By default, no protocols are offered or accepted.
+ */
+struct turtls_AppProtos {
+    /**
+     * The encoded list of protocols.
+     *
+     * Lifetime: this pointer must be valid for the duration of the handshake.
+     */
+    const uint8_t *protos;
+    /**
+     * The length of `protos` in bytes.
+     */
+    size_t len;
+    /**
+     * A function that chooses one of the protocols offered by the client (servers only).
+     *
+     * `protos`: the protocols offered by the client, encoded like `AppProtos.protos`.
+     * `len`: the length of `protos` in bytes.
+     * `ctx`: `select_ctx`.
+     *
+     * This function must return the index of the chosen protocol in the client's list, or a
+     * negative number if none of them are acceptable.
+     */
+    ptrdiff_t (*select)(const uint8_t *protos, size_t len, const void *ctx);
+    /**
+     * Contextual data for `select`.
+     *
+     * Lifetime: this pointer must be valid for the duration of the handshake.
+     */
+    const void *select_ctx;
+};
 
 /**
  * The extensions to use in the handshake.
@@ -298,6 +555,26 @@ struct turtls_Extensions {
      * Refer to its specific documentation for more information.
      */
     turtls_SupGroups sup_groups;
+    /**
+     * The application protocols to offer to the server or accept from the client.
+     *
+     * Refer to its specific documentation for more information.
+     */
+    struct turtls_AppProtos app_protos;
+    /**
+     * The size of the largest protected record the peer may send (RFC 8449), or `0` for no limit.
+     *
+     * This counts the plaintext of a record and its inner content type, so a limit of `n` allows
+     * `n - 1` bytes of data per record. It must be at least `64`, and limits above `16385` are
+     * sent as `16385`. Records that exceed the limit are rejected with `RecordOverflow`.
+     *
+     * Clients also request the largest `max_fragment_length` that fits within the limit, for
+     * servers that don't support record size limits. Servers ignore the limit unless the client
+     * sends one too. Either way, the peer's limit is always respected.
+     *
+     * Default value: `0`
+     */
+    uint16_t record_size_limit;
 };
 
 /**
@@ -316,6 +593,115 @@ typedef uint8_t turtls_CipherList;
  * This is a good option. You should probably leave it enabled.
  */
 #define turtls_CipherList_CHA_CHA_POLY1305_SHA256 2
+/**
+ * AES-256 GCM with SHA-384.
+ *
+ * This is slower than AES-128 GCM, but has a larger security margin.
+ */
+#define turtls_CipherList_AES_256_GCM_SHA384 4
+/**
+ * AES-128 CCM with SHA-256.
+ *
+ * This is mostly used by constrained devices. It isn't enabled by default.
+ */
+#define turtls_CipherList_AES_128_CCM_SHA256 8
+/**
+ * AES-128 CCM with an 8-byte tag and SHA-256.
+ *
+ * The shorter tag saves bandwidth on constrained networks, but makes forgeries easier. It
+ * isn't enabled by default.
+ */
+#define turtls_CipherList_AES_128_CCM_8_SHA256 16
+
+/**
+ * A DER-encoded X.509 certificate.
+ */
+struct turtls_Cert {
+    /**
+     * The DER encoding of the certificate.
+     *
+     * Lifetime: this pointer must be valid for the duration of the handshake.
+     */
+    const uint8_t *der;
+    /**
+     * The length of the certificate in bytes.
+     */
+    size_t len;
+};
+
+/**
+ * The certificate chain and private key used to authenticate to the peer.
+ *
+ * By default, no certificates are provided.
+ */
+struct turtls_Identity {
+    /**
+     * The certificate chain, starting with the end-entity certificate.
+     *
+     * Lifetime: this pointer must be valid for the duration of the handshake.
+     */
+    const struct turtls_Cert *certs;
+    /**
+     * The number of certificates in `certs`.
+     */
+    size_t num_certs;
+    /**
+     * The private key that corresponds to the end-entity certificate.
+     *
+     * A secp256r1 key is encoded as a big-endian integer, and an Ed25519 key is the 32-byte
+     * private key from RFC 8032.
+     */
+    uint8_t priv_key[32];
+};
+
+/**
+ * The settings for issuing and accepting session tickets as the server.
+ *
+ * Clients resume sessions with tickets to skip the certificate exchange, and may also send early
+ * data (0-RTT). By default, no tickets are issued.
+ */
+struct turtls_Tickets {
+    /**
+     * The secret key used to encrypt and decrypt tickets.
+     *
+     * This should be random, and servers that share it can resume each other's sessions. If it
+     * is all zeros, tickets are neither issued nor accepted.
+     */
+    uint8_t key[32];
+    /**
+     * The lifetime of tickets in seconds, up to 604800 (seven days).
+     *
+     * Default value: `7200`
+     */
+    uint32_t lifetime;
+    /**
+     * The maximum number of bytes of early data to accept from the client.
+     *
+     * If this is `0` or `anti_replay` is `NULL`, early data is rejected.
+     *
+     * Default value: `0`
+     */
+    uint32_t max_early_data;
+    /**
+     * A function that returns whether or not a ClientHello with early data is new.
+     *
+     * `id`: a value that is unique to the ClientHello.
+     * `len`: the length of `id` in bytes.
+     * `ctx`: `anti_replay_ctx`.
+     *
+     * This function must return `false` if it was called with the same `id` before, in which
+     * case the early data is rejected. ClientHellos more than 10 seconds older or newer than
+     * expected are rejected before this is called, so each `id` only needs to be remembered
+     * for 20 seconds.
+     */
+    bool (*anti_replay)(const uint8_t *id, size_t len, const void *ctx);
+    /**
+     * Contextual data for `anti_replay`.
+     *
+     * Lifetime: this pointer must be valid for the duration of the handshake.
+     */
+    const void *anti_replay_ctx;
+};
 
 /**
  * The configurations to use for a specific TLS connection.
@@ -324,11 +710,23 @@ typedef uint8_t turtls_CipherList;
  */
 struct turtls_Config {
     /**
-     * The timeout in milliseconds to use for record layer reads during the handshake.
+     * The timeout in milliseconds to use for record layer reads.
      *
      * Default value: `10000`
      */
     uint64_t timeout_millis;
+    /**
+     * Whether or not to return instead of waiting for the peer when reading or writing.
+     *
+     * If this is `true`, the handshake returns `WantRead` when `read_fn` returns `0` and
+     * `WantWrite` when `write_fn` can't write everything, and `timeout_millis` is ignored. The
+     * handshake is resumed by calling the same handshake function with the same connection and
+     * config once the IO is ready. After the handshake, `turtls_read` and `turtls_send` return
+     * `WantRead` and `WantWrite` likewise.
+     *
+     * Default value: `false`
+     */
+    bool non_blocking;
     /**
      * The extensions to use.
      */
@@ -337,12 +735,109 @@ struct turtls_Config {
      * The cipher suites to use.
      */
     turtls_CipherList cipher_suites;
+    /**
+     * The certificate chain and private key used to authenticate to the peer.
+     *
+     * This is required for servers. Clients send it if the server requests a certificate, as
+     * long as its signature scheme and issuers are acceptable to the server.
+     *
+     * This can be loaded from DER or PEM with `turtls_load_identity`.
+     */
+    struct turtls_Identity identity;
+    /**
+     * The certificates loaded by `turtls_load_identity`, which `identity` points to.
+     *
+     * This must be freed with `turtls_free_config` and shouldn't be modified directly.
+     */
+    struct turtls_IdentityStore *identity_store;
+    /**
+     * The root certificates used to authenticate the peer.
+     *
+     * If this is `NULL`, no peer can be authenticated. Trust anchors can be added with
+     * `turtls_add_trust_anchor` and must be freed with `turtls_free_config`.
+     *
+     * Lifetime: this pointer must be valid for the duration of the handshake.
+     */
+    struct turtls_TrustStore *trust_store;
+    /**
+     * Whether or not the server requests a certificate from the client.
+     *
+     * Client certificates are verified with `trust_store`. Clients ignore this.
+     *
+     * Default value: `TURTLS_CLIENT_AUTH_NONE`
+     */
+    enum turtls_ClientAuth client_auth;
+    /**
+     * A session from `turtls_get_session` to resume, or `NULL` for a full handshake.
+     *
+     * The session is ignored if it has expired or if it was established with a different server
+     * name. The server may also decline to resume it.
+     *
+     * Lifetime: this pointer must be valid for the duration of the handshake.
+     */
+    const uint8_t *session;
+    /**
+     * The length of `session` in bytes.
+     */
+    size_t session_len;
+    /**
+     * Application data to send to the server in the first flight (0-RTT), or `NULL`.
+     *
+     * Early data is only sent when resuming a session whose server accepts enough of it. Early
+     * data can be replayed by an attacker, so it must be safe to process more than once. If
+     * `turtls_early_data_status` doesn't report that it was accepted, it must be sent again with
+     * `turtls_send`.
+     *
+     * Lifetime: this pointer must be valid for the duration of the handshake.
+     */
+    const uint8_t *early_data;
+    /**
+     * The length of `early_data` in bytes.
+     */
+    size_t early_data_len;
+    /**
+     * The settings for issuing and accepting session tickets as the server.
+     */
+    struct turtls_Tickets tickets;
+    /**
+     * A function that receives the secrets of each connection, for debugging only.
+     *
+     * `line`: a line in the NSS key log format (`SSLKEYLOGFILE`), such as
+     * `CLIENT_HANDSHAKE_TRAFFIC_SECRET <client random> <secret>`. It isn't null-terminated and
+     * doesn't end with a newline.
+     * `len`: the length of `line` in bytes.
+     * `ctx`: `keylog_ctx`.
+     *
+     * Anyone with these secrets can decrypt the connection, so this should be `NULL` in
+     * production. By default, nothing is logged.
+     */
+    void (*keylog)(const char *line, size_t len, const void *ctx);
+    /**
+     * Contextual data for `keylog`.
+     *
+     * Lifetime: this pointer must be valid for the duration of the connection.
+     */
+    const void *keylog_ctx;
 };
 
 #ifdef __cplusplus
 extern "C" {
 #endif // __cplusplus
 
+/**
+ * Adds a DER-encoded root certificate to the trust anchors of `config`.
+ *
+ * The certificate is copied, so `der` only needs to be valid for the duration of this call.
+ * Returns `false` if the certificate couldn't be parsed.
+ *
+ * The trust anchors must be freed by `turtls_free_config` to avoid memory leakage.
+ *
+ * # Safety
+ * `config` must be valid.
+ * `der` must be valid for reads of `len` bytes.
+ */
+bool turtls_add_trust_anchor(struct turtls_Config *config, const uint8_t *der, size_t len);
+
 /**
  * Allocates a connection buffer.
  *
@@ -353,9 +848,13 @@ struct turtls_Connection *turtls_alloc(void);
 /**
  * Performs a TLS handshake as the client, returning the handshake status.
  *
- * If any error is returned, the connection is automatically closed.
+ * In non-blocking mode, `WantRead` or `WantWrite` is returned if the handshake can't continue
+ * until the IO is ready. The handshake is resumed by calling this function again with the same
+ * connection and config.
+ *
+ * If any other error is returned, the connection is automatically closed.
  *
- * # Safety:
+ * # Safety
  * `config` must be valid.
  * `connection` must be valid.
  */
@@ -366,26 +865,196 @@ struct turtls_ShakeResult turtls_client_handshake(struct turtls_Io io,
 /**
  * Alerts the peer and closes the connection.
  *
- * # Safety:
+ * # Safety
  * `connection` may be `NULL` but must be valid.
  */
 void turtls_close(struct turtls_Connection *connection);
 
+/**
+ * Returns whether or not the early data of the connection was accepted.
+ *
+ * For clients, this reports whether the server accepted `Config.early_data`. For servers, this
+ * reports whether early data from the client was accepted, in which case it can be read with
+ * `turtls_read`.
+ *
+ * # Safety
+ * `connection` must be valid.
+ */
+enum turtls_EarlyDataStatus turtls_early_data_status(const struct turtls_Connection *connection);
+
 /**
  * Frees a connection buffer.
  *
  * This buffer must have been allocated by `turtls_alloc`.
  *
- * # Safety:
+ * # Safety
  * `connection` must be allocated by `turtls_alloc`.
  */
 void turtls_free(struct turtls_Connection *connection);
 
+/**
+ * Frees the memory owned by `config`, such as its trust anchors and loaded identity.
+ *
+ * `config` can still be used afterwards, but it won't have any trust anchors, and its identity is
+ * reset if it was loaded by `turtls_load_identity`.
+ *
+ * # Safety
+ * `config` may be `NULL` but must be valid.
+ */
+void turtls_free_config(struct turtls_Config *config);
+
 /**
  * Generates a default configuration struct.
  */
 struct turtls_Config turtls_generate_config(void);
 
+/**
+ * Copies the application protocol negotiated with the peer (ALPN) to `buf`, returning its
+ * length.
+ *
+ * If no protocol was negotiated or the connection is closed, `0` is returned. If the protocol
+ * is longer than `len`, nothing is copied, so `buf` may be `NULL` to query the length. Protocols
+ * are never longer than 255 bytes.
+ *
+ * # Safety
+ * `connection` must be valid.
+ * `buf` must be `NULL` or valid for writes of `len` bytes.
+ */
+size_t turtls_get_app_proto(const struct turtls_Connection *connection, uint8_t *buf, size_t len);
+
+/**
+ * Copies the DER-encoded end-entity certificate of the peer to `buf`, returning its length.
+ *
+ * Servers can use this to identify clients that authenticated with a certificate. When a session
+ * is resumed, servers report the client's certificate from the original handshake, but clients
+ * report nothing. If the peer didn't send a certificate or the connection is closed, `0` is
+ * returned. If the certificate is longer than `len`, nothing is copied, so `buf` may be `NULL`
+ * to query the length.
+ *
+ * # Safety
+ * `connection` must be valid.
+ * `buf` must be `NULL` or valid for writes of `len` bytes.
+ */
+size_t turtls_get_peer_cert(const struct turtls_Connection *connection, uint8_t *buf, size_t len);
+
+/**
+ * Copies the most recent session received from the server to `buf`, returning its length.
+ *
+ * The session is an opaque byte string that can be stored and later set in `Config.session` to
+ * resume the connection. It contains secrets, so it must be stored securely. Sessions are
+ * received after the handshake, so at least one call to `turtls_read` is usually needed before
+ * one is available. The session of a closed connection remains available until the next
+ * handshake.
+ *
+ * If no session is available, `0` is returned. If the session is longer than `len`, nothing is
+ * copied, so `buf` may be `NULL` to query the length.
+ *
+ * # Safety
+ * `connection` must be valid.
+ * `buf` must be `NULL` or valid for writes of `len` bytes.
+ */
+size_t turtls_get_session(const struct turtls_Connection *connection, uint8_t *buf, size_t len);
+
+/**
+ * Loads the certificate chain and private key that `config` authenticates with.
+ *
+ * `certs` is either PEM-encoded or a sequence of DER-encoded certificates, starting with the
+ * end-entity certificate. `priv_key` is a secp256r1 or Ed25519 key, encoded as PKCS #8 or
+ * SEC 1 (`EC PRIVATE KEY`) in DER or PEM. Both are copied, so they only need to be valid for the
+ * duration of this call. Returns `false` if they couldn't be parsed or the key doesn't match the
+ * end-entity certificate, in which case `config` is unchanged.
+ *
+ * The certificates replace `config.identity` and must be freed by `turtls_free_config` to avoid
+ * memory leakage.
+ *
+ * # Safety
+ * `config` must be valid.
+ * `certs` must be valid for reads of `certs_len` bytes.
+ * `priv_key` must be valid for reads of `priv_key_len` bytes.
+ */
+bool turtls_load_identity(struct turtls_Config *config,
+                          const uint8_t *certs,
+                          size_t certs_len,
+                          const uint8_t *priv_key,
+                          size_t priv_key_len);
+
+/**
+ * Reads up to `len` bytes of application data from the peer into `buf`.
+ *
+ * The number of bytes read is written to `amt_read`. Post-handshake messages from the peer are
+ * processed automatically.
+ *
+ * In non-blocking mode, `WantRead` is returned if no application data has arrived yet, or
+ * `WantWrite` if records from an earlier call must be written first.
+ *
+ * If any other error is returned, the connection is automatically closed. If the peer closed the
+ * connection, `ReceivedAlert` is returned with `CloseNotify`.
+ *
+ * # Safety
+ * `connection` must be valid.
+ * `buf` must be valid for writes of `len` bytes.
+ * `amt_read` must be valid.
+ */
+struct turtls_DataResult turtls_read(struct turtls_Connection *connection,
+                                     void *buf,
+                                     size_t len,
+                                     size_t *amt_read);
+
+/**
+ * Encrypts and sends `len` bytes of `buf` to the peer.
+ *
+ * The data is split into as many records as needed.
+ *
+ * In non-blocking mode, `WantWrite` is returned if some of the records couldn't be written yet.
+ * The data is still accepted, and the remaining records are written by the next call to this
+ * function, which may send no data.
+ *
+ * If any other error is returned, the connection is automatically closed.
+ *
+ * # Safety
+ * `connection` must be valid.
+ * `buf` must be valid for reads of `len` bytes.
+ */
+struct turtls_DataResult turtls_send(struct turtls_Connection *connection,
+                                     const void *buf,
+                                     size_t len);
+
+/**
+ * Performs a TLS handshake as the server, returning the handshake status.
+ *
+ * `config` must contain an identity (a certificate chain and private key).
+ *
+ * In non-blocking mode, `WantRead` or `WantWrite` is returned if the handshake can't continue
+ * until the IO is ready. The handshake is resumed by calling this function again with the same
+ * connection and config.
+ *
+ * If any other error is returned, the connection is automatically closed.
+ *
+ * # Safety
+ * `config` must be valid.
+ * `connection` must be valid.
+ */
+struct turtls_ShakeResult turtls_server_handshake(struct turtls_Io io,
+                                                  struct turtls_Connection *connection,
+                                                  const struct turtls_Config *config);
+
+/**
+ * Updates the keys used to send data to the peer.
+ *
+ * If `request_update` is `true`, the peer is asked to update the keys it sends data with too.
+ * Keys are also updated automatically before they encrypt too many records to be secure.
+ *
+ * In non-blocking mode, `WantWrite` is returned if the KeyUpdate message couldn't be written yet.
+ * It is written by the next call to `turtls_send`.
+ *
+ * If any other error is returned, the connection is automatically closed.
+ *
+ * # Safety
+ * `connection` must be valid.
+ */
+struct turtls_DataResult turtls_update_keys(struct turtls_Connection *connection,
+                                            bool request_update);
+
 #ifdef __cplusplus
 }  // extern "C"
 #endif  // __cplusplus
@@ -1,48 +1,170 @@
+use crylib::aead::ccm::Ccm;
 use crylib::aead::chacha::ChaCha20Poly1305;
-use crylib::aead::gcm::{Aes128, AesCipher, Gcm};
+use crylib::aead::gcm::{Aes128, Aes256, AesCipher, Gcm};
 use crylib::aead::{Aead, BadData, IV_SIZE, TAG_SIZE};
-use crylib::hash::Sha256;
 
-use crate::{key_schedule, CipherList};
+use crate::key_schedule::{self, Digest};
+use crate::CipherList;
+
+/// The length of the tag of CCM_8.
+const CCM_8_TAG_SIZE: usize = 8;
+
+/// The length of the shortest tag of any supported cipher suite.
+pub(crate) const MIN_TAG_LEN: usize = CCM_8_TAG_SIZE;
 
 pub(crate) enum ManyAead {
     Aes128Gcm {
         writer: Gcm<Aes128>,
         reader: Gcm<Aes128>,
     },
+    Aes256Gcm {
+        writer: Gcm<Aes256>,
+        reader: Gcm<Aes256>,
+    },
     ChaChaPoly {
         writer: ChaCha20Poly1305,
         reader: ChaCha20Poly1305,
     },
+    Aes128Ccm {
+        writer: Ccm<Aes128>,
+        reader: Ccm<Aes128>,
+    },
+    Aes128Ccm8 {
+        writer: Ccm<Aes128, CCM_8_TAG_SIZE>,
+        reader: Ccm<Aes128, CCM_8_TAG_SIZE>,
+    },
 }
 
 impl ManyAead {
+    /// The length of the tag in bytes.
+    pub(crate) const fn tag_len(&self) -> usize {
+        match self {
+            Self::Aes128Ccm8 { .. } => CCM_8_TAG_SIZE,
+            _ => TAG_SIZE,
+        }
+    }
+
+    /// The number of records that can be safely encrypted with one key.
+    ///
+    /// AES-GCM is limited to 2^24.5 full-size records by RFC 8446 section 5.5, and AES-CCM to
+    /// 2^23.5 by RFC 9147 section 4.5.3. ChaCha20-Poly1305 has no practical limit.
+    const fn record_limit(&self) -> u64 {
+        match self {
+            Self::Aes128Gcm { .. } | Self::Aes256Gcm { .. } => 1 << 24,
+            Self::Aes128Ccm { .. } | Self::Aes128Ccm8 { .. } => 1 << 23,
+            Self::ChaChaPoly { .. } => 1 << 62,
+        }
+    }
+
+    /// Encrypts `msg` and writes the tag to `tag`, which must be [`Self::tag_len`] bytes long.
     pub(crate) fn encrypt_inline(
         &mut self,
         msg: &mut [u8],
         add_data: &[u8],
         iv: &[u8; IV_SIZE],
-    ) -> [u8; TAG_SIZE] {
+        tag: &mut [u8],
+    ) {
         match self {
-            Self::Aes128Gcm { writer, .. } => writer.encrypt_inline(msg, add_data, iv),
-            Self::ChaChaPoly { writer, .. } => writer.encrypt_inline(msg, add_data, iv),
+            Self::Aes128Gcm { writer, .. } => {
+                tag.copy_from_slice(&writer.encrypt_inline(msg, add_data, iv))
+            },
+            Self::Aes256Gcm { writer, .. } => {
+                tag.copy_from_slice(&writer.encrypt_inline(msg, add_data, iv))
+            },
+            Self::ChaChaPoly { writer, .. } => {
+                tag.copy_from_slice(&writer.encrypt_inline(msg, add_data, iv))
+            },
+            Self::Aes128Ccm { writer, .. } => {
+                tag.copy_from_slice(&writer.encrypt_inline(msg, add_data, iv))
+            },
+            Self::Aes128Ccm8 { writer, .. } => {
+                tag.copy_from_slice(&writer.encrypt_inline(msg, add_data, iv))
+            },
         }
     }
 
+    /// Decrypts `msg`, returning an error if `tag` doesn't match.
     pub(crate) fn decrypt_inline(
         &mut self,
         msg: &mut [u8],
         add_data: &[u8],
         iv: &[u8; IV_SIZE],
-        tag: &[u8; TAG_SIZE],
+        tag: &[u8],
     ) -> Result<(), BadData> {
         match self {
-            Self::Aes128Gcm { reader, .. } => reader.decrypt_inline(msg, add_data, iv, tag),
-            Self::ChaChaPoly { reader, .. } => reader.decrypt_inline(msg, add_data, iv, tag),
+            Self::Aes128Gcm { reader, .. } => {
+                reader.decrypt_inline(msg, add_data, iv, tag_array(tag)?)
+            },
+            Self::Aes256Gcm { reader, .. } => {
+                reader.decrypt_inline(msg, add_data, iv, tag_array(tag)?)
+            },
+            Self::ChaChaPoly { reader, .. } => {
+                reader.decrypt_inline(msg, add_data, iv, tag_array(tag)?)
+            },
+            Self::Aes128Ccm { reader, .. } => {
+                reader.decrypt_inline(msg, add_data, iv, tag_array(tag)?)
+            },
+            Self::Aes128Ccm8 { reader, .. } => {
+                reader.decrypt_inline(msg, add_data, iv, tag_array(tag)?)
+            },
         }
     }
 }
 
+/// Implements a function that swaps one direction's keys between two [`ManyAead`]s.
+macro_rules! impl_swap {
+    ($fn:ident, $key:ident) => {
+        impl ManyAead {
+            #[doc = concat!("Swaps the `", stringify!($key), "` of `self` and `other`.")]
+            ///
+            /// # Panics
+            ///
+            /// This function will panic if `self` and `other` use different AEADs.
+            fn $fn(&mut self, other: &mut Self) {
+                match (self, other) {
+                    (Self::Aes128Gcm { $key: x, .. }, Self::Aes128Gcm { $key: y, .. }) => {
+                        std::mem::swap(x, y)
+                    },
+                    (Self::Aes256Gcm { $key: x, .. }, Self::Aes256Gcm { $key: y, .. }) => {
+                        std::mem::swap(x, y)
+                    },
+                    (Self::ChaChaPoly { $key: x, .. }, Self::ChaChaPoly { $key: y, .. }) => {
+                        std::mem::swap(x, y)
+                    },
+                    (Self::Aes128Ccm { $key: x, .. }, Self::Aes128Ccm { $key: y, .. }) => {
+                        std::mem::swap(x, y)
+                    },
+                    (Self::Aes128Ccm8 { $key: x, .. }, Self::Aes128Ccm8 { $key: y, .. }) => {
+                        std::mem::swap(x, y)
+                    },
+                    _ => panic!("both AEADs must use the same cipher suite"),
+                }
+            }
+        }
+    };
+}
+
+impl_swap!(swap_reader, reader);
+impl_swap!(swap_writer, writer);
+
+/// Converts `tag` to an array, failing if it has the wrong length.
+fn tag_array<const T_LEN: usize>(tag: &[u8]) -> Result<&[u8; T_LEN], BadData> {
+    tag.try_into().map_err(|_| BadData)
+}
+
+/// Derives the write and read keys from their traffic secrets.
+fn traffic_keys<const K_LEN: usize>(
+    write_secret: &Digest,
+    read_secret: &Digest,
+) -> ([u8; K_LEN], [u8; K_LEN]) {
+    let mut write_key = [0; K_LEN];
+    key_schedule::hkdf_expand_label(&mut write_key, write_secret, b"key", b"");
+
+    let mut read_key = [0; K_LEN];
+    key_schedule::hkdf_expand_label(&mut read_key, read_secret, b"key", b"");
+    (write_key, read_key)
+}
+
 pub(crate) struct TlsAead {
     aead: ManyAead,
     write_iv: [u8; IV_SIZE],
@@ -68,8 +190,8 @@ impl TlsAead {
     }
 
     pub(crate) fn new(
-        write_secret: &[u8; Sha256::HASH_SIZE],
-        read_secret: &[u8; Sha256::HASH_SIZE],
+        write_secret: &Digest,
+        read_secret: &Digest,
         cipher: CipherList,
     ) -> Option<Self> {
         let mut write_iv = [0; IV_SIZE];
@@ -78,73 +200,113 @@ impl TlsAead {
         let mut read_iv = [0; IV_SIZE];
         key_schedule::hkdf_expand_label(&mut read_iv, read_secret, b"iv", b"");
 
-        match cipher.suites {
+        let aead = match cipher.suites {
             CipherList::AES_128_GCM_SHA256 => {
-                let mut write_key = [0; Aes128::KEY_SIZE];
-                key_schedule::hkdf_expand_label(&mut write_key, write_secret, b"key", b"");
-
-                let mut read_key = [0; Aes128::KEY_SIZE];
-                key_schedule::hkdf_expand_label(&mut read_key, read_secret, b"key", b"");
-
-                Some(Self {
-                    aead: ManyAead::Aes128Gcm {
-                        writer: Gcm::<Aes128>::new(write_key),
-                        reader: Gcm::<Aes128>::new(read_key),
-                    },
-                    write_iv,
-                    write_nonce: Self::NONCE_INIT,
-                    read_iv,
-                    read_nonce: Self::NONCE_INIT,
-                })
+                let (write_key, read_key) =
+                    traffic_keys::<{ Aes128::KEY_SIZE }>(write_secret, read_secret);
+                ManyAead::Aes128Gcm {
+                    writer: Gcm::new(write_key),
+                    reader: Gcm::new(read_key),
+                }
+            },
+            CipherList::AES_256_GCM_SHA384 => {
+                let (write_key, read_key) =
+                    traffic_keys::<{ Aes256::KEY_SIZE }>(write_secret, read_secret);
+                ManyAead::Aes256Gcm {
+                    writer: Gcm::new(write_key),
+                    reader: Gcm::new(read_key),
+                }
             },
             CipherList::CHA_CHA_POLY1305_SHA256 => {
-                let mut write_key = [0; ChaCha20Poly1305::KEY_SIZE];
-                key_schedule::hkdf_expand_label(&mut write_key, write_secret, b"key", b"");
+                let (write_key, read_key) =
+                    traffic_keys::<{ ChaCha20Poly1305::KEY_SIZE }>(write_secret, read_secret);
+                ManyAead::ChaChaPoly {
+                    writer: ChaCha20Poly1305::new(write_key),
+                    reader: ChaCha20Poly1305::new(read_key),
+                }
+            },
+            CipherList::AES_128_CCM_SHA256 => {
+                let (write_key, read_key) =
+                    traffic_keys::<{ Aes128::KEY_SIZE }>(write_secret, read_secret);
+                ManyAead::Aes128Ccm {
+                    writer: Ccm::new(write_key),
+                    reader: Ccm::new(read_key),
+                }
+            },
+            CipherList::AES_128_CCM_8_SHA256 => {
+                let (write_key, read_key) =
+                    traffic_keys::<{ Aes128::KEY_SIZE }>(write_secret, read_secret);
+                ManyAead::Aes128Ccm8 {
+                    writer: Ccm::new(write_key),
+                    reader: Ccm::new(read_key),
+                }
+            },
+            _ => return None,
+        };
+        Some(Self {
+            aead,
+            write_iv,
+            write_nonce: Self::NONCE_INIT,
+            read_iv,
+            read_nonce: Self::NONCE_INIT,
+        })
+    }
 
-                let mut read_key = [0; ChaCha20Poly1305::KEY_SIZE];
-                key_schedule::hkdf_expand_label(&mut read_key, read_secret, b"key", b"");
+    /// The length of the tag in bytes.
+    pub(crate) const fn tag_len(&self) -> usize {
+        self.aead.tag_len()
+    }
 
-                Some(Self {
-                    aead: ManyAead::ChaChaPoly {
-                        writer: ChaCha20Poly1305::new(write_key),
-                        reader: ChaCha20Poly1305::new(read_key),
-                    },
-                    write_iv,
-                    write_nonce: Self::NONCE_INIT,
-                    read_iv,
-                    read_nonce: Self::NONCE_INIT,
-                })
-            },
-            _ => None,
-        }
+    /// Returns `true` if the write keys must be updated before more records are sent.
+    pub(crate) const fn needs_update(&self) -> bool {
+        self.write_nonce >= self.aead.record_limit()
     }
 
     pub(crate) fn decrypt_inline(
         &mut self,
         msg: &mut [u8],
         add_data: &[u8],
-        tag: &[u8; TAG_SIZE],
+        tag: &[u8],
     ) -> Result<(), BadData> {
+        // the peer must update its keys before the nonce runs out
+        let Some(next_nonce) = self.read_nonce.checked_add(1) else {
+            return Err(BadData);
+        };
         let mut init_vec = self.read_iv;
         let counter = self.read_nonce.to_be_bytes();
         for (byte_1, byte_2) in init_vec.iter_mut().rev().zip(counter.into_iter().rev()) {
             *byte_1 ^= byte_2;
         }
-        // overflow must not happen
-        self.read_nonce = self.read_nonce.checked_add(1).unwrap();
+        self.aead.decrypt_inline(msg, add_data, &init_vec, tag)?;
+        // only records that decrypt use up a nonce, so others can be skipped
+        self.read_nonce = next_nonce;
+        Ok(())
+    }
 
-        self.aead.decrypt_inline(msg, add_data, &init_vec, tag)
+    /// Swaps the read keys of `self` and `other`, which must use the same cipher suite.
+    pub(crate) fn swap_reader(&mut self, other: &mut Self) {
+        self.aead.swap_reader(&mut other.aead);
+        std::mem::swap(&mut self.read_iv, &mut other.read_iv);
+        std::mem::swap(&mut self.read_nonce, &mut other.read_nonce);
     }
 
-    pub(crate) fn encrypt_inline(&mut self, msg: &mut [u8], add_data: &[u8]) -> [u8; TAG_SIZE] {
-        let mut init_vec = self.read_iv;
-        let counter = self.read_nonce.to_be_bytes();
+    /// Swaps the write keys of `self` and `other`, which must use the same cipher suite.
+    pub(crate) fn swap_writer(&mut self, other: &mut Self) {
+        self.aead.swap_writer(&mut other.aead);
+        std::mem::swap(&mut self.write_iv, &mut other.write_iv);
+        std::mem::swap(&mut self.write_nonce, &mut other.write_nonce);
+    }
+
+    /// Encrypts `msg` and writes the tag to `tag`, which must be [`Self::tag_len`] bytes long.
+    pub(crate) fn encrypt_inline(&mut self, msg: &mut [u8], add_data: &[u8], tag: &mut [u8]) {
+        let mut init_vec = self.write_iv;
+        let counter = self.write_nonce.to_be_bytes();
         for (byte_1, byte_2) in init_vec.iter_mut().rev().zip(counter.into_iter().rev()) {
             *byte_1 ^= byte_2;
         }
-        // overflow must not happen
-        self.read_nonce = self.read_nonce.checked_add(1).unwrap();
+        // the keys are updated long before the nonce can overflow
+        self.write_nonce += 1;
 
-        self.aead.encrypt_inline(msg, add_data, &init_vec)
+        self.aead.encrypt_inline(msg, add_data, &init_vec, tag)
     }
 }
@@ -1,3 +1,4 @@
+#[derive(Clone, Copy)]
 #[repr(u8)]
 pub enum AlertLevel {
     #[expect(unused, reason = "TLS 1.3 requires all alerts to be fatal")]
@@ -45,6 +46,7 @@ pub enum Alert {
     InsufficientSecurity = 71,
     /// An unrelated internal error has occured.
     InternalError = 80,
+    /// The client tried to fall back to an older protocol version without need.
     InappropriateFallback = 86,
     /// The user interupted the handshake.
     UserCancelled = 90,
@@ -54,7 +56,9 @@ pub enum Alert {
     UnsupportedExtension = 110,
     /// The provided server name is unrecognized.
     UnrecognizedName = 112,
+    /// The server sent an invalid OCSP response.
     BadCertStatusResponse = 113,
+    /// No acceptable PSK identity was provided.
     UnknownPskIdentity = 115,
     /// A certificate is required.
     CertRequired = 116,
@@ -63,6 +67,7 @@ pub enum Alert {
 }
 
 impl Alert {
+    /// Converts a byte into an [`Alert`], returning `CloseNotify` if the byte is unknown.
     pub fn from_byte(byte: u8) -> Self {
         use Alert::*;
         // TODO: use inline const once stabilized
@@ -99,6 +104,7 @@ impl Alert {
     }
 }
 
+#[derive(Clone, Copy)]
 pub(crate) struct AlertMsg {
     level: AlertLevel,
     pub description: Alert,
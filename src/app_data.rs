@@ -0,0 +1,112 @@
+//! Sending and reading application data once the handshake is complete.
+
+use crate::alert::Alert;
+use crate::error::{DataResult, TlsError};
+use crate::handshake::{ShakeType, SHAKE_HEADER_SIZE};
+use crate::key_update;
+use crate::record::{ContentType, IoError};
+use crate::session::Session;
+use crate::state::State;
+
+/// Encrypts `data` and sends it to the peer, splitting it into as many records as needed.
+///
+/// The write keys are updated whenever they have encrypted as many records as is safe. In
+/// non-blocking mode, records that couldn't be written yet are written first.
+pub(crate) fn send(state: &mut State, data: &[u8]) -> DataResult {
+    match send_records(state, data).and_then(|()| state.rl.unenc_rl.flush()) {
+        Ok(true) => DataResult::Ok,
+        Ok(false) => DataResult::WantWrite,
+        Err(err) => err.into(),
+    }
+}
+
+fn send_records(state: &mut State, data: &[u8]) -> Result<(), IoError> {
+    for chunk in data.chunks(state.rl.max_write_len) {
+        if state.rl.aead.needs_update() {
+            key_update::send(state, false)?;
+        }
+        state.rl.write(ContentType::ApplicationData, chunk)?;
+    }
+    Ok(())
+}
+
+/// Reads application data into `buf`, returning the number of bytes read.
+///
+/// Data left over from a previously-read record is returned first. Otherwise, records are read
+/// until one containing application data arrives, processing any post-handshake messages along
+/// the way.
+///
+/// If an error occurs, the peer is alerted (if applicable) and the connection is closed.
+pub(crate) fn read(state: &mut State, buf: &mut [u8]) -> Result<usize, DataResult> {
+    if !state.app_data.is_empty() {
+        let amt = buf.len().min(state.app_data.len());
+        buf[..amt].copy_from_slice(&state.app_data[..amt]);
+        state.app_data.drain(..amt);
+        return Ok(amt);
+    }
+
+    // the peer may be waiting for records that couldn't be written yet
+    state.rl.unenc_rl.flush()?;
+    match read_record(state, buf) {
+        Err(DataResult::WantRead) if state.rl.unenc_rl.has_pending() => Err(DataResult::WantWrite),
+        result => result.inspect_err(|err| match *err {
+            DataResult::SentAlert(alert) => state.rl.alert_and_close(alert),
+            DataResult::ReceivedAlert(_) => state.rl.close(),
+            _ => (),
+        }),
+    }
+}
+
+/// Reads records until one containing application data arrives.
+///
+/// As much of the data as fits is copied to `buf`, and the rest is saved for later reads.
+fn read_record(state: &mut State, buf: &mut [u8]) -> Result<usize, DataResult> {
+    loop {
+        let msg_type = state.rl.read(state.record_timeout)?;
+
+        if msg_type == ContentType::Handshake.to_byte() {
+            state
+                .shake_buf
+                .push(state.rl.buf())
+                .map_err(TlsError::Sent)?;
+            process_post_shake(state)?;
+            continue;
+        }
+        // handshake messages must not be interleaved with other records
+        if msg_type != ContentType::ApplicationData.to_byte() || !state.shake_buf.is_empty() {
+            return Err(DataResult::SentAlert(Alert::UnexpectedMessage));
+        }
+
+        let record = state.rl.buf();
+        // empty records must not be mistaken for the end of the stream
+        if record.is_empty() {
+            continue;
+        }
+        let amt = buf.len().min(record.len());
+        buf[..amt].copy_from_slice(&record[..amt]);
+        state.app_data.extend_from_slice(&record[amt..]);
+        return Ok(amt);
+    }
+}
+
+/// Processes the complete handshake messages received after the handshake.
+fn process_post_shake(state: &mut State) -> Result<(), DataResult> {
+    while let Some(msg) = state.shake_buf.next_msg().map_err(TlsError::Sent)? {
+        match msg[0] {
+            x if x == ShakeType::NewSessionTicket.to_byte() => {
+                // only servers send tickets
+                let Some(ref resumption) = state.resumption else {
+                    return Err(DataResult::SentAlert(Alert::UnexpectedMessage));
+                };
+                if let Some(session) = Session::from_ticket(&msg[SHAKE_HEADER_SIZE..], resumption)
+                    .map_err(TlsError::Sent)?
+                {
+                    state.session = Some(session);
+                }
+            },
+            x if x == ShakeType::KeyUpdate.to_byte() => key_update::receive(state, &msg)?,
+            _ => return Err(DataResult::SentAlert(Alert::UnexpectedMessage)),
+        }
+    }
+    Ok(())
+}
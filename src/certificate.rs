@@ -0,0 +1,429 @@
+//! The CertificateRequest, Certificate, and CertificateVerify handshake messages.
+
+use crylib::big_int::UBigInt;
+use crylib::ec::{ecdsa, ed25519, EllipticCurve, Secp256r1};
+use crylib::finite_field::FieldElement;
+use crylib::hash::Sha256;
+use getrandom::{getrandom, Error};
+
+use crate::alert::Alert;
+use crate::cipher_suites::SignatureScheme;
+use crate::dh::NamedGroup;
+use crate::extensions::{ExtensionType, Extensions, SigAlgs};
+use crate::handshake::{ShakeType, SHAKE_HEADER_SIZE};
+use crate::x509::{Certificate, PublicKey, SigAlg, SigHash};
+
+/// A parsed Certificate message.
+pub(crate) struct CertificateRef<'a> {
+    /// The DER-encoded certificates, starting with the end-entity certificate.
+    pub(crate) certs: Vec<&'a [u8]>,
+}
+
+impl<'a> CertificateRef<'a> {
+    const CONTEXT_LEN_SIZE: usize = 1;
+    const LIST_LEN_SIZE: usize = 3;
+    const CERT_LEN_SIZE: usize = 3;
+    const EXTS_LEN_SIZE: usize = 2;
+
+    /// Parses the body of a Certificate message.
+    ///
+    /// The list of certificates may be empty if the client declined to send one.
+    pub(crate) fn parse(mut msg: &'a [u8]) -> Result<Self, Alert> {
+        if msg.len() < Self::CONTEXT_LEN_SIZE + Self::LIST_LEN_SIZE {
+            return Err(Alert::DecodeError);
+        }
+        // the context is always empty outside of post-handshake authentication
+        if msg[0] != 0 {
+            return Err(Alert::IllegalParam);
+        }
+        msg = &msg[Self::CONTEXT_LEN_SIZE..];
+
+        let list_len = u32::from_be_bytes([0, msg[0], msg[1], msg[2]]) as usize;
+        msg = &msg[Self::LIST_LEN_SIZE..];
+        if list_len != msg.len() {
+            return Err(Alert::DecodeError);
+        }
+
+        let mut certs = Vec::new();
+        while !msg.is_empty() {
+            if msg.len() < Self::CERT_LEN_SIZE {
+                return Err(Alert::DecodeError);
+            }
+            let cert_len = u32::from_be_bytes([0, msg[0], msg[1], msg[2]]) as usize;
+            msg = &msg[Self::CERT_LEN_SIZE..];
+
+            if cert_len == 0 || msg.len() < cert_len + Self::EXTS_LEN_SIZE {
+                return Err(Alert::DecodeError);
+            }
+            certs.push(&msg[..cert_len]);
+            msg = &msg[cert_len..];
+
+            let exts_len = u16::from_be_bytes([msg[0], msg[1]]) as usize;
+            msg = &msg[Self::EXTS_LEN_SIZE..];
+            if msg.len() < exts_len {
+                return Err(Alert::DecodeError);
+            }
+            // no certificate extensions are supported, so they are skipped
+            msg = &msg[exts_len..];
+        }
+        Ok(Self { certs })
+    }
+
+    /// Returns the Certificate message, including its header.
+    pub(crate) fn to_vec(&self) -> Vec<u8> {
+        let list_len: usize = self
+            .certs
+            .iter()
+            .map(|cert| Self::CERT_LEN_SIZE + cert.len() + Self::EXTS_LEN_SIZE)
+            .sum();
+        let len = Self::CONTEXT_LEN_SIZE + Self::LIST_LEN_SIZE + list_len;
+
+        let mut msg = Vec::with_capacity(SHAKE_HEADER_SIZE + len);
+        msg.push(ShakeType::Certificate.to_byte());
+        msg.extend_from_slice(&(len as u32).to_be_bytes()[1..]);
+
+        // the context is always empty outside of post-handshake authentication
+        msg.push(0);
+        msg.extend_from_slice(&(list_len as u32).to_be_bytes()[1..]);
+        for cert in &self.certs {
+            msg.extend_from_slice(&(cert.len() as u32).to_be_bytes()[1..]);
+            msg.extend_from_slice(cert);
+            // no certificate extensions are supported
+            msg.extend_from_slice(&[0; Self::EXTS_LEN_SIZE]);
+        }
+        msg
+    }
+}
+
+/// A parsed CertificateRequest message.
+pub(crate) struct CertRequestRef<'a> {
+    /// The signature schemes the server accepts in CertificateVerify.
+    pub(crate) sig_algs: SigAlgs,
+    /// The DER-encoded distinguished names of the certificate authorities the server accepts.
+    ///
+    /// If this is empty, the server accepts any authority.
+    pub(crate) authorities: Vec<&'a [u8]>,
+}
+
+impl<'a> CertRequestRef<'a> {
+    const CONTEXT_LEN_SIZE: usize = 1;
+    const AUTHORITIES_LEN_SIZE: usize = 2;
+    const NAME_LEN_SIZE: usize = 2;
+
+    /// Parses the body of a CertificateRequest message.
+    ///
+    /// Unrecognized extensions are ignored.
+    pub(crate) fn parse(msg: &'a [u8]) -> Result<Self, Alert> {
+        if msg.len() < Self::CONTEXT_LEN_SIZE + Extensions::LEN_SIZE {
+            return Err(Alert::DecodeError);
+        }
+        // the context is always empty outside of post-handshake authentication
+        if msg[0] != 0 {
+            return Err(Alert::IllegalParam);
+        }
+        let mut extensions = &msg[Self::CONTEXT_LEN_SIZE + Extensions::LEN_SIZE..];
+        if u16::from_be_bytes([msg[1], msg[2]]) as usize != extensions.len() {
+            return Err(Alert::DecodeError);
+        }
+
+        let mut sig_algs = None;
+        let mut authorities = Vec::new();
+        while !extensions.is_empty() {
+            if extensions.len() < Extensions::HEADER_SIZE {
+                return Err(Alert::DecodeError);
+            }
+            let len = u16::from_be_bytes([extensions[2], extensions[3]]) as usize;
+            if extensions.len() - Extensions::HEADER_SIZE < len {
+                return Err(Alert::DecodeError);
+            }
+            let ext = &extensions[Extensions::HEADER_SIZE..][..len];
+
+            match &extensions[..size_of::<ExtensionType>()] {
+                x if x == ExtensionType::SignatureAlgorithms.to_be_bytes() => {
+                    sig_algs = Some(SigAlgs::parse_list(ext)?);
+                },
+                x if x == ExtensionType::CertAuthorities.to_be_bytes() => {
+                    authorities = Self::parse_authorities(ext)?;
+                },
+                _ => (),
+            }
+
+            extensions = &extensions[Extensions::HEADER_SIZE + len..];
+        }
+
+        let Some(sig_algs) = sig_algs else {
+            return Err(Alert::MissingExtension);
+        };
+        Ok(Self {
+            sig_algs,
+            authorities,
+        })
+    }
+
+    /// Parses the body of the certificate_authorities extension.
+    fn parse_authorities(ext: &'a [u8]) -> Result<Vec<&'a [u8]>, Alert> {
+        if ext.len() < Self::AUTHORITIES_LEN_SIZE
+            || u16::from_be_bytes([ext[0], ext[1]]) as usize
+                != ext.len() - Self::AUTHORITIES_LEN_SIZE
+        {
+            return Err(Alert::DecodeError);
+        }
+        let mut list = &ext[Self::AUTHORITIES_LEN_SIZE..];
+        let mut authorities = Vec::new();
+        while !list.is_empty() {
+            if list.len() < Self::NAME_LEN_SIZE {
+                return Err(Alert::DecodeError);
+            }
+            let len = u16::from_be_bytes([list[0], list[1]]) as usize;
+            list = &list[Self::NAME_LEN_SIZE..];
+            if len == 0 || list.len() < len {
+                return Err(Alert::DecodeError);
+            }
+            authorities.push(&list[..len]);
+            list = &list[len..];
+        }
+        if authorities.is_empty() {
+            return Err(Alert::DecodeError);
+        }
+        Ok(authorities)
+    }
+
+    /// Returns whether or not a certificate chain is issued by one of the accepted authorities.
+    ///
+    /// `certs` starts with the end-entity certificate.
+    pub(crate) fn accepts_chain(&self, certs: &[&[u8]]) -> bool {
+        if self.authorities.is_empty() {
+            return true;
+        }
+        certs
+            .iter()
+            .filter_map(|cert| Certificate::parse(cert).ok())
+            .any(|cert| {
+                self.authorities
+                    .iter()
+                    .any(|name| *name == cert.issuer.raw || *name == cert.subject.raw)
+            })
+    }
+
+    /// Returns the CertificateRequest message, including its header.
+    pub(crate) fn to_vec(&self) -> Vec<u8> {
+        let mut extensions = Vec::new();
+        self.sig_algs.write_cert_request(&mut extensions);
+
+        let authorities_len: usize = self
+            .authorities
+            .iter()
+            .map(|name| Self::NAME_LEN_SIZE + name.len())
+            .sum();
+        // a list that doesn't fit is left out, in which case any authority is accepted
+        if authorities_len > 0 && Self::AUTHORITIES_LEN_SIZE + authorities_len <= u16::MAX as usize
+        {
+            extensions.extend_from_slice(&ExtensionType::CertAuthorities.to_be_bytes());
+            extensions.extend_from_slice(
+                &((Self::AUTHORITIES_LEN_SIZE + authorities_len) as u16).to_be_bytes(),
+            );
+            extensions.extend_from_slice(&(authorities_len as u16).to_be_bytes());
+            for name in &self.authorities {
+                extensions.extend_from_slice(&(name.len() as u16).to_be_bytes());
+                extensions.extend_from_slice(name);
+            }
+        }
+
+        let len = Self::CONTEXT_LEN_SIZE + Extensions::LEN_SIZE + extensions.len();
+        let mut msg = Vec::with_capacity(SHAKE_HEADER_SIZE + len);
+        msg.push(ShakeType::CertificateRequest.to_byte());
+        msg.extend_from_slice(&(len as u32).to_be_bytes()[1..]);
+        // the context is always empty outside of post-handshake authentication
+        msg.push(0);
+        msg.extend_from_slice(&(extensions.len() as u16).to_be_bytes());
+        msg.extend_from_slice(&extensions);
+        msg
+    }
+}
+
+/// A parsed CertificateVerify message.
+pub(crate) struct CertVerifyRef<'a> {
+    pub(crate) scheme: [u8; size_of::<SignatureScheme>()],
+    pub(crate) signature: &'a [u8],
+}
+
+impl<'a> CertVerifyRef<'a> {
+    const SIG_LEN_SIZE: usize = 2;
+
+    /// Parses the body of a CertificateVerify message.
+    pub(crate) fn parse(msg: &'a [u8]) -> Result<Self, Alert> {
+        if msg.len() < size_of::<SignatureScheme>() + Self::SIG_LEN_SIZE {
+            return Err(Alert::DecodeError);
+        }
+        let scheme = [msg[0], msg[1]];
+        let sig_len = u16::from_be_bytes([msg[2], msg[3]]) as usize;
+        let signature = &msg[size_of::<SignatureScheme>() + Self::SIG_LEN_SIZE..];
+        if sig_len != signature.len() {
+            return Err(Alert::DecodeError);
+        }
+        Ok(Self { scheme, signature })
+    }
+
+    /// Verifies the signature with the peer's public key.
+    ///
+    /// `transcript` is the transcript hash up to, but not including, the CertificateVerify
+    /// message.
+    pub(crate) fn verify(
+        &self,
+        pub_key: &PublicKey,
+        context: &[u8],
+        transcript: &[u8],
+    ) -> Result<(), Alert> {
+        // in TLS 1.3, each ECDSA scheme can only be used with one curve
+        let (sig_alg, curve) = match self.scheme {
+            x if x == SignatureScheme::EcdsaSecp256r1Sha256.to_be_bytes() => {
+                (SigAlg::Ecdsa(SigHash::Sha256), Some(NamedGroup::Secp256r1))
+            },
+            x if x == SignatureScheme::EcdsaSecp384r1Sha384.to_be_bytes() => {
+                (SigAlg::Ecdsa(SigHash::Sha384), Some(NamedGroup::Secp384r1))
+            },
+            x if x == SignatureScheme::EcdsaSecp521r1Sha512.to_be_bytes() => {
+                (SigAlg::Ecdsa(SigHash::Sha512), Some(NamedGroup::Secp521r1))
+            },
+            // RSASSA-PKCS1-v1_5 can't be used for CertificateVerify
+            x if x == SignatureScheme::RsaPssRsaeSha256.to_be_bytes() => {
+                (SigAlg::RsaPss(SigHash::Sha256), None)
+            },
+            x if x == SignatureScheme::RsaPssRsaeSha384.to_be_bytes() => {
+                (SigAlg::RsaPss(SigHash::Sha384), None)
+            },
+            x if x == SignatureScheme::RsaPssRsaeSha512.to_be_bytes() => {
+                (SigAlg::RsaPss(SigHash::Sha512), None)
+            },
+            x if x == SignatureScheme::Ed25519.to_be_bytes() => (SigAlg::Ed25519, None),
+            _ => return Err(Alert::IllegalParam),
+        };
+        let matches_key = match *pub_key {
+            PublicKey::Ec {
+                curve: key_curve, ..
+            } => curve == Some(key_curve),
+            PublicKey::Rsa { .. } => matches!(sig_alg, SigAlg::RsaPss(_)),
+            PublicKey::Ed25519(_) => sig_alg == SigAlg::Ed25519,
+        };
+        if !matches_key {
+            return Err(Alert::IllegalParam);
+        }
+        pub_key.verify(
+            sig_alg,
+            &signed_content(context, transcript),
+            self.signature,
+        )
+    }
+
+    /// Returns the CertificateVerify message, including its header.
+    pub(crate) fn to_vec(&self) -> Vec<u8> {
+        let len = size_of::<SignatureScheme>() + Self::SIG_LEN_SIZE + self.signature.len();
+
+        let mut msg = Vec::with_capacity(SHAKE_HEADER_SIZE + len);
+        msg.push(ShakeType::CertificateVerify.to_byte());
+        msg.extend_from_slice(&(len as u32).to_be_bytes()[1..]);
+        msg.extend_from_slice(&self.scheme);
+        msg.extend_from_slice(&(self.signature.len() as u16).to_be_bytes());
+        msg.extend_from_slice(self.signature);
+        msg
+    }
+}
+
+/// The context string of the server's CertificateVerify signature.
+pub(crate) const SER_CONTEXT: &[u8] = b"TLS 1.3, server CertificateVerify";
+/// The context string of the client's CertificateVerify signature.
+pub(crate) const CLI_CONTEXT: &[u8] = b"TLS 1.3, client CertificateVerify";
+
+/// Returns the content that is covered by a CertificateVerify signature.
+fn signed_content(context: &[u8], transcript: &[u8]) -> Vec<u8> {
+    const PREFIX: [u8; 64] = [0x20; 64];
+
+    let mut content = Vec::with_capacity(PREFIX.len() + context.len() + 1 + transcript.len());
+    content.extend_from_slice(&PREFIX);
+    content.extend_from_slice(context);
+    content.push(0);
+    content.extend_from_slice(transcript);
+    content
+}
+
+/// The private key used to sign the CertificateVerify message.
+pub(crate) enum SigningKey {
+    Secp256r1(FieldElement<4, <Secp256r1 as EllipticCurve<4>>::Order>),
+    Ed25519(ed25519::SigningKey),
+}
+
+impl SigningKey {
+    /// Creates the key for the end-entity certificate `cert`.
+    ///
+    /// The type of `priv_key` is chosen by the certificate's public key. Returns `None` if the
+    /// key type isn't supported or `priv_key` doesn't match the certificate.
+    pub(crate) fn new(cert: &[u8], priv_key: &[u8; 32]) -> Option<Self> {
+        let cert = Certificate::parse(cert).ok()?;
+        match cert.public_key {
+            PublicKey::Ec {
+                curve: NamedGroup::Secp256r1,
+                point,
+            } => {
+                let key = FieldElement::try_new(UBigInt::<4>::from_be_bytes(*priv_key)).ok()?;
+                // a key of zero results in the point at infinity
+                let pub_key = Secp256r1::BASE_POINT.mul_scalar(&key).as_affine()?;
+                let mut encoded = [0x04; 65];
+                encoded[1..33].copy_from_slice(&pub_key.x().into_inner().to_be_bytes());
+                encoded[33..].copy_from_slice(&pub_key.y().into_inner().to_be_bytes());
+                (encoded == *point).then_some(Self::Secp256r1(key))
+            },
+            PublicKey::Ed25519(pub_key) => {
+                let key = ed25519::SigningKey::from_seed(priv_key);
+                (key.public_key() == pub_key).then_some(Self::Ed25519(key))
+            },
+            _ => None,
+        }
+    }
+
+    /// Returns the [`SigAlgs`](crate::extensions::SigAlgs) flag of the signature scheme.
+    pub(crate) const fn sig_algs_flag(&self) -> u16 {
+        match self {
+            Self::Secp256r1(_) => SigAlgs::ECDSA_SECP256R1,
+            Self::Ed25519(_) => SigAlgs::ED25519,
+        }
+    }
+
+    /// Returns the signature scheme that this key signs with.
+    pub(crate) const fn scheme(&self) -> SignatureScheme {
+        match self {
+            Self::Secp256r1(_) => SignatureScheme::EcdsaSecp256r1Sha256,
+            Self::Ed25519(_) => SignatureScheme::Ed25519,
+        }
+    }
+
+    /// Signs the transcript, returning the signature in its TLS encoding.
+    pub(crate) fn sign(&self, context: &[u8], transcript: &[u8]) -> Result<Vec<u8>, Error> {
+        let content = signed_content(context, transcript);
+        match self {
+            Self::Secp256r1(key) => sign_secp256r1(key, &content),
+            Self::Ed25519(key) => Ok(key.sign(&content).to_vec()),
+        }
+    }
+}
+
+/// Signs `msg` with ECDSA secp256r1 and SHA-256, returning the DER-encoded signature.
+fn sign_secp256r1(
+    priv_key: &FieldElement<4, <Secp256r1 as EllipticCurve<4>>::Order>,
+    msg: &[u8],
+) -> Result<Vec<u8>, Error> {
+    // the nonce is hedged, so a weak random number generator can't leak the key
+    let mut extra_entropy = [0; 32];
+    getrandom(&mut extra_entropy)?;
+    let sig =
+        ecdsa::sign_hedged::<4, Secp256r1, { Sha256::HASH_SIZE }, { Sha256::BLOCK_SIZE }, Sha256>(
+            msg,
+            priv_key,
+            &extra_entropy,
+        );
+
+    let mut der =
+        vec![0; ecdsa::Signature::<4, <Secp256r1 as EllipticCurve<4>>::Order>::der_max_len()];
+    let len = sig.encode_der(&mut der);
+    der.truncate(len);
+    Ok(der)
+}
@@ -1,9 +1,5 @@
-use crylib::big_int::UBigInt;
-use crylib::ec::{EllipticCurve, Secp256r1};
-use crylib::finite_field::FieldElement;
-use getrandom::getrandom;
-
-use crate::{extensions::SupGroups, record::RecordLayer};
+use crate::key_schedule::HashAlg;
+use crate::record::RecordLayer;
 
 /// The supported ciphersuites.
 #[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
@@ -21,6 +17,19 @@ impl CipherList {
     ///
     /// This is a good option. You should probably leave it enabled.
     pub const CHA_CHA_POLY1305_SHA256: u8 = 0b00000010;
+    /// AES-256 GCM with SHA-384.
+    ///
+    /// This is slower than AES-128 GCM, but has a larger security margin.
+    pub const AES_256_GCM_SHA384: u8 = 0b00000100;
+    /// AES-128 CCM with SHA-256.
+    ///
+    /// This is mostly used by constrained devices. It isn't enabled by default.
+    pub const AES_128_CCM_SHA256: u8 = 0b00001000;
+    /// AES-128 CCM with an 8-byte tag and SHA-256.
+    ///
+    /// The shorter tag saves bandwidth on constrained networks, but makes forgeries easier. It
+    /// isn't enabled by default.
+    pub const AES_128_CCM_8_SHA256: u8 = 0b00010000;
 
     pub(crate) const LEN_SIZE: usize = 2;
 
@@ -35,20 +44,55 @@ impl CipherList {
         if self.suites & Self::CHA_CHA_POLY1305_SHA256 > 0 {
             record_layer.push_u16(CipherSuite::ChaCha20Poly1305Sha256.as_int());
         }
+        if self.suites & Self::AES_256_GCM_SHA384 > 0 {
+            record_layer.push_u16(CipherSuite::Aes256GcmSha384.as_int());
+        }
+        if self.suites & Self::AES_128_CCM_SHA256 > 0 {
+            record_layer.push_u16(CipherSuite::Aes128CcmSha256.as_int());
+        }
+        if self.suites & Self::AES_128_CCM_8_SHA256 > 0 {
+            record_layer.push_u16(CipherSuite::Aes128Ccm8Sha256.as_int());
+        }
+    }
+
+    /// Returns the hash function of the cipher suite.
+    ///
+    /// `self` must contain exactly one cipher suite.
+    pub(crate) const fn hash_alg(self) -> HashAlg {
+        match self.suites {
+            Self::AES_256_GCM_SHA384 => HashAlg::Sha384,
+            _ => HashAlg::Sha256,
+        }
+    }
+
+    /// Returns the cipher suites in `self` that use `alg`.
+    pub(crate) const fn with_hash(self, alg: HashAlg) -> Self {
+        let sha384 = self.suites & Self::AES_256_GCM_SHA384;
+        Self {
+            suites: match alg {
+                HashAlg::Sha256 => self.suites & !sha384,
+                HashAlg::Sha384 => sha384,
+            },
+        }
     }
 
     pub(crate) fn parse_singular(suite: [u8; size_of::<CipherSuite>()]) -> Self {
-        // fill in more values once more ciphersuites are supported
         match suite {
             x if x == CipherSuite::Aes128GcmSha256.as_int().to_be_bytes() => Self {
                 suites: Self::AES_128_GCM_SHA256,
             },
-            x if x == CipherSuite::Aes256GcmSha384.as_int().to_be_bytes() => Self { suites: 0 },
+            x if x == CipherSuite::Aes256GcmSha384.as_int().to_be_bytes() => Self {
+                suites: Self::AES_256_GCM_SHA384,
+            },
             x if x == CipherSuite::ChaCha20Poly1305Sha256.as_int().to_be_bytes() => Self {
                 suites: Self::CHA_CHA_POLY1305_SHA256,
             },
-            x if x == CipherSuite::Aes128CcmSha256.as_int().to_be_bytes() => Self { suites: 0 },
-            x if x == CipherSuite::Aes128Ccm8Sha256.as_int().to_be_bytes() => Self { suites: 0 },
+            x if x == CipherSuite::Aes128CcmSha256.as_int().to_be_bytes() => Self {
+                suites: Self::AES_128_CCM_SHA256,
+            },
+            x if x == CipherSuite::Aes128Ccm8Sha256.as_int().to_be_bytes() => Self {
+                suites: Self::AES_128_CCM_8_SHA256,
+            },
             _ => Self { suites: 0 },
         }
     }
@@ -57,7 +101,9 @@ impl CipherList {
 impl Default for CipherList {
     fn default() -> Self {
         Self {
-            suites: Self::AES_128_GCM_SHA256 | Self::CHA_CHA_POLY1305_SHA256,
+            suites: Self::AES_128_GCM_SHA256
+                | Self::CHA_CHA_POLY1305_SHA256
+                | Self::AES_256_GCM_SHA384,
         }
     }
 }
@@ -77,14 +123,15 @@ impl CipherSuite {
         self as u16
     }
 
+    #[expect(unused, reason = "the server handshake is not yet supported")]
     pub(crate) const fn to_be_bytes(self) -> [u8; 2] {
         self.as_int().to_be_bytes()
     }
 }
 
-pub(crate) struct NoSharedSuites;
-
+#[derive(Clone, Copy)]
 #[repr(u16)]
+#[expect(unused, reason = "not every signature scheme is supported yet")]
 pub(crate) enum SignatureScheme {
     RsaPkcs1Sha256 = 0x401,
     RsaPkcs1Sha384 = 0x501,
@@ -92,7 +139,7 @@ pub(crate) enum SignatureScheme {
 
     EcdsaSecp256r1Sha256 = 0x403,
     EcdsaSecp384r1Sha384 = 0x503,
-    EcdsaSecp512r1Sha512 = 0x603,
+    EcdsaSecp521r1Sha512 = 0x603,
 
     RsaPssRsaeSha256 = 0x804,
     RsaPssRsaeSha384 = 0x805,
@@ -0,0 +1,678 @@
+//! The client side of the handshake.
+
+use crate::aead::TlsAead;
+use crate::alert::Alert;
+use crate::certificate::{
+    CertRequestRef, CertVerifyRef, CertificateRef, SigningKey, CLI_CONTEXT, SER_CONTEXT,
+};
+use crate::cipher_suites::CipherList;
+use crate::client_hello::ClientHello;
+use crate::config::{Config, ConfigError};
+use crate::dh::GroupKeys;
+use crate::error::{ShakeResult, TlsError};
+use crate::extensions::{
+    EncExtRef, Extensions, KeyShare, MaxFragLen, RecordSizeLimit, RetryExtRef, SerHelExtRef,
+    SigAlgs, SupGroups,
+};
+use crate::finished::Finished;
+use crate::handshake::{ShakeType, SHAKE_HEADER_SIZE};
+use crate::key_schedule::{self, Digest, ShakeSecrets};
+use crate::key_update::AppSecrets;
+use crate::keylog::{self, KeyLog};
+use crate::record::ContentType;
+use crate::server_hello::RecvdSerHello;
+use crate::session::{EarlyDataStatus, Resumption, Session};
+use crate::state::{Shake, State};
+use crate::x509::{self, Certificate};
+
+/// The message the client is waiting for after ServerHello.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum CliState {
+    WaitEncExt,
+    WaitCertReq,
+    WaitCert,
+    WaitCertVerify,
+    WaitFinished,
+    Connected,
+}
+
+/// The parameters agreed upon in ServerHello.
+struct Negotiated {
+    cipher_suite: CipherList,
+    dh_shared_secret: Box<[u8]>,
+    /// The PSK of the offered session, if the server accepted it.
+    psk: Option<Digest>,
+    /// The keys early data was sent with, if the server may still accept it.
+    early_aead: Option<TlsAead>,
+    /// The application protocol of the offered session, which the server must choose again if
+    /// it accepts early data.
+    session_app_proto: Box<[u8]>,
+    /// Whether or not the server requested a certificate.
+    cert_requested: bool,
+    /// The key to authenticate with if a certificate was requested and ours is acceptable.
+    client_key: Option<SigningKey>,
+    keylog: KeyLog,
+}
+
+/// How far the client has gotten in a handshake that is waiting for the server.
+///
+/// This is kept in the connection state so the handshake can be resumed.
+pub(crate) enum Progress {
+    /// ClientHello was sent, and ServerHello or HelloRetryRequest is expected.
+    WaitSerHello(Box<Hello>),
+    /// The server's encrypted messages, from EncryptedExtensions to Finished, are being read.
+    WaitFlight(Box<Flight>),
+    /// The handshake is complete, but the client's last messages haven't been written yet.
+    Flushing,
+}
+
+/// The ClientHello that was sent, which the server's response is checked against.
+pub(crate) struct Hello {
+    /// The random bytes, which must stay the same if ClientHello is sent again.
+    random: [u8; ClientHello::RANDOM_BYTES_LEN],
+    keys: GroupKeys,
+    /// The groups key shares were sent for.
+    key_share_groups: SupGroups,
+    /// The cookie echoed from HelloRetryRequest, or an empty vector.
+    cookie: Vec<u8>,
+    /// The session offered for resumption, if any.
+    session: Option<Session>,
+    /// Whether or not early data was sent with the session.
+    early_data: bool,
+    /// The keys early data was sent with, if the server may still accept it.
+    early_aead: Option<TlsAead>,
+    /// The cipher suite chosen in HelloRetryRequest, if the server sent one.
+    retry_suite: Option<CipherList>,
+}
+
+impl Hello {
+    /// Returns the ClientHello message to send.
+    fn client_hello<'a>(&'a self, config: &Config) -> ClientHello<'a> {
+        ClientHello {
+            random: self.random,
+            cipher_suites: config.cipher_suites,
+            extensions: config.extensions,
+            key_share_groups: self.key_share_groups,
+            cookie: &self.cookie,
+            psk: self.session.as_ref(),
+            early_data: self.early_data,
+        }
+    }
+}
+
+/// The state of the client while it reads the server's encrypted messages.
+pub(crate) struct Flight {
+    cli_state: CliState,
+    negotiated: Negotiated,
+    secrets: ShakeSecrets,
+}
+
+/// Performs the handshake as the client, or resumes it if it would have blocked.
+///
+/// If an error occurs, the peer is alerted (if applicable) and the connection is closed.
+pub(crate) fn handshake(state: &mut State, config: &Config) -> ShakeResult {
+    let mut progress = match state.shake.take() {
+        Some(Shake::Client(progress)) => progress,
+        _ => {
+            if !config.extensions.app_protos.is_valid() {
+                return ShakeResult::ConfigError(ConfigError::InvalidAppProtos);
+            }
+            if !RecordSizeLimit::is_valid(config.extensions.record_size_limit) {
+                return ShakeResult::ConfigError(ConfigError::InvalidRecordSizeLimit);
+            }
+            // a certificate that is valid for any name must not be accepted
+            // SAFETY: the caller of `turtls_client_handshake` guarantees that the config is valid.
+            if !unsafe { config.trust_anchors() }.is_empty()
+                && config.extensions.server_name.as_bytes().is_empty()
+            {
+                return ShakeResult::ConfigError(ConfigError::MissingServerName);
+            }
+            match say_hello(state, config) {
+                Ok(hello) => Progress::WaitSerHello(Box::new(hello)),
+                Err(err) => return err,
+            }
+        },
+    };
+
+    match advance(state, config, &mut progress) {
+        Ok(()) => ShakeResult::Ok,
+        Err(ShakeResult::WantRead | ShakeResult::WantWrite) => {
+            state.shake = Some(Shake::Client(progress));
+            state.want()
+        },
+        Err(err) => {
+            if let ShakeResult::SentAlert(alert) = err {
+                // the handshake keys are only used once ServerHello has been processed
+                match progress {
+                    Progress::WaitSerHello(_) => state.rl.unenc_rl.alert_and_close(alert),
+                    _ => state.rl.alert_and_close(alert),
+                }
+            }
+            err
+        },
+    }
+}
+
+/// Processes messages from the server until the handshake is complete or would block.
+fn advance(state: &mut State, config: &Config, progress: &mut Progress) -> Result<(), ShakeResult> {
+    // the peer may be waiting for records that couldn't be written yet
+    state.rl.unenc_rl.flush()?;
+    loop {
+        match progress {
+            Progress::WaitSerHello(hello) => {
+                let msg = read_ser_hello(state)?;
+                if let Some(flight) = process_ser_hello(state, config, hello, &msg)? {
+                    *progress = Progress::WaitFlight(Box::new(flight));
+                }
+            },
+            Progress::WaitFlight(flight) => {
+                let msg = state.shake_buf.read(&mut state.rl, state.record_timeout)?;
+                flight.cli_state = process_msg(
+                    state,
+                    config,
+                    &flight.secrets,
+                    &mut flight.negotiated,
+                    flight.cli_state,
+                    &msg,
+                )?;
+                if flight.cli_state == CliState::Connected {
+                    *progress = Progress::Flushing;
+                }
+            },
+            Progress::Flushing if state.rl.unenc_rl.flush()? => return Ok(()),
+            Progress::Flushing => return Err(ShakeResult::WantWrite),
+        }
+    }
+}
+
+/// Sends ClientHello, followed by early data if there is any to send with the configured session.
+fn say_hello(state: &mut State, config: &Config) -> Result<Hello, ShakeResult> {
+    // SAFETY: the caller of `turtls_client_handshake` guarantees that the config is valid.
+    let session = Session::from_bytes(unsafe { config.session() })
+        .filter(|session| session.is_usable(config));
+
+    // SAFETY: the caller of `turtls_client_handshake` guarantees that the config is valid.
+    let early_data = unsafe { config.early_data() };
+    let early_data = match session {
+        Some(ref session) if session.allows_early_data(config, early_data) => early_data,
+        _ => &[],
+    };
+
+    let mut hello = Hello {
+        random: ClientHello::gen_random().map_err(|_| ShakeResult::RngError)?,
+        keys: GroupKeys::generate(config.extensions.sup_groups)?,
+        key_share_groups: config.extensions.sup_groups,
+        cookie: Vec::new(),
+        session,
+        early_data: !early_data.is_empty(),
+        early_aead: None,
+        retry_suite: None,
+    };
+    hello
+        .client_hello(config)
+        .write_to(&mut state.rl.unenc_rl, &hello.keys)?;
+
+    hello.early_aead = match hello.session {
+        Some(ref session) if hello.early_data => {
+            let keylog = KeyLog::new(config, hello.random);
+            Some(send_early_data(state, session, early_data, &keylog)?)
+        },
+        _ => None,
+    };
+    Ok(hello)
+}
+
+/// Processes ServerHello or HelloRetryRequest.
+///
+/// HelloRetryRequest is answered by sending ClientHello again, and `None` is returned. Otherwise,
+/// the handshake keys are derived and the server's encrypted messages are read next.
+fn process_ser_hello(
+    state: &mut State,
+    config: &Config,
+    hello: &mut Hello,
+    msg: &[u8],
+) -> Result<Option<Flight>, ShakeResult> {
+    let server_hello = RecvdSerHello::parse(&msg[SHAKE_HEADER_SIZE..]).map_err(TlsError::Sent)?;
+    match hello.retry_suite {
+        // only one HelloRetryRequest may be sent
+        Some(_) if server_hello.is_retry => {
+            return Err(ShakeResult::SentAlert(Alert::UnexpectedMessage));
+        },
+        // the server must choose the cipher suite it chose in HelloRetryRequest
+        Some(suite) if server_hello.cipher_suite != suite => {
+            return Err(ShakeResult::SentAlert(Alert::IllegalParam));
+        },
+        None if server_hello.is_retry => {
+            retry(state, config, hello, &server_hello, msg)?;
+            return Ok(None);
+        },
+        _ => (),
+    }
+    state.rl.unenc_rl.update_transcript(msg);
+
+    let negotiated = ser_hello_secret(&server_hello, config, hello)?;
+    let cipher_suite = negotiated.cipher_suite;
+
+    state
+        .rl
+        .unenc_rl
+        .set_transcript_hash(cipher_suite.hash_alg());
+    let secrets = ShakeSecrets::new(
+        &negotiated.dh_shared_secret,
+        negotiated.psk.as_ref(),
+        &state.rl.unenc_rl.transcript(),
+    );
+    negotiated
+        .keylog
+        .log(keylog::CLI_SHAKE, &secrets.cli_shake_traf_secret);
+    negotiated
+        .keylog
+        .log(keylog::SER_SHAKE, &secrets.ser_shake_traf_secret);
+
+    state.rl.aead = TlsAead::new(
+        &secrets.cli_shake_traf_secret,
+        &secrets.ser_shake_traf_secret,
+        cipher_suite,
+    )
+    .expect("cipher suite was already negotiated");
+
+    Ok(Some(Flight {
+        cli_state: CliState::WaitEncExt,
+        negotiated,
+        secrets,
+    }))
+}
+
+/// Sends ClientHello again with the changes requested by HelloRetryRequest.
+fn retry(
+    state: &mut State,
+    config: &Config,
+    hello: &mut Hello,
+    server_hello: &RecvdSerHello,
+    msg: &[u8],
+) -> Result<(), ShakeResult> {
+    let retry = RetryExtRef::parse(server_hello.extensions).map_err(TlsError::Sent)?;
+    if server_hello.cipher_suite.suites & config.cipher_suites.suites == 0 {
+        return Err(ShakeResult::SentAlert(Alert::IllegalParam));
+    }
+    match retry.selected_group {
+        Some(group) => {
+            // the server must ask for a group we support but didn't send a key share for
+            if group.groups & config.extensions.sup_groups.groups == 0
+                || group.groups & hello.key_share_groups.groups != 0
+            {
+                return Err(ShakeResult::SentAlert(Alert::IllegalParam));
+            }
+            hello.keys = GroupKeys::generate(group)?;
+            hello.key_share_groups = group;
+        },
+        // HelloRetryRequest must change something in ClientHello
+        None if retry.cookie.is_empty() => {
+            return Err(ShakeResult::SentAlert(Alert::IllegalParam));
+        },
+        None => (),
+    }
+    hello.cookie = retry.cookie.to_vec();
+    // early data is rejected by HelloRetryRequest and can't be sent again
+    hello.early_data = false;
+    hello.early_aead = None;
+    // the session can only be offered again if its hash matches the server's cipher suite
+    hello.session = hello
+        .session
+        .take()
+        .filter(|session| session.hash_alg() == server_hello.cipher_suite.hash_alg());
+    hello.retry_suite = Some(server_hello.cipher_suite);
+
+    // the hash of the cipher suite is needed to replace ClientHello with its hash
+    state
+        .rl
+        .unenc_rl
+        .set_transcript_hash(server_hello.cipher_suite.hash_alg());
+    state.rl.unenc_rl.hash_transcript();
+    state.rl.unenc_rl.update_transcript(msg);
+    hello
+        .client_hello(config)
+        .write_to(&mut state.rl.unenc_rl, &hello.keys)?;
+    Ok(())
+}
+
+/// Sends `early_data` protected with the client early traffic secret of `session`.
+///
+/// ClientHello must be the only message in the transcript. The keys are returned so they can be
+/// used to send EndOfEarlyData if the server accepts the early data.
+fn send_early_data(
+    state: &mut State,
+    session: &Session,
+    early_data: &[u8],
+    keylog: &KeyLog,
+) -> Result<TlsAead, ShakeResult> {
+    let transcript = state.rl.unenc_rl.transcript_with(session.hash_alg(), &[]);
+    let early_secret = key_schedule::early_traffic_secret(&session.psk, &transcript);
+    keylog.log(keylog::CLI_EARLY, &early_secret);
+    let mut early_aead = TlsAead::new(&early_secret, &early_secret, session.cipher_suite)
+        .expect("session cipher suite is supported");
+
+    std::mem::swap(&mut state.rl.aead, &mut early_aead);
+    let result = state.rl.write(ContentType::ApplicationData, early_data);
+    std::mem::swap(&mut state.rl.aead, &mut early_aead);
+    result?;
+
+    // the early data is rejected unless the server accepts it in EncryptedExtensions
+    state.early_data = EarlyDataStatus::Rejected;
+    Ok(early_aead)
+}
+
+/// Reads a ServerHello or HelloRetryRequest message.
+///
+/// The message isn't added to the transcript.
+fn read_ser_hello(state: &mut State) -> Result<Vec<u8>, ShakeResult> {
+    let msg = state
+        .shake_buf
+        .read_plain(&mut state.rl.unenc_rl, state.record_timeout)?;
+    if msg[0] != ShakeType::ServerHello.to_byte() {
+        return Err(ShakeResult::SentAlert(Alert::UnexpectedMessage));
+    }
+    // ServerHello must end on a record boundary because the keys change after it
+    if !state.shake_buf.is_empty() {
+        return Err(ShakeResult::SentAlert(Alert::UnexpectedMessage));
+    }
+    Ok(msg)
+}
+
+/// Parses the ServerHello extensions, returning the negotiated parameters.
+///
+/// `hello` describes the ClientHello the server responded to.
+fn ser_hello_secret(
+    server_hello: &RecvdSerHello,
+    config: &Config,
+    hello: &mut Hello,
+) -> Result<Negotiated, ShakeResult> {
+    let extensions = SerHelExtRef::parse(server_hello.extensions).map_err(TlsError::Sent)?;
+    let dh_shared_secret =
+        KeyShare::parse_ser(extensions.key_share, hello.key_share_groups, &hello.keys)
+            .map_err(TlsError::Sent)?;
+
+    let cipher_suite = CipherList {
+        suites: server_hello.cipher_suite.suites & config.cipher_suites.suites,
+    };
+    if cipher_suite.suites == 0 {
+        return Err(ShakeResult::SentAlert(Alert::IllegalParam));
+    }
+
+    let psk = match (extensions.selected_psk, &hello.session) {
+        (None, _) => None,
+        // only one PSK is offered, and its hash must match the cipher suite
+        (Some(0), Some(session)) if session.hash_alg() == cipher_suite.hash_alg() => {
+            Some(session.psk)
+        },
+        _ => return Err(ShakeResult::SentAlert(Alert::IllegalParam)),
+    };
+    // early data can only be accepted along with the PSK and cipher suite it was sent with
+    let early_aead = hello.early_aead.take().filter(|_| {
+        psk.is_some()
+            && hello
+                .session
+                .as_ref()
+                .is_some_and(|session| session.cipher_suite == cipher_suite)
+    });
+    Ok(Negotiated {
+        cipher_suite,
+        dh_shared_secret,
+        psk,
+        early_aead,
+        session_app_proto: hello
+            .session
+            .as_ref()
+            .map_or_else(Box::default, |session| session.app_proto.clone()),
+        cert_requested: false,
+        client_key: None,
+        keylog: KeyLog::new(config, hello.random),
+    })
+}
+
+/// Processes a single handshake message, returning the next state.
+fn process_msg(
+    state: &mut State,
+    config: &Config,
+    secrets: &ShakeSecrets,
+    negotiated: &mut Negotiated,
+    cli_state: CliState,
+    msg: &[u8],
+) -> Result<CliState, ShakeResult> {
+    let body = &msg[SHAKE_HEADER_SIZE..];
+    let transcript = state.rl.unenc_rl.transcript();
+
+    let next_state = match cli_state {
+        CliState::WaitEncExt if msg[0] == ShakeType::EncryptedExtensions.to_byte() => {
+            if body.len() < Extensions::LEN_SIZE
+                || u16::from_be_bytes([body[0], body[1]]) as usize
+                    != body.len() - Extensions::LEN_SIZE
+            {
+                return Err(ShakeResult::SentAlert(Alert::DecodeError));
+            }
+            let enc_ext =
+                EncExtRef::parse(&body[Extensions::LEN_SIZE..]).map_err(TlsError::Sent)?;
+            // the server may only acknowledge a server name we sent
+            if enc_ext.server_name && config.extensions.server_name.as_bytes().is_empty() {
+                return Err(ShakeResult::SentAlert(Alert::UnsupportedExtension));
+            }
+            let app_proto = match enc_ext.app_proto {
+                // the server may only choose a protocol we offered
+                Some(proto) if config.extensions.app_protos.contains(proto) => proto,
+                Some(_) if config.extensions.app_protos.len() == 0 => {
+                    return Err(ShakeResult::SentAlert(Alert::UnsupportedExtension));
+                },
+                Some(_) => return Err(ShakeResult::SentAlert(Alert::IllegalParam)),
+                None => &[],
+            };
+            state.app_proto = Box::from(app_proto);
+            set_record_limits(state, config, &enc_ext)?;
+
+            if enc_ext.early_data {
+                match state.early_data {
+                    EarlyDataStatus::NotSent => {
+                        return Err(ShakeResult::SentAlert(Alert::UnsupportedExtension));
+                    },
+                    // early data can't be accepted with a different PSK, cipher suite, or
+                    // application protocol
+                    _ if negotiated.early_aead.is_none()
+                        || *negotiated.session_app_proto != *app_proto =>
+                    {
+                        return Err(ShakeResult::SentAlert(Alert::IllegalParam));
+                    },
+                    _ => state.early_data = EarlyDataStatus::Accepted,
+                }
+            } else {
+                negotiated.early_aead = None;
+            }
+            if negotiated.psk.is_some() {
+                CliState::WaitFinished
+            } else {
+                CliState::WaitCertReq
+            }
+        },
+        CliState::WaitCertReq if msg[0] == ShakeType::CertificateRequest.to_byte() => {
+            let request = CertRequestRef::parse(body).map_err(TlsError::Sent)?;
+            negotiated.cert_requested = true;
+            negotiated.client_key = client_key(config, &request);
+            CliState::WaitCert
+        },
+        CliState::WaitCertReq | CliState::WaitCert
+            if msg[0] == ShakeType::Certificate.to_byte() =>
+        {
+            let certificate = CertificateRef::parse(body).map_err(TlsError::Sent)?;
+            if certificate.certs.is_empty() {
+                return Err(ShakeResult::SentAlert(Alert::DecodeError));
+            }
+            // SAFETY: the caller of `turtls_client_handshake` guarantees that the config is valid.
+            let anchors = unsafe { config.trust_anchors() };
+            let leaf = x509::verify_chain(&certificate.certs, anchors, x509::unix_time())
+                .map_err(TlsError::Sent)?;
+            leaf.verify_name(config.extensions.server_name.as_bytes())
+                .map_err(TlsError::Sent)?;
+            state.peer_cert = certificate.certs[0].to_vec();
+            CliState::WaitCertVerify
+        },
+        CliState::WaitCertVerify if msg[0] == ShakeType::CertificateVerify.to_byte() => {
+            let cert_verify = CertVerifyRef::parse(body).map_err(TlsError::Sent)?;
+            if SigAlgs::parse_singular(cert_verify.scheme).algorithms
+                & config.extensions.sig_algs.algorithms
+                == 0
+            {
+                return Err(ShakeResult::SentAlert(Alert::IllegalParam));
+            }
+            let peer_cert = Certificate::parse(&state.peer_cert).map_err(TlsError::Sent)?;
+            cert_verify
+                .verify(&peer_cert.public_key, SER_CONTEXT, &transcript)
+                .map_err(TlsError::Sent)?;
+            CliState::WaitFinished
+        },
+        CliState::WaitFinished if msg[0] == ShakeType::Finished.to_byte() => {
+            Finished::verify(body, &secrets.ser_shake_traf_secret, &transcript)
+                .map_err(TlsError::Sent)?;
+            // Finished must end on a record boundary because the keys change after it
+            if !state.shake_buf.is_empty() {
+                return Err(ShakeResult::SentAlert(Alert::UnexpectedMessage));
+            }
+            state.rl.unenc_rl.update_transcript(msg);
+            send_finished(state, config, secrets, negotiated)?;
+            return Ok(CliState::Connected);
+        },
+        _ => return Err(ShakeResult::SentAlert(Alert::UnexpectedMessage)),
+    };
+    state.rl.unenc_rl.update_transcript(msg);
+    Ok(next_state)
+}
+
+/// Applies the record size limits the server agreed to in EncryptedExtensions.
+///
+/// The server may respond to our record size limit or, if it doesn't support that, echo our
+/// maximum fragment length, which then limits records in both directions.
+fn set_record_limits(
+    state: &mut State,
+    config: &Config,
+    enc_ext: &EncExtRef,
+) -> Result<(), ShakeResult> {
+    let limit = config.extensions.record_size_limit;
+    let code = MaxFragLen::from_limit(limit);
+    match (enc_ext.record_size_limit, enc_ext.max_frag_len) {
+        (Some(_), Some(_)) => return Err(ShakeResult::SentAlert(Alert::IllegalParam)),
+        // the server may only respond to extensions we sent
+        (Some(_), None) if limit == 0 => {
+            return Err(ShakeResult::SentAlert(Alert::UnsupportedExtension));
+        },
+        (None, Some(_)) if code.is_none() => {
+            return Err(ShakeResult::SentAlert(Alert::UnsupportedExtension));
+        },
+        (Some(ser_limit), None) => {
+            state.rl.max_write_len = RecordSizeLimit::max_data_len(ser_limit);
+            state.rl.max_read_len = RecordSizeLimit::max_data_len(limit);
+        },
+        (None, Some(ser_code)) if Some(ser_code) != code => {
+            return Err(ShakeResult::SentAlert(Alert::IllegalParam));
+        },
+        (None, Some(ser_code)) => {
+            state.rl.max_write_len = MaxFragLen::max_data_len(ser_code);
+            state.rl.max_read_len = MaxFragLen::max_data_len(ser_code);
+        },
+        (None, None) => (),
+    }
+    Ok(())
+}
+
+/// Returns the key to authenticate with in response to a CertificateRequest.
+///
+/// `None` is returned if there is no identity, or if the server doesn't accept its signature
+/// scheme or certificate authorities. In that case, an empty Certificate message is sent.
+fn client_key(config: &Config, request: &CertRequestRef) -> Option<SigningKey> {
+    // SAFETY: the caller of `turtls_client_handshake` guarantees that the config is valid.
+    let certs = unsafe { config.identity.certs() };
+    let key = SigningKey::new(certs.first()?, &config.identity.priv_key)?;
+    (request.sig_algs.algorithms & key.sig_algs_flag() != 0 && request.accepts_chain(&certs))
+        .then_some(key)
+}
+
+/// Sends the client's Finished message and switches to the application traffic keys.
+///
+/// The transcript must include the server's Finished message. If the server accepted early data,
+/// EndOfEarlyData is sent first, and if the server requested a certificate, Certificate and
+/// CertificateVerify are sent before Finished.
+fn send_finished(
+    state: &mut State,
+    config: &Config,
+    secrets: &ShakeSecrets,
+    negotiated: &mut Negotiated,
+) -> Result<(), ShakeResult> {
+    let cipher_suite = negotiated.cipher_suite;
+    let (cli_app_traf_secret, ser_app_traf_secret) =
+        secrets.app_secrets(&state.rl.unenc_rl.transcript());
+    let exp_master = secrets.exp_master(&state.rl.unenc_rl.transcript());
+
+    if let Some(mut early_aead) = negotiated.early_aead.take() {
+        let end_of_early_data = [ShakeType::EndOfEarlyData.to_byte(), 0, 0, 0];
+        state.rl.unenc_rl.update_transcript(&end_of_early_data);
+
+        state.rl.aead.swap_writer(&mut early_aead);
+        let result = state.rl.write(ContentType::Handshake, &end_of_early_data);
+        state.rl.aead.swap_writer(&mut early_aead);
+        result?;
+    }
+
+    if negotiated.cert_requested {
+        send_cert(state, config, negotiated.client_key.as_ref())?;
+    }
+
+    let transcript = state.rl.unenc_rl.transcript();
+
+    let finished = Finished::new(&secrets.cli_shake_traf_secret, &transcript).to_vec();
+    state.rl.unenc_rl.update_transcript(&finished);
+    state.rl.write(ContentType::Handshake, &finished)?;
+
+    state.resumption = Some(Resumption {
+        res_master: secrets.res_master(&state.rl.unenc_rl.transcript()),
+        cipher_suite,
+        server_name: Box::from(config.extensions.server_name.as_bytes()),
+        app_proto: state.app_proto.clone(),
+    });
+
+    negotiated.keylog.log(keylog::CLI_APP, &cli_app_traf_secret);
+    negotiated.keylog.log(keylog::SER_APP, &ser_app_traf_secret);
+    negotiated.keylog.log(keylog::EXPORTER, &exp_master);
+    let app_secrets = AppSecrets::new(cli_app_traf_secret, ser_app_traf_secret, cipher_suite);
+    state.rl.aead = app_secrets.aead();
+    state.app_secrets = Some(app_secrets);
+    Ok(())
+}
+
+/// Sends the client's Certificate message and, if `key` isn't `None`, CertificateVerify.
+///
+/// If `key` is `None`, the certificate list is empty.
+fn send_cert(
+    state: &mut State,
+    config: &Config,
+    key: Option<&SigningKey>,
+) -> Result<(), ShakeResult> {
+    let certs = match key {
+        // SAFETY: the caller of `turtls_client_handshake` guarantees that the config is valid.
+        Some(_) => unsafe { config.identity.certs() },
+        None => Vec::new(),
+    };
+    let mut flight = CertificateRef { certs }.to_vec();
+    state.rl.unenc_rl.update_transcript(&flight);
+
+    if let Some(key) = key {
+        let signature = key
+            .sign(CLI_CONTEXT, &state.rl.unenc_rl.transcript())
+            .map_err(|_| ShakeResult::RngError)?;
+        let cert_verify = CertVerifyRef {
+            scheme: key.scheme().to_be_bytes(),
+            signature: &signature,
+        }
+        .to_vec();
+        state.rl.unenc_rl.update_transcript(&cert_verify);
+        flight.extend_from_slice(&cert_verify);
+    }
+
+    state.rl.write(ContentType::Handshake, &flight)?;
+    Ok(())
+}
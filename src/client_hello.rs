@@ -1,18 +1,30 @@
-use crate::cipher_suites::CipherList;
+use crate::alert::Alert;
+use crate::cipher_suites::{CipherList, CipherSuite};
 use crate::dh::GroupKeys;
-use crate::extensions::Extensions;
+use crate::extensions::{Extensions, SupGroups};
 use crate::handshake::ShakeType;
-use crate::record::{ContentType, RecordLayer};
+use crate::record::{ContentType, IoError, RecordLayer};
+use crate::session::Session;
 use crate::versions::ProtocolVersion;
 use crate::versions::LEGACY_PROTO_VERS;
 use getrandom::{getrandom, Error};
 
-pub(crate) struct ClientHello {
+pub(crate) struct ClientHello<'a> {
+    /// The random bytes, which must stay the same if ClientHello is sent again.
+    pub(crate) random: [u8; ClientHello::RANDOM_BYTES_LEN],
     pub(crate) cipher_suites: CipherList,
     pub(crate) extensions: Extensions,
+    /// The groups to send key shares for.
+    pub(crate) key_share_groups: SupGroups,
+    /// The cookie to echo from HelloRetryRequest, or an empty slice.
+    pub(crate) cookie: &'a [u8],
+    /// The session to offer for resumption, if any.
+    pub(crate) psk: Option<&'a Session>,
+    /// Whether or not early data will be sent with the session.
+    pub(crate) early_data: bool,
 }
 
-impl ClientHello {
+impl ClientHello<'_> {
     pub(crate) const RANDOM_BYTES_LEN: usize = 32;
     pub(crate) const LEGACY_SESSION_ID: u8 = 0;
     pub(crate) const LEGACY_COMPRESSION_METHODS: [u8; 2] = [1, 0];
@@ -26,14 +38,23 @@ impl ClientHello {
             // TODO use size_of_val once it is const-stabilized
             + size_of_val(&Self::LEGACY_COMPRESSION_METHODS)
             + Extensions::LEN_SIZE
-            + self.extensions.len_client()
+            + self
+                .extensions
+                .len_client(self.key_share_groups, self.cookie, self.psk, self.early_data)
+    }
+
+    /// Generates the random bytes to use in ClientHello.
+    pub(crate) fn gen_random() -> Result<[u8; ClientHello::RANDOM_BYTES_LEN], Error> {
+        let mut random = [0; Self::RANDOM_BYTES_LEN];
+        getrandom(&mut random)?;
+        Ok(random)
     }
 
     pub(crate) fn write_to(
         &self,
         record_layer: &mut RecordLayer,
         keys: &GroupKeys,
-    ) -> Result<(), CliHelError> {
+    ) -> Result<(), IoError> {
         record_layer.start_as(ContentType::Handshake);
         record_layer.push(ShakeType::ClientHello.to_byte());
 
@@ -42,9 +63,7 @@ impl ClientHello {
 
         record_layer.push_u16(LEGACY_PROTO_VERS.as_int());
 
-        let mut random_bytes = [0; Self::RANDOM_BYTES_LEN];
-        getrandom(&mut random_bytes)?;
-        record_layer.extend_from_slice(&random_bytes);
+        record_layer.extend_from_slice(&self.random);
 
         record_layer.push(Self::LEGACY_SESSION_ID);
 
@@ -54,72 +73,90 @@ impl ClientHello {
 
         record_layer.extend_from_slice(&Self::LEGACY_COMPRESSION_METHODS);
 
-        let len = self.extensions.len_client() as u16;
+        let len = self.extensions.len_client(
+            self.key_share_groups,
+            self.cookie,
+            self.psk,
+            self.early_data,
+        ) as u16;
         record_layer.push_u16(len);
-        self.extensions.write_client(record_layer, keys);
-
-        record_layer.finish_and_send();
-        Ok(())
-    }
-}
-
-pub(crate) enum CliHelError {
-    RngError,
-    IoError,
-}
-
-impl From<Error> for CliHelError {
-    fn from(_: Error) -> Self {
-        Self::RngError
+        self.extensions.write_client(
+            record_layer,
+            keys,
+            self.key_share_groups,
+            self.cookie,
+            self.psk,
+            self.early_data,
+        );
+
+        record_layer.finish_and_send()
     }
 }
 
-pub(crate) enum CliHelloParseError {
-    MissingData,
-    InvalidLengthEncoding,
-}
-
+/// A parsed ClientHello message.
 pub(crate) struct ClientHelloRef<'a> {
-    pub(crate) random_bytes: &'a [u8; 32],
+    pub(crate) random: [u8; ClientHello::RANDOM_BYTES_LEN],
     pub(crate) session_id: &'a [u8],
     pub(crate) cipher_suites: &'a [u8],
     pub(crate) extensions: &'a [u8],
 }
 
 impl<'a> ClientHelloRef<'a> {
-    pub(crate) fn parse(client_hello: &'a [u8]) -> Result<Self, CliHelloParseError> {
-        let mut pos = size_of::<ProtocolVersion>();
-        let random_bytes = <&[u8; ClientHello::RANDOM_BYTES_LEN]>::try_from(
-            &client_hello[pos..][..ClientHello::RANDOM_BYTES_LEN],
-        )
-        .unwrap();
-        pos += random_bytes.len();
-
-        let legacy_session_id_len = client_hello[34];
-        pos += 1;
-        if legacy_session_id_len > 32 {
-            return Err(CliHelloParseError::InvalidLengthEncoding);
+    const MAX_SESSION_ID_LEN: usize = 32;
+    const LEGACY_COMPRESSION_METHODS_LEN_SIZE: usize = 1;
+
+    /// Parses the body of a ClientHello message.
+    ///
+    /// The extensions are not parsed.
+    pub(crate) fn parse(mut client_hello: &'a [u8]) -> Result<Self, Alert> {
+        if client_hello.len() < size_of::<ProtocolVersion>() + ClientHello::RANDOM_BYTES_LEN + 1 {
+            return Err(Alert::DecodeError);
         }
-
-        let session_id = &client_hello[pos..][..legacy_session_id_len as usize];
-        pos += legacy_session_id_len as usize;
-
-        let cipher_suites_len = u16::from_be_bytes(client_hello[pos..][..2].try_into().unwrap());
-        pos += 2;
-        if cipher_suites_len > 0xfffe {
-            return Err(CliHelloParseError::InvalidLengthEncoding);
+        let random = client_hello[size_of::<ProtocolVersion>()..][..ClientHello::RANDOM_BYTES_LEN]
+            .try_into()
+            .unwrap();
+        client_hello =
+            &client_hello[size_of::<ProtocolVersion>() + ClientHello::RANDOM_BYTES_LEN..];
+
+        let session_id_len = client_hello[0] as usize;
+        client_hello = &client_hello[size_of_val(&ClientHello::LEGACY_SESSION_ID)..];
+        if session_id_len > Self::MAX_SESSION_ID_LEN
+            || client_hello.len() < session_id_len + CipherList::LEN_SIZE
+        {
+            return Err(Alert::DecodeError);
         }
+        let session_id = &client_hello[..session_id_len];
+        client_hello = &client_hello[session_id_len..];
+
+        let cipher_suites_len = u16::from_be_bytes([client_hello[0], client_hello[1]]) as usize;
+        client_hello = &client_hello[CipherList::LEN_SIZE..];
+        if !cipher_suites_len.is_multiple_of(size_of::<CipherSuite>())
+            || client_hello.len() < cipher_suites_len + Self::LEGACY_COMPRESSION_METHODS_LEN_SIZE
+        {
+            return Err(Alert::DecodeError);
+        }
+        let cipher_suites = &client_hello[..cipher_suites_len];
+        client_hello = &client_hello[cipher_suites_len..];
 
-        let cipher_suites = &client_hello[pos..][..cipher_suites_len as usize];
-        pos += cipher_suites_len as usize;
+        let compression_methods_len = client_hello[0] as usize;
+        client_hello = &client_hello[Self::LEGACY_COMPRESSION_METHODS_LEN_SIZE..];
+        if client_hello.len() < compression_methods_len + Extensions::LEN_SIZE {
+            return Err(Alert::DecodeError);
+        }
+        // TLS 1.3 clients must only offer the null compression method
+        if client_hello[..compression_methods_len] != ClientHello::LEGACY_COMPRESSION_METHODS[1..] {
+            return Err(Alert::IllegalParam);
+        }
+        client_hello = &client_hello[compression_methods_len..];
 
-        let legacy_compression_methods_len = client_hello[pos];
-        pos += 1;
-        pos += legacy_compression_methods_len as usize;
+        let extensions_len = u16::from_be_bytes([client_hello[0], client_hello[1]]) as usize;
+        let extensions = &client_hello[Extensions::LEN_SIZE..];
+        if extensions_len != extensions.len() {
+            return Err(Alert::DecodeError);
+        }
 
-        let extensions = &client_hello[pos + 1..];
         Ok(Self {
-            random_bytes,
+            random,
             session_id,
             cipher_suites,
             extensions,
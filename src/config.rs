@@ -1,26 +1,168 @@
+use std::ffi::{c_char, c_void};
+use std::ptr::{null, null_mut};
+
+use crate::certificate::SigningKey;
+use crate::der::Reader;
 use crate::{cipher_suites::CipherList, extensions::Extensions};
+use crate::{pem, x509};
 
 /// The configurations to use for a specific TLS connection.
 ///
 /// This can be automatically generated by `turtls_generate_config`.
 #[repr(C)]
 pub struct Config {
-    /// The timeout in milliseconds to use for record layer reads during the handshake.
+    /// The timeout in milliseconds to use for record layer reads.
     ///
     /// Default value: `10000`
     pub timeout_millis: u64,
+    /// Whether or not to return instead of waiting for the peer when reading or writing.
+    ///
+    /// If this is `true`, the handshake returns `WantRead` when `read_fn` returns `0` and
+    /// `WantWrite` when `write_fn` can't write everything, and `timeout_millis` is ignored. The
+    /// handshake is resumed by calling the same handshake function with the same connection and
+    /// config once the IO is ready. After the handshake, `turtls_read` and `turtls_send` return
+    /// `WantRead` and `WantWrite` likewise.
+    ///
+    /// Default value: `false`
+    pub non_blocking: bool,
     /// The extensions to use.
     pub extensions: Extensions,
     /// The cipher suites to use.
     pub cipher_suites: CipherList,
+    /// The certificate chain and private key used to authenticate to the peer.
+    ///
+    /// This is required for servers. Clients send it if the server requests a certificate, as
+    /// long as its signature scheme and issuers are acceptable to the server.
+    ///
+    /// This can be loaded from DER or PEM with `turtls_load_identity`.
+    pub identity: Identity,
+    /// The certificates loaded by `turtls_load_identity`, which `identity` points to.
+    ///
+    /// This must be freed with `turtls_free_config` and shouldn't be modified directly.
+    pub identity_store: *mut IdentityStore,
+    /// The root certificates used to authenticate the peer.
+    ///
+    /// If this is `NULL`, no peer can be authenticated. Trust anchors can be added with
+    /// `turtls_add_trust_anchor` and must be freed with `turtls_free_config`.
+    ///
+    /// Lifetime: this pointer must be valid for the duration of the handshake.
+    pub trust_store: *mut TrustStore,
+    /// Whether or not the server requests a certificate from the client.
+    ///
+    /// Client certificates are verified with `trust_store`. Clients ignore this.
+    ///
+    /// Default value: `TURTLS_CLIENT_AUTH_NONE`
+    pub client_auth: ClientAuth,
+    /// A session from `turtls_get_session` to resume, or `NULL` for a full handshake.
+    ///
+    /// The session is ignored if it has expired or if it was established with a different server
+    /// name. The server may also decline to resume it.
+    ///
+    /// Lifetime: this pointer must be valid for the duration of the handshake.
+    pub session: *const u8,
+    /// The length of `session` in bytes.
+    pub session_len: usize,
+    /// Application data to send to the server in the first flight (0-RTT), or `NULL`.
+    ///
+    /// Early data is only sent when resuming a session whose server accepts enough of it. Early
+    /// data can be replayed by an attacker, so it must be safe to process more than once. If
+    /// `turtls_early_data_status` doesn't report that it was accepted, it must be sent again with
+    /// `turtls_send`.
+    ///
+    /// Lifetime: this pointer must be valid for the duration of the handshake.
+    pub early_data: *const u8,
+    /// The length of `early_data` in bytes.
+    pub early_data_len: usize,
+    /// The settings for issuing and accepting session tickets as the server.
+    pub tickets: Tickets,
+    /// A function that receives the secrets of each connection, for debugging only.
+    ///
+    /// `line`: a line in the NSS key log format (`SSLKEYLOGFILE`), such as
+    /// `CLIENT_HANDSHAKE_TRAFFIC_SECRET <client random> <secret>`. It isn't null-terminated and
+    /// doesn't end with a newline.
+    /// `len`: the length of `line` in bytes.
+    /// `ctx`: `keylog_ctx`.
+    ///
+    /// Anyone with these secrets can decrypt the connection, so this should be `NULL` in
+    /// production. By default, nothing is logged.
+    pub keylog: Option<extern "C" fn(line: *const c_char, len: usize, ctx: *const c_void)>,
+    /// Contextual data for `keylog`.
+    ///
+    /// Lifetime: this pointer must be valid for the duration of the connection.
+    pub keylog_ctx: *const c_void,
+}
+
+impl Config {
+    /// Returns the serialized session to resume, or an empty slice if there is none.
+    ///
+    /// # Safety
+    /// `session` must be `NULL` or valid for reads of `session_len` bytes.
+    pub(crate) unsafe fn session(&self) -> &[u8] {
+        if self.session.is_null() {
+            return &[];
+        }
+        // SAFETY: the caller guarantees that `session` is valid.
+        unsafe { std::slice::from_raw_parts(self.session, self.session_len) }
+    }
+
+    /// Returns the early data to send, or an empty slice if there is none.
+    ///
+    /// # Safety
+    /// `early_data` must be `NULL` or valid for reads of `early_data_len` bytes.
+    pub(crate) unsafe fn early_data(&self) -> &[u8] {
+        if self.early_data.is_null() {
+            return &[];
+        }
+        // SAFETY: the caller guarantees that `early_data` is valid.
+        unsafe { std::slice::from_raw_parts(self.early_data, self.early_data_len) }
+    }
+
+    /// Frees the identity store and resets `identity` if it was loaded by `turtls_load_identity`.
+    ///
+    /// # Safety
+    /// `identity_store` must be `NULL` or allocated by `turtls_load_identity`.
+    pub(crate) unsafe fn free_identity(&mut self) {
+        if self.identity_store.is_null() {
+            return;
+        }
+        // SAFETY: the caller guarantees that the identity store was allocated by
+        // `turtls_load_identity`.
+        let _ = unsafe { Box::from_raw(self.identity_store) };
+        self.identity_store = null_mut();
+        self.identity = Identity::default();
+    }
+
+    /// Returns the DER encodings of the trust anchors.
+    ///
+    /// # Safety
+    /// `trust_store` must be `NULL` or valid.
+    pub(crate) unsafe fn trust_anchors(&self) -> &[Box<[u8]>] {
+        if self.trust_store.is_null() {
+            return &[];
+        }
+        // SAFETY: the caller guarantees that `trust_store` is valid.
+        unsafe { &(*self.trust_store).anchors }
+    }
 }
 
 impl Default for Config {
     fn default() -> Self {
         Self {
             timeout_millis: 10_000,
+            non_blocking: false,
             extensions: Extensions::default(),
             cipher_suites: CipherList::default(),
+            identity: Identity::default(),
+            identity_store: null_mut(),
+            trust_store: null_mut(),
+            client_auth: ClientAuth::None,
+            session: null(),
+            session_len: 0,
+            early_data: null(),
+            early_data_len: 0,
+            tickets: Tickets::default(),
+            keylog: None,
+            keylog_ctx: null(),
         }
     }
 }
@@ -33,4 +175,226 @@ pub enum ConfigError {
     /// One or more extensions is missing.
     // TODO: make this store which extension is missing
     MissingExtensions,
+    /// The certificate chain or private key is missing or invalid.
+    MissingIdentity,
+    /// The client has trust anchors but no server name to verify the server's certificate with.
+    MissingServerName,
+    /// The application protocols are incorrectly encoded.
+    InvalidAppProtos,
+    /// The record size limit is less than `64`.
+    InvalidRecordSizeLimit,
+}
+
+/// Whether or not the server authenticates the client with a certificate (mutual TLS).
+///
+/// Certificates aren't requested when resuming a session. Instead, the certificate the client
+/// authenticated with when the ticket was issued is used.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[repr(C)]
+pub enum ClientAuth {
+    /// No certificate is requested.
+    None,
+    /// A certificate is requested, but the client may decline to send one.
+    ///
+    /// If the client sends a certificate, it must be valid.
+    Request,
+    /// A valid certificate is required.
+    Require,
+}
+
+/// A DER-encoded X.509 certificate.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[repr(C)]
+pub struct Cert {
+    /// The DER encoding of the certificate.
+    ///
+    /// Lifetime: this pointer must be valid for the duration of the handshake.
+    pub der: *const u8,
+    /// The length of the certificate in bytes.
+    pub len: usize,
+}
+
+/// The certificate chain and private key used to authenticate to the peer.
+///
+/// By default, no certificates are provided.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[repr(C)]
+pub struct Identity {
+    /// The certificate chain, starting with the end-entity certificate.
+    ///
+    /// Lifetime: this pointer must be valid for the duration of the handshake.
+    pub certs: *const Cert,
+    /// The number of certificates in `certs`.
+    pub num_certs: usize,
+    /// The private key that corresponds to the end-entity certificate.
+    ///
+    /// A secp256r1 key is encoded as a big-endian integer, and an Ed25519 key is the 32-byte
+    /// private key from RFC 8032.
+    pub priv_key: [u8; 32],
+}
+
+impl Identity {
+    /// Returns the certificate chain as a list of slices.
+    ///
+    /// # Safety
+    /// `certs` must be `NULL` or point to `num_certs` valid certificates.
+    pub(crate) unsafe fn certs(&self) -> Vec<&[u8]> {
+        if self.certs.is_null() {
+            return Vec::new();
+        }
+        // SAFETY: the caller guarantees that `certs` is valid.
+        let certs = unsafe { std::slice::from_raw_parts(self.certs, self.num_certs) };
+        certs
+            .iter()
+            // SAFETY: the caller guarantees that each certificate is valid.
+            .map(|cert| unsafe { std::slice::from_raw_parts(cert.der, cert.len) })
+            .collect()
+    }
+}
+
+impl Default for Identity {
+    fn default() -> Self {
+        Self {
+            certs: null(),
+            num_certs: 0,
+            priv_key: [0; 32],
+        }
+    }
+}
+
+/// The settings for issuing and accepting session tickets as the server.
+///
+/// Clients resume sessions with tickets to skip the certificate exchange, and may also send early
+/// data (0-RTT). By default, no tickets are issued.
+#[derive(Clone, Copy, Debug)]
+#[repr(C)]
+pub struct Tickets {
+    /// The secret key used to encrypt and decrypt tickets.
+    ///
+    /// This should be random, and servers that share it can resume each other's sessions. If it
+    /// is all zeros, tickets are neither issued nor accepted.
+    pub key: [u8; 32],
+    /// The lifetime of tickets in seconds, up to 604800 (seven days).
+    ///
+    /// Default value: `7200`
+    pub lifetime: u32,
+    /// The maximum number of bytes of early data to accept from the client.
+    ///
+    /// If this is `0` or `anti_replay` is `NULL`, early data is rejected.
+    ///
+    /// Default value: `0`
+    pub max_early_data: u32,
+    /// A function that returns whether or not a ClientHello with early data is new.
+    ///
+    /// `id`: a value that is unique to the ClientHello.
+    /// `len`: the length of `id` in bytes.
+    /// `ctx`: `anti_replay_ctx`.
+    ///
+    /// This function must return `false` if it was called with the same `id` before, in which
+    /// case the early data is rejected. ClientHellos more than 10 seconds older or newer than
+    /// expected are rejected before this is called, so each `id` only needs to be remembered
+    /// for 20 seconds.
+    pub anti_replay: Option<extern "C" fn(id: *const u8, len: usize, ctx: *const c_void) -> bool>,
+    /// Contextual data for `anti_replay`.
+    ///
+    /// Lifetime: this pointer must be valid for the duration of the handshake.
+    pub anti_replay_ctx: *const c_void,
+}
+
+impl Tickets {
+    /// Returns whether or not tickets are issued and accepted.
+    pub(crate) fn enabled(&self) -> bool {
+        self.key != [0; 32] && self.lifetime != 0
+    }
+
+    /// Returns whether or not the early data with `id` should be accepted.
+    ///
+    /// This is `false` if early data is disabled or if `id` was seen before.
+    pub(crate) fn accept_early_data(&self, id: &[u8]) -> bool {
+        let Some(anti_replay) = self.anti_replay else {
+            return false;
+        };
+        self.max_early_data != 0 && anti_replay(id.as_ptr(), id.len(), self.anti_replay_ctx)
+    }
+}
+
+impl Default for Tickets {
+    fn default() -> Self {
+        Self {
+            key: [0; 32],
+            lifetime: 7200,
+            max_early_data: 0,
+            anti_replay: None,
+            anti_replay_ctx: null(),
+        }
+    }
+}
+
+/// A set of trusted root certificates.
+pub struct TrustStore {
+    /// The DER encodings of the certificates.
+    pub(crate) anchors: Vec<Box<[u8]>>,
+}
+
+/// A certificate chain loaded into a config.
+pub struct IdentityStore {
+    /// The DER encodings of the certificates.
+    #[expect(
+        unused,
+        reason = "the certificates are only owned here and read via `cert_refs`"
+    )]
+    certs: Vec<Box<[u8]>>,
+    /// The certificates as they are referenced by [`Identity::certs`].
+    pub(crate) cert_refs: Box<[Cert]>,
+}
+
+impl IdentityStore {
+    /// Decodes a certificate chain and the private key of its end-entity certificate.
+    ///
+    /// `certs` is PEM or a sequence of DER certificates, and `priv_key` is PKCS #8 or SEC 1 in
+    /// PEM or DER. Returns `None` if either is malformed or they don't match.
+    pub(crate) fn load(certs: &[u8], priv_key: &[u8]) -> Option<(Self, [u8; 32])> {
+        let certs = if pem::is_pem(certs) {
+            pem::decode(certs)?
+                .into_iter()
+                .filter(|block| block.label == b"CERTIFICATE")
+                .map(|block| block.der.into_boxed_slice())
+                .collect()
+        } else {
+            let mut reader = Reader::new(certs);
+            let mut chain = Vec::new();
+            while !reader.is_empty() {
+                chain.push(Box::from(reader.read_any().ok()?.raw));
+            }
+            chain
+        };
+        if certs.is_empty()
+            || certs
+                .iter()
+                .any(|cert| x509::Certificate::parse(cert).is_err())
+        {
+            return None;
+        }
+
+        let priv_key = if pem::is_pem(priv_key) {
+            // OpenSSL may put the curve in its own block before the key
+            let block = pem::decode(priv_key)?
+                .into_iter()
+                .find(|block| block.label == b"PRIVATE KEY" || block.label == b"EC PRIVATE KEY")?;
+            x509::parse_priv_key(&block.der)
+        } else {
+            x509::parse_priv_key(priv_key)
+        }
+        .ok()?;
+        SigningKey::new(&certs[0], &priv_key)?;
+
+        let cert_refs = certs
+            .iter()
+            .map(|cert| Cert {
+                der: cert.as_ptr(),
+                len: cert.len(),
+            })
+            .collect();
+        Some((Self { certs, cert_refs }, priv_key))
+    }
 }
@@ -0,0 +1,410 @@
+//! A zero-copy decoder for the subset of DER used by X.509 certificates.
+//!
+//! Only the distinguished encoding is accepted: lengths must be minimal, indefinite lengths are
+//! rejected, and integers must not have redundant leading bytes.
+
+/// The error that is returned when DER data is malformed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct DerError;
+
+/// DER tags, including the class and constructed bits.
+pub(crate) mod tag {
+    pub(crate) const BOOLEAN: u8 = 0x01;
+    pub(crate) const INTEGER: u8 = 0x02;
+    pub(crate) const BIT_STRING: u8 = 0x03;
+    pub(crate) const OCTET_STRING: u8 = 0x04;
+    pub(crate) const NULL: u8 = 0x05;
+    pub(crate) const OID: u8 = 0x06;
+    pub(crate) const UTC_TIME: u8 = 0x17;
+    pub(crate) const GENERALIZED_TIME: u8 = 0x18;
+    pub(crate) const SEQUENCE: u8 = 0x30;
+    pub(crate) const SET: u8 = 0x31;
+
+    /// Returns the tag of an explicitly-tagged, context-specific field.
+    pub(crate) const fn explicit(num: u8) -> u8 {
+        0xa0 | num
+    }
+
+    /// Returns the tag of an implicitly-tagged, primitive, context-specific field.
+    pub(crate) const fn implicit(num: u8) -> u8 {
+        0x80 | num
+    }
+}
+
+/// A single DER element.
+#[derive(Clone, Copy)]
+pub(crate) struct Tlv<'a> {
+    pub(crate) tag: u8,
+    pub(crate) value: &'a [u8],
+    /// The whole element, including its tag and length.
+    pub(crate) raw: &'a [u8],
+}
+
+/// Reads consecutive DER elements from a buffer.
+#[derive(Clone, Copy)]
+pub(crate) struct Reader<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    /// The highest tag number that fits in a single byte.
+    const MAX_LOW_TAG: u8 = 0x1e;
+
+    pub(crate) const fn new(data: &'a [u8]) -> Self {
+        Self { data }
+    }
+
+    pub(crate) const fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    /// Returns the tag of the next element without consuming it.
+    pub(crate) fn peek_tag(&self) -> Option<u8> {
+        self.data.first().copied()
+    }
+
+    /// Reads the next element.
+    pub(crate) fn read_any(&mut self) -> Result<Tlv<'a>, DerError> {
+        let [tag, first_len, ..] = *self.data else {
+            return Err(DerError);
+        };
+        // multi-byte tags aren't used by X.509
+        if tag & 0x1f > Self::MAX_LOW_TAG {
+            return Err(DerError);
+        }
+
+        let (header_len, len) = if first_len < 0x80 {
+            (2, first_len as usize)
+        } else {
+            let len_len = (first_len & 0x7f) as usize;
+            // indefinite lengths aren't allowed, and no element is 4 GiB or longer
+            if len_len == 0 || len_len > size_of::<u32>() || self.data.len() < 2 + len_len {
+                return Err(DerError);
+            }
+            let len_bytes = &self.data[2..][..len_len];
+            // the length must be encoded with as few bytes as possible
+            if len_bytes[0] == 0 {
+                return Err(DerError);
+            }
+            let len = len_bytes
+                .iter()
+                .fold(0, |len, &byte| (len << 8) | byte as usize);
+            if len < 0x80 {
+                return Err(DerError);
+            }
+            (2 + len_len, len)
+        };
+
+        if self.data.len() - header_len < len {
+            return Err(DerError);
+        }
+        let (raw, rest) = self.data.split_at(header_len + len);
+        self.data = rest;
+        Ok(Tlv {
+            tag,
+            value: &raw[header_len..],
+            raw,
+        })
+    }
+
+    /// Reads the next element, which must have the tag `tag`, and returns its value.
+    pub(crate) fn read(&mut self, tag: u8) -> Result<&'a [u8], DerError> {
+        let tlv = self.read_any()?;
+        if tlv.tag != tag {
+            return Err(DerError);
+        }
+        Ok(tlv.value)
+    }
+
+    /// Reads the next element if it has the tag `tag`.
+    pub(crate) fn read_optional(&mut self, tag: u8) -> Result<Option<&'a [u8]>, DerError> {
+        if self.peek_tag() != Some(tag) {
+            return Ok(None);
+        }
+        self.read(tag).map(Some)
+    }
+
+    /// Reads a SEQUENCE and returns a reader over its contents.
+    pub(crate) fn read_sequence(&mut self) -> Result<Reader<'a>, DerError> {
+        self.read(tag::SEQUENCE).map(Reader::new)
+    }
+
+    /// Reads a BOOLEAN.
+    pub(crate) fn read_bool(&mut self) -> Result<bool, DerError> {
+        match self.read(tag::BOOLEAN)? {
+            [0] => Ok(false),
+            [0xff] => Ok(true),
+            _ => Err(DerError),
+        }
+    }
+
+    /// Reads a non-negative INTEGER and returns its big-endian bytes without a leading zero.
+    pub(crate) fn read_uint(&mut self) -> Result<&'a [u8], DerError> {
+        uint_bytes(self.read(tag::INTEGER)?)
+    }
+
+    /// Reads a BIT STRING with no unused bits and returns its bytes.
+    pub(crate) fn read_bit_string(&mut self) -> Result<&'a [u8], DerError> {
+        match self.read(tag::BIT_STRING)? {
+            [0, bytes @ ..] => Ok(bytes),
+            _ => Err(DerError),
+        }
+    }
+
+    /// Reads a UTCTime or GeneralizedTime and returns it in seconds since the Unix epoch.
+    pub(crate) fn read_time(&mut self) -> Result<i64, DerError> {
+        let tlv = self.read_any()?;
+        let (year, rest) = match tlv.tag {
+            tag::UTC_TIME if tlv.value.len() == 13 => {
+                let year = parse_digits(&tlv.value[..2])? as i64;
+                // RFC 5280 maps two-digit years to 1950 through 2049
+                let year = if year < 50 { 2000 + year } else { 1900 + year };
+                (year, &tlv.value[2..])
+            },
+            tag::GENERALIZED_TIME if tlv.value.len() == 15 => {
+                (parse_digits(&tlv.value[..4])? as i64, &tlv.value[4..])
+            },
+            _ => return Err(DerError),
+        };
+        // certificates must use UTC and whole seconds
+        if rest[10] != b'Z' {
+            return Err(DerError);
+        }
+        let month = parse_digits(&rest[0..2])?;
+        let day = parse_digits(&rest[2..4])?;
+        let hour = parse_digits(&rest[4..6])?;
+        let min = parse_digits(&rest[6..8])?;
+        let sec = parse_digits(&rest[8..10])?;
+        if !(1..=12).contains(&month)
+            || day == 0
+            || day > days_in_month(year, month)
+            || hour > 23
+            || min > 59
+            || sec > 59
+        {
+            return Err(DerError);
+        }
+        Ok(days_from_civil(year, month, day) * 86400 + (hour * 3600 + min * 60 + sec) as i64)
+    }
+}
+
+/// Returns the big-endian bytes of a non-negative INTEGER without a leading zero.
+fn uint_bytes(int: &[u8]) -> Result<&[u8], DerError> {
+    match int {
+        [] => Err(DerError),
+        // negative
+        [first, ..] if first & 0x80 != 0 => Err(DerError),
+        // a leading zero is only allowed when the next byte would make the integer negative
+        [0, second, ..] if second & 0x80 == 0 => Err(DerError),
+        [0, rest @ ..] if !rest.is_empty() => Ok(rest),
+        _ => Ok(int),
+    }
+}
+
+fn parse_digits(digits: &[u8]) -> Result<u32, DerError> {
+    digits.iter().try_fold(0, |num, &digit| {
+        if !digit.is_ascii_digit() {
+            return Err(DerError);
+        }
+        Ok(num * 10 + (digit - b'0') as u32)
+    })
+}
+
+const fn is_leap_year(year: i64) -> bool {
+    year % 4 == 0 && (year % 100 != 0 || year % 400 == 0)
+}
+
+const fn days_in_month(year: i64, month: u32) -> u32 {
+    match month {
+        2 if is_leap_year(year) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/// Returns the number of days between the Unix epoch and a date in the Gregorian calendar.
+const fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    // shift the year to start in March so leap days are at the end
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let month = ((month + 9) % 12) as i64;
+    let day_of_year = (153 * month + 2) / 5 + day as i64 - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146097 + day_of_era - 719468
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{tag, DerError, Reader};
+
+    /// Reads a single time element.
+    fn time(tag: u8, value: &[u8]) -> Result<i64, DerError> {
+        let der = [&[tag, value.len() as u8], value].concat();
+        Reader::new(&der).read_time()
+    }
+
+    #[test]
+    fn lengths() {
+        let mut reader = Reader::new(&[0x04, 0x02, 0xaa, 0xbb, 0x05, 0x00]);
+        assert_eq!(reader.read(tag::OCTET_STRING), Ok([0xaa, 0xbb].as_slice()));
+        assert_eq!(reader.read(tag::NULL), Ok([].as_slice()));
+        assert!(reader.is_empty());
+
+        let long = [[0x04, 0x81, 0x80].as_slice(), &[0xaa; 0x80]].concat();
+        assert_eq!(
+            Reader::new(&long).read(tag::OCTET_STRING),
+            Ok([0xaa; 0x80].as_slice())
+        );
+        let long = [[0x04, 0x82, 0x01, 0x00].as_slice(), &[0xaa; 0x100]].concat();
+        assert_eq!(
+            Reader::new(&long).read(tag::OCTET_STRING),
+            Ok([0xaa; 0x100].as_slice())
+        );
+    }
+
+    #[test]
+    fn bad_lengths() {
+        // long form for a short length
+        assert!(Reader::new(&[0x04, 0x81, 0x01, 0xaa]).read_any().is_err());
+        assert!(Reader::new(&[0x04, 0x81, 0x7f]).read_any().is_err());
+        // redundant leading zero in the length
+        let long = [[0x04, 0x82, 0x00, 0x80].as_slice(), &[0xaa; 0x80]].concat();
+        assert_eq!(Reader::new(&long).read_any().err(), Some(DerError));
+        // indefinite length
+        assert!(Reader::new(&[0x30, 0x80, 0x00, 0x00]).read_any().is_err());
+        // too many length bytes
+        assert!(Reader::new(&[0x04, 0x85, 0x01, 0x00, 0x00, 0x00, 0x00])
+            .read_any()
+            .is_err());
+        // truncated
+        assert!(Reader::new(&[0x04, 0x03, 0xaa, 0xbb]).read_any().is_err());
+        assert!(Reader::new(&[0x04, 0x82, 0x01]).read_any().is_err());
+        assert!(Reader::new(&[0x04]).read_any().is_err());
+        assert!(Reader::new(&[]).read_any().is_err());
+        // multi-byte tag
+        assert!(Reader::new(&[0x1f, 0x81, 0x00, 0x00]).read_any().is_err());
+    }
+
+    #[test]
+    fn tags() {
+        let mut reader = Reader::new(&[0x02, 0x01, 0x05]);
+        assert_eq!(reader.read_optional(tag::explicit(0)), Ok(None));
+        assert_eq!(reader.read(tag::OCTET_STRING), Err(DerError));
+
+        let mut reader = Reader::new(&[0xa0, 0x03, 0x02, 0x01, 0x02, 0x02, 0x01, 0x05]);
+        let version = reader.read_optional(tag::explicit(0)).unwrap().unwrap();
+        assert_eq!(Reader::new(version).read_uint(), Ok([0x02].as_slice()));
+        assert_eq!(reader.read_uint(), Ok([0x05].as_slice()));
+        assert!(reader.is_empty());
+    }
+
+    #[test]
+    fn integers() {
+        let uint = |der: &[u8]| Reader::new(der).read_uint().map(<[u8]>::to_vec);
+        assert_eq!(uint(&[0x02, 0x01, 0x00]), Ok(vec![0x00]));
+        assert_eq!(uint(&[0x02, 0x01, 0x7f]), Ok(vec![0x7f]));
+        assert_eq!(uint(&[0x02, 0x02, 0x00, 0x80]), Ok(vec![0x80]));
+        assert_eq!(
+            uint(&[0x02, 0x03, 0x01, 0x00, 0x01]),
+            Ok(vec![0x01, 0x00, 0x01])
+        );
+        // negative
+        assert_eq!(uint(&[0x02, 0x01, 0x80]), Err(DerError));
+        assert_eq!(uint(&[0x02, 0x01, 0xff]), Err(DerError));
+        assert_eq!(uint(&[0x02, 0x02, 0xff, 0x7f]), Err(DerError));
+        // redundant leading byte
+        assert_eq!(uint(&[0x02, 0x02, 0x00, 0x7f]), Err(DerError));
+        assert_eq!(uint(&[0x02, 0x03, 0x00, 0x00, 0x80]), Err(DerError));
+        assert_eq!(uint(&[0x02, 0x00]), Err(DerError));
+    }
+
+    #[test]
+    fn bools_and_bit_strings() {
+        assert_eq!(Reader::new(&[0x01, 0x01, 0xff]).read_bool(), Ok(true));
+        assert_eq!(Reader::new(&[0x01, 0x01, 0x00]).read_bool(), Ok(false));
+        assert_eq!(Reader::new(&[0x01, 0x01, 0x01]).read_bool(), Err(DerError));
+        assert_eq!(
+            Reader::new(&[0x01, 0x02, 0x00, 0x00]).read_bool(),
+            Err(DerError)
+        );
+
+        let bits = Reader::new(&[0x03, 0x03, 0x00, 0xaa, 0xbb]).read_bit_string();
+        assert_eq!(bits, Ok([0xaa, 0xbb].as_slice()));
+        // unused bits
+        assert!(Reader::new(&[0x03, 0x02, 0x04, 0xa0])
+            .read_bit_string()
+            .is_err());
+        assert!(Reader::new(&[0x03, 0x00]).read_bit_string().is_err());
+    }
+
+    #[test]
+    fn utc_times() {
+        assert_eq!(time(tag::UTC_TIME, b"700101000000Z"), Ok(0));
+        assert_eq!(time(tag::UTC_TIME, b"240229123456Z"), Ok(1_709_210_096));
+        // two-digit years cover 1950 through 2049
+        assert_eq!(time(tag::UTC_TIME, b"491231235959Z"), Ok(2_524_607_999));
+        assert_eq!(time(tag::UTC_TIME, b"500101000000Z"), Ok(-631_152_000));
+        assert_eq!(time(tag::UTC_TIME, b"991231235959Z"), Ok(946_684_799));
+        assert_eq!(time(tag::UTC_TIME, b"000101000000Z"), Ok(946_684_800));
+    }
+
+    #[test]
+    fn generalized_times() {
+        assert_eq!(time(tag::GENERALIZED_TIME, b"19700101000000Z"), Ok(0));
+        assert_eq!(
+            time(tag::GENERALIZED_TIME, b"20500101000000Z"),
+            Ok(2_524_608_000)
+        );
+        assert_eq!(
+            time(tag::GENERALIZED_TIME, b"99991231235959Z"),
+            Ok(253_402_300_799)
+        );
+        assert_eq!(
+            time(tag::GENERALIZED_TIME, b"19491231235959Z"),
+            Ok(-631_152_001)
+        );
+        assert_eq!(
+            time(tag::GENERALIZED_TIME, b"20000229000000Z"),
+            Ok(951_782_400)
+        );
+    }
+
+    #[test]
+    fn bad_times() {
+        for utc_time in [
+            // not UTC
+            b"2401010000000".as_slice(),
+            b"240101000000+0000",
+            b"2401010000Z",
+            b"240101000000.5Z",
+            b"241301000000Z",
+            b"240001000000Z",
+            b"240100000000Z",
+            b"240431000000Z",
+            b"230229000000Z",
+            b"240101240000Z",
+            b"240101006000Z",
+            b"240101000060Z",
+            b"24010100000aZ",
+            b"2401010000 0Z",
+        ] {
+            assert_eq!(time(tag::UTC_TIME, utc_time), Err(DerError));
+        }
+        for generalized_time in [
+            b"202401010000000".as_slice(),
+            b"20240101000000",
+            b"20240101000000.5Z",
+            b"2024010100000Z",
+            b"19000229000000Z",
+            b"2024-1-1000000Z",
+        ] {
+            assert_eq!(time(tag::GENERALIZED_TIME, generalized_time), Err(DerError));
+        }
+        // the length decides which type the time is
+        assert_eq!(time(tag::UTC_TIME, b"20240101000000Z"), Err(DerError));
+        assert_eq!(time(tag::GENERALIZED_TIME, b"240101000000Z"), Err(DerError));
+        assert_eq!(time(tag::OCTET_STRING, b"240101000000Z"), Err(DerError));
+    }
+}
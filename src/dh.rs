@@ -2,19 +2,17 @@
 
 use crate::extensions::SupGroups;
 use crylib::big_int::UBigInt;
-use crylib::ec::{EllipticCurve, Secp256r1};
+use crylib::ec::{x25519, EllipticCurve, Secp256r1, Secp384r1, Secp521r1};
 use crylib::finite_field::FieldElement;
 use getrandom::getrandom;
 
+#[derive(Clone, Copy, PartialEq, Eq)]
 #[repr(u16)]
 pub(crate) enum NamedGroup {
     Secp256r1 = 0x17,
-    #[expect(unused, reason = "Secp384r1 is not yet supported")]
     Secp384r1 = 0x18,
-    #[expect(unused, reason = "Secp512r1 is not yet supported")]
     Secp521r1 = 0x19,
 
-    #[expect(unused, reason = "X25519 is not yet supported")]
     X25519 = 0x1d,
     #[expect(unused, reason = "X448 is not yet supported")]
     X448 = 0x1e,
@@ -42,26 +40,64 @@ impl NamedGroup {
 }
 
 pub(crate) struct GroupKeys {
-    pub(crate) secp256r1: FieldElement<4, <Secp256r1 as EllipticCurve>::Order>,
+    pub(crate) secp256r1: FieldElement<4, <Secp256r1 as EllipticCurve<4>>::Order>,
+    pub(crate) secp384r1: FieldElement<6, <Secp384r1 as EllipticCurve<6>>::Order>,
+    pub(crate) secp521r1: FieldElement<9, <Secp521r1 as EllipticCurve<9>>::Order>,
+    pub(crate) x25519: [u8; x25519::KEY_SIZE],
 }
 
 impl GroupKeys {
+    /// Generates private keys for each group in `groups`.
+    ///
+    /// The keys for other groups are left as zero and must not be used.
     pub(crate) fn generate(groups: SupGroups) -> Result<Self, KeyGenError> {
         if groups.groups == 0 {
             return Err(KeyGenError::NoGroups);
         }
-        let mut buf = [0; 32];
-        getrandom(&mut buf)?;
+        let mut keys = Self {
+            secp256r1: FieldElement::ZERO,
+            secp384r1: FieldElement::ZERO,
+            secp521r1: FieldElement::ZERO,
+            x25519: [0; x25519::KEY_SIZE],
+        };
 
-        if buf == [0; 32] {
-            return Err(KeyGenError::PrivKeyIsZero);
+        if groups.groups & SupGroups::SECP256R1 != 0 {
+            let mut buf = [0; 32];
+            getrandom(&mut buf)?;
+            // SAFETY: `[u64; 4]` and `[u8; 32]` have the same memory layout.
+            let as_u64s: [u64; 4] = unsafe { std::mem::transmute(buf) };
+            keys.secp256r1 = FieldElement::<4, _>::new(UBigInt(as_u64s));
+            if keys.secp256r1 == FieldElement::ZERO {
+                return Err(KeyGenError::PrivKeyIsZero);
+            }
         }
 
-        // SAFETY: `[u64; 4]` and `[u8; 32]` have the same memory layout.
-        let as_u64s: [u64; 4] = unsafe { std::mem::transmute(buf) };
-        return Ok(Self {
-            secp256r1: FieldElement::<4, _>::new(UBigInt(as_u64s)),
-        });
+        if groups.groups & SupGroups::SECP384R1 != 0 {
+            let mut buf = [0; 48];
+            getrandom(&mut buf)?;
+            keys.secp384r1 = FieldElement::<6, _>::new(UBigInt::<6>::from_be_bytes(buf));
+            if keys.secp384r1 == FieldElement::ZERO {
+                return Err(KeyGenError::PrivKeyIsZero);
+            }
+        }
+
+        if groups.groups & SupGroups::SECP521R1 != 0 {
+            // the private key only needs 521 bits
+            let mut buf = [0; 72];
+            getrandom(&mut buf[6..])?;
+            buf[6] &= 1;
+            keys.secp521r1 = FieldElement::<9, _>::new(UBigInt::<9>::from_be_bytes(buf));
+            if keys.secp521r1 == FieldElement::ZERO {
+                return Err(KeyGenError::PrivKeyIsZero);
+            }
+        }
+
+        // X25519 keys are clamped, so every value is valid
+        if groups.groups & SupGroups::X25519 != 0 {
+            getrandom(&mut keys.x25519)?;
+        }
+
+        Ok(keys)
     }
 }
 
@@ -1,8 +1,10 @@
+//! Errors that can occur during a connection.
+
 use crate::alert::Alert;
 use crate::config::ConfigError;
-use crate::client_hello::CliHelError;
-use crate::record::ReadError;
 use crate::dh::KeyGenError;
+use crate::record::{IoError, ReadError};
+use crate::server_hello::SerHelError;
 
 #[derive(Debug)]
 pub(crate) enum TlsError {
@@ -14,7 +16,7 @@ pub(crate) enum TlsError {
 
 /// The result of the handshake.
 ///
-/// If a value other than `Ok` is returned, the connection is closed.
+/// If a value other than `Ok`, `WantRead`, or `WantWrite` is returned, the connection is closed.
 #[must_use]
 #[repr(C)]
 pub enum ShakeResult {
@@ -34,13 +36,22 @@ pub enum ShakeResult {
     PrivKeyIsZero,
     /// Indicates there was an error in the config struct.
     ConfigError(ConfigError),
+    /// Indicates that the handshake is waiting for data from the peer (non-blocking IO only).
+    ///
+    /// The handshake is resumed by calling the handshake function again once data can be read.
+    WantRead,
+    /// Indicates that the handshake is waiting to write data to the peer (non-blocking IO only).
+    ///
+    /// The handshake is resumed by calling the handshake function again once data can be
+    /// written.
+    WantWrite,
 }
 
-impl From<CliHelError> for ShakeResult {
-    fn from(value: CliHelError) -> Self {
+impl From<SerHelError> for ShakeResult {
+    fn from(value: SerHelError) -> Self {
         match value {
-            CliHelError::IoError => Self::IoError,
-            CliHelError::RngError => Self::RngError,
+            SerHelError::IoError => Self::IoError,
+            SerHelError::RngError => Self::RngError,
         }
     }
 }
@@ -59,11 +70,18 @@ impl From<ReadError> for ShakeResult {
         match value {
             ReadError::IoError => Self::IoError,
             ReadError::Timeout => Self::Timeout,
-            ReadError::Alert(err) => err.into()
+            ReadError::Alert(err) => err.into(),
+            ReadError::WouldBlock => Self::WantRead,
         }
     }
 }
 
+impl From<IoError> for ShakeResult {
+    fn from(_: IoError) -> Self {
+        Self::IoError
+    }
+}
+
 impl From<KeyGenError> for ShakeResult {
     fn from(value: KeyGenError) -> Self {
         match value {
@@ -73,3 +91,58 @@ impl From<KeyGenError> for ShakeResult {
         }
     }
 }
+
+/// The result of sending or reading application data.
+///
+/// If a value other than `Ok`, `WantRead`, or `WantWrite` is returned, the connection is closed.
+#[must_use]
+#[repr(C)]
+pub enum DataResult {
+    /// Indicates that the operation was successful.
+    Ok,
+    /// Indicates that the peer sent an alert.
+    ///
+    /// `CloseNotify` indicates that the peer closed the connection.
+    ReceivedAlert(Alert),
+    /// Indicates that an alert was sent to the peer.
+    SentAlert(Alert),
+    /// Indicates that there was an error performing an IO operation.
+    IoError,
+    /// Indicates that the record read took too long.
+    Timeout,
+    /// Indicates that there is no open connection.
+    NotConnected,
+    /// Indicates that no data could be read without blocking (non-blocking IO only).
+    WantRead,
+    /// Indicates that the data was accepted, but not all of it could be written without blocking
+    /// (non-blocking IO only).
+    ///
+    /// The rest is written by the next call to `turtls_send`, which may send no data.
+    WantWrite,
+}
+
+impl From<TlsError> for DataResult {
+    fn from(value: TlsError) -> Self {
+        match value {
+            TlsError::Sent(err) => Self::SentAlert(err),
+            TlsError::Received(err) => Self::ReceivedAlert(err),
+        }
+    }
+}
+
+impl From<ReadError> for DataResult {
+    fn from(value: ReadError) -> Self {
+        match value {
+            ReadError::IoError => Self::IoError,
+            ReadError::Timeout => Self::Timeout,
+            ReadError::Alert(err) => err.into(),
+            ReadError::WouldBlock => Self::WantRead,
+        }
+    }
+}
+
+impl From<IoError> for DataResult {
+    fn from(_: IoError) -> Self {
+        Self::IoError
+    }
+}
@@ -1,22 +1,29 @@
+//! TLS extensions.
+
 use core::slice;
-use std::ffi::c_char;
+use std::ffi::{c_char, c_void};
+use std::iter;
+use std::net::IpAddr;
 use std::ptr::null;
 
-use crylib::ec::{EllipticCurve, Secp256r1};
+use crylib::ec::{x25519, EllipticCurve, Secp256r1, Secp384r1, Secp521r1};
 use crylib::finite_field::FieldElement;
 
 use crate::alert::Alert;
 use crate::cipher_suites::SignatureScheme;
 use crate::dh::{GroupKeys, NamedGroup};
-use crate::record::RecordLayer;
+use crate::handshake::{ShakeType, SHAKE_HEADER_SIZE};
+use crate::key_schedule;
+use crate::record::{ContentType, RecordLayer};
+use crate::session::Session;
 use crate::versions::ProtocolVersion;
 use crylib::big_int::UBigInt;
 use crylib::ec::AffinePoint;
 
+#[derive(Clone, Copy)]
 #[repr(u16)]
 pub(crate) enum ExtensionType {
     ServerName = 0,
-    #[expect(unused, reason = "MaxFragmentLength not yet supported")]
     MaxFragmentLength = 1,
     #[expect(unused, reason = "StatusRequest not yet supported")]
     StatusRequest = 5,
@@ -26,8 +33,7 @@ pub(crate) enum ExtensionType {
     UseSrtp = 14,
     #[expect(unused, reason = "Heartbeat not yet supported")]
     Heartbeat = 15,
-    #[expect(unused, reason = "AppLayerProtoReneg not yet supported")]
-    AppLayerProtoReneg = 16,
+    AppLayerProtoNegotiation = 16,
     #[expect(unused, reason = "SignedCertTimestamp not yet supported")]
     SignedCertTimestamp = 18,
     #[expect(unused, reason = "ClientCertType not yet supported")]
@@ -36,16 +42,12 @@ pub(crate) enum ExtensionType {
     ServerCertType = 20,
     #[expect(unused, reason = "Padding not yet supported")]
     Padding = 21,
-    #[expect(unused, reason = "PreSharedKey not yet supported")]
+    RecordSizeLimit = 28,
     PreSharedKey = 41,
-    #[expect(unused, reason = "EarlyData not yet supported")]
     EarlyData = 42,
     SupportedVersions = 43,
-    #[expect(unused, reason = "Cookie not yet supported")]
     Cookie = 44,
-    #[expect(unused, reason = "PskExchangeModes not yet supported")]
     PskExchangeModes = 45,
-    #[expect(unused, reason = "CertAuthorities not yet supported")]
     CertAuthorities = 47,
     #[expect(unused, reason = "OidFilters not yet supported")]
     OidFilters = 48,
@@ -69,7 +71,7 @@ impl ExtensionType {
 /// The extensions to use in the handshake.
 ///
 /// Refer to each extension's individual documentation for specific usage information.
-#[derive(Default, PartialEq, Eq, Clone, Copy)]
+#[derive(Default, Clone, Copy)]
 #[repr(C)]
 pub struct Extensions {
     /// The server name to send to the server or to expect from the client.
@@ -84,6 +86,22 @@ pub struct Extensions {
     ///
     /// Refer to its specific documentation for more information.
     pub sup_groups: SupGroups,
+    /// The application protocols to offer to the server or accept from the client.
+    ///
+    /// Refer to its specific documentation for more information.
+    pub app_protos: AppProtos,
+    /// The size of the largest protected record the peer may send (RFC 8449), or `0` for no limit.
+    ///
+    /// This counts the plaintext of a record and its inner content type, so a limit of `n` allows
+    /// `n - 1` bytes of data per record. It must be at least `64`, and limits above `16385` are
+    /// sent as `16385`. Records that exceed the limit are rejected with `RecordOverflow`.
+    ///
+    /// Clients also request the largest `max_fragment_length` that fits within the limit, for
+    /// servers that don't support record size limits. Servers ignore the limit unless the client
+    /// sends one too. Either way, the peer's limit is always respected.
+    ///
+    /// Default value: `0`
+    pub record_size_limit: u16,
 }
 
 impl Extensions {
@@ -92,61 +110,104 @@ impl Extensions {
     /// The size of each individual extension's length encoding
     const EXTENSION_LEN_SIZE: usize = 2;
     /// The size of each extension header.
-    const HEADER_SIZE: usize = size_of::<ExtensionType>() + Self::EXTENSION_LEN_SIZE;
+    pub(crate) const HEADER_SIZE: usize = size_of::<ExtensionType>() + Self::EXTENSION_LEN_SIZE;
 
-    /// The length of the extensions in ClientHello
-    pub(crate) fn len_client(&self) -> usize {
+    /// The length of the extensions in ClientHello.
+    ///
+    /// `key_share_groups` are the groups to send key shares for, `cookie` is the cookie to echo
+    /// from HelloRetryRequest (or an empty slice), `psk` is the session to offer, if any, and
+    /// `early_data` is whether or not early data will be sent.
+    pub(crate) fn len_client(
+        &self,
+        key_share_groups: SupGroups,
+        cookie: &[u8],
+        psk: Option<&Session>,
+        early_data: bool,
+    ) -> usize {
         const fn new_len(new_len: usize) -> usize {
             new_len + (((new_len > 0) as usize) * Extensions::HEADER_SIZE)
         }
 
         let mut len = 0;
         len += new_len(self.server_name.len());
+        len += new_len(self.app_protos.len());
+        len += RecordSizeLimit::len(self.record_size_limit);
+        len += MaxFragLen::len(MaxFragLen::from_limit(self.record_size_limit));
         len += new_len(self.sig_algs.len());
         len += new_len(self.sup_groups.len());
         len += SupVersions::len();
-        len += new_len(KeyShare::len(&self.sup_groups));
+        len += new_len(KeyShare::len(&key_share_groups));
+        len += new_len(Cookie::len(cookie));
+        len += PskModes::len();
+        len += EarlyData::len(early_data);
+        len += new_len(PreSharedKey::len(psk));
 
         len
     }
 
     /// Write the extensions to ClientHello.
-    pub(crate) fn write_client(&self, record_layer: &mut RecordLayer, keys: &GroupKeys) {
+    ///
+    /// `key_share_groups` are the groups to send key shares for, `cookie` is the cookie to echo
+    /// from HelloRetryRequest (or an empty slice), `psk` is the session to offer, if any, and
+    /// `early_data` is whether or not early data will be sent.
+    pub(crate) fn write_client(
+        &self,
+        record_layer: &mut RecordLayer,
+        keys: &GroupKeys,
+        key_share_groups: SupGroups,
+        cookie: &[u8],
+        psk: Option<&Session>,
+        early_data: bool,
+    ) {
         self.server_name.write_client(record_layer);
+        self.app_protos.write_client(record_layer);
+        RecordSizeLimit::write_client(record_layer, self.record_size_limit);
+        MaxFragLen::write_client(record_layer, MaxFragLen::from_limit(self.record_size_limit));
         self.sig_algs.write_client(record_layer);
         SupVersions::write_client(record_layer);
         self.sup_groups.write_client(record_layer);
-        KeyShare::write_client(record_layer, &self.sup_groups, keys);
+        KeyShare::write_client(record_layer, &key_share_groups, keys);
+        Cookie::write_client(record_layer, cookie);
+        PskModes::write_client(record_layer);
+        EarlyData::write(record_layer, early_data);
+        // the binders cover the rest of ClientHello, so this must be the last extension
+        PreSharedKey::write_client(record_layer, psk);
     }
 }
 
 pub(crate) struct SerHelExtRef<'a> {
     pub(crate) key_share: &'a [u8],
+    /// The index of the PSK the server accepted, if it accepted one.
+    pub(crate) selected_psk: Option<u16>,
 }
 
 impl<'a> SerHelExtRef<'a> {
     /// Parse the ServerHello extensions.
     pub(crate) fn parse(mut extensions: &'a [u8]) -> Result<Self, Alert> {
         let mut key_share: &[u8] = &[];
+        let mut selected_psk = None;
         while extensions.len() >= Extensions::HEADER_SIZE {
             let len = u16::from_be_bytes(
                 extensions[size_of::<ExtensionType>()..][..Extensions::LEN_SIZE]
                     .try_into()
                     .unwrap(),
             ) as usize;
+            if extensions.len() - Extensions::HEADER_SIZE < len {
+                return Err(Alert::DecodeError);
+            }
 
             match &extensions[..size_of::<ExtensionType>()] {
                 x if x == ExtensionType::SupportedVersions.to_be_bytes() => {
-                    if len != size_of::<ProtocolVersion>() {
-                        return Err(Alert::DecodeError);
-                    }
-                    if extensions[Extensions::HEADER_SIZE..][..size_of::<ProtocolVersion>()] != ProtocolVersion::TlsOneThree.to_be_bytes() {
-                        return Err(Alert::ProtocolVersion);
-                    }
+                    SupVersions::parse_ser(&extensions[Extensions::HEADER_SIZE..][..len])?;
                 },
                 x if x == ExtensionType::KeyShare.to_be_bytes() => {
                     key_share = &extensions[Extensions::HEADER_SIZE..][..len]
                 },
+                x if x == ExtensionType::PreSharedKey.to_be_bytes() => {
+                    selected_psk = Some(PreSharedKey::parse_ser(
+                        &extensions[Extensions::HEADER_SIZE..][..len],
+                    )?);
+                },
                 _ => {
                     return Err(Alert::UnsupportedExtension);
                 },
@@ -154,17 +215,332 @@ impl<'a> SerHelExtRef<'a> {
 
             extensions = &extensions[Extensions::HEADER_SIZE + len..];
         }
-        if key_share.len() == 0 {
+        if key_share.is_empty() {
             return Err(Alert::MissingExtension);
         }
         Ok(Self {
             key_share,
+            selected_psk,
+        })
+    }
+}
+
+/// The extensions sent by the server in HelloRetryRequest.
+pub(crate) struct RetryExtRef<'a> {
+    /// The group the server wants a key share for, if it asked for one.
+    pub(crate) selected_group: Option<SupGroups>,
+    /// The cookie to echo in the second ClientHello, or an empty slice if none was sent.
+    pub(crate) cookie: &'a [u8],
+}
+
+impl<'a> RetryExtRef<'a> {
+    /// Parse the HelloRetryRequest extensions.
+    pub(crate) fn parse(mut extensions: &'a [u8]) -> Result<Self, Alert> {
+        let mut selected_group = None;
+        let mut cookie: &[u8] = &[];
+        while !extensions.is_empty() {
+            if extensions.len() < Extensions::HEADER_SIZE {
+                return Err(Alert::DecodeError);
+            }
+            let len = u16::from_be_bytes(
+                extensions[size_of::<ExtensionType>()..][..Extensions::EXTENSION_LEN_SIZE]
+                    .try_into()
+                    .unwrap(),
+            ) as usize;
+            if extensions.len() - Extensions::HEADER_SIZE < len {
+                return Err(Alert::DecodeError);
+            }
+            let ext = &extensions[Extensions::HEADER_SIZE..][..len];
+
+            match &extensions[..size_of::<ExtensionType>()] {
+                x if x == ExtensionType::SupportedVersions.to_be_bytes() => {
+                    SupVersions::parse_ser(ext)?;
+                },
+                x if x == ExtensionType::KeyShare.to_be_bytes() => {
+                    // HelloRetryRequest only contains the selected group
+                    if len != size_of::<NamedGroup>() {
+                        return Err(Alert::DecodeError);
+                    }
+                    selected_group = Some(SupGroups::parse_singular([ext[0], ext[1]]));
+                },
+                x if x == ExtensionType::Cookie.to_be_bytes() => cookie = Cookie::parse(ext)?,
+                _ => return Err(Alert::UnsupportedExtension),
+            }
+
+            extensions = &extensions[Extensions::HEADER_SIZE + len..];
+        }
+        Ok(Self {
+            selected_group,
+            cookie,
+        })
+    }
+}
+
+/// The extensions sent by the client in ClientHello.
+pub(crate) struct CliHelExtRef<'a> {
+    /// The host name the client is connecting to, if it sent one.
+    pub(crate) server_name: Option<&'a [u8]>,
+    /// The application protocols offered by the client, without their length encoding.
+    pub(crate) app_protos: Option<&'a [u8]>,
+    pub(crate) sig_algs: SigAlgs,
+    pub(crate) sup_groups: SupGroups,
+    /// The client's key shares, without their length encoding.
+    pub(crate) key_shares: &'a [u8],
+    /// Whether or not the client supports PSK with (EC)DHE key exchange.
+    pub(crate) psk_dhe_ke: bool,
+    /// The PSKs offered by the client, if it offered any.
+    pub(crate) psks: Option<OfferedPsks<'a>>,
+    /// Whether or not the client sent early data.
+    pub(crate) early_data: bool,
+    /// The client's record size limit, if it sent one.
+    pub(crate) record_size_limit: Option<u16>,
+    /// The client's maximum fragment length code, if it sent one.
+    pub(crate) max_frag_len: Option<u8>,
+}
+
+impl<'a> CliHelExtRef<'a> {
+    /// Parse the ClientHello extensions.
+    ///
+    /// Unrecognized extensions are ignored.
+    pub(crate) fn parse(mut extensions: &'a [u8]) -> Result<Self, Alert> {
+        let mut server_name = None;
+        let mut app_protos = None;
+        let mut sig_algs = SigAlgs { algorithms: 0 };
+        let mut sup_groups = SupGroups { groups: 0 };
+        let mut key_shares = None;
+        let mut sup_tls_13 = false;
+        let mut psk_dhe_ke = false;
+        let mut psks = None;
+        let mut early_data = false;
+        let mut record_size_limit = None;
+        let mut max_frag_len = None;
+
+        while !extensions.is_empty() {
+            if extensions.len() < Extensions::HEADER_SIZE {
+                return Err(Alert::DecodeError);
+            }
+            let len = u16::from_be_bytes(
+                extensions[size_of::<ExtensionType>()..][..Extensions::EXTENSION_LEN_SIZE]
+                    .try_into()
+                    .unwrap(),
+            ) as usize;
+            if extensions.len() - Extensions::HEADER_SIZE < len {
+                return Err(Alert::DecodeError);
+            }
+            let ext = &extensions[Extensions::HEADER_SIZE..][..len];
+
+            match &extensions[..size_of::<ExtensionType>()] {
+                x if x == ExtensionType::ServerName.to_be_bytes() => {
+                    server_name = Some(ServerName::parse_cli(ext)?);
+                },
+                x if x == ExtensionType::AppLayerProtoNegotiation.to_be_bytes() => {
+                    app_protos = Some(AppProtos::parse_cli(ext)?);
+                },
+                x if x == ExtensionType::SignatureAlgorithms.to_be_bytes() => {
+                    sig_algs = SigAlgs::parse_list(ext)?;
+                },
+                x if x == ExtensionType::SupportedGroups.to_be_bytes() => {
+                    for group in list_items::<{ size_of::<NamedGroup>() }>(ext)? {
+                        sup_groups.groups |= SupGroups::parse_singular(group).groups;
+                    }
+                },
+                x if x == ExtensionType::SupportedVersions.to_be_bytes() => {
+                    sup_tls_13 = SupVersions::parse_cli(ext)?;
+                },
+                x if x == ExtensionType::KeyShare.to_be_bytes() => {
+                    if ext.len() < KeyShare::LEN_SIZE
+                        || u16::from_be_bytes([ext[0], ext[1]]) as usize
+                            != ext.len() - KeyShare::LEN_SIZE
+                    {
+                        return Err(Alert::DecodeError);
+                    }
+                    key_shares = Some(&ext[KeyShare::LEN_SIZE..]);
+                },
+                x if x == ExtensionType::PskExchangeModes.to_be_bytes() => {
+                    psk_dhe_ke = PskModes::parse_cli(ext)?;
+                },
+                x if x == ExtensionType::EarlyData.to_be_bytes() => {
+                    if !ext.is_empty() {
+                        return Err(Alert::DecodeError);
+                    }
+                    early_data = true;
+                },
+                x if x == ExtensionType::RecordSizeLimit.to_be_bytes() => {
+                    record_size_limit = Some(RecordSizeLimit::parse(ext)?);
+                },
+                x if x == ExtensionType::MaxFragmentLength.to_be_bytes() => {
+                    max_frag_len = Some(MaxFragLen::parse(ext)?);
+                },
+                x if x == ExtensionType::PreSharedKey.to_be_bytes() => {
+                    // the binders cover the rest of ClientHello, so this must be the last extension
+                    if extensions.len() != Extensions::HEADER_SIZE + len {
+                        return Err(Alert::IllegalParam);
+                    }
+                    psks = Some(OfferedPsks::parse(ext)?);
+                },
+                _ => (),
+            }
+
+            extensions = &extensions[Extensions::HEADER_SIZE + len..];
+        }
+
+        if !sup_tls_13 {
+            return Err(Alert::ProtocolVersion);
+        }
+        let Some(key_shares) = key_shares else {
+            return Err(Alert::MissingExtension);
+        };
+        if sig_algs.algorithms == 0 || sup_groups.groups == 0 {
+            return Err(Alert::HandshakeFailure);
+        }
+
+        Ok(Self {
+            server_name,
+            app_protos,
+            sig_algs,
+            sup_groups,
+            key_shares,
+            psk_dhe_ke,
+            psks,
+            early_data,
+            record_size_limit,
+            max_frag_len,
+        })
+    }
+}
+
+/// Splits a list with a two-byte length encoding into items of size `N`.
+fn list_items<const N: usize>(list: &[u8]) -> Result<impl Iterator<Item = [u8; N]> + '_, Alert> {
+    const LEN_SIZE: usize = 2;
+    if list.len() < LEN_SIZE
+        || u16::from_be_bytes([list[0], list[1]]) as usize != list.len() - LEN_SIZE
+        || !(list.len() - LEN_SIZE).is_multiple_of(N)
+    {
+        return Err(Alert::DecodeError);
+    }
+    Ok(list[LEN_SIZE..]
+        .chunks_exact(N)
+        .map(|item| item.try_into().unwrap()))
+}
+
+/// The extensions sent by the server in EncryptedExtensions.
+pub(crate) struct EncExtRef<'a> {
+    /// Whether or not the server acknowledged the client's server name.
+    pub(crate) server_name: bool,
+    /// Whether or not the server accepted the client's early data.
+    pub(crate) early_data: bool,
+    /// The application protocol chosen by the server, if it chose one.
+    pub(crate) app_proto: Option<&'a [u8]>,
+    /// The server's record size limit, if it sent one.
+    pub(crate) record_size_limit: Option<u16>,
+    /// The maximum fragment length code echoed by the server, if it echoed one.
+    pub(crate) max_frag_len: Option<u8>,
+}
+
+impl<'a> EncExtRef<'a> {
+    /// Parse the EncryptedExtensions extensions.
+    pub(crate) fn parse(mut extensions: &'a [u8]) -> Result<Self, Alert> {
+        let mut server_name = false;
+        let mut early_data = false;
+        let mut app_proto = None;
+        let mut record_size_limit = None;
+        let mut max_frag_len = None;
+        while !extensions.is_empty() {
+            if extensions.len() < Extensions::HEADER_SIZE {
+                return Err(Alert::DecodeError);
+            }
+            let len = u16::from_be_bytes(
+                extensions[size_of::<ExtensionType>()..][..Extensions::EXTENSION_LEN_SIZE]
+                    .try_into()
+                    .unwrap(),
+            ) as usize;
+            if extensions.len() - Extensions::HEADER_SIZE < len {
+                return Err(Alert::DecodeError);
+            }
+
+            match &extensions[..size_of::<ExtensionType>()] {
+                // the server acknowledges our server name with an empty extension
+                x if x == ExtensionType::ServerName.to_be_bytes() => {
+                    if len != 0 {
+                        return Err(Alert::DecodeError);
+                    }
+                    server_name = true;
+                },
+                x if x == ExtensionType::EarlyData.to_be_bytes() => {
+                    if len != 0 {
+                        return Err(Alert::DecodeError);
+                    }
+                    early_data = true;
+                },
+                x if x == ExtensionType::AppLayerProtoNegotiation.to_be_bytes() => {
+                    app_proto = Some(AppProtos::parse_ser(
+                        &extensions[Extensions::HEADER_SIZE..][..len],
+                    )?);
+                },
+                x if x == ExtensionType::RecordSizeLimit.to_be_bytes() => {
+                    record_size_limit = Some(RecordSizeLimit::parse(
+                        &extensions[Extensions::HEADER_SIZE..][..len],
+                    )?);
+                },
+                x if x == ExtensionType::MaxFragmentLength.to_be_bytes() => {
+                    max_frag_len = Some(MaxFragLen::parse(
+                        &extensions[Extensions::HEADER_SIZE..][..len],
+                    )?);
+                },
+                // the server may tell us which groups it prefers, but we don't need to know
+                x if x == ExtensionType::SupportedGroups.to_be_bytes() => (),
+                _ => return Err(Alert::UnsupportedExtension),
+            }
+
+            extensions = &extensions[Extensions::HEADER_SIZE + len..];
+        }
+        Ok(Self {
+            server_name,
+            early_data,
+            app_proto,
+            record_size_limit,
+            max_frag_len,
         })
     }
+
+    /// Returns the EncryptedExtensions message, including its header.
+    pub(crate) fn to_vec(&self) -> Vec<u8> {
+        let mut extensions = Vec::new();
+        if self.server_name {
+            // the server acknowledges the client's server name with an empty extension
+            extensions.extend_from_slice(&ExtensionType::ServerName.to_be_bytes());
+            extensions.extend_from_slice(&[0; Extensions::EXTENSION_LEN_SIZE]);
+        }
+        if self.early_data {
+            extensions.extend_from_slice(&ExtensionType::EarlyData.to_be_bytes());
+            extensions.extend_from_slice(&[0; Extensions::EXTENSION_LEN_SIZE]);
+        }
+        if let Some(app_proto) = self.app_proto {
+            AppProtos::write_ser(&mut extensions, app_proto);
+        }
+        if let Some(limit) = self.record_size_limit {
+            RecordSizeLimit::write_ser(&mut extensions, limit);
+        }
+        if let Some(code) = self.max_frag_len {
+            MaxFragLen::write_ser(&mut extensions, code);
+        }
+
+        let len = Extensions::LEN_SIZE + extensions.len();
+        let mut msg = Vec::with_capacity(SHAKE_HEADER_SIZE + len);
+        msg.push(ShakeType::EncryptedExtensions.to_byte());
+        msg.extend_from_slice(&(len as u32).to_be_bytes()[1..]);
+        msg.extend_from_slice(&(extensions.len() as u16).to_be_bytes());
+        msg.extend_from_slice(&extensions);
+        msg
+    }
 }
 
 /// The server name to send to the server or expect from the client.
 ///
+/// Clients verify that the server's certificate was issued for this name. The name may also be
+/// an IP address, which is verified but not sent to the server. A client that has trust anchors
+/// must set a name, or the handshake fails with `ConfigError::MissingServerName`.
+///
 /// If no server name is to be sent or expected, set `name` to `NULL` and `len` to `0`.
 /// By default, no name will be sent or expected.
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
@@ -187,14 +563,14 @@ impl ServerName {
     const INNER_LEN_SIZE: usize = 2;
 
     pub(crate) fn len(&self) -> usize {
-        if self.name.is_null() {
+        if self.name.is_null() || self.len == 0 || self.is_ip_addr() {
             return 0;
         }
         Self::LEN_SIZE + size_of_val(&Self::NAME_TYPE) + Self::INNER_LEN_SIZE + self.len
     }
 
     pub(crate) fn write_client(&self, record_layer: &mut RecordLayer) {
-        if self.name.is_null() || self.len == 0 {
+        if self.len() == 0 {
             return;
         }
         record_layer.push_u16(Self::TAG.as_int());
@@ -210,9 +586,43 @@ impl ServerName {
         len -= Self::INNER_LEN_SIZE;
         record_layer.push_u16(len as u16);
 
+        record_layer.extend_from_slice(self.as_bytes());
+    }
+
+    /// Returns the server name, or an empty slice if no name is set.
+    pub(crate) fn as_bytes(&self) -> &[u8] {
+        if self.name.is_null() {
+            return &[];
+        }
         // SAFETY: the creator of `ServerName` guarantees the length and pointer are valid.
-        let server_name = unsafe { slice::from_raw_parts(self.name as *const u8, self.len) };
-        record_layer.extend_from_slice(server_name);
+        unsafe { slice::from_raw_parts(self.name as *const u8, self.len) }
+    }
+
+    /// Returns whether the name is an IP address.
+    ///
+    /// IP addresses can't be sent as a server name.
+    fn is_ip_addr(&self) -> bool {
+        str::from_utf8(self.as_bytes()).is_ok_and(|name| name.parse::<IpAddr>().is_ok())
+    }
+
+    /// Parse the server name extension sent by the client, returning the host name.
+    pub(crate) fn parse_cli(ext: &[u8]) -> Result<&[u8], Alert> {
+        if ext.len() < Self::LEN_SIZE + size_of_val(&Self::NAME_TYPE) + Self::INNER_LEN_SIZE
+            || u16::from_be_bytes([ext[0], ext[1]]) as usize != ext.len() - Self::LEN_SIZE
+        {
+            return Err(Alert::DecodeError);
+        }
+        let entry = &ext[Self::LEN_SIZE..];
+
+        // only one name of each type may be sent, and host names are the only type
+        if entry[0] != Self::NAME_TYPE {
+            return Err(Alert::IllegalParam);
+        }
+        let name = &entry[size_of_val(&Self::NAME_TYPE) + Self::INNER_LEN_SIZE..];
+        if u16::from_be_bytes([entry[1], entry[2]]) as usize != name.len() || name.is_empty() {
+            return Err(Alert::DecodeError);
+        }
+        Ok(name)
     }
 }
 
@@ -225,6 +635,180 @@ impl Default for ServerName {
     }
 }
 
+/// The application protocols to negotiate with the peer (ALPN), such as `h2` or `http/1.1`.
+///
+/// The protocols are listed in order of preference, and each one is prefixed with its length in
+/// a single byte. For example, `h2` and `http/1.1` are encoded as `"\x02h2\x08http/1.1"`.
+///
+/// Clients offer these protocols to the server. Servers choose the first of these protocols that
+/// the client also offers, or let `select` choose if it isn't `NULL`. If a server can't choose a
+/// protocol, the handshake fails with `NoAppProtocol`. If the client doesn't offer any protocols,
+/// no protocol is negotiated.
+///
+/// If no protocols are to be offered or accepted, set `protos` to `NULL` and `len` to `0`.
+/// By default, no protocols are offered or accepted.
+#[derive(Clone, Copy, Debug)]
+#[repr(C)]
+pub struct AppProtos {
+    /// The encoded list of protocols.
+    ///
+    /// Lifetime: this pointer must be valid for the duration of the handshake.
+    pub protos: *const u8,
+    /// The length of `protos` in bytes.
+    pub len: usize,
+    /// A function that chooses one of the protocols offered by the client (servers only).
+    ///
+    /// `protos`: the protocols offered by the client, encoded like `AppProtos.protos`.
+    /// `len`: the length of `protos` in bytes.
+    /// `ctx`: `select_ctx`.
+    ///
+    /// This function must return the index of the chosen protocol in the client's list, or a
+    /// negative number if none of them are acceptable.
+    pub select: Option<extern "C" fn(protos: *const u8, len: usize, ctx: *const c_void) -> isize>,
+    /// Contextual data for `select`.
+    ///
+    /// Lifetime: this pointer must be valid for the duration of the handshake.
+    pub select_ctx: *const c_void,
+}
+
+impl AppProtos {
+    const TAG: ExtensionType = ExtensionType::AppLayerProtoNegotiation;
+    const LEN_SIZE: usize = 2;
+    const NAME_LEN_SIZE: usize = 1;
+
+    /// Returns the encoded list of protocols, or an empty slice if there are none.
+    fn as_bytes(&self) -> &[u8] {
+        if self.protos.is_null() {
+            return &[];
+        }
+        // SAFETY: the creator of `AppProtos` guarantees the length and pointer are valid.
+        unsafe { slice::from_raw_parts(self.protos, self.len) }
+    }
+
+    /// Returns whether or not the list of protocols is correctly encoded.
+    pub(crate) fn is_valid(&self) -> bool {
+        let protos = self.as_bytes();
+        protos.len() <= u16::MAX as usize - Self::LEN_SIZE
+            && Self::names(protos).all(|name| name.is_ok())
+    }
+
+    /// Splits an encoded list of protocols into their names.
+    ///
+    /// Empty names and truncated lists are decoding errors.
+    fn names(mut protos: &[u8]) -> impl Iterator<Item = Result<&[u8], Alert>> {
+        iter::from_fn(move || {
+            let (&len, rest) = protos.split_first()?;
+            if len == 0 || rest.len() < len as usize {
+                protos = &[];
+                return Some(Err(Alert::DecodeError));
+            }
+            let (name, rest) = rest.split_at(len as usize);
+            protos = rest;
+            Some(Ok(name))
+        })
+    }
+
+    /// Returns whether or not `proto` is one of the protocols.
+    pub(crate) fn contains(&self, proto: &[u8]) -> bool {
+        Self::names(self.as_bytes()).any(|name| name == Ok(proto))
+    }
+
+    /// The length of the extension in ClientHello, excluding its header.
+    pub(crate) fn len(&self) -> usize {
+        let protos = self.as_bytes();
+        if protos.is_empty() {
+            return 0;
+        }
+        Self::LEN_SIZE + protos.len()
+    }
+
+    pub(crate) fn write_client(&self, record_layer: &mut RecordLayer) {
+        if self.len() == 0 {
+            return;
+        }
+        record_layer.push_u16(Self::TAG.as_int());
+        record_layer.push_u16(self.len() as u16);
+        record_layer.push_u16((self.len() - Self::LEN_SIZE) as u16);
+        record_layer.extend_from_slice(self.as_bytes());
+    }
+
+    /// Parse the extension sent by the client, returning the encoded list of protocols.
+    pub(crate) fn parse_cli(ext: &[u8]) -> Result<&[u8], Alert> {
+        if ext.len() <= Self::LEN_SIZE
+            || u16::from_be_bytes([ext[0], ext[1]]) as usize != ext.len() - Self::LEN_SIZE
+        {
+            return Err(Alert::DecodeError);
+        }
+        let protos = &ext[Self::LEN_SIZE..];
+        for name in Self::names(protos) {
+            name?;
+        }
+        Ok(protos)
+    }
+
+    /// Chooses one of the protocols offered by the client.
+    ///
+    /// If no protocols are configured, `None` is returned.
+    pub(crate) fn select<'a>(&self, offered: &'a [u8]) -> Result<Option<&'a [u8]>, Alert> {
+        if let Some(select) = self.select {
+            let index = select(offered.as_ptr(), offered.len(), self.select_ctx);
+            let Ok(index) = usize::try_from(index) else {
+                return Err(Alert::NoAppProtocol);
+            };
+            return match Self::names(offered).nth(index) {
+                Some(name) => name.map(Some),
+                None => Err(Alert::NoAppProtocol),
+            };
+        }
+
+        let protos = self.as_bytes();
+        if protos.is_empty() {
+            return Ok(None);
+        }
+        // respect our own preference
+        for proto in Self::names(protos) {
+            let proto = proto?;
+            if let Some(name) = Self::names(offered).find(|name| *name == Ok(proto)) {
+                return name.map(Some);
+            }
+        }
+        Err(Alert::NoAppProtocol)
+    }
+
+    /// Parse the extension sent by the server, returning the chosen protocol.
+    pub(crate) fn parse_ser(ext: &[u8]) -> Result<&[u8], Alert> {
+        // the server must choose exactly one protocol
+        if ext.len() <= Self::LEN_SIZE + Self::NAME_LEN_SIZE
+            || u16::from_be_bytes([ext[0], ext[1]]) as usize != ext.len() - Self::LEN_SIZE
+            || ext[Self::LEN_SIZE] as usize != ext.len() - Self::LEN_SIZE - Self::NAME_LEN_SIZE
+        {
+            return Err(Alert::DecodeError);
+        }
+        Ok(&ext[Self::LEN_SIZE + Self::NAME_LEN_SIZE..])
+    }
+
+    /// Writes the extension to EncryptedExtensions, choosing `proto`.
+    fn write_ser(extensions: &mut Vec<u8>, proto: &[u8]) {
+        let list_len = Self::NAME_LEN_SIZE + proto.len();
+        extensions.extend_from_slice(&Self::TAG.to_be_bytes());
+        extensions.extend_from_slice(&((Self::LEN_SIZE + list_len) as u16).to_be_bytes());
+        extensions.extend_from_slice(&(list_len as u16).to_be_bytes());
+        extensions.push(proto.len() as u8);
+        extensions.extend_from_slice(proto);
+    }
+}
+
+impl Default for AppProtos {
+    fn default() -> Self {
+        Self {
+            protos: null(),
+            len: 0,
+            select: None,
+            select_ctx: null(),
+        }
+    }
+}
+
 /// A list of curves to use for key exchange.
 ///
 /// Use bit-OR to turn an option on and bit-NAND to turn an option off.
@@ -242,6 +826,28 @@ impl SupGroups {
     ///
     /// This is a reasonable default curve to enable.
     pub const SECP256R1: u16 = 0b0000000000000001;
+    /// Curve25519 (X25519).
+    ///
+    /// This is the curve most peers prefer. You should probably leave it enabled.
+    pub const X25519: u16 = 0b0000000000000010;
+    /// NIST-P 384.
+    ///
+    /// Key exchange with this curve is much slower than with X25519 or NIST-P 256, so it isn't
+    /// enabled by default.
+    pub const SECP384R1: u16 = 0b0000000000000100;
+    /// NIST-P 521.
+    ///
+    /// Key exchange with this curve is much slower than with X25519 or NIST-P 256, so it isn't
+    /// enabled by default.
+    pub const SECP521R1: u16 = 0b0000000000001000;
+
+    /// Every supported group, in order of preference.
+    const PREFERENCE: [u16; 4] = [
+        Self::X25519,
+        Self::SECP256R1,
+        Self::SECP384R1,
+        Self::SECP521R1,
+    ];
 
     pub(crate) const fn len(&self) -> usize {
         self.groups.count_ones() as usize * size_of::<NamedGroup>() + Self::LEN_SIZE
@@ -258,8 +864,48 @@ impl SupGroups {
 
         record_layer.push_u16((len - Self::LEN_SIZE) as u16);
 
-        if self.groups & Self::SECP256R1 > 0 {
-            record_layer.push_u16(NamedGroup::Secp256r1.as_int());
+        for group in self.iter() {
+            record_layer.push_u16(group.named_group().as_int());
+        }
+    }
+
+    /// Iterates over each group in `self`, in order of preference.
+    pub(crate) fn iter(self) -> impl Iterator<Item = SupGroups> {
+        Self::PREFERENCE
+            .into_iter()
+            .filter(move |&group| self.groups & group > 0)
+            .map(|groups| Self { groups })
+    }
+
+    /// Returns the [`NamedGroup`] of `self`.
+    ///
+    /// `self` must be a single supported group.
+    const fn named_group(self) -> NamedGroup {
+        match self.groups {
+            Self::SECP256R1 => NamedGroup::Secp256r1,
+            Self::SECP384R1 => NamedGroup::Secp384r1,
+            Self::SECP521R1 => NamedGroup::Secp521r1,
+            Self::X25519 => NamedGroup::X25519,
+            _ => panic!("`self` must be a single supported group"),
+        }
+    }
+
+    pub(crate) fn parse_singular(group: [u8; size_of::<NamedGroup>()]) -> Self {
+        // fill in more values once more groups are supported
+        match group {
+            x if x == NamedGroup::Secp256r1.to_be_bytes() => Self {
+                groups: Self::SECP256R1,
+            },
+            x if x == NamedGroup::Secp384r1.to_be_bytes() => Self {
+                groups: Self::SECP384R1,
+            },
+            x if x == NamedGroup::Secp521r1.to_be_bytes() => Self {
+                groups: Self::SECP521R1,
+            },
+            x if x == NamedGroup::X25519.to_be_bytes() => Self {
+                groups: Self::X25519,
+            },
+            _ => Self { groups: 0 },
         }
     }
 }
@@ -267,7 +913,7 @@ impl SupGroups {
 impl Default for SupGroups {
     fn default() -> Self {
         Self {
-            groups: Self::SECP256R1,
+            groups: Self::X25519 | Self::SECP256R1,
         }
     }
 }
@@ -285,6 +931,30 @@ pub struct SigAlgs {
 impl SigAlgs {
     /// The Elliptic Curve Digital Signature Algorithm with curve Secp256r1 (NIST-P 256).
     pub const ECDSA_SECP256R1: u16 = 0b0000000000000001;
+    /// The Elliptic Curve Digital Signature Algorithm with curve Secp384r1 (NIST-P 384).
+    pub const ECDSA_SECP384R1: u16 = 0b0000000000000010;
+    /// The Elliptic Curve Digital Signature Algorithm with curve Secp521r1 (NIST-P 521).
+    pub const ECDSA_SECP521R1: u16 = 0b0000000000000100;
+    /// RSASSA-PSS with SHA-256 and an rsaEncryption key.
+    pub const RSA_PSS_RSAE_SHA256: u16 = 0b0000000000001000;
+    /// RSASSA-PSS with SHA-384 and an rsaEncryption key.
+    pub const RSA_PSS_RSAE_SHA384: u16 = 0b0000000000010000;
+    /// RSASSA-PSS with SHA-512 and an rsaEncryption key.
+    pub const RSA_PSS_RSAE_SHA512: u16 = 0b0000000000100000;
+    /// RSASSA-PKCS1-v1_5 with SHA-256.
+    ///
+    /// In TLS 1.3, this may only be used for signatures in certificates.
+    pub const RSA_PKCS1_SHA256: u16 = 0b0000000001000000;
+    /// RSASSA-PKCS1-v1_5 with SHA-384.
+    ///
+    /// In TLS 1.3, this may only be used for signatures in certificates.
+    pub const RSA_PKCS1_SHA384: u16 = 0b0000000010000000;
+    /// RSASSA-PKCS1-v1_5 with SHA-512.
+    ///
+    /// In TLS 1.3, this may only be used for signatures in certificates.
+    pub const RSA_PKCS1_SHA512: u16 = 0b0000000100000000;
+    /// EdDSA with Ed25519.
+    pub const ED25519: u16 = 0b0000001000000000;
     const TAG: ExtensionType = ExtensionType::SignatureAlgorithms;
     const LEN_SIZE: usize = 2;
 
@@ -301,8 +971,104 @@ impl SigAlgs {
         record_layer.push_u16(len);
 
         record_layer.push_u16(len - Self::LEN_SIZE as u16);
-        if self.algorithms & Self::ECDSA_SECP256R1 > 0 {
-            record_layer.push_u16(SignatureScheme::EcdsaSecp256r1Sha256.as_int());
+        for scheme in self.schemes() {
+            record_layer.push_u16(scheme.as_int());
+        }
+    }
+
+    /// Writes the extension to `extensions`, as sent in CertificateRequest.
+    pub(crate) fn write_cert_request(&self, extensions: &mut Vec<u8>) {
+        let len = self.len() as u16;
+        extensions.extend_from_slice(&Self::TAG.to_be_bytes());
+        extensions.extend_from_slice(&len.to_be_bytes());
+
+        extensions.extend_from_slice(&(len - Self::LEN_SIZE as u16).to_be_bytes());
+        for scheme in self.schemes() {
+            extensions.extend_from_slice(&scheme.to_be_bytes());
+        }
+    }
+
+    /// Returns the signature schemes in the order they are sent.
+    fn schemes(&self) -> impl Iterator<Item = SignatureScheme> + '_ {
+        const SCHEMES: [(u16, SignatureScheme); 10] = [
+            (
+                SigAlgs::ECDSA_SECP256R1,
+                SignatureScheme::EcdsaSecp256r1Sha256,
+            ),
+            (
+                SigAlgs::ECDSA_SECP384R1,
+                SignatureScheme::EcdsaSecp384r1Sha384,
+            ),
+            (
+                SigAlgs::ECDSA_SECP521R1,
+                SignatureScheme::EcdsaSecp521r1Sha512,
+            ),
+            (SigAlgs::ED25519, SignatureScheme::Ed25519),
+            (
+                SigAlgs::RSA_PSS_RSAE_SHA256,
+                SignatureScheme::RsaPssRsaeSha256,
+            ),
+            (
+                SigAlgs::RSA_PSS_RSAE_SHA384,
+                SignatureScheme::RsaPssRsaeSha384,
+            ),
+            (
+                SigAlgs::RSA_PSS_RSAE_SHA512,
+                SignatureScheme::RsaPssRsaeSha512,
+            ),
+            (SigAlgs::RSA_PKCS1_SHA256, SignatureScheme::RsaPkcs1Sha256),
+            (SigAlgs::RSA_PKCS1_SHA384, SignatureScheme::RsaPkcs1Sha384),
+            (SigAlgs::RSA_PKCS1_SHA512, SignatureScheme::RsaPkcs1Sha512),
+        ];
+        SCHEMES
+            .into_iter()
+            .filter(|&(flag, _)| self.algorithms & flag > 0)
+            .map(|(_, scheme)| scheme)
+    }
+
+    /// Parses the body of the extension, ignoring unsupported signature schemes.
+    pub(crate) fn parse_list(ext: &[u8]) -> Result<Self, Alert> {
+        let mut sig_algs = Self { algorithms: 0 };
+        for scheme in list_items::<{ size_of::<SignatureScheme>() }>(ext)? {
+            sig_algs.algorithms |= Self::parse_singular(scheme).algorithms;
+        }
+        Ok(sig_algs)
+    }
+
+    pub(crate) fn parse_singular(scheme: [u8; size_of::<SignatureScheme>()]) -> Self {
+        // fill in more values once more signature schemes are supported
+        match scheme {
+            x if x == SignatureScheme::EcdsaSecp256r1Sha256.to_be_bytes() => Self {
+                algorithms: Self::ECDSA_SECP256R1,
+            },
+            x if x == SignatureScheme::EcdsaSecp384r1Sha384.to_be_bytes() => Self {
+                algorithms: Self::ECDSA_SECP384R1,
+            },
+            x if x == SignatureScheme::EcdsaSecp521r1Sha512.to_be_bytes() => Self {
+                algorithms: Self::ECDSA_SECP521R1,
+            },
+            x if x == SignatureScheme::RsaPssRsaeSha256.to_be_bytes() => Self {
+                algorithms: Self::RSA_PSS_RSAE_SHA256,
+            },
+            x if x == SignatureScheme::RsaPssRsaeSha384.to_be_bytes() => Self {
+                algorithms: Self::RSA_PSS_RSAE_SHA384,
+            },
+            x if x == SignatureScheme::RsaPssRsaeSha512.to_be_bytes() => Self {
+                algorithms: Self::RSA_PSS_RSAE_SHA512,
+            },
+            x if x == SignatureScheme::RsaPkcs1Sha256.to_be_bytes() => Self {
+                algorithms: Self::RSA_PKCS1_SHA256,
+            },
+            x if x == SignatureScheme::RsaPkcs1Sha384.to_be_bytes() => Self {
+                algorithms: Self::RSA_PKCS1_SHA384,
+            },
+            x if x == SignatureScheme::RsaPkcs1Sha512.to_be_bytes() => Self {
+                algorithms: Self::RSA_PKCS1_SHA512,
+            },
+            x if x == SignatureScheme::Ed25519.to_be_bytes() => Self {
+                algorithms: Self::ED25519,
+            },
+            _ => Self { algorithms: 0 },
         }
     }
 }
@@ -310,7 +1076,16 @@ impl SigAlgs {
 impl Default for SigAlgs {
     fn default() -> Self {
         Self {
-            algorithms: Self::ECDSA_SECP256R1,
+            algorithms: Self::ECDSA_SECP256R1
+                | Self::ECDSA_SECP384R1
+                | Self::ECDSA_SECP521R1
+                | Self::RSA_PSS_RSAE_SHA256
+                | Self::RSA_PSS_RSAE_SHA384
+                | Self::RSA_PSS_RSAE_SHA512
+                | Self::RSA_PKCS1_SHA256
+                | Self::RSA_PKCS1_SHA384
+                | Self::RSA_PKCS1_SHA512
+                | Self::ED25519,
         }
     }
 }
@@ -327,9 +1102,493 @@ impl SupVersions {
         Self::VALUE.len()
     }
 
+    const SER_VALUE: [u8; 6] = [0, 43, 0, 2, 0x03, 0x04];
+    const LEN_SIZE: usize = 1;
+
     pub(crate) fn write_client(record_layer: &mut RecordLayer) {
         record_layer.extend_from_slice(&Self::VALUE);
     }
+
+    pub(crate) const fn len_ser() -> usize {
+        Self::SER_VALUE.len()
+    }
+
+    pub(crate) fn write_ser(record_layer: &mut RecordLayer) {
+        record_layer.extend_from_slice(&Self::SER_VALUE);
+    }
+
+    /// Parse the supported versions extension sent by the server.
+    ///
+    /// The server must have selected TLS 1.3.
+    pub(crate) fn parse_ser(ext: &[u8]) -> Result<(), Alert> {
+        if ext.len() != size_of::<ProtocolVersion>() {
+            return Err(Alert::DecodeError);
+        }
+        if ext != ProtocolVersion::TlsOneThree.to_be_bytes() {
+            return Err(Alert::ProtocolVersion);
+        }
+        Ok(())
+    }
+
+    /// Parse the supported versions extension sent by the client.
+    ///
+    /// Returns whether or not TLS 1.3 is supported by the client.
+    pub(crate) fn parse_cli(ext: &[u8]) -> Result<bool, Alert> {
+        if ext.len() < Self::LEN_SIZE
+            || ext[0] as usize != ext.len() - Self::LEN_SIZE
+            || !(ext[0] as usize).is_multiple_of(size_of::<ProtocolVersion>())
+        {
+            return Err(Alert::DecodeError);
+        }
+        Ok(ext[Self::LEN_SIZE..]
+            .chunks_exact(size_of::<ProtocolVersion>())
+            .any(|version| version == ProtocolVersion::TlsOneThree.to_be_bytes()))
+    }
+}
+
+/// A cookie sent by the server in HelloRetryRequest, which the client echoes.
+pub(crate) struct Cookie;
+
+impl Cookie {
+    const TAG: ExtensionType = ExtensionType::Cookie;
+    const LEN_SIZE: usize = 2;
+
+    /// The length of the extension, excluding its header.
+    ///
+    /// If `cookie` is empty, the extension isn't sent and `0` is returned.
+    pub(crate) const fn len(cookie: &[u8]) -> usize {
+        if cookie.is_empty() {
+            return 0;
+        }
+        Self::LEN_SIZE + cookie.len()
+    }
+
+    pub(crate) fn write_client(record_layer: &mut RecordLayer, cookie: &[u8]) {
+        if cookie.is_empty() {
+            return;
+        }
+        record_layer.push_u16(Self::TAG.as_int());
+        record_layer.push_u16(Self::len(cookie) as u16);
+        record_layer.push_u16(cookie.len() as u16);
+        record_layer.extend_from_slice(cookie);
+    }
+
+    /// Parse the cookie extension, returning the cookie.
+    pub(crate) fn parse(ext: &[u8]) -> Result<&[u8], Alert> {
+        if ext.len() <= Self::LEN_SIZE
+            || u16::from_be_bytes([ext[0], ext[1]]) as usize != ext.len() - Self::LEN_SIZE
+        {
+            return Err(Alert::DecodeError);
+        }
+        Ok(&ext[Self::LEN_SIZE..])
+    }
+}
+
+/// The PSK key exchange modes supported by the client.
+///
+/// Only PSK with (EC)DHE key exchange is supported. Sending this also tells the server that
+/// tickets can be used.
+pub(crate) struct PskModes;
+
+impl PskModes {
+    const TAG: ExtensionType = ExtensionType::PskExchangeModes;
+    const LEN_SIZE: usize = 1;
+    const PSK_DHE_KE: u8 = 1;
+
+    /// The length of the extension, including its header.
+    pub(crate) const fn len() -> usize {
+        Extensions::HEADER_SIZE + Self::LEN_SIZE + size_of_val(&Self::PSK_DHE_KE)
+    }
+
+    pub(crate) fn write_client(record_layer: &mut RecordLayer) {
+        record_layer.push_u16(Self::TAG.as_int());
+        record_layer.push_u16((Self::len() - Extensions::HEADER_SIZE) as u16);
+        record_layer.push(size_of_val(&Self::PSK_DHE_KE) as u8);
+        record_layer.push(Self::PSK_DHE_KE);
+    }
+
+    /// Parse the extension sent by the client.
+    ///
+    /// Returns whether or not the client supports psk_dhe_ke.
+    pub(crate) fn parse_cli(ext: &[u8]) -> Result<bool, Alert> {
+        if ext.len() <= Self::LEN_SIZE || ext[0] as usize != ext.len() - Self::LEN_SIZE {
+            return Err(Alert::DecodeError);
+        }
+        Ok(ext[Self::LEN_SIZE..].contains(&Self::PSK_DHE_KE))
+    }
+}
+
+/// Indicates that the client sent early data, or that the server accepted it.
+///
+/// The extension is empty in ClientHello and EncryptedExtensions.
+pub(crate) struct EarlyData;
+
+impl EarlyData {
+    const TAG: ExtensionType = ExtensionType::EarlyData;
+    const MAX_SIZE_LEN: usize = size_of::<u32>();
+
+    /// The length of the (empty) extension, including its header.
+    ///
+    /// If `early_data` is `false`, the extension isn't sent and `0` is returned.
+    pub(crate) const fn len(early_data: bool) -> usize {
+        early_data as usize * Extensions::HEADER_SIZE
+    }
+
+    /// Writes the empty extension if `early_data` is `true`.
+    pub(crate) fn write(record_layer: &mut RecordLayer, early_data: bool) {
+        if !early_data {
+            return;
+        }
+        record_layer.push_u16(Self::TAG.as_int());
+        record_layer.push_u16(0);
+    }
+
+    /// The length of the extension in NewSessionTicket, including its header.
+    pub(crate) const fn len_ticket() -> usize {
+        Extensions::HEADER_SIZE + Self::MAX_SIZE_LEN
+    }
+
+    /// Writes the extension to NewSessionTicket.
+    pub(crate) fn write_ticket(msg: &mut Vec<u8>, max_early_data: u32) {
+        msg.extend_from_slice(&Self::TAG.to_be_bytes());
+        msg.extend_from_slice(&(Self::MAX_SIZE_LEN as u16).to_be_bytes());
+        msg.extend_from_slice(&max_early_data.to_be_bytes());
+    }
+
+    /// Parse the extensions of NewSessionTicket, returning the maximum amount of early data the
+    /// server accepts.
+    ///
+    /// Unrecognized extensions are ignored.
+    pub(crate) fn parse_ticket(mut extensions: &[u8]) -> Result<u32, Alert> {
+        let mut max_early_data = 0;
+        while !extensions.is_empty() {
+            if extensions.len() < Extensions::HEADER_SIZE {
+                return Err(Alert::DecodeError);
+            }
+            let len = u16::from_be_bytes([extensions[2], extensions[3]]) as usize;
+            if extensions.len() - Extensions::HEADER_SIZE < len {
+                return Err(Alert::DecodeError);
+            }
+            let ext = &extensions[Extensions::HEADER_SIZE..][..len];
+            if extensions[..size_of::<ExtensionType>()] == Self::TAG.to_be_bytes() {
+                let Ok(max) = ext.try_into() else {
+                    return Err(Alert::DecodeError);
+                };
+                max_early_data = u32::from_be_bytes(max);
+            }
+            extensions = &extensions[Extensions::HEADER_SIZE + len..];
+        }
+        Ok(max_early_data)
+    }
+}
+
+/// The size of the largest protected record an endpoint accepts (RFC 8449).
+///
+/// The limit counts the plaintext of a record and its inner content type, but not its header or
+/// tag.
+pub(crate) struct RecordSizeLimit;
+
+impl RecordSizeLimit {
+    const TAG: ExtensionType = ExtensionType::RecordSizeLimit;
+    const SIZE: usize = size_of::<u16>();
+    /// The smallest limit that may be sent.
+    const MIN: u16 = 64;
+    /// The largest limit that may be sent in TLS 1.3: a full record and its content type.
+    const MAX: u16 = RecordLayer::MAX_LEN as u16 + 1;
+
+    /// Returns whether or not `limit` can be sent, or is `0` for no limit.
+    pub(crate) const fn is_valid(limit: u16) -> bool {
+        limit == 0 || limit >= Self::MIN
+    }
+
+    /// Returns the largest amount of data a record may contain under `limit`.
+    ///
+    /// If `limit` is `0`, the maximum record size is returned.
+    pub(crate) fn max_data_len(limit: u16) -> usize {
+        if limit == 0 {
+            return RecordLayer::MAX_LEN;
+        }
+        limit.min(Self::MAX) as usize - size_of::<ContentType>()
+    }
+
+    /// The length of the extension in ClientHello, including its header.
+    ///
+    /// If `limit` is `0`, the extension isn't sent and `0` is returned.
+    pub(crate) const fn len(limit: u16) -> usize {
+        (limit != 0) as usize * (Extensions::HEADER_SIZE + Self::SIZE)
+    }
+
+    /// Writes the extension to ClientHello if `limit` isn't `0`.
+    pub(crate) fn write_client(record_layer: &mut RecordLayer, limit: u16) {
+        if limit == 0 {
+            return;
+        }
+        record_layer.push_u16(Self::TAG.as_int());
+        record_layer.push_u16(Self::SIZE as u16);
+        record_layer.push_u16(limit.min(Self::MAX));
+    }
+
+    /// Writes the extension to EncryptedExtensions.
+    ///
+    /// If `limit` is `0`, the largest limit is sent.
+    fn write_ser(extensions: &mut Vec<u8>, limit: u16) {
+        let limit = if limit == 0 {
+            Self::MAX
+        } else {
+            limit.min(Self::MAX)
+        };
+        extensions.extend_from_slice(&Self::TAG.to_be_bytes());
+        extensions.extend_from_slice(&(Self::SIZE as u16).to_be_bytes());
+        extensions.extend_from_slice(&limit.to_be_bytes());
+    }
+
+    /// Parse the extension sent by the peer, returning its limit.
+    pub(crate) fn parse(ext: &[u8]) -> Result<u16, Alert> {
+        let Ok(limit) = ext.try_into() else {
+            return Err(Alert::DecodeError);
+        };
+        let limit = u16::from_be_bytes(limit);
+        if limit < Self::MIN {
+            return Err(Alert::IllegalParam);
+        }
+        Ok(limit)
+    }
+}
+
+/// The maximum length of the data in each record, in both directions (RFC 6066).
+///
+/// Lengths are encoded as codes from `1` to `4`, meaning 2^9 to 2^12 bytes. This is superseded by
+/// [`RecordSizeLimit`], so it is only negotiated with servers that don't support that.
+pub(crate) struct MaxFragLen;
+
+impl MaxFragLen {
+    const TAG: ExtensionType = ExtensionType::MaxFragmentLength;
+    const MIN_CODE: u8 = 1;
+    const MAX_CODE: u8 = 4;
+
+    /// Returns the largest amount of data a record may contain with `code`.
+    pub(crate) const fn max_data_len(code: u8) -> usize {
+        1 << (8 + code)
+    }
+
+    /// Returns the code of the largest length that fits within the record size limit `limit`.
+    ///
+    /// If `limit` is `0`, too small, or allows full records, no code is returned.
+    pub(crate) fn from_limit(limit: u16) -> Option<u8> {
+        if limit == 0 || RecordSizeLimit::max_data_len(limit) >= RecordLayer::MAX_LEN {
+            return None;
+        }
+        (Self::MIN_CODE..=Self::MAX_CODE)
+            .rev()
+            .find(|&code| Self::max_data_len(code) <= RecordSizeLimit::max_data_len(limit))
+    }
+
+    /// The length of the extension in ClientHello, including its header.
+    ///
+    /// If `code` is `None`, the extension isn't sent and `0` is returned.
+    pub(crate) const fn len(code: Option<u8>) -> usize {
+        code.is_some() as usize * (Extensions::HEADER_SIZE + size_of::<u8>())
+    }
+
+    /// Writes the extension to ClientHello if `code` isn't `None`.
+    pub(crate) fn write_client(record_layer: &mut RecordLayer, code: Option<u8>) {
+        let Some(code) = code else {
+            return;
+        };
+        record_layer.push_u16(Self::TAG.as_int());
+        record_layer.push_u16(size_of::<u8>() as u16);
+        record_layer.push(code);
+    }
+
+    /// Writes the extension to EncryptedExtensions, echoing the client's `code`.
+    fn write_ser(extensions: &mut Vec<u8>, code: u8) {
+        extensions.extend_from_slice(&Self::TAG.to_be_bytes());
+        extensions.extend_from_slice(&(size_of::<u8>() as u16).to_be_bytes());
+        extensions.push(code);
+    }
+
+    /// Parse the extension sent by the peer, returning its code.
+    pub(crate) fn parse(ext: &[u8]) -> Result<u8, Alert> {
+        let &[code] = ext else {
+            return Err(Alert::DecodeError);
+        };
+        if !(Self::MIN_CODE..=Self::MAX_CODE).contains(&code) {
+            return Err(Alert::IllegalParam);
+        }
+        Ok(code)
+    }
+}
+
+/// A pre-shared key from a previous connection, offered to resume its session.
+pub(crate) struct PreSharedKey;
+
+impl PreSharedKey {
+    const TAG: ExtensionType = ExtensionType::PreSharedKey;
+    const LIST_LEN_SIZE: usize = 2;
+    const IDENTITY_LEN_SIZE: usize = 2;
+    const BINDER_LEN_SIZE: usize = 1;
+
+    /// The length of the list of binders, including its length encoding.
+    fn binders_len(session: &Session) -> usize {
+        Self::LIST_LEN_SIZE + Self::BINDER_LEN_SIZE + session.hash_alg().size()
+    }
+
+    /// The length of the extension, excluding its header.
+    ///
+    /// If `session` is `None`, the extension isn't sent and `0` is returned.
+    pub(crate) fn len(session: Option<&Session>) -> usize {
+        session.map_or(0, |session| {
+            Self::LIST_LEN_SIZE
+                + Self::IDENTITY_LEN_SIZE
+                + session.ticket.len()
+                + size_of::<u32>()
+                + Self::binders_len(session)
+        })
+    }
+
+    /// Writes the extension to ClientHello, including the binder of the session.
+    ///
+    /// The binder covers everything written to ClientHello before it, so this must be the last
+    /// extension.
+    pub(crate) fn write_client(record_layer: &mut RecordLayer, session: Option<&Session>) {
+        let Some(session) = session else {
+            return;
+        };
+        record_layer.push_u16(Self::TAG.as_int());
+        record_layer.push_u16(Self::len(Some(session)) as u16);
+
+        record_layer
+            .push_u16((Self::IDENTITY_LEN_SIZE + session.ticket.len() + size_of::<u32>()) as u16);
+        record_layer.push_u16(session.ticket.len() as u16);
+        record_layer.extend_from_slice(&session.ticket);
+        record_layer.push_u32(session.obfuscated_age());
+
+        let transcript = record_layer.transcript_with(session.hash_alg(), record_layer.buf());
+        let binder =
+            key_schedule::verify_data(&key_schedule::binder_key(&session.psk), &transcript);
+        record_layer.push_u16((Self::binders_len(session) - Self::LIST_LEN_SIZE) as u16);
+        record_layer.push(binder.len() as u8);
+        record_layer.extend_from_slice(&binder);
+    }
+
+    /// Parse the extension sent by the server, returning the index of the selected PSK.
+    pub(crate) fn parse_ser(ext: &[u8]) -> Result<u16, Alert> {
+        let Ok(selected) = ext.try_into() else {
+            return Err(Alert::DecodeError);
+        };
+        Ok(u16::from_be_bytes(selected))
+    }
+
+    /// The length of the extension in ServerHello, including its header.
+    pub(crate) const fn len_ser() -> usize {
+        Extensions::HEADER_SIZE + size_of::<u16>()
+    }
+
+    /// Writes the extension to ServerHello, selecting the PSK at index `selected`.
+    pub(crate) fn write_ser(record_layer: &mut RecordLayer, selected: u16) {
+        record_layer.push_u16(Self::TAG.as_int());
+        record_layer.push_u16(size_of::<u16>() as u16);
+        record_layer.push_u16(selected);
+    }
+}
+
+/// The PSKs offered by the client in ClientHello.
+pub(crate) struct OfferedPsks<'a> {
+    /// The identities, without their length encoding.
+    identities: &'a [u8],
+    /// The binders, without their length encoding.
+    binders: &'a [u8],
+}
+
+impl<'a> OfferedPsks<'a> {
+    /// The minimum length of a binder.
+    const MIN_BINDER_LEN: usize = 32;
+
+    /// Parse the extension sent by the client.
+    ///
+    /// There must be a binder for each identity.
+    fn parse(ext: &'a [u8]) -> Result<Self, Alert> {
+        if ext.len() < PreSharedKey::LIST_LEN_SIZE {
+            return Err(Alert::DecodeError);
+        }
+        let identities_len = u16::from_be_bytes([ext[0], ext[1]]) as usize;
+        let ext = &ext[PreSharedKey::LIST_LEN_SIZE..];
+        if identities_len == 0 || ext.len() < identities_len + PreSharedKey::LIST_LEN_SIZE {
+            return Err(Alert::DecodeError);
+        }
+        let (identities, ext) = ext.split_at(identities_len);
+
+        let binders_len = u16::from_be_bytes([ext[0], ext[1]]) as usize;
+        let binders = &ext[PreSharedKey::LIST_LEN_SIZE..];
+        if binders_len != binders.len() {
+            return Err(Alert::DecodeError);
+        }
+
+        let psks = Self {
+            identities,
+            binders,
+        };
+        // iterating checks that each item is well-formed
+        let mut num_identities = 0;
+        for identity in psks.identities() {
+            identity?;
+            num_identities += 1;
+        }
+        let mut num_binders = 0;
+        for binder in psks.binders() {
+            binder?;
+            num_binders += 1;
+        }
+        if num_identities != num_binders {
+            return Err(Alert::IllegalParam);
+        }
+        Ok(psks)
+    }
+
+    /// The length of the binders, including their length encoding.
+    ///
+    /// The binders are at the end of ClientHello, so the hash of ClientHello up to them is found
+    /// by removing this many bytes.
+    pub(crate) fn binders_len(&self) -> usize {
+        PreSharedKey::LIST_LEN_SIZE + self.binders.len()
+    }
+
+    /// Returns the identities and their obfuscated ticket ages.
+    pub(crate) fn identities(&self) -> impl Iterator<Item = Result<(&'a [u8], u32), Alert>> {
+        let mut identities = self.identities;
+        std::iter::from_fn(move || {
+            if identities.is_empty() {
+                return None;
+            }
+            if identities.len() < PreSharedKey::IDENTITY_LEN_SIZE {
+                return Some(Err(Alert::DecodeError));
+            }
+            let len = u16::from_be_bytes([identities[0], identities[1]]) as usize;
+            let rest = &identities[PreSharedKey::IDENTITY_LEN_SIZE..];
+            if len == 0 || rest.len() < len + size_of::<u32>() {
+                return Some(Err(Alert::DecodeError));
+            }
+            let (identity, rest) = rest.split_at(len);
+            let (age, rest) = rest.split_at(size_of::<u32>());
+            identities = rest;
+            Some(Ok((identity, u32::from_be_bytes(age.try_into().unwrap()))))
+        })
+    }
+
+    /// Returns the binders, in the same order as the identities.
+    pub(crate) fn binders(&self) -> impl Iterator<Item = Result<&'a [u8], Alert>> {
+        let mut binders = self.binders;
+        std::iter::from_fn(move || {
+            let (&len, rest) = binders.split_first()?;
+            let len = len as usize;
+            if len < Self::MIN_BINDER_LEN || rest.len() < len {
+                return Some(Err(Alert::DecodeError));
+            }
+            let (binder, rest) = rest.split_at(len);
+            binders = rest;
+            Some(Ok(binder))
+        })
+    }
 }
 
 pub(crate) struct KeyShare;
@@ -341,16 +1600,36 @@ impl KeyShare {
     pub(crate) const TAG: ExtensionType = ExtensionType::KeyShare;
     /// The minimum length of this extension in ServerHello.
     pub(crate) const MIN_SER_LEN: usize = size_of::<NamedGroup>() + size_of::<u16>();
+    /// The length of this extension in HelloRetryRequest, including its header.
+    pub(crate) const RETRY_LEN: usize = Extensions::HEADER_SIZE + size_of::<NamedGroup>();
+    const SECP256R1_COORD_LEN: usize = 32;
+    const SECP384R1_COORD_LEN: usize = 48;
+    const SECP521R1_COORD_LEN: usize = 66;
 
-    pub(crate) const fn len(groups: &SupGroups) -> usize {
-        if groups.groups & SupGroups::SECP256R1 == 0 {
+    /// The length of an uncompressed point with `coord_len`-byte coordinates.
+    const fn point_len(coord_len: usize) -> usize {
+        size_of_val(&Self::LEGACY_FORM) + 2 * coord_len
+    }
+
+    /// The length of a single KeyShareEntry for `group`.
+    ///
+    /// `group` must be a single group.
+    const fn entry_len(group: SupGroups) -> usize {
+        let key_len = match group.groups {
+            SupGroups::SECP256R1 => Self::point_len(Self::SECP256R1_COORD_LEN),
+            SupGroups::SECP384R1 => Self::point_len(Self::SECP384R1_COORD_LEN),
+            SupGroups::SECP521R1 => Self::point_len(Self::SECP521R1_COORD_LEN),
+            SupGroups::X25519 => x25519::KEY_SIZE,
+            _ => panic!("`group` must be a single supported group"),
+        };
+        size_of::<NamedGroup>() + Self::INNER_LEN_SIZE + key_len
+    }
+
+    pub(crate) fn len(groups: &SupGroups) -> usize {
+        if groups.groups == 0 {
             return 0;
         }
-        // TODO: use size_of_val(&Self::LEGACY_FORM) once const-stabilized
-        1 + 2 * size_of::<FieldElement<4, <Secp256r1 as EllipticCurve>::Order>>()
-            + Self::INNER_LEN_SIZE
-            + size_of::<NamedGroup>()
-            + Self::LEN_SIZE
+        Self::LEN_SIZE + groups.iter().map(Self::entry_len).sum::<usize>()
     }
 
     pub(crate) fn write_client(
@@ -369,25 +1648,54 @@ impl KeyShare {
         len -= Self::LEN_SIZE as u16;
         record_layer.push_u16(len);
 
-        if groups.groups & SupGroups::SECP256R1 > 0 {
-            record_layer.extend_from_slice(&NamedGroup::Secp256r1.to_be_bytes());
+        for group in groups.iter() {
+            Self::write_entry(record_layer, group, keys);
+        }
+    }
 
-            len -= (size_of::<NamedGroup>() + Self::INNER_LEN_SIZE) as u16;
-            record_layer.push_u16(len);
+    /// The length of the KeyShare extension in ServerHello, including its header.
+    ///
+    /// `group` must be a single group.
+    pub(crate) const fn len_ser(group: SupGroups) -> usize {
+        Extensions::HEADER_SIZE + Self::entry_len(group)
+    }
 
-            record_layer.push(Self::LEGACY_FORM);
+    /// Writes the KeyShare extension to ServerHello.
+    ///
+    /// `group` must be a single group.
+    pub(crate) fn write_ser(record_layer: &mut RecordLayer, group: SupGroups, keys: &GroupKeys) {
+        record_layer.push_u16(Self::TAG.as_int());
+        record_layer.push_u16(Self::entry_len(group) as u16);
+        Self::write_entry(record_layer, group, keys);
+    }
 
-            let point = Secp256r1::BASE_POINT
-                .mul_scalar(&keys.secp256r1)
-                .as_affine()
-                .expect("private key isn't 0");
+    /// Writes the KeyShare extension to HelloRetryRequest, which only contains the selected group.
+    ///
+    /// `group` must be a single group.
+    pub(crate) fn write_retry(record_layer: &mut RecordLayer, group: SupGroups) {
+        record_layer.push_u16(Self::TAG.as_int());
+        record_layer.push_u16(size_of::<NamedGroup>() as u16);
+        record_layer.extend_from_slice(&group.named_group().to_be_bytes());
+    }
 
-            record_layer.extend_from_slice(&point.x().into_inner().to_be_bytes());
-            record_layer.extend_from_slice(&point.y().into_inner().to_be_bytes());
+    /// Writes a single KeyShareEntry for `group`.
+    ///
+    /// `group` must be a single supported group.
+    fn write_entry(record_layer: &mut RecordLayer, group: SupGroups, keys: &GroupKeys) {
+        record_layer.extend_from_slice(&group.named_group().to_be_bytes());
+        record_layer.push_u16((Self::entry_len(group) - Self::MIN_SER_LEN) as u16);
+        match group.groups {
+            SupGroups::SECP256R1 => Self::write_secp256r1(record_layer, keys),
+            SupGroups::SECP384R1 => Self::write_secp384r1(record_layer, keys),
+            SupGroups::SECP521R1 => Self::write_secp521r1(record_layer, keys),
+            SupGroups::X25519 => {
+                record_layer.extend_from_slice(&x25519::x25519(&keys.x25519, &x25519::BASE_POINT))
+            },
+            _ => panic!("`group` must be a single supported group"),
         }
     }
 
-    /// Parse the KeyShare extensions and calculate the shared secret.
+    /// Parse the KeyShare extension sent by the server and calculate the shared secret.
     pub(crate) fn parse_ser(
         key_share: &[u8],
         sup_groups: SupGroups,
@@ -396,36 +1704,159 @@ impl KeyShare {
         if key_share.len() < KeyShare::MIN_SER_LEN {
             return Err(Alert::DecodeError);
         }
-        if sup_groups.groups & SupGroups::SECP256R1 > 0
-            && key_share[0..2] == NamedGroup::Secp256r1.to_be_bytes()
-        {
-            let raw_x = UBigInt::<4>::from_be_bytes(key_share[5..][..32].try_into().unwrap());
-            let x: FieldElement<4, Secp256r1> = FieldElement::try_from(raw_x)
-                .map_err(|_| Alert::IllegalParam)
-                .expect("x is valid field element");
+        let group = Self::entry_group(key_share);
+        // the server must choose a group the client sent a key share for
+        if group.groups & sup_groups.groups == 0 {
+            return Err(Alert::IllegalParam);
+        }
+        Self::secret(key_share, group, group_keys)
+    }
 
-            let raw_y = UBigInt::<4>::from_be_bytes(key_share[37..][..32].try_into().unwrap());
-            let y: FieldElement<4, Secp256r1> = FieldElement::try_from(raw_y)
-                .map_err(|_| Alert::IllegalParam)
-                .expect("y is valid field element");
+    /// Find the client's first KeyShareEntry for one of `groups`.
+    ///
+    /// The client sends its key shares in order of preference. If the client didn't send a key
+    /// share for any of `groups`, `None` is returned.
+    pub(crate) fn find_cli(
+        mut key_shares: &[u8],
+        groups: SupGroups,
+    ) -> Result<Option<&[u8]>, Alert> {
+        while !key_shares.is_empty() {
+            if key_shares.len() < Self::MIN_SER_LEN {
+                return Err(Alert::DecodeError);
+            }
+            let len = u16::from_be_bytes([key_shares[2], key_shares[3]]) as usize;
+            if key_shares.len() - Self::MIN_SER_LEN < len {
+                return Err(Alert::DecodeError);
+            }
+            let (entry, rest) = key_shares.split_at(Self::MIN_SER_LEN + len);
+            if Self::entry_group(entry).groups & groups.groups != 0 {
+                return Ok(Some(entry));
+            }
+            key_shares = rest;
+        }
+        Ok(None)
+    }
 
-            let mut point = AffinePoint::new(x, y)
-                .ok_or(Alert::IllegalParam)
-                .expect("point is on curve")
-                .as_projective();
-            point.mul_scalar_assign(&group_keys.secp256r1);
-            let as_affine = point
-                .as_affine()
-                .expect("private key isn't zero and point is on curve");
+    /// The group of a KeyShareEntry.
+    pub(crate) fn entry_group(entry: &[u8]) -> SupGroups {
+        SupGroups::parse_singular([entry[0], entry[1]])
+    }
 
-            return Ok(Box::new(as_affine.x().to_be_bytes()));
+    /// Calculate the shared secret from a KeyShareEntry for `group`.
+    ///
+    /// `group` must be a single supported group.
+    pub(crate) fn secret(
+        entry: &[u8],
+        group: SupGroups,
+        group_keys: &GroupKeys,
+    ) -> Result<Box<[u8]>, Alert> {
+        match group.groups {
+            SupGroups::SECP256R1 => Self::secp256r1_secret(entry, group_keys),
+            SupGroups::SECP384R1 => Self::secp384r1_secret(entry, group_keys),
+            SupGroups::SECP521R1 => Self::secp521r1_secret(entry, group_keys),
+            SupGroups::X25519 => Self::x25519_secret(entry, group_keys),
+            _ => panic!("`group` must be a single supported group"),
         }
-        return Err(Alert::HandshakeFailure);
     }
-}
 
-impl Default for KeyShare {
-    fn default() -> Self {
-        Self {}
+    /// Calculate the shared secret from an X25519 KeyShareEntry.
+    fn x25519_secret(entry: &[u8], group_keys: &GroupKeys) -> Result<Box<[u8]>, Alert> {
+        let Ok(point) = <&[u8; x25519::KEY_SIZE]>::try_from(&entry[Self::MIN_SER_LEN..]) else {
+            return Err(Alert::DecodeError);
+        };
+        if u16::from_be_bytes([entry[2], entry[3]]) as usize != point.len() {
+            return Err(Alert::DecodeError);
+        }
+
+        let secret = x25519::x25519(&group_keys.x25519, point);
+        // a point of small order results in an all-zero secret
+        if secret == [0; x25519::KEY_SIZE] {
+            return Err(Alert::IllegalParam);
+        }
+        Ok(Box::new(secret))
     }
 }
+
+/// Implements the KeyShareEntry functions for a NIST curve.
+///
+/// `$n` is the number of limbs in the curve's field elements, and `$coord_len` is the number of
+/// bytes in each encoded coordinate.
+macro_rules! impl_nist_key_share {
+    ($n:literal, $curve:ident, $key:ident, $coord_len:ident, $write:ident, $secret:ident) => {
+        impl KeyShare {
+            #[doc = concat!("Writes the public key of a ", stringify!($key), " KeyShareEntry.")]
+            fn $write(record_layer: &mut RecordLayer, keys: &GroupKeys) {
+                record_layer.push(Self::LEGACY_FORM);
+
+                let point = $curve::BASE_POINT
+                    .mul_scalar(&keys.$key)
+                    .as_affine()
+                    .expect("private key isn't 0");
+
+                let skip = $n * size_of::<u64>() - Self::$coord_len;
+                record_layer.extend_from_slice(&point.x().into_inner().to_be_bytes()[skip..]);
+                record_layer.extend_from_slice(&point.y().into_inner().to_be_bytes()[skip..]);
+            }
+
+            #[doc = concat!("Calculate the shared secret from a ", stringify!($key), " KeyShareEntry.")]
+            fn $secret(entry: &[u8], group_keys: &GroupKeys) -> Result<Box<[u8]>, Alert> {
+                let point = &entry[Self::MIN_SER_LEN..];
+                if point.len() != Self::point_len(Self::$coord_len)
+                    || u16::from_be_bytes([entry[2], entry[3]]) as usize != point.len()
+                {
+                    return Err(Alert::DecodeError);
+                }
+                if point[0] != Self::LEGACY_FORM {
+                    return Err(Alert::IllegalParam);
+                }
+                let (raw_x, raw_y) = point[1..].split_at(Self::$coord_len);
+
+                let skip = $n * size_of::<u64>() - Self::$coord_len;
+                let mut buf = [0; $n * size_of::<u64>()];
+
+                buf[skip..].copy_from_slice(raw_x);
+                let x: FieldElement<$n, $curve> =
+                    FieldElement::try_from(UBigInt::<$n>::from_be_bytes(buf))
+                        .map_err(|_| Alert::IllegalParam)?;
+
+                buf[skip..].copy_from_slice(raw_y);
+                let y: FieldElement<$n, $curve> =
+                    FieldElement::try_from(UBigInt::<$n>::from_be_bytes(buf))
+                        .map_err(|_| Alert::IllegalParam)?;
+
+                let mut point = AffinePoint::<$n, _>::new(x, y)
+                    .ok_or(Alert::IllegalParam)?
+                    .as_projective();
+                point.mul_scalar_assign(&group_keys.$key);
+                let as_affine = point.as_affine().ok_or(Alert::IllegalParam)?;
+
+                Ok(Box::from(&as_affine.x().to_be_bytes()[skip..]))
+            }
+        }
+    };
+}
+
+impl_nist_key_share!(
+    4,
+    Secp256r1,
+    secp256r1,
+    SECP256R1_COORD_LEN,
+    write_secp256r1,
+    secp256r1_secret
+);
+impl_nist_key_share!(
+    6,
+    Secp384r1,
+    secp384r1,
+    SECP384R1_COORD_LEN,
+    write_secp384r1,
+    secp384r1_secret
+);
+impl_nist_key_share!(
+    9,
+    Secp521r1,
+    secp521r1,
+    SECP521R1_COORD_LEN,
+    write_secp521r1,
+    secp521r1_secret
+);
@@ -0,0 +1,51 @@
+//! The Finished handshake message.
+
+use crate::alert::Alert;
+use crate::handshake::{ShakeType, SHAKE_HEADER_SIZE};
+use crate::key_schedule::{self, Digest};
+
+pub(crate) struct Finished {
+    verify_data: Digest,
+}
+
+impl Finished {
+    /// Creates a Finished message.
+    ///
+    /// `base_key` is the sender's handshake traffic secret and `transcript` is the transcript
+    /// hash up to, but not including, this message.
+    pub(crate) fn new(base_key: &Digest, transcript: &Digest) -> Self {
+        Self {
+            verify_data: key_schedule::verify_data(base_key, transcript),
+        }
+    }
+
+    /// Returns the message, including its header.
+    pub(crate) fn to_vec(&self) -> Vec<u8> {
+        let mut msg = Vec::with_capacity(SHAKE_HEADER_SIZE + self.verify_data.len());
+        msg.push(ShakeType::Finished.to_byte());
+        msg.extend_from_slice(&(self.verify_data.len() as u32).to_be_bytes()[1..]);
+        msg.extend_from_slice(&self.verify_data);
+        msg
+    }
+
+    /// Verifies the body of a Finished message sent by the peer.
+    ///
+    /// `base_key` is the peer's handshake traffic secret and `transcript` is the transcript hash
+    /// up to, but not including, this message.
+    pub(crate) fn verify(msg: &[u8], base_key: &Digest, transcript: &Digest) -> Result<(), Alert> {
+        if msg.len() != base_key.len() {
+            return Err(Alert::DecodeError);
+        }
+        let expected = Self::new(base_key, transcript);
+
+        // compare in constant time
+        let diff = msg
+            .iter()
+            .zip(expected.verify_data.iter())
+            .fold(0, |diff, (x, y)| diff | (x ^ y));
+        if diff != 0 {
+            return Err(Alert::DecryptErorr);
+        }
+        Ok(())
+    }
+}
@@ -1,3 +1,10 @@
+use std::time::Duration;
+
+use crate::alert::Alert;
+use crate::error::TlsError;
+use crate::record::{ContentType, EncryptedRecLayer, ReadError, RecordLayer};
+
+#[derive(Clone, Copy)]
 #[repr(u8)]
 pub(crate) enum ShakeType {
     ClientHello = 1,
@@ -22,53 +29,192 @@ impl ShakeType {
 pub(crate) const SHAKE_LEN_SIZE: usize = 3;
 pub(crate) const SHAKE_HEADER_SIZE: usize = size_of::<ShakeType>() + SHAKE_LEN_SIZE;
 
-// use this for encrypted handshake messages
-//pub(crate) fn read_encry_handshake<'a>(
-//    buf: &'a mut [u8],
-//    expected_type: ShakeType,
-//    io: &Io,
-//    timeout: Duration,
-//) -> Result<&'a [u8], ShakeMsgParseError> {
-//    assert!(
-//        buf.len() >= RecordLayer::MAX_LEN + RecordLayer::SUFFIX_SIZE,
-//        "buf must be able to fit at least one record"
-//    );
-//    let record_size = RecordLayer::read_to(
-//        (&mut buf[..RecordLayer::MAX_LEN + RecordLayer::SUFFIX_SIZE])
-//            .try_into()
-//            .unwrap(),
-//        ContentType::Handshake,
-//        io,
-//        timeout,
-//    )?;
-//    if buf[0] != expected_type.to_byte() {
-//        io.alert(Alert::UnexpectedMessage);
-//        return Err(ShakeMsgParseError::Failed);
-//    }
-//
-//    let mut len = u32::from_be_bytes([0, buf[1], buf[2], buf[3]]) as usize;
-//    while len > record_size - SHAKE_HEADER_SIZE
-//        && buf.len() >= record_size + RecordLayer::MAX_LEN + RecordLayer::SUFFIX_SIZE
-//    {
-//        len += RecordLayer::read_to(
-//            (&mut buf[record_size..][..RecordLayer::MAX_LEN + RecordLayer::SUFFIX_SIZE])
-//                .try_into()
-//                .unwrap(),
-//            ContentType::Handshake,
-//            io,
-//            timeout,
-//        )?;
-//    }
-//    todo!()
-//}
-//
-//pub(crate) enum ShakeMsgParseError {
-//    RecordError(ReadError),
-//    Failed,
-//}
-//
-//impl From<ReadError> for ShakeMsgParseError {
-//    fn from(value: ReadError) -> Self {
-//        Self::RecordError(value)
-//    }
-//}
+/// Reassembles the handshake messages received from the peer.
+///
+/// A message may be split across multiple records, and a record may contain multiple messages.
+/// Messages are not added to the transcript.
+#[derive(Default)]
+pub(crate) struct ShakeBuf {
+    buf: Vec<u8>,
+}
+
+impl ShakeBuf {
+    /// The maximum length of a message body that will be buffered.
+    pub(crate) const MAX_LEN: usize = 0x20000;
+
+    /// Reads the next plaintext handshake message, including its header.
+    ///
+    /// Records are read from `rl` until a whole message has been received. Any ChangeCipherSpec
+    /// records that are received are ignored.
+    pub(crate) fn read_plain(
+        &mut self,
+        rl: &mut RecordLayer,
+        timeout: Duration,
+    ) -> Result<Vec<u8>, ReadError> {
+        loop {
+            if let Some(msg) = self.next_msg().map_err(TlsError::Sent)? {
+                return Ok(msg);
+            }
+            let msg_type = rl.read(timeout)?;
+            if msg_type == ContentType::ChangeCipherSpec.to_byte() {
+                continue;
+            }
+            if msg_type != ContentType::Handshake.to_byte() {
+                return Err(TlsError::Sent(Alert::UnexpectedMessage).into());
+            }
+            self.push(rl.buf()).map_err(TlsError::Sent)?;
+        }
+    }
+
+    /// Reads the next encrypted handshake message, including its header.
+    ///
+    /// Records are read from `rl` until a whole message has been received.
+    pub(crate) fn read(
+        &mut self,
+        rl: &mut EncryptedRecLayer,
+        timeout: Duration,
+    ) -> Result<Vec<u8>, ReadError> {
+        loop {
+            if let Some(msg) = self.next_msg().map_err(TlsError::Sent)? {
+                return Ok(msg);
+            }
+            if rl.read(timeout)? != ContentType::Handshake.to_byte() {
+                return Err(TlsError::Sent(Alert::UnexpectedMessage).into());
+            }
+            self.push(rl.buf()).map_err(TlsError::Sent)?;
+        }
+    }
+
+    /// Buffers the contents of a handshake record.
+    pub(crate) fn push(&mut self, record: &[u8]) -> Result<(), Alert> {
+        // handshake records must not be empty
+        if record.is_empty() {
+            return Err(Alert::UnexpectedMessage);
+        }
+        self.buf.extend_from_slice(record);
+        Ok(())
+    }
+
+    /// Removes the next message, including its header, from the buffer if it is complete.
+    pub(crate) fn next_msg(&mut self) -> Result<Option<Vec<u8>>, Alert> {
+        if self.buf.len() < SHAKE_HEADER_SIZE {
+            return Ok(None);
+        }
+        let len = u32::from_be_bytes([0, self.buf[1], self.buf[2], self.buf[3]]) as usize;
+        if len > Self::MAX_LEN {
+            return Err(Alert::IllegalParam);
+        }
+        if self.buf.len() - SHAKE_HEADER_SIZE < len {
+            return Ok(None);
+        }
+        Ok(Some(self.buf.drain(..SHAKE_HEADER_SIZE + len).collect()))
+    }
+
+    /// Returns `true` if no partial or unread messages are buffered.
+    ///
+    /// Messages that come right before a key change must end on a record boundary, so this must
+    /// be checked before changing keys.
+    pub(crate) fn is_empty(&self) -> bool {
+        self.buf.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ShakeBuf;
+    use crate::alert::Alert;
+
+    const FINISHED: [u8; 8] = [0x14, 0x00, 0x00, 0x04, 0xaa, 0xbb, 0xcc, 0xdd];
+    const KEY_UPDATE: [u8; 5] = [0x18, 0x00, 0x00, 0x01, 0x00];
+    const END_OF_EARLY_DATA: [u8; 4] = [0x05, 0x00, 0x00, 0x00];
+
+    #[test]
+    fn split_msg() {
+        let mut buf = ShakeBuf::default();
+        for record in [&FINISHED[..2], &FINISHED[2..5], &FINISHED[5..7]] {
+            buf.push(record).unwrap();
+            assert_eq!(buf.next_msg(), Ok(None));
+            assert!(!buf.is_empty());
+        }
+        buf.push(&FINISHED[7..]).unwrap();
+        assert_eq!(buf.next_msg(), Ok(Some(FINISHED.to_vec())));
+        assert!(buf.is_empty());
+        assert_eq!(buf.next_msg(), Ok(None));
+    }
+
+    #[test]
+    fn coalesced_msgs() {
+        let mut buf = ShakeBuf::default();
+        buf.push(&[FINISHED.as_slice(), &KEY_UPDATE].concat())
+            .unwrap();
+        assert_eq!(buf.next_msg(), Ok(Some(FINISHED.to_vec())));
+        assert!(!buf.is_empty());
+        assert_eq!(buf.next_msg(), Ok(Some(KEY_UPDATE.to_vec())));
+        assert!(buf.is_empty());
+    }
+
+    #[test]
+    fn split_header() {
+        let mut buf = ShakeBuf::default();
+        // the second message's header is split between records
+        buf.push(&[FINISHED.as_slice(), &KEY_UPDATE[..2]].concat())
+            .unwrap();
+        assert_eq!(buf.next_msg(), Ok(Some(FINISHED.to_vec())));
+        assert_eq!(buf.next_msg(), Ok(None));
+        buf.push(&KEY_UPDATE[2..3]).unwrap();
+        assert_eq!(buf.next_msg(), Ok(None));
+        buf.push(&KEY_UPDATE[3..]).unwrap();
+        assert_eq!(buf.next_msg(), Ok(Some(KEY_UPDATE.to_vec())));
+        assert!(buf.is_empty());
+    }
+
+    #[test]
+    fn empty_msg() {
+        let mut buf = ShakeBuf::default();
+        buf.push(&END_OF_EARLY_DATA).unwrap();
+        assert_eq!(buf.next_msg(), Ok(Some(END_OF_EARLY_DATA.to_vec())));
+        assert!(buf.is_empty());
+    }
+
+    #[test]
+    fn max_len() {
+        let [_, len @ ..] = (ShakeBuf::MAX_LEN as u32).to_be_bytes();
+        let mut buf = ShakeBuf::default();
+        buf.push(&[&[0x0b], len.as_slice()].concat()).unwrap();
+        assert_eq!(buf.next_msg(), Ok(None));
+
+        let [_, len @ ..] = (ShakeBuf::MAX_LEN as u32 + 1).to_be_bytes();
+        let mut buf = ShakeBuf::default();
+        // the length is rejected before the body is received
+        buf.push(&[&[0x0b], len.as_slice()].concat()).unwrap();
+        assert_eq!(buf.next_msg(), Err(Alert::IllegalParam));
+        let mut buf = ShakeBuf::default();
+        buf.push(&[0x0b, 0xff, 0xff, 0xff]).unwrap();
+        assert_eq!(buf.next_msg(), Err(Alert::IllegalParam));
+    }
+
+    #[test]
+    fn empty_record() {
+        let mut buf = ShakeBuf::default();
+        assert_eq!(buf.push(&[]), Err(Alert::UnexpectedMessage));
+        assert!(buf.is_empty());
+
+        buf.push(&FINISHED[..3]).unwrap();
+        assert_eq!(buf.push(&[]), Err(Alert::UnexpectedMessage));
+    }
+
+    #[test]
+    fn partial_msg() {
+        let mut buf = ShakeBuf::default();
+        assert!(buf.is_empty());
+        // a partial message must not be left over when keys change
+        buf.push(&[FINISHED.as_slice(), &KEY_UPDATE[..4]].concat())
+            .unwrap();
+        assert_eq!(buf.next_msg(), Ok(Some(FINISHED.to_vec())));
+        assert_eq!(buf.next_msg(), Ok(None));
+        assert!(!buf.is_empty());
+        buf.push(&KEY_UPDATE[4..]).unwrap();
+        assert_eq!(buf.next_msg(), Ok(Some(KEY_UPDATE.to_vec())));
+        assert!(buf.is_empty());
+    }
+}
@@ -1,23 +1,188 @@
-use crylib::hash::Sha256;
+use std::ops::Deref;
+
+use crylib::hash::{BufHasher, Hasher, Sha256, Sha384};
 use crylib::hkdf;
+use crylib::hmac::Hmac;
 
-const LABEL_PREFIX: &'static [u8] = b"tls13";
+const LABEL_PREFIX: &[u8] = b"tls13 ";
 const MAX_LABEL_LEN: usize = 12;
+const MAX_CONTEXT_LEN: usize = u8::MAX as usize;
+
+/// The hash function of a cipher suite.
+///
+/// This is used for the transcript hash and throughout the key schedule.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub(crate) enum HashAlg {
+    Sha256,
+    Sha384,
+}
+
+impl HashAlg {
+    /// The size of the largest supported hash.
+    pub(crate) const MAX_SIZE: usize = Sha384::HASH_SIZE;
+
+    /// The size of the hash in bytes.
+    pub(crate) const fn size(self) -> usize {
+        match self {
+            Self::Sha256 => Sha256::HASH_SIZE,
+            Self::Sha384 => Sha384::HASH_SIZE,
+        }
+    }
+
+    pub(crate) fn hash(self, msg: &[u8]) -> Digest {
+        match self {
+            Self::Sha256 => Digest::new(self, &Sha256::hash(msg)),
+            Self::Sha384 => Digest::new(self, &Sha384::hash(msg)),
+        }
+    }
+
+    pub(crate) fn hmac(self, key: &[u8], msg: &[u8]) -> Digest {
+        match self {
+            Self::Sha256 => Digest::new(
+                self,
+                &Hmac::<{ Sha256::HASH_SIZE }, { Sha256::BLOCK_SIZE }, Sha256>::auth(key, msg),
+            ),
+            Self::Sha384 => Digest::new(
+                self,
+                &Hmac::<{ Sha384::HASH_SIZE }, { Sha384::BLOCK_SIZE }, Sha384>::auth(key, msg),
+            ),
+        }
+    }
+
+    pub(crate) fn extract(self, salt: &[u8], ikm: &[u8]) -> Digest {
+        match self {
+            Self::Sha256 => Digest::new(
+                self,
+                &hkdf::extract::<{ Sha256::HASH_SIZE }, { Sha256::BLOCK_SIZE }, Sha256>(salt, ikm),
+            ),
+            Self::Sha384 => Digest::new(
+                self,
+                &hkdf::extract::<{ Sha384::HASH_SIZE }, { Sha384::BLOCK_SIZE }, Sha384>(salt, ikm),
+            ),
+        }
+    }
+
+    fn expand(self, key: &mut [u8], pr_key: &[u8], info: &[u8]) {
+        // we can safely unwrap because `Digest` always has the length of its hash function
+        match self {
+            Self::Sha256 => hkdf::expand::<{ Sha256::HASH_SIZE }, { Sha256::BLOCK_SIZE }, Sha256>(
+                key,
+                pr_key.try_into().unwrap(),
+                info,
+            ),
+            Self::Sha384 => hkdf::expand::<{ Sha384::HASH_SIZE }, { Sha384::BLOCK_SIZE }, Sha384>(
+                key,
+                pr_key.try_into().unwrap(),
+                info,
+            ),
+        }
+    }
+}
+
+/// A hash or secret whose length depends on the negotiated hash function.
+#[derive(Clone, Copy)]
+pub(crate) struct Digest {
+    buf: [u8; HashAlg::MAX_SIZE],
+    alg: HashAlg,
+}
+
+impl Digest {
+    /// Creates a [`Digest`] from `bytes`, which must be the size of `alg`.
+    pub(crate) fn new(alg: HashAlg, bytes: &[u8]) -> Self {
+        debug_assert_eq!(bytes.len(), alg.size());
+        let mut buf = [0; HashAlg::MAX_SIZE];
+        buf[..bytes.len()].copy_from_slice(bytes);
+        Self { buf, alg }
+    }
+
+    pub(crate) const fn alg(&self) -> HashAlg {
+        self.alg
+    }
+}
+
+impl Deref for Digest {
+    type Target = [u8];
+
+    fn deref(&self) -> &Self::Target {
+        &self.buf[..self.alg.size()]
+    }
+}
+
+/// The running hash of the handshake messages.
+///
+/// Until the cipher suite is negotiated, messages are hashed with every supported hash function.
+#[derive(Clone)]
+pub(crate) struct Transcript {
+    sha256: BufHasher<{ Sha256::HASH_SIZE }, { Sha256::BLOCK_SIZE }, Sha256>,
+    sha384: BufHasher<{ Sha384::HASH_SIZE }, { Sha384::BLOCK_SIZE }, Sha384>,
+    alg: Option<HashAlg>,
+}
+
+impl Transcript {
+    pub(crate) fn new() -> Self {
+        Self {
+            sha256: BufHasher::new(),
+            sha384: BufHasher::new(),
+            alg: None,
+        }
+    }
+
+    /// Chooses the hash function of the negotiated cipher suite.
+    pub(crate) fn set_alg(&mut self, alg: HashAlg) {
+        self.alg = Some(alg);
+    }
+
+    pub(crate) fn update(&mut self, msg: &[u8]) {
+        if self.alg != Some(HashAlg::Sha384) {
+            self.sha256.update_with(msg);
+        }
+        if self.alg != Some(HashAlg::Sha256) {
+            self.sha384.update_with(msg);
+        }
+    }
+
+    /// Returns the current transcript hash.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if the hash function has not been chosen.
+    pub(crate) fn current(&self) -> Digest {
+        match self.alg.expect("the hash function was already chosen") {
+            HashAlg::Sha256 => Digest::new(HashAlg::Sha256, &self.sha256.clone().finish()),
+            HashAlg::Sha384 => Digest::new(HashAlg::Sha384, &self.sha384.clone().finish()),
+        }
+    }
 
-pub(crate) fn hkdf_expand_label(
-    key: &mut [u8],
-    secret: &[u8; Sha256::HASH_SIZE],
-    label: &[u8],
-    context: &[u8],
-) {
+    /// Returns the transcript hash with `alg` as if `partial` had been added.
+    ///
+    /// This is used for PSK binders, which cover a partial ClientHello. `alg` must be the chosen
+    /// hash function, if one was chosen.
+    pub(crate) fn current_with(&self, alg: HashAlg, partial: &[u8]) -> Digest {
+        debug_assert!(self.alg.is_none_or(|chosen| chosen == alg));
+        match alg {
+            HashAlg::Sha256 => {
+                let mut hasher = self.sha256.clone();
+                hasher.update_with(partial);
+                Digest::new(alg, &hasher.finish())
+            },
+            HashAlg::Sha384 => {
+                let mut hasher = self.sha384.clone();
+                hasher.update_with(partial);
+                Digest::new(alg, &hasher.finish())
+            },
+        }
+    }
+}
+
+pub(crate) fn hkdf_expand_label(key: &mut [u8], secret: &Digest, label: &[u8], context: &[u8]) {
     assert!(label.len() <= MAX_LABEL_LEN);
-    assert!(context.len() <= Sha256::HASH_SIZE);
+    assert!(context.len() <= MAX_CONTEXT_LEN);
 
     let mut hkdf_label = [0; size_of::<u16>()
         + 2 * size_of::<u8>()
         + LABEL_PREFIX.len()
         + MAX_LABEL_LEN
-        + Sha256::HASH_SIZE];
+        + MAX_CONTEXT_LEN];
 
     let mut pos = 0;
     hkdf_label[pos..][..size_of::<u16>()].copy_from_slice(&(key.len() as u16).to_be_bytes());
@@ -32,20 +197,128 @@ pub(crate) fn hkdf_expand_label(
     hkdf_label[pos..][..label.len()].copy_from_slice(label);
     pos += label.len();
 
-    hkdf_label[pos] = Sha256::HASH_SIZE as u8;
+    hkdf_label[pos] = context.len() as u8;
     pos += 1;
 
     hkdf_label[pos..][..context.len()].copy_from_slice(context);
+    pos += context.len();
+
+    secret.alg.expand(key, secret, &hkdf_label[..pos]);
+}
+
+pub(crate) fn derive_secret(secret: &Digest, label: &[u8], transcript: &Digest) -> Digest {
+    let mut derived = [0; HashAlg::MAX_SIZE];
+    let derived = &mut derived[..secret.alg.size()];
+    hkdf_expand_label(derived, secret, label, transcript);
+    Digest::new(secret.alg, derived)
+}
+
+/// Derives the next secret in the key schedule from `secret` and `ikm`.
+///
+/// This is used to derive both the handshake secret (from the early secret) and the master
+/// secret (from the handshake secret).
+pub(crate) fn next_stage(secret: &Digest, ikm: &[u8]) -> Digest {
+    let salt = derive_secret(secret, b"derived", &secret.alg.hash(b""));
+    secret.alg.extract(&salt, ikm)
+}
+
+/// Calculates the `verify_data` of a Finished message.
+///
+/// `base_key` is the sender's handshake traffic secret and `transcript` is the transcript hash
+/// up to, but not including, the Finished message.
+pub(crate) fn verify_data(base_key: &Digest, transcript: &Digest) -> Digest {
+    let mut finished_key = [0; HashAlg::MAX_SIZE];
+    let finished_key = &mut finished_key[..base_key.alg.size()];
+    hkdf_expand_label(finished_key, base_key, b"finished", b"");
+    base_key.alg.hmac(finished_key, transcript)
+}
+
+/// Derives the early secret from a pre-shared key, or from zeros if there is none.
+pub(crate) fn early_secret(alg: HashAlg, psk: Option<&Digest>) -> Digest {
+    let zeros = [0; HashAlg::MAX_SIZE];
+    let ikm = psk.map_or(&zeros[..alg.size()], |psk| psk);
+    alg.extract(&zeros[..alg.size()], ikm)
+}
 
-    hkdf::expand::<{ Sha256::HASH_SIZE }, { Sha256::BLOCK_SIZE }, Sha256>(key, secret, &hkdf_label);
+/// Derives the key used to calculate the binder of a resumption PSK.
+///
+/// The binder is calculated like the `verify_data` of a Finished message, with this key as the
+/// base key and the transcript hash up to and including the ClientHello, truncated before the
+/// list of binders.
+pub(crate) fn binder_key(psk: &Digest) -> Digest {
+    let early_secret = early_secret(psk.alg, Some(psk));
+    derive_secret(&early_secret, b"res binder", &psk.alg.hash(b""))
 }
 
-pub(crate) fn derive_secret(
-    secret: &[u8; Sha256::HASH_SIZE],
-    label: &[u8],
-    transcript: &[u8; Sha256::HASH_SIZE],
-) -> [u8; Sha256::HASH_SIZE] {
-    let mut derived = [0; Sha256::HASH_SIZE];
-    hkdf_expand_label(&mut derived, secret, label, transcript);
-    derived
+/// Derives the client's early traffic secret, which protects 0-RTT data.
+///
+/// `transcript` is the transcript hash up to and including ClientHello.
+pub(crate) fn early_traffic_secret(psk: &Digest, transcript: &Digest) -> Digest {
+    let early_secret = early_secret(psk.alg, Some(psk));
+    derive_secret(&early_secret, b"c e traffic", transcript)
+}
+
+/// Derives the PSK of a ticket from the resumption master secret and the ticket's nonce.
+pub(crate) fn ticket_psk(res_master: &Digest, nonce: &[u8]) -> Digest {
+    let mut psk = [0; HashAlg::MAX_SIZE];
+    let psk = &mut psk[..res_master.alg.size()];
+    hkdf_expand_label(psk, res_master, b"resumption", nonce);
+    Digest::new(res_master.alg, psk)
+}
+
+/// Derives the next application traffic secret from the current one, as is done by KeyUpdate.
+pub(crate) fn next_traffic_secret(secret: &Digest) -> Digest {
+    let mut next = [0; HashAlg::MAX_SIZE];
+    let next = &mut next[..secret.alg.size()];
+    hkdf_expand_label(next, secret, b"traffic upd", b"");
+    Digest::new(secret.alg, next)
+}
+
+/// The secrets derived from the handshake secret.
+pub(crate) struct ShakeSecrets {
+    master_secret: Digest,
+    pub(crate) cli_shake_traf_secret: Digest,
+    pub(crate) ser_shake_traf_secret: Digest,
+}
+
+impl ShakeSecrets {
+    /// Derives the handshake secrets from the (EC)DHE shared secret and the pre-shared key, if
+    /// one was accepted.
+    ///
+    /// `transcript` is the transcript hash up to and including ServerHello. Its hash function is
+    /// used for every secret.
+    pub(crate) fn new(dh_shared_secret: &[u8], psk: Option<&Digest>, transcript: &Digest) -> Self {
+        let alg = transcript.alg;
+        let early_secret = early_secret(alg, psk);
+        let handshake_secret = next_stage(&early_secret, dh_shared_secret);
+        Self {
+            master_secret: next_stage(&handshake_secret, &[0; HashAlg::MAX_SIZE][..alg.size()]),
+            cli_shake_traf_secret: derive_secret(&handshake_secret, b"c hs traffic", transcript),
+            ser_shake_traf_secret: derive_secret(&handshake_secret, b"s hs traffic", transcript),
+        }
+    }
+
+    /// Derives the client and server application traffic secrets, in that order.
+    ///
+    /// `transcript` is the transcript hash up to and including the server's Finished message.
+    pub(crate) fn app_secrets(&self, transcript: &Digest) -> (Digest, Digest) {
+        (
+            derive_secret(&self.master_secret, b"c ap traffic", transcript),
+            derive_secret(&self.master_secret, b"s ap traffic", transcript),
+        )
+    }
+
+    /// Derives the exporter master secret, from which keying material is exported.
+    ///
+    /// `transcript` is the transcript hash up to and including the server's Finished message.
+    pub(crate) fn exp_master(&self, transcript: &Digest) -> Digest {
+        derive_secret(&self.master_secret, b"exp master", transcript)
+    }
+
+    /// Derives the resumption master secret, from which the PSKs of tickets are derived.
+    ///
+    /// `transcript` is the transcript hash up to and including the client's Finished message.
+    pub(crate) fn res_master(&self, transcript: &Digest) -> Digest {
+        derive_secret(&self.master_secret, b"res master", transcript)
+    }
 }
@@ -0,0 +1,122 @@
+//! Updating the application traffic keys after the handshake.
+
+use crate::aead::TlsAead;
+use crate::alert::Alert;
+use crate::cipher_suites::CipherList;
+use crate::error::{DataResult, TlsError};
+use crate::handshake::{ShakeType, SHAKE_HEADER_SIZE};
+use crate::key_schedule::{self, Digest};
+use crate::record::{ContentType, IoError};
+use crate::state::State;
+
+/// The current application traffic secrets, from which the next keys are derived.
+pub(crate) struct AppSecrets {
+    cipher_suite: CipherList,
+    write_secret: Digest,
+    read_secret: Digest,
+}
+
+impl AppSecrets {
+    pub(crate) fn new(write_secret: Digest, read_secret: Digest, cipher_suite: CipherList) -> Self {
+        Self {
+            cipher_suite,
+            write_secret,
+            read_secret,
+        }
+    }
+
+    /// Returns the AEAD for the current secrets.
+    pub(crate) fn aead(&self) -> TlsAead {
+        TlsAead::new(&self.write_secret, &self.read_secret, self.cipher_suite)
+            .expect("cipher suite was already negotiated")
+    }
+
+    /// Switches the write keys of `aead` to the next generation.
+    fn update_writer(&mut self, aead: &mut TlsAead) {
+        self.write_secret = key_schedule::next_traffic_secret(&self.write_secret);
+        let mut next = TlsAead::new(&self.write_secret, &self.write_secret, self.cipher_suite)
+            .expect("cipher suite was already negotiated");
+        aead.swap_writer(&mut next);
+    }
+
+    /// Switches the read keys of `aead` to the next generation.
+    fn update_reader(&mut self, aead: &mut TlsAead) {
+        self.read_secret = key_schedule::next_traffic_secret(&self.read_secret);
+        let mut next = TlsAead::new(&self.read_secret, &self.read_secret, self.cipher_suite)
+            .expect("cipher suite was already negotiated");
+        aead.swap_reader(&mut next);
+    }
+}
+
+/// A KeyUpdate message.
+pub(crate) struct KeyUpdate;
+
+impl KeyUpdate {
+    const UPDATE_NOT_REQUESTED: u8 = 0;
+    const UPDATE_REQUESTED: u8 = 1;
+    const LEN: usize = 1;
+
+    /// Returns the message, including its header.
+    ///
+    /// If `update_requested` is `true`, the peer must respond with its own KeyUpdate.
+    fn to_msg(update_requested: bool) -> [u8; SHAKE_HEADER_SIZE + Self::LEN] {
+        let request_update = if update_requested {
+            Self::UPDATE_REQUESTED
+        } else {
+            Self::UPDATE_NOT_REQUESTED
+        };
+        [
+            ShakeType::KeyUpdate.to_byte(),
+            0,
+            0,
+            Self::LEN as u8,
+            request_update,
+        ]
+    }
+
+    /// Parses the body of a KeyUpdate message, returning whether or not an update was requested.
+    fn parse(body: &[u8]) -> Result<bool, Alert> {
+        match *body {
+            [Self::UPDATE_NOT_REQUESTED] => Ok(false),
+            [Self::UPDATE_REQUESTED] => Ok(true),
+            [_] => Err(Alert::IllegalParam),
+            _ => Err(Alert::DecodeError),
+        }
+    }
+}
+
+/// Sends KeyUpdate and switches to the next write keys.
+///
+/// If `update_requested` is `true`, the peer is asked to update its keys too.
+pub(crate) fn send(state: &mut State, update_requested: bool) -> Result<(), IoError> {
+    let secrets = state
+        .app_secrets
+        .as_mut()
+        .expect("the handshake is complete");
+    state
+        .rl
+        .write(ContentType::Handshake, &KeyUpdate::to_msg(update_requested))?;
+    secrets.update_writer(&mut state.rl.aead);
+    Ok(())
+}
+
+/// Processes a KeyUpdate message received from the peer, including its header.
+///
+/// The read keys are updated, and if the peer requested it, the write keys are updated too.
+pub(crate) fn receive(state: &mut State, msg: &[u8]) -> Result<(), DataResult> {
+    let update_requested = KeyUpdate::parse(&msg[SHAKE_HEADER_SIZE..]).map_err(TlsError::Sent)?;
+    // KeyUpdate must end on a record boundary because the keys change after it
+    if !state.shake_buf.is_empty() {
+        return Err(DataResult::SentAlert(Alert::UnexpectedMessage));
+    }
+    let secrets = state
+        .app_secrets
+        .as_mut()
+        .expect("the handshake is complete");
+    secrets.update_reader(&mut state.rl.aead);
+
+    if update_requested {
+        send(state, false)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,62 @@
+//! Logging of traffic secrets in the NSS key log format, which tools such as Wireshark use to
+//! decrypt captured connections.
+
+use std::ffi::{c_char, c_void};
+use std::fmt::Write;
+
+use crate::client_hello::ClientHello;
+use crate::config::Config;
+
+/// The label of the client early traffic secret.
+pub(crate) const CLI_EARLY: &str = "CLIENT_EARLY_TRAFFIC_SECRET";
+/// The label of the client handshake traffic secret.
+pub(crate) const CLI_SHAKE: &str = "CLIENT_HANDSHAKE_TRAFFIC_SECRET";
+/// The label of the server handshake traffic secret.
+pub(crate) const SER_SHAKE: &str = "SERVER_HANDSHAKE_TRAFFIC_SECRET";
+/// The label of the first client application traffic secret.
+///
+/// The secrets after each KeyUpdate aren't logged, since they can be derived from this one.
+pub(crate) const CLI_APP: &str = "CLIENT_TRAFFIC_SECRET_0";
+/// The label of the first server application traffic secret.
+pub(crate) const SER_APP: &str = "SERVER_TRAFFIC_SECRET_0";
+/// The label of the exporter master secret.
+pub(crate) const EXPORTER: &str = "EXPORTER_SECRET";
+
+/// Logs the secrets of a single connection.
+#[derive(Clone, Copy)]
+pub(crate) struct KeyLog {
+    callback: Option<extern "C" fn(line: *const c_char, len: usize, ctx: *const c_void)>,
+    ctx: *const c_void,
+    /// The random bytes of ClientHello, which identify the connection.
+    client_random: [u8; ClientHello::RANDOM_BYTES_LEN],
+}
+
+impl KeyLog {
+    pub(crate) fn new(config: &Config, client_random: [u8; ClientHello::RANDOM_BYTES_LEN]) -> Self {
+        Self {
+            callback: config.keylog,
+            ctx: config.keylog_ctx,
+            client_random,
+        }
+    }
+
+    /// Logs `secret` with `label`.
+    pub(crate) fn log(&self, label: &str, secret: &[u8]) {
+        let Some(callback) = self.callback else {
+            return;
+        };
+        let mut line = String::with_capacity(
+            label.len() + 2 + 2 * (ClientHello::RANDOM_BYTES_LEN + secret.len()),
+        );
+        line.push_str(label);
+        line.push(' ');
+        for byte in self.client_random {
+            write!(line, "{byte:02x}").unwrap();
+        }
+        line.push(' ');
+        for byte in secret {
+            write!(line, "{byte:02x}").unwrap();
+        }
+        callback(line.as_ptr().cast(), line.len(), self.ctx);
+    }
+}
@@ -7,40 +7,43 @@
 
 mod aead;
 mod alert;
+mod app_data;
+mod certificate;
 mod cipher_suites;
+mod client;
 mod client_hello;
 mod config;
+mod der;
 mod dh;
+mod finished;
 mod handshake;
 mod key_schedule;
+mod key_update;
+mod keylog;
+mod pem;
 mod record;
+mod server;
 mod server_hello;
+mod session;
 mod state;
 mod versions;
+mod x509;
 
 pub mod error;
 pub mod extensions;
 
-use std::time::Duration;
+use std::ffi::c_void;
 
-use aead::TlsAead;
-use client_hello::ClientHello;
-use crylib::{
-    hash::{Hasher, Sha256},
-    hkdf,
-};
-use dh::GroupKeys;
-use error::TlsError;
-use extensions::KeyShare;
-use record::{ContentType, ReadError};
-use server_hello::RecvdSerHello;
-use state::{Connection, State};
+use state::{Connection, Shake, State};
 
 pub use alert::Alert;
 pub use cipher_suites::CipherList;
-pub use config::{Config, ConfigError};
-pub use error::ShakeResult;
+pub use config::{
+    Cert, ClientAuth, Config, ConfigError, Identity, IdentityStore, Tickets, TrustStore,
+};
+pub use error::{DataResult, ShakeResult};
 pub use record::Io;
+pub use session::EarlyDataStatus;
 
 /// Generates a default configuration struct.
 #[no_mangle]
@@ -48,19 +51,137 @@ pub extern "C" fn turtls_generate_config() -> Config {
     Config::default()
 }
 
+/// Adds a DER-encoded root certificate to the trust anchors of `config`.
+///
+/// The certificate is copied, so `der` only needs to be valid for the duration of this call.
+/// Returns `false` if the certificate couldn't be parsed.
+///
+/// The trust anchors must be freed by `turtls_free_config` to avoid memory leakage.
+///
+/// # Safety
+/// `config` must be valid.
+/// `der` must be valid for reads of `len` bytes.
+#[no_mangle]
+pub unsafe extern "C" fn turtls_add_trust_anchor(
+    config: *mut Config,
+    der: *const u8,
+    len: usize,
+) -> bool {
+    assert!(!config.is_null() && config.is_aligned());
+    assert!(!der.is_null());
+
+    // SAFETY: the caller guarantees that the pointer is valid.
+    let config = unsafe { &mut *config };
+
+    // SAFETY: the caller guarantees that the buffer is valid.
+    let der = unsafe { std::slice::from_raw_parts(der, len) };
+    if x509::Certificate::parse(der).is_err() {
+        return false;
+    }
+
+    if config.trust_store.is_null() {
+        config.trust_store = Box::leak(Box::new(TrustStore {
+            anchors: Vec::new(),
+        }));
+    }
+    // SAFETY: the trust store is either valid or was just allocated.
+    let trust_store = unsafe { &mut *config.trust_store };
+    trust_store.anchors.push(Box::from(der));
+    true
+}
+
+/// Loads the certificate chain and private key that `config` authenticates with.
+///
+/// `certs` is either PEM-encoded or a sequence of DER-encoded certificates, starting with the
+/// end-entity certificate. `priv_key` is a secp256r1 or Ed25519 key, encoded as PKCS #8 or
+/// SEC 1 (`EC PRIVATE KEY`) in DER or PEM. Both are copied, so they only need to be valid for the
+/// duration of this call. Returns `false` if they couldn't be parsed or the key doesn't match the
+/// end-entity certificate, in which case `config` is unchanged.
+///
+/// The certificates replace `config.identity` and must be freed by `turtls_free_config` to avoid
+/// memory leakage.
+///
+/// # Safety
+/// `config` must be valid.
+/// `certs` must be valid for reads of `certs_len` bytes.
+/// `priv_key` must be valid for reads of `priv_key_len` bytes.
+#[no_mangle]
+pub unsafe extern "C" fn turtls_load_identity(
+    config: *mut Config,
+    certs: *const u8,
+    certs_len: usize,
+    priv_key: *const u8,
+    priv_key_len: usize,
+) -> bool {
+    assert!(!config.is_null() && config.is_aligned());
+    assert!(!certs.is_null() && !priv_key.is_null());
+
+    // SAFETY: the caller guarantees that the pointer is valid.
+    let config = unsafe { &mut *config };
+
+    // SAFETY: the caller guarantees that the buffers are valid.
+    let (certs, priv_key) = unsafe {
+        (
+            std::slice::from_raw_parts(certs, certs_len),
+            std::slice::from_raw_parts(priv_key, priv_key_len),
+        )
+    };
+    let Some((store, priv_key)) = IdentityStore::load(certs, priv_key) else {
+        return false;
+    };
+
+    // SAFETY: the caller guarantees that `config` is valid.
+    unsafe { config.free_identity() };
+    let store = Box::leak(Box::new(store));
+    config.identity = Identity {
+        certs: store.cert_refs.as_ptr(),
+        num_certs: store.cert_refs.len(),
+        priv_key,
+    };
+    config.identity_store = store;
+    true
+}
+
+/// Frees the memory owned by `config`, such as its trust anchors and loaded identity.
+///
+/// `config` can still be used afterwards, but it won't have any trust anchors, and its identity is
+/// reset if it was loaded by `turtls_load_identity`.
+///
+/// # Safety
+/// `config` may be `NULL` but must be valid.
+#[no_mangle]
+pub unsafe extern "C" fn turtls_free_config(config: *mut Config) {
+    if config.is_null() || !config.is_aligned() {
+        return;
+    }
+    // SAFETY: the caller guarantees that the pointer is valid.
+    let config = unsafe { &mut *config };
+
+    if !config.trust_store.is_null() {
+        // SAFETY: the trust store was allocated by `turtls_add_trust_anchor`.
+        let _ = unsafe { Box::from_raw(config.trust_store) };
+        config.trust_store = std::ptr::null_mut();
+    }
+    // SAFETY: the caller guarantees that `config` is valid.
+    unsafe { config.free_identity() };
+}
+
 /// Allocates a connection buffer.
 ///
 /// This buffer must be freed by `turtls_free` to avoid memory leakage.
 #[no_mangle]
 pub extern "C" fn turtls_alloc() -> *mut Connection {
-    Box::leak(Box::new(Connection(None)))
+    Box::leak(Box::new(Connection {
+        state: None,
+        session: None,
+    }))
 }
 
 /// Frees a connection buffer.
 ///
 /// This buffer must have been allocated by `turtls_alloc`.
 ///
-/// # Safety:
+/// # Safety
 /// `connection` must be allocated by `turtls_alloc`.
 #[no_mangle]
 pub unsafe extern "C" fn turtls_free(connection: *mut Connection) {
@@ -73,9 +194,13 @@ pub unsafe extern "C" fn turtls_free(connection: *mut Connection) {
 
 /// Performs a TLS handshake as the client, returning the handshake status.
 ///
-/// If any error is returned, the connection is automatically closed.
+/// In non-blocking mode, `WantRead` or `WantWrite` is returned if the handshake can't continue
+/// until the IO is ready. The handshake is resumed by calling this function again with the same
+/// connection and config.
+///
+/// If any other error is returned, the connection is automatically closed.
 ///
-/// # Safety:
+/// # Safety
 /// `config` must be valid.
 /// `connection` must be valid.
 #[no_mangle]
@@ -93,112 +218,225 @@ pub unsafe extern "C" fn turtls_client_handshake(
 
     // SAFETY: the caller guarantees that the pointer is valid.
     let config = unsafe { &*config };
-    let record_timeout = Duration::from_millis(config.timeout_millis);
 
-    let keys = match GroupKeys::generate(config.extensions.sup_groups) {
-        Ok(keys) => keys,
-        Err(err) => return err.into(),
-    };
-    let client_hello = ClientHello {
-        cipher_suites: config.cipher_suites,
-        extensions: config.extensions,
+    let state = match connection.state {
+        // resume the handshake that would have blocked
+        Some(ref mut state) if matches!(state.shake, Some(Shake::Client(_))) => {
+            state.rl.unenc_rl.set_io(io);
+            state
+        },
+        _ => connection.start(State::new(io, config)),
     };
 
-    // TODO: is this precomputed at compile time?
-    let early_secret = hkdf::extract::<{ Sha256::HASH_SIZE }, { Sha256::BLOCK_SIZE }, Sha256>(
-        &[0; Sha256::HASH_SIZE],
-        &[0; Sha256::HASH_SIZE],
-    );
+    let result = client::handshake(state, config);
+    if !matches!(
+        result,
+        ShakeResult::Ok | ShakeResult::WantRead | ShakeResult::WantWrite
+    ) {
+        connection.end();
+    }
+    result
+}
 
-    *connection = Connection(Some(State::new(io)));
-    let state = connection.0.as_mut().expect("connection state exists");
+/// Performs a TLS handshake as the server, returning the handshake status.
+///
+/// `config` must contain an identity (a certificate chain and private key).
+///
+/// In non-blocking mode, `WantRead` or `WantWrite` is returned if the handshake can't continue
+/// until the IO is ready. The handshake is resumed by calling this function again with the same
+/// connection and config.
+///
+/// If any other error is returned, the connection is automatically closed.
+///
+/// # Safety
+/// `config` must be valid.
+/// `connection` must be valid.
+#[no_mangle]
+pub unsafe extern "C" fn turtls_server_handshake(
+    io: Io,
+    connection: *mut Connection,
+    config: *const Config,
+) -> ShakeResult {
+    assert!(!config.is_null() && config.is_aligned());
+    assert!(!connection.is_null() && connection.is_aligned());
 
-    if let Err(err) = client_hello.write_to(&mut state.rl.unenc_rl, &keys) {
-        // don't alert because we haven't even sent ClientHello
-        return err.into();
-    }
+    // SAFETY: the caller guarantees that the pointer is valid.
+    let connection = unsafe { &mut *connection };
 
-    let server_hello = match RecvdSerHello::read(&mut state.rl.unenc_rl, record_timeout) {
-        Ok(server_hello) => server_hello,
-        Err(err) => {
-            if let ReadError::Alert(TlsError::Sent(alert)) = err {
-                state.rl.unenc_rl.alert_and_close(alert);
-            }
-            *connection = Connection(None);
-            return err.into();
+    // SAFETY: the caller guarantees that the pointer is valid.
+    let config = unsafe { &*config };
+
+    let state = match connection.state {
+        // resume the handshake that would have blocked
+        Some(ref mut state) if matches!(state.shake, Some(Shake::Server(_))) => {
+            state.rl.unenc_rl.set_io(io);
+            state
         },
+        _ => connection.start(State::new(io, config)),
     };
 
-    let dh_shared_secret = match KeyShare::parse_ser(
-        server_hello.extensions.key_share,
-        config.extensions.sup_groups,
-        &keys,
+    let result = server::handshake(state, config);
+    if !matches!(
+        result,
+        ShakeResult::Ok | ShakeResult::WantRead | ShakeResult::WantWrite
     ) {
-        Ok(secret) => secret,
-        Err(err) => {
-            state.rl.unenc_rl.alert_and_close(err);
-            *connection = Connection(None);
-            return ShakeResult::SentAlert(err);
-        },
+        connection.end();
+    }
+    result
+}
+
+/// Encrypts and sends `len` bytes of `buf` to the peer.
+///
+/// The data is split into as many records as needed.
+///
+/// In non-blocking mode, `WantWrite` is returned if some of the records couldn't be written yet.
+/// The data is still accepted, and the remaining records are written by the next call to this
+/// function, which may send no data.
+///
+/// If any other error is returned, the connection is automatically closed.
+///
+/// # Safety
+/// `connection` must be valid.
+/// `buf` must be valid for reads of `len` bytes.
+#[no_mangle]
+pub unsafe extern "C" fn turtls_send(
+    connection: *mut Connection,
+    buf: *const c_void,
+    len: usize,
+) -> DataResult {
+    assert!(!connection.is_null() && connection.is_aligned());
+
+    // SAFETY: the caller guarantees that the pointer is valid.
+    let connection = unsafe { &mut *connection };
+
+    let Some(state) = connection
+        .state
+        .as_mut()
+        .filter(|state| state.shake.is_none())
+    else {
+        return DataResult::NotConnected;
     };
-    let cipher_suite = CipherList {
-        suites: server_hello.cipher_suite.suites & config.cipher_suites.suites,
+    let buf = if len == 0 {
+        &[]
+    } else {
+        assert!(!buf.is_null());
+        // SAFETY: the caller guarantees that the buffer is valid.
+        unsafe { std::slice::from_raw_parts(buf as *const u8, len) }
     };
 
-    let salt = key_schedule::derive_secret(&early_secret, b"derived", &Sha256::hash(b""));
-    let handshake_secret = hkdf::extract::<{ Sha256::HASH_SIZE }, { Sha256::BLOCK_SIZE }, Sha256>(
-        &salt,
-        &dh_shared_secret,
-    );
-    let transcript = state.rl.unenc_rl.transcript();
-    let cli_shake_traf_secret =
-        key_schedule::derive_secret(&handshake_secret, b"c hs traffic", &transcript);
-    let ser_shake_traf_secret =
-        key_schedule::derive_secret(&handshake_secret, b"s hs traffic", &transcript);
-
-    state.rl.aead = match TlsAead::new(&cli_shake_traf_secret, &ser_shake_traf_secret, cipher_suite)
-    {
-        Some(aead) => aead,
-        None => {
-            state.rl.unenc_rl.alert_and_close(Alert::HandshakeFailure);
-            *connection = Connection(None);
-            return ShakeResult::SentAlert(Alert::HandshakeFailure);
-        },
+    let result = app_data::send(state, buf);
+    if !matches!(
+        result,
+        DataResult::Ok | DataResult::WantRead | DataResult::WantWrite
+    ) {
+        connection.end();
+    }
+    result
+}
+
+/// Reads up to `len` bytes of application data from the peer into `buf`.
+///
+/// The number of bytes read is written to `amt_read`. Post-handshake messages from the peer are
+/// processed automatically.
+///
+/// In non-blocking mode, `WantRead` is returned if no application data has arrived yet, or
+/// `WantWrite` if records from an earlier call must be written first.
+///
+/// If any other error is returned, the connection is automatically closed. If the peer closed the
+/// connection, `ReceivedAlert` is returned with `CloseNotify`.
+///
+/// # Safety
+/// `connection` must be valid.
+/// `buf` must be valid for writes of `len` bytes.
+/// `amt_read` must be valid.
+#[no_mangle]
+pub unsafe extern "C" fn turtls_read(
+    connection: *mut Connection,
+    buf: *mut c_void,
+    len: usize,
+    amt_read: *mut usize,
+) -> DataResult {
+    assert!(!connection.is_null() && connection.is_aligned());
+    assert!(!amt_read.is_null() && amt_read.is_aligned());
+
+    // SAFETY: the caller guarantees that the pointer is valid.
+    let connection = unsafe { &mut *connection };
+
+    // SAFETY: the caller guarantees that the pointer is valid.
+    let amt_read = unsafe { &mut *amt_read };
+    *amt_read = 0;
+
+    let Some(state) = connection
+        .state
+        .as_mut()
+        .filter(|state| state.shake.is_none())
+    else {
+        return DataResult::NotConnected;
     };
-    let mut msg_type = match state.rl.unenc_rl.read(record_timeout) {
-        Ok(msg_type) => msg_type,
+    if len == 0 {
+        return DataResult::Ok;
+    }
+    assert!(!buf.is_null());
+
+    // SAFETY: the caller guarantees that the buffer is valid.
+    let buf = unsafe { std::slice::from_raw_parts_mut(buf as *mut u8, len) };
+
+    match app_data::read(state, buf) {
+        Ok(amt) => {
+            *amt_read = amt;
+            DataResult::Ok
+        },
+        Err(err @ (DataResult::WantRead | DataResult::WantWrite)) => err,
         Err(err) => {
-            if let ReadError::Alert(TlsError::Sent(alert)) = err {
-                state.rl.alert_and_close(alert);
-            }
-            return err.into();
+            connection.end();
+            err
         },
+    }
+}
+
+/// Updates the keys used to send data to the peer.
+///
+/// If `request_update` is `true`, the peer is asked to update the keys it sends data with too.
+/// Keys are also updated automatically before they encrypt too many records to be secure.
+///
+/// In non-blocking mode, `WantWrite` is returned if the KeyUpdate message couldn't be written yet.
+/// It is written by the next call to `turtls_send`.
+///
+/// If any other error is returned, the connection is automatically closed.
+///
+/// # Safety
+/// `connection` must be valid.
+#[no_mangle]
+pub unsafe extern "C" fn turtls_update_keys(
+    connection: *mut Connection,
+    request_update: bool,
+) -> DataResult {
+    assert!(!connection.is_null() && connection.is_aligned());
+
+    // SAFETY: the caller guarantees that the pointer is valid.
+    let connection = unsafe { &mut *connection };
+
+    let Some(state) = connection
+        .state
+        .as_mut()
+        .filter(|state| state.shake.is_none())
+    else {
+        return DataResult::NotConnected;
     };
-    if msg_type == ContentType::ChangeCipherSpec.to_byte() {
-        msg_type = match state.rl.unenc_rl.read(record_timeout) {
-            Ok(msg_type) => msg_type,
-            Err(err) => {
-                if let ReadError::Alert(TlsError::Sent(alert)) = err {
-                    state.rl.alert_and_close(alert);
-                }
-                *connection = Connection(None);
-                return err.into();
-            },
-        }
-    }
-    if msg_type != ContentType::ApplicationData.to_byte() {
-        println!("{msg_type}");
-        state.rl.alert_and_close(Alert::UnexpectedMessage);
-        *connection = Connection(None);
-        return ShakeResult::SentAlert(Alert::UnexpectedMessage);
-    }
-    state.rl.decrypt().expect("decryption went well");
-    todo!("finish handshake");
+
+    match key_update::send(state, request_update) {
+        Ok(()) if state.rl.unenc_rl.has_pending() => DataResult::WantWrite,
+        Ok(()) => DataResult::Ok,
+        Err(err) => {
+            connection.end();
+            err.into()
+        },
+    }
 }
 
 /// Alerts the peer and closes the connection.
 ///
-/// # Safety:
+/// # Safety
 /// `connection` may be `NULL` but must be valid.
 #[no_mangle]
 pub unsafe extern "C" fn turtls_close(connection: *mut Connection) {
@@ -208,8 +446,135 @@ pub unsafe extern "C" fn turtls_close(connection: *mut Connection) {
     // SAFETY: the caller guarantees that the pointer is valid.
     let connection = unsafe { &mut *connection };
 
-    if let Some(ref mut state) = connection.0 {
+    if let Some(ref mut state) = connection.state {
         state.rl.alert_and_close(Alert::CloseNotify);
-        *connection = Connection(None);
+        connection.end();
+    }
+}
+
+/// Copies the most recent session received from the server to `buf`, returning its length.
+///
+/// The session is an opaque byte string that can be stored and later set in `Config.session` to
+/// resume the connection. It contains secrets, so it must be stored securely. Sessions are
+/// received after the handshake, so at least one call to `turtls_read` is usually needed before
+/// one is available. The session of a closed connection remains available until the next
+/// handshake.
+///
+/// If no session is available, `0` is returned. If the session is longer than `len`, nothing is
+/// copied, so `buf` may be `NULL` to query the length.
+///
+/// # Safety
+/// `connection` must be valid.
+/// `buf` must be `NULL` or valid for writes of `len` bytes.
+#[no_mangle]
+pub unsafe extern "C" fn turtls_get_session(
+    connection: *const Connection,
+    buf: *mut u8,
+    len: usize,
+) -> usize {
+    assert!(!connection.is_null() && connection.is_aligned());
+
+    // SAFETY: the caller guarantees that the pointer is valid.
+    let connection = unsafe { &*connection };
+
+    let Some(session) = connection.session() else {
+        return 0;
+    };
+    let session = session.to_bytes();
+    if !buf.is_null() && session.len() <= len {
+        // SAFETY: the caller guarantees that the buffer is valid.
+        let buf = unsafe { std::slice::from_raw_parts_mut(buf, len) };
+        buf[..session.len()].copy_from_slice(&session);
+    }
+    session.len()
+}
+
+/// Copies the application protocol negotiated with the peer (ALPN) to `buf`, returning its
+/// length.
+///
+/// If no protocol was negotiated or the connection is closed, `0` is returned. If the protocol
+/// is longer than `len`, nothing is copied, so `buf` may be `NULL` to query the length. Protocols
+/// are never longer than 255 bytes.
+///
+/// # Safety
+/// `connection` must be valid.
+/// `buf` must be `NULL` or valid for writes of `len` bytes.
+#[no_mangle]
+pub unsafe extern "C" fn turtls_get_app_proto(
+    connection: *const Connection,
+    buf: *mut u8,
+    len: usize,
+) -> usize {
+    assert!(!connection.is_null() && connection.is_aligned());
+
+    // SAFETY: the caller guarantees that the pointer is valid.
+    let connection = unsafe { &*connection };
+
+    let Some(ref state) = connection.state else {
+        return 0;
+    };
+    let app_proto = &state.app_proto;
+    if !buf.is_null() && app_proto.len() <= len {
+        // SAFETY: the caller guarantees that the buffer is valid.
+        let buf = unsafe { std::slice::from_raw_parts_mut(buf, len) };
+        buf[..app_proto.len()].copy_from_slice(app_proto);
     }
+    app_proto.len()
+}
+
+/// Copies the DER-encoded end-entity certificate of the peer to `buf`, returning its length.
+///
+/// Servers can use this to identify clients that authenticated with a certificate. When a session
+/// is resumed, servers report the client's certificate from the original handshake, but clients
+/// report nothing. If the peer didn't send a certificate or the connection is closed, `0` is
+/// returned. If the certificate is longer than `len`, nothing is copied, so `buf` may be `NULL`
+/// to query the length.
+///
+/// # Safety
+/// `connection` must be valid.
+/// `buf` must be `NULL` or valid for writes of `len` bytes.
+#[no_mangle]
+pub unsafe extern "C" fn turtls_get_peer_cert(
+    connection: *const Connection,
+    buf: *mut u8,
+    len: usize,
+) -> usize {
+    assert!(!connection.is_null() && connection.is_aligned());
+
+    // SAFETY: the caller guarantees that the pointer is valid.
+    let connection = unsafe { &*connection };
+
+    let Some(ref state) = connection.state else {
+        return 0;
+    };
+    let peer_cert = &state.peer_cert;
+    if !buf.is_null() && peer_cert.len() <= len {
+        // SAFETY: the caller guarantees that the buffer is valid.
+        let buf = unsafe { std::slice::from_raw_parts_mut(buf, len) };
+        buf[..peer_cert.len()].copy_from_slice(peer_cert);
+    }
+    peer_cert.len()
+}
+
+/// Returns whether or not the early data of the connection was accepted.
+///
+/// For clients, this reports whether the server accepted `Config.early_data`. For servers, this
+/// reports whether early data from the client was accepted, in which case it can be read with
+/// `turtls_read`.
+///
+/// # Safety
+/// `connection` must be valid.
+#[no_mangle]
+pub unsafe extern "C" fn turtls_early_data_status(
+    connection: *const Connection,
+) -> EarlyDataStatus {
+    assert!(!connection.is_null() && connection.is_aligned());
+
+    // SAFETY: the caller guarantees that the pointer is valid.
+    let connection = unsafe { &*connection };
+
+    connection
+        .state
+        .as_ref()
+        .map_or(EarlyDataStatus::NotSent, |state| state.early_data)
 }
@@ -0,0 +1,88 @@
+//! A decoder for the PEM encoding of certificates and keys (RFC 7468).
+
+/// A decoded PEM block.
+pub(crate) struct Block<'a> {
+    /// The label of the block, such as `CERTIFICATE`.
+    pub(crate) label: &'a [u8],
+    /// The decoded contents of the block.
+    pub(crate) der: Vec<u8>,
+}
+
+const BEGIN: &[u8] = b"-----BEGIN ";
+const END: &[u8] = b"-----END ";
+const DASHES: &[u8] = b"-----";
+
+/// Returns whether or not `data` is PEM-encoded rather than DER-encoded.
+pub(crate) fn is_pem(data: &[u8]) -> bool {
+    data.windows(BEGIN.len()).any(|window| window == BEGIN)
+}
+
+/// Decodes every block in `text`, ignoring any text between blocks.
+///
+/// Returns `None` if a block is malformed, such as when it has headers.
+pub(crate) fn decode(text: &[u8]) -> Option<Vec<Block<'_>>> {
+    let mut lines = text.split(|&byte| byte == b'\n').map(<[u8]>::trim_ascii);
+    let mut blocks = Vec::new();
+    while let Some(line) = lines.next() {
+        let Some(label) = line
+            .strip_prefix(BEGIN)
+            .and_then(|line| line.strip_suffix(DASHES))
+        else {
+            continue;
+        };
+
+        let mut base64 = Vec::new();
+        loop {
+            let line = lines.next()?;
+            if let Some(end_label) = line
+                .strip_prefix(END)
+                .and_then(|line| line.strip_suffix(DASHES))
+            {
+                if end_label != label {
+                    return None;
+                }
+                break;
+            }
+            base64.extend_from_slice(line);
+        }
+        blocks.push(Block {
+            label,
+            der: decode_base64(&base64)?,
+        });
+    }
+    Some(blocks)
+}
+
+/// Decodes padded base64 without any whitespace.
+fn decode_base64(text: &[u8]) -> Option<Vec<u8>> {
+    if !text.len().is_multiple_of(4) {
+        return None;
+    }
+    let text = text
+        .strip_suffix(b"==")
+        .or_else(|| text.strip_suffix(b"="))
+        .unwrap_or(text);
+
+    let mut bytes = Vec::with_capacity(text.len() / 4 * 3 + 2);
+    let mut acc = 0_u32;
+    let mut bits = 0;
+    for &char in text {
+        let value = match char {
+            b'A'..=b'Z' => char - b'A',
+            b'a'..=b'z' => char - b'a' + 26,
+            b'0'..=b'9' => char - b'0' + 52,
+            b'+' => 62,
+            b'/' => 63,
+            _ => return None,
+        };
+        acc = (acc << 6) | u32::from(value);
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            bytes.push((acc >> bits) as u8);
+            acc &= (1 << bits) - 1;
+        }
+    }
+    // the bits left over from padding must be zero
+    (acc == 0).then_some(bytes)
+}
@@ -1,9 +1,8 @@
 mod enc_rl;
 mod unenc_rl;
 
-use crylib::hash::{BufHasher, Sha256};
-
 use crate::error::TlsError;
+use crate::key_schedule::Transcript;
 pub(crate) use enc_rl::EncryptedRecLayer;
 pub use unenc_rl::Io;
 
@@ -29,7 +28,13 @@ pub(crate) struct RecordLayer {
     len: usize,
     msg_type: ContentType,
     io: Io,
-    transcript: BufHasher<{ Sha256::HASH_SIZE }, { Sha256::BLOCK_SIZE }, Sha256>,
+    transcript: Transcript,
+    /// Whether or not reads and writes return instead of waiting for the peer.
+    non_blocking: bool,
+    /// The start of a record that was partly received when a read would have blocked.
+    partial: Vec<u8>,
+    /// Records that have been sent but not yet written because writing would have blocked.
+    pending: Vec<u8>,
 }
 
 #[derive(Debug)]
@@ -37,4 +42,16 @@ pub(crate) enum ReadError {
     IoError,
     Alert(TlsError),
     Timeout,
+    /// No more data can be read without blocking.
+    WouldBlock,
+}
+
+impl From<TlsError> for ReadError {
+    fn from(value: TlsError) -> Self {
+        Self::Alert(value)
+    }
 }
+
+/// The error that is returned when writing to the peer fails.
+#[derive(Debug)]
+pub(crate) struct IoError;
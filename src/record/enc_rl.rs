@@ -1,56 +1,118 @@
 use std::time::Duration;
 
-use crylib::aead::{BadData, TAG_SIZE};
+use crylib::aead::BadData;
 
-use super::{ContentType, Io, ReadError, RecordLayer};
+use super::{ContentType, Io, IoError, ReadError, RecordLayer};
 use crate::aead::TlsAead;
-use crate::alert::Alert;
+use crate::alert::{Alert, AlertLevel, AlertMsg};
 use crate::error::TlsError;
 
 pub(crate) struct EncryptedRecLayer {
     pub(crate) aead: TlsAead,
     pub(crate) unenc_rl: RecordLayer,
+    /// The largest amount of data to send in each record, as limited by the peer.
+    pub(crate) max_write_len: usize,
+    /// The largest amount of data the peer may send in each record.
+    pub(crate) max_read_len: usize,
 }
 
 impl EncryptedRecLayer {
-    pub const MIN_LEN: usize = TAG_SIZE + 1;
-    pub(crate) fn new(io: Io) -> Self {
+    pub(crate) fn new(io: Io, non_blocking: bool) -> Self {
         Self {
             aead: TlsAead::new_zeroed(),
-            unenc_rl: RecordLayer::new(io),
+            unenc_rl: RecordLayer::new(io, non_blocking),
+            max_write_len: RecordLayer::MAX_LEN,
+            max_read_len: RecordLayer::MAX_LEN,
         }
     }
 
+    /// Decrypts the record in the buffer, returning its inner content type.
+    ///
+    /// Padding and the inner content type are removed from the buffer.
     pub(crate) fn decrypt(&mut self) -> Result<u8, TlsError> {
-        if self.unenc_rl.len() < Self::MIN_LEN {
+        let tag_len = self.aead.tag_len();
+        // the record must have a tag and an inner content type
+        if self.unenc_rl.len() < tag_len + size_of::<ContentType>() {
             return Err(TlsError::Sent(Alert::DecodeError));
         }
         let (header, msg) = self.unenc_rl.buf.split_at_mut(RecordLayer::HEADER_SIZE);
-        let (msg, tag) =
-            msg.split_at_mut((self.unenc_rl.len - RecordLayer::HEADER_SIZE) - TAG_SIZE);
-        let tag: &[u8; TAG_SIZE] = tag[..TAG_SIZE].try_into().unwrap();
+        let (msg, tag) = msg.split_at_mut((self.unenc_rl.len - RecordLayer::HEADER_SIZE) - tag_len);
 
-        if let Err(BadData) = self.aead.decrypt_inline(msg, header, tag) {
+        if let Err(BadData) = self.aead.decrypt_inline(msg, header, &tag[..tag_len]) {
             return Err(TlsError::Sent(Alert::BadRecordMac));
         }
 
-        self.unenc_rl.len -= TAG_SIZE;
+        self.unenc_rl.len -= tag_len;
 
         let Some(padding) = self.unenc_rl.buf().iter().rev().position(|&x| x != 0) else {
             return Err(TlsError::Sent(Alert::UnexpectedMessage));
         };
 
-        self.unenc_rl.len -= padding;
-        self.unenc_rl.len -= 1;
-        let msg_type = self.unenc_rl.buf()[self.unenc_rl.len() - 1];
+        self.unenc_rl.len -= padding + size_of::<ContentType>();
+        if self.unenc_rl.len() > self.max_read_len {
+            return Err(TlsError::Sent(Alert::RecordOverflow));
+        }
+
+        let msg_type = self.unenc_rl.buf[self.unenc_rl.len];
+        if msg_type == ContentType::Alert.to_byte() {
+            if self.unenc_rl.len() != AlertMsg::SIZE {
+                return Err(TlsError::Sent(Alert::DecodeError));
+            }
+            return Err(TlsError::Received(Alert::from_byte(
+                self.unenc_rl.buf()[size_of::<AlertLevel>()],
+            )));
+        }
         Ok(msg_type)
     }
 
+    /// Reads and decrypts a single record, returning its inner content type.
+    ///
+    /// Any ChangeCipherSpec records that are received are ignored.
+    pub(crate) fn read(&mut self, timeout: Duration) -> Result<u8, ReadError> {
+        let mut msg_type = self.unenc_rl.read(timeout)?;
+        while msg_type == ContentType::ChangeCipherSpec.to_byte() {
+            msg_type = self.unenc_rl.read(timeout)?;
+        }
+        if msg_type != ContentType::ApplicationData.to_byte() {
+            return Err(ReadError::Alert(TlsError::Sent(Alert::UnexpectedMessage)));
+        }
+        Ok(self.decrypt()?)
+    }
+
+    /// Encrypts `msg` and sends it to the peer as one or more records of type `msg_type`.
+    pub(crate) fn write(&mut self, msg_type: ContentType, msg: &[u8]) -> Result<(), IoError> {
+        for chunk in msg.chunks(self.max_write_len) {
+            self.unenc_rl.start_as(ContentType::ApplicationData);
+
+            let inner_len = chunk.len() + size_of::<ContentType>();
+            let tag_len = self.aead.tag_len();
+            self.unenc_rl.set_len((inner_len + tag_len) as u16);
+
+            let (header, body) = self.unenc_rl.buf.split_at_mut(RecordLayer::HEADER_SIZE);
+            body[..chunk.len()].copy_from_slice(chunk);
+            body[chunk.len()] = msg_type.to_byte();
+
+            let (body, tag) = body.split_at_mut(inner_len);
+            self.aead.encrypt_inline(body, header, &mut tag[..tag_len]);
+
+            self.unenc_rl.len = RecordLayer::HEADER_SIZE + inner_len + tag_len;
+            self.unenc_rl.send()?;
+        }
+        Ok(())
+    }
+
     pub(crate) fn buf(&self) -> &[u8] {
         self.unenc_rl.buf()
     }
 
     pub(crate) fn alert_and_close(&mut self, alert: Alert) {
-        todo!()
+        // don't worry about errors because we're closing the connection anyways
+        let _ = self.write(ContentType::Alert, &AlertMsg::new(alert).to_be_bytes());
+        self.close();
+    }
+
+    /// Closes the connection without alerting the peer.
+    pub(crate) fn close(&self) {
+        self.unenc_rl.io.close();
     }
 }
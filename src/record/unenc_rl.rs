@@ -1,10 +1,10 @@
-use super::{ContentType, ReadError, RecordLayer};
+use super::{ContentType, IoError, ReadError, RecordLayer};
 use crate::alert::{Alert, AlertLevel, AlertMsg};
 use crate::error::TlsError;
+use crate::handshake::ShakeType;
+use crate::key_schedule::{Digest, HashAlg, Transcript};
 use crate::versions::LEGACY_PROTO_VERS;
 
-use crylib::hash::{BufHasher, Hasher, Sha256};
-
 use std::ffi::c_void;
 use std::time::{Duration, Instant};
 
@@ -18,6 +18,9 @@ pub struct Io {
     /// `buf`: the buffer to write.
     /// `amt`: the number of bytes to write.
     /// `ctx`: contextual data (e.g. a file descriptor).
+    ///
+    /// This function must return a negative value on error, and the number of bytes written
+    /// otherwise. If `Config::non_blocking` is set, it must return `0` instead of blocking.
     pub write_fn: extern "C" fn(buf: *const c_void, amt: usize, ctx: *const c_void) -> isize,
     /// A *non-blocking* read function.
     ///
@@ -50,6 +53,18 @@ impl Io {
         (self.write_fn)(buf as *const _ as *const c_void, buf.len(), self.ctx)
     }
 
+    /// Writes all of `buf`, retrying until every byte has been written.
+    pub(crate) fn write_all(&self, mut buf: &[u8]) -> Result<(), IoError> {
+        while !buf.is_empty() {
+            let written = self.write(buf);
+            if written < 0 {
+                return Err(IoError);
+            }
+            buf = &buf[written as usize..];
+        }
+        Ok(())
+    }
+
     /// Closes the connection
     pub(crate) fn close(&self) {
         (self.close_fn)(self.ctx);
@@ -67,13 +82,16 @@ impl RecordLayer {
         self.io = io;
     }
 
-    pub(crate) fn new(io: Io) -> Self {
+    pub(crate) fn new(io: Io, non_blocking: bool) -> Self {
         Self {
             buf: [0; Self::BUF_SIZE],
             len: 0,
             msg_type: ContentType::Invalid,
             io,
-            transcript: BufHasher::new(),
+            transcript: Transcript::new(),
+            non_blocking,
+            partial: Vec::new(),
+            pending: Vec::new(),
         }
     }
 
@@ -88,20 +106,55 @@ impl RecordLayer {
         self.len = Self::HEADER_SIZE;
     }
 
-    fn set_len(&mut self, len: u16) {
+    pub(super) fn set_len(&mut self, len: u16) {
         self.buf[Self::HEADER_SIZE - Self::LEN_SIZE..Self::HEADER_SIZE]
             .copy_from_slice(&len.to_be_bytes());
     }
 
     pub(crate) fn finish(&mut self) {
         self.transcript
-            .update_with(&self.buf[Self::HEADER_SIZE..self.len]);
+            .update(&self.buf[Self::HEADER_SIZE..self.len]);
         self.set_len(self.len() as u16);
     }
 
-    pub(crate) fn finish_and_send(&mut self) {
+    pub(crate) fn finish_and_send(&mut self) -> Result<(), IoError> {
         self.finish();
-        self.io.write(&self.buf);
+        self.send()
+    }
+
+    /// Sends the record currently in the buffer, including its header.
+    ///
+    /// If the IO is non-blocking, the record is queued and as much of the queue as possible is
+    /// written.
+    pub(super) fn send(&mut self) -> Result<(), IoError> {
+        if !self.non_blocking {
+            return self.io.write_all(&self.buf[..self.len]);
+        }
+        self.pending.extend_from_slice(&self.buf[..self.len]);
+        self.flush().map(|_| ())
+    }
+
+    /// Writes as much of the queued records as possible without blocking, returning whether or
+    /// not all of them were written.
+    pub(crate) fn flush(&mut self) -> Result<bool, IoError> {
+        let mut written = 0;
+        while written < self.pending.len() {
+            let amt = self.io.write(&self.pending[written..]);
+            if amt < 0 {
+                return Err(IoError);
+            }
+            if amt == 0 {
+                break;
+            }
+            written += amt as usize;
+        }
+        self.pending.drain(..written);
+        Ok(self.pending.is_empty())
+    }
+
+    /// Returns `true` if records are queued because writing them would have blocked.
+    pub(crate) fn has_pending(&self) -> bool {
+        !self.pending.is_empty()
     }
 
     /// The length of the data in the buffer.
@@ -117,7 +170,8 @@ impl RecordLayer {
 
     pub(crate) fn push(&mut self, value: u8) {
         if self.len() == Self::MAX_LEN {
-            self.finish_and_send();
+            // if the connection is broken, the final call to `finish_and_send` will fail too
+            let _ = self.finish_and_send();
             self.start();
         }
         self.buf[self.len] = value;
@@ -144,6 +198,7 @@ impl RecordLayer {
         self.push(value as u8);
     }
 
+    #[expect(unused, reason = "not yet needed by the handshake")]
     pub(crate) fn push_u64(&mut self, value: u64) {
         self.push((value >> 56) as u8);
         self.push((value >> 48) as u8);
@@ -165,17 +220,19 @@ impl RecordLayer {
             return;
         }
 
-        self.buf[self.len..].copy_from_slice(&slice[..diff]);
+        self.buf[self.len..][..diff].copy_from_slice(&slice[..diff]);
         self.len = Self::MAX_LEN + Self::HEADER_SIZE;
 
         for chunk in slice[diff..].chunks(Self::MAX_LEN) {
-            self.finish_and_send();
+            // if the connection is broken, the final call to `finish_and_send` will fail too
+            let _ = self.finish_and_send();
             self.start();
             self.buf[Self::HEADER_SIZE..][..chunk.len()].copy_from_slice(chunk);
             self.len = Self::HEADER_SIZE + chunk.len();
         }
     }
 
+    #[expect(unused, reason = "not yet needed by the handshake")]
     pub(crate) fn extend(&mut self, amt: usize) {
         self.extend_with(0, amt);
     }
@@ -188,10 +245,27 @@ impl RecordLayer {
     }
 
     /// Reads a single record into [`RecordLayer`]'s internal buffer.
+    ///
+    /// If the IO is non-blocking and the whole record hasn't arrived, `ReadError::WouldBlock` is
+    /// returned and the part that did arrive is kept for the next read.
     pub(crate) fn read(&mut self, timeout: Duration) -> Result<u8, ReadError> {
+        // continue the record that was being received when the last read would have blocked
+        let mut received = self.partial.len();
+        self.buf[..received].copy_from_slice(&self.partial);
+        self.partial.clear();
+
+        let result = self.read_record(&mut received, timeout);
+        if let Err(ReadError::WouldBlock) = result {
+            self.partial.extend_from_slice(&self.buf[..received]);
+        }
+        result
+    }
+
+    /// Reads the rest of a record, of which `received` bytes are already in the buffer.
+    fn read_record(&mut self, received: &mut usize, timeout: Duration) -> Result<u8, ReadError> {
         let start_time = Instant::now();
 
-        self.fill_buf(0, Self::HEADER_SIZE, timeout, start_time)?;
+        self.fill_buf(received, Self::HEADER_SIZE, timeout, start_time)?;
 
         let len = u16::from_be_bytes(
             self.buf[Self::HEADER_SIZE - Self::LEN_SIZE..Self::HEADER_SIZE]
@@ -205,36 +279,38 @@ impl RecordLayer {
 
         let msg_type = self.buf[0];
         if msg_type == ContentType::Alert.to_byte() {
-            // don't worry about errors because we're already handling an error
-            let _ = self.fill_buf(Self::HEADER_SIZE, AlertMsg::SIZE, timeout, start_time);
+            // don't worry about other errors because we're already handling an error
+            let size = Self::HEADER_SIZE + AlertMsg::SIZE;
+            if let Err(ReadError::WouldBlock) = self.fill_buf(received, size, timeout, start_time) {
+                return Err(ReadError::WouldBlock);
+            }
 
             return Err(ReadError::Alert(TlsError::Received(Alert::from_byte(
                 self.buf[Self::HEADER_SIZE + size_of::<AlertLevel>()],
             ))));
         }
 
-        self.fill_buf(Self::HEADER_SIZE, len, timeout, start_time)?;
+        self.fill_buf(received, Self::HEADER_SIZE + len, timeout, start_time)?;
         self.len = len + Self::HEADER_SIZE;
-        if self.buf[0] == ContentType::Handshake.to_byte() {
-            self.transcript
-                .update_with(&self.buf[Self::HEADER_SIZE..self.len]);
-        }
         Ok(msg_type)
     }
 
+    /// Reads into the buffer until it holds `size` bytes, of which `received` are already there.
     fn fill_buf(
         &mut self,
-        start_index: usize,
+        received: &mut usize,
         size: usize,
         timeout: Duration,
         start_time: Instant,
     ) -> Result<(), ReadError> {
-        assert!(start_index + size < Self::BUF_SIZE);
-        let buf = &mut self.buf[start_index..][..size];
-        let mut bytes_read = 0;
+        assert!(size <= Self::BUF_SIZE);
+
+        while *received < size {
+            let new_bytes = self.io.read(&mut self.buf[*received..size]);
 
-        while bytes_read < size {
-            let new_bytes = self.io.read(&mut buf[bytes_read..]);
+            if new_bytes == 0 && self.non_blocking {
+                return Err(ReadError::WouldBlock);
+            }
 
             if start_time.elapsed() > timeout {
                 return Err(ReadError::Timeout);
@@ -244,7 +320,7 @@ impl RecordLayer {
                 return Err(ReadError::IoError);
             }
 
-            bytes_read += new_bytes as usize
+            *received += new_bytes as usize
         }
         Ok(())
     }
@@ -259,13 +335,47 @@ impl RecordLayer {
                 .unwrap(),
             alert,
         );
-        let _ = self
-            .io
-            .write(&self.buf[..Self::HEADER_SIZE + AlertMsg::SIZE]);
+        // records that are still queued must be written first
+        self.pending
+            .extend_from_slice(&self.buf[..Self::HEADER_SIZE + AlertMsg::SIZE]);
+        let _ = self.flush();
         self.io.close();
     }
 
-    pub(crate) fn transcript(&self) -> [u8; Sha256::HASH_SIZE] {
-        self.transcript.clone().finish()
+    pub(crate) fn transcript(&self) -> Digest {
+        self.transcript.current()
+    }
+
+    /// Returns the transcript hash with `alg` as if `partial` had been added.
+    ///
+    /// This can be used before the hash function is chosen.
+    pub(crate) fn transcript_with(&self, alg: HashAlg, partial: &[u8]) -> Digest {
+        self.transcript.current_with(alg, partial)
+    }
+
+    /// Chooses the hash function of the transcript.
+    ///
+    /// This must be called once the cipher suite is known, before the transcript hash is used.
+    pub(crate) fn set_transcript_hash(&mut self, alg: HashAlg) {
+        self.transcript.set_alg(alg);
+    }
+
+    /// Replaces the transcript with a synthetic `message_hash` message containing its hash.
+    ///
+    /// This is done to the first ClientHello when the server responds with HelloRetryRequest.
+    pub(crate) fn hash_transcript(&mut self) {
+        let hash = self.transcript();
+        self.transcript = Transcript::new();
+        self.transcript.set_alg(hash.alg());
+        self.transcript
+            .update(&[ShakeType::MessageHash.to_byte(), 0, 0, hash.len() as u8]);
+        self.transcript.update(&hash);
+    }
+
+    /// Adds a handshake message to the transcript.
+    ///
+    /// Received messages and encrypted messages aren't added automatically.
+    pub(crate) fn update_transcript(&mut self, msg: &[u8]) {
+        self.transcript.update(msg);
     }
 }
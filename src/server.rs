@@ -0,0 +1,902 @@
+//! The server side of the handshake.
+
+use crate::aead::{self, TlsAead};
+use crate::alert::Alert;
+use crate::certificate::{self, CertRequestRef, CertVerifyRef, CertificateRef, SigningKey};
+use crate::cipher_suites::{CipherList, CipherSuite};
+use crate::client_hello::ClientHelloRef;
+use crate::config::{ClientAuth, Config, ConfigError};
+use crate::dh::GroupKeys;
+use crate::error::{ShakeResult, TlsError};
+use crate::extensions::{
+    CliHelExtRef, EncExtRef, KeyShare, MaxFragLen, OfferedPsks, RecordSizeLimit, SigAlgs, SupGroups,
+};
+use crate::finished::Finished;
+use crate::handshake::{ShakeType, SHAKE_HEADER_SIZE};
+use crate::key_schedule::{self, Digest, ShakeSecrets};
+use crate::key_update::AppSecrets;
+use crate::keylog::{self, KeyLog};
+use crate::record::{ContentType, RecordLayer};
+use crate::server_hello::ServerHello;
+use crate::session::{EarlyDataStatus, ServerTicket};
+use crate::state::{Shake, State};
+use crate::x509::{self, Certificate};
+
+/// The nonce of the ticket issued after the handshake.
+///
+/// Only one ticket is issued per connection, so the nonce doesn't need to change.
+const TICKET_NONCE: [u8; 1] = [0];
+
+/// The parameters agreed upon with the client.
+struct Negotiated {
+    cipher_suite: CipherList,
+    /// Whether or not the client's server name should be acknowledged.
+    server_name: bool,
+    /// The server name sent by the client, or an empty slice if it didn't send one.
+    client_server_name: Box<[u8]>,
+    /// Whether or not the client authenticated with a ticket instead of the server's certificate.
+    resumed: bool,
+    /// Whether or not a certificate is requested from the client.
+    ///
+    /// This is always `ClientAuth::None` when resuming a session.
+    client_auth: ClientAuth,
+    early_data: EarlyData,
+    record_limit: RecordLimit,
+    secrets: ShakeSecrets,
+    keylog: KeyLog,
+}
+
+/// How the client asked to limit the size of records.
+enum RecordLimit {
+    /// The client didn't ask for a limit.
+    None,
+    /// The client sent its record size limit, which is answered with ours.
+    Size(u16),
+    /// The client asked for a maximum fragment length with this code, which is echoed and limits
+    /// records in both directions.
+    FragLen(u8),
+}
+
+impl RecordLimit {
+    /// The largest amount of data the client may send in each record, given `our_limit`.
+    fn max_read_len(&self, our_limit: u16) -> usize {
+        match *self {
+            Self::None => RecordLayer::MAX_LEN,
+            Self::Size(_) => RecordSizeLimit::max_data_len(our_limit),
+            Self::FragLen(code) => MaxFragLen::max_data_len(code),
+        }
+    }
+
+    /// The largest amount of data to send to the client in each record.
+    fn max_write_len(&self) -> usize {
+        match *self {
+            Self::None => RecordLayer::MAX_LEN,
+            Self::Size(limit) => RecordSizeLimit::max_data_len(limit),
+            Self::FragLen(code) => MaxFragLen::max_data_len(code),
+        }
+    }
+}
+
+/// What to do with early data sent by the client.
+enum EarlyData {
+    /// The client didn't send early data.
+    None,
+    /// Up to `max` bytes of early data are read with the client early traffic secret.
+    Accepted { secret: Digest, max: u32 },
+    /// Up to `max` bytes of early data are skipped.
+    Rejected { max: u32 },
+}
+
+/// A PSK offered by the client that can be resumed.
+struct AcceptedPsk<'a> {
+    /// The index of the PSK in the client's list.
+    index: u16,
+    ticket: ServerTicket,
+    /// The age of the ticket reported by the client, obfuscated with `age_add`.
+    obfuscated_age: u32,
+    binder: &'a [u8],
+}
+
+/// How far the server has gotten in a handshake that is waiting for the client.
+///
+/// This is kept in the connection state so the handshake can be resumed.
+pub(crate) enum Progress {
+    /// ClientHello is expected, or ClientHello again if HelloRetryRequest was sent.
+    WaitCliHello(Box<Hello>),
+    /// The client's messages after ClientHello, up to Finished, are being read.
+    WaitFlight(Box<Flight>),
+    /// The handshake is complete, but the server's last messages haven't been written yet.
+    Flushing,
+}
+
+/// The state of the server while it waits for ClientHello.
+pub(crate) struct Hello {
+    /// The key that authenticates the server's certificate.
+    priv_key: SigningKey,
+    /// The choices made in HelloRetryRequest, if it was sent.
+    retry: Option<Retry>,
+}
+
+/// The choices made in HelloRetryRequest, which the second ClientHello must agree with.
+struct Retry {
+    cipher_suite: CipherList,
+    /// The group the client must send a key share for.
+    group: SupGroups,
+    /// How many more bytes of early data may be skipped, if the first ClientHello offered any.
+    ///
+    /// HelloRetryRequest rejects early data, which may be sent before the second ClientHello.
+    early_data: Option<usize>,
+}
+
+/// The state of the server while it reads the client's messages after ClientHello.
+pub(crate) struct Flight {
+    ser_state: SerState,
+    negotiated: Negotiated,
+    /// Holds the application traffic read keys until the client's Finished message is verified.
+    app_aead: TlsAead,
+}
+
+/// What the server is waiting for after sending its Finished message.
+enum SerState {
+    /// Early data is read until EndOfEarlyData, and up to `remaining` more bytes may be received.
+    ///
+    /// `shake_aead` holds the handshake read keys while the early data keys are in use.
+    ReadEarlyData {
+        remaining: usize,
+        shake_aead: Box<TlsAead>,
+    },
+    /// Rejected early data is skipped, and up to `remaining` more bytes may be received.
+    SkipEarlyData {
+        remaining: usize,
+    },
+    WaitCert,
+    WaitCertVerify,
+    WaitFinished,
+}
+
+/// Performs the handshake as the server, or resumes it if it would have blocked.
+///
+/// If an error occurs, the peer is alerted (if applicable) and the connection is closed.
+pub(crate) fn handshake(state: &mut State, config: &Config) -> ShakeResult {
+    let mut progress = match state.shake.take() {
+        Some(Shake::Server(progress)) => progress,
+        _ => {
+            // SAFETY: the creator of `Config` guarantees that the certificates are valid.
+            let certs = unsafe { config.identity.certs() };
+            let Some(priv_key) = certs
+                .first()
+                .and_then(|cert| SigningKey::new(cert, &config.identity.priv_key))
+            else {
+                return ShakeResult::ConfigError(ConfigError::MissingIdentity);
+            };
+
+            if !config.extensions.app_protos.is_valid() {
+                return ShakeResult::ConfigError(ConfigError::InvalidAppProtos);
+            }
+            if !RecordSizeLimit::is_valid(config.extensions.record_size_limit) {
+                return ShakeResult::ConfigError(ConfigError::InvalidRecordSizeLimit);
+            }
+            Progress::WaitCliHello(Box::new(Hello {
+                priv_key,
+                retry: None,
+            }))
+        },
+    };
+
+    match advance(state, config, &mut progress) {
+        Ok(()) => ShakeResult::Ok,
+        Err(ShakeResult::WantRead | ShakeResult::WantWrite) => {
+            state.shake = Some(Shake::Server(progress));
+            state.want()
+        },
+        Err(err) => {
+            if let ShakeResult::SentAlert(alert) = err {
+                // the handshake keys are only used once ServerHello has been sent
+                match progress {
+                    Progress::WaitCliHello(_) => state.rl.unenc_rl.alert_and_close(alert),
+                    _ => state.rl.alert_and_close(alert),
+                }
+            }
+            err
+        },
+    }
+}
+
+/// Processes messages from the client until the handshake is complete or would block.
+fn advance(state: &mut State, config: &Config, progress: &mut Progress) -> Result<(), ShakeResult> {
+    // the peer may be waiting for records that couldn't be written yet
+    state.rl.unenc_rl.flush()?;
+    loop {
+        match progress {
+            Progress::WaitCliHello(hello) => {
+                let Some(negotiated) = negotiate(state, config, hello)? else {
+                    continue;
+                };
+                state.rl.aead = TlsAead::new(
+                    &negotiated.secrets.ser_shake_traf_secret,
+                    &negotiated.secrets.cli_shake_traf_secret,
+                    negotiated.cipher_suite,
+                )
+                .expect("cipher suite was already negotiated");
+
+                send_ser_flight(state, config, &negotiated, &hello.priv_key)?;
+                let flight = start_cli_flight(state, config, negotiated);
+                *progress = Progress::WaitFlight(Box::new(flight));
+            },
+            Progress::WaitFlight(flight) => {
+                read_cli_flight(state, config, flight)?;
+                send_ticket(state, config, &flight.negotiated)?;
+                *progress = Progress::Flushing;
+            },
+            Progress::Flushing if state.rl.unenc_rl.flush()? => return Ok(()),
+            Progress::Flushing => return Err(ShakeResult::WantWrite),
+        }
+    }
+}
+
+/// Reads the ClientHello, chooses the connection parameters, and responds with ServerHello.
+///
+/// If the client didn't send a key share we can use, HelloRetryRequest is sent instead, and
+/// `None` is returned.
+fn negotiate(
+    state: &mut State,
+    config: &Config,
+    hello: &mut Hello,
+) -> Result<Option<Negotiated>, ShakeResult> {
+    let msg = read_cli_hello(state, hello.retry.as_mut())?;
+
+    let client_hello = ClientHelloRef::parse(&msg[SHAKE_HEADER_SIZE..]).map_err(TlsError::Sent)?;
+    let extensions = CliHelExtRef::parse(client_hello.extensions).map_err(TlsError::Sent)?;
+
+    // respect the client's preference
+    let Some(cipher_suite) = client_hello
+        .cipher_suites
+        .chunks_exact(size_of::<CipherSuite>())
+        .map(|suite| CipherList::parse_singular([suite[0], suite[1]]))
+        .find(|suite| suite.suites & config.cipher_suites.suites != 0)
+    else {
+        return Err(ShakeResult::SentAlert(Alert::HandshakeFailure));
+    };
+    // the second ClientHello must not change the cipher suite or offer early data again
+    if let Some(ref retry) = hello.retry {
+        if cipher_suite != retry.cipher_suite || extensions.early_data {
+            return Err(ShakeResult::SentAlert(Alert::IllegalParam));
+        }
+    }
+    state
+        .rl
+        .unenc_rl
+        .set_transcript_hash(cipher_suite.hash_alg());
+
+    let groups = SupGroups {
+        groups: extensions.sup_groups.groups & config.extensions.sup_groups.groups,
+    };
+
+    // the client must accept the scheme of the server's key
+    if extensions.sig_algs.algorithms & hello.priv_key.sig_algs_flag() == 0 {
+        return Err(ShakeResult::SentAlert(Alert::HandshakeFailure));
+    }
+
+    let our_name = config.extensions.server_name.as_bytes();
+    let server_name = match extensions.server_name {
+        Some(name) if !our_name.is_empty() => {
+            if !name.eq_ignore_ascii_case(our_name) {
+                return Err(ShakeResult::SentAlert(Alert::UnrecognizedName));
+            }
+            true
+        },
+        _ => false,
+    };
+
+    let key_share = match hello.retry {
+        // the client must only send a key share for the group chosen in HelloRetryRequest
+        Some(ref retry) => {
+            match KeyShare::find_cli(extensions.key_shares, retry.group).map_err(TlsError::Sent)? {
+                Some(entry) if entry.len() == extensions.key_shares.len() => entry,
+                _ => return Err(ShakeResult::SentAlert(Alert::IllegalParam)),
+            }
+        },
+        // use the client's most preferred key share that we support
+        None => match KeyShare::find_cli(extensions.key_shares, groups).map_err(TlsError::Sent)? {
+            Some(entry) => entry,
+            None => {
+                // ask for a key share for our most preferred group that the client supports
+                let Some(group) = groups.iter().next() else {
+                    return Err(ShakeResult::SentAlert(Alert::HandshakeFailure));
+                };
+                // the first ClientHello is replaced with its hash in the transcript
+                state.rl.unenc_rl.update_transcript(&msg);
+                state.rl.unenc_rl.hash_transcript();
+                ServerHello {
+                    leg_session_id: client_hello.session_id,
+                    cipher_suite,
+                    group,
+                    psk: None,
+                }
+                .write_retry(&mut state.rl.unenc_rl)?;
+
+                hello.retry = Some(Retry {
+                    cipher_suite,
+                    group,
+                    early_data: extensions
+                        .early_data
+                        .then_some(config.tickets.max_early_data as usize),
+                });
+                return Ok(None);
+            },
+        },
+    };
+    let group = KeyShare::entry_group(key_share);
+    let keys = GroupKeys::generate(group)?;
+    let dh_shared_secret = KeyShare::secret(key_share, group, &keys).map_err(TlsError::Sent)?;
+
+    // PSKs may only be used with (EC)DHE
+    let psk = match extensions.psks {
+        Some(ref psks) if extensions.psk_dhe_ke && config.tickets.enabled() => {
+            accept_psk(state, config, psks, &msg, cipher_suite).map_err(TlsError::Sent)?
+        },
+        _ => None,
+    };
+    let client_server_name: Box<[u8]> = Box::from(extensions.server_name.unwrap_or_default());
+    // a resumed session keeps the client's identity from when the ticket was issued
+    if let Some(ref psk) = psk {
+        state.peer_cert = psk.ticket.peer_cert.to_vec();
+    }
+
+    if let Some(offered) = extensions.app_protos {
+        let app_proto = config
+            .extensions
+            .app_protos
+            .select(offered)
+            .map_err(TlsError::Sent)?;
+        state.app_proto = Box::from(app_proto.unwrap_or_default());
+    }
+
+    let accept_early_data = psk.as_ref().is_some_and(|psk| {
+        extensions.early_data
+            && psk.index == 0
+            && psk.ticket.max_early_data != 0
+            && psk.ticket.cipher_suite == cipher_suite
+            && psk.ticket.server_name == client_server_name
+            && psk.ticket.app_proto == state.app_proto
+            && psk.ticket.is_fresh(psk.obfuscated_age)
+            && config.tickets.accept_early_data(psk.binder)
+    });
+    state.rl.unenc_rl.update_transcript(&msg);
+
+    let keylog = KeyLog::new(config, client_hello.random);
+    let early_data = match psk {
+        Some(ref psk) if accept_early_data => {
+            let secret = key_schedule::early_traffic_secret(
+                &psk.ticket.psk,
+                &state.rl.unenc_rl.transcript(),
+            );
+            keylog.log(keylog::CLI_EARLY, &secret);
+            EarlyData::Accepted {
+                secret,
+                max: psk.ticket.max_early_data,
+            }
+        },
+        // a client only sends as much early data as the ticket allows
+        _ if extensions.early_data => EarlyData::Rejected {
+            max: psk.as_ref().map_or(config.tickets.max_early_data, |psk| {
+                psk.ticket.max_early_data
+            }),
+        },
+        _ => EarlyData::None,
+    };
+    state.early_data = match early_data {
+        // early data sent with the first ClientHello was rejected by HelloRetryRequest
+        EarlyData::None
+            if hello
+                .retry
+                .as_ref()
+                .is_some_and(|retry| retry.early_data.is_some()) =>
+        {
+            EarlyDataStatus::Rejected
+        },
+        EarlyData::None => EarlyDataStatus::NotSent,
+        EarlyData::Accepted { .. } => EarlyDataStatus::Accepted,
+        EarlyData::Rejected { .. } => EarlyDataStatus::Rejected,
+    };
+
+    let server_hello = ServerHello {
+        leg_session_id: client_hello.session_id,
+        cipher_suite,
+        group,
+        psk: psk.as_ref().map(|psk| psk.index),
+    };
+    server_hello.write_to(&mut state.rl.unenc_rl, &keys)?;
+
+    let secrets = ShakeSecrets::new(
+        &dh_shared_secret,
+        psk.as_ref().map(|psk| &psk.ticket.psk),
+        &state.rl.unenc_rl.transcript(),
+    );
+    keylog.log(keylog::CLI_SHAKE, &secrets.cli_shake_traf_secret);
+    keylog.log(keylog::SER_SHAKE, &secrets.ser_shake_traf_secret);
+
+    Ok(Some(Negotiated {
+        cipher_suite,
+        server_name,
+        client_server_name,
+        resumed: psk.is_some(),
+        client_auth: if psk.is_some() {
+            ClientAuth::None
+        } else {
+            config.client_auth
+        },
+        early_data,
+        // the record size limit supersedes the maximum fragment length
+        record_limit: match (extensions.record_size_limit, extensions.max_frag_len) {
+            (Some(limit), _) => RecordLimit::Size(limit),
+            (None, Some(code)) => RecordLimit::FragLen(code),
+            (None, None) => RecordLimit::None,
+        },
+        secrets,
+        keylog,
+    }))
+}
+
+/// Reads a ClientHello message.
+///
+/// After HelloRetryRequest, the early data sent with the first ClientHello is skipped. The
+/// message isn't added to the transcript.
+fn read_cli_hello(state: &mut State, retry: Option<&mut Retry>) -> Result<Vec<u8>, ShakeResult> {
+    if let Some(Retry {
+        early_data: Some(remaining),
+        ..
+    }) = retry
+    {
+        // the early data ends where the second ClientHello starts
+        if state.shake_buf.is_empty() {
+            skip_retry_early_data(state, remaining)?;
+        }
+    }
+    let msg = state
+        .shake_buf
+        .read_plain(&mut state.rl.unenc_rl, state.record_timeout)?;
+    if msg[0] != ShakeType::ClientHello.to_byte() {
+        return Err(ShakeResult::SentAlert(Alert::UnexpectedMessage));
+    }
+    // ClientHello must end on a record boundary because the keys change after it
+    if !state.shake_buf.is_empty() {
+        return Err(ShakeResult::SentAlert(Alert::UnexpectedMessage));
+    }
+    Ok(msg)
+}
+
+/// Skips the early data sent with the first ClientHello until a handshake record is received.
+///
+/// The handshake record is buffered. If more than `remaining` bytes of early data are received,
+/// the handshake fails.
+fn skip_retry_early_data(state: &mut State, remaining: &mut usize) -> Result<(), ShakeResult> {
+    loop {
+        let msg_type = state.rl.unenc_rl.read(state.record_timeout)?;
+        if msg_type == ContentType::Handshake.to_byte() {
+            state
+                .shake_buf
+                .push(state.rl.unenc_rl.buf())
+                .map_err(TlsError::Sent)?;
+            return Ok(());
+        }
+        if msg_type == ContentType::ChangeCipherSpec.to_byte() {
+            continue;
+        }
+        if msg_type != ContentType::ApplicationData.to_byte() {
+            return Err(ShakeResult::SentAlert(Alert::UnexpectedMessage));
+        }
+        // the cipher suite of the early data isn't known, so the shortest tag is assumed
+        let len = state
+            .rl
+            .unenc_rl
+            .buf()
+            .len()
+            .saturating_sub(aead::MIN_TAG_LEN + size_of::<ContentType>());
+        *remaining = remaining
+            .checked_sub(len)
+            .ok_or(ShakeResult::SentAlert(Alert::UnexpectedMessage))?;
+    }
+}
+
+/// Returns the first PSK offered by the client that was issued by us and can be used with
+/// `cipher_suite`.
+///
+/// `client_hello` is the whole ClientHello message, which must not be in the transcript yet.
+fn accept_psk<'a>(
+    state: &State,
+    config: &Config,
+    psks: &OfferedPsks<'a>,
+    client_hello: &[u8],
+    cipher_suite: CipherList,
+) -> Result<Option<AcceptedPsk<'a>>, Alert> {
+    let alg = cipher_suite.hash_alg();
+    for (index, (identity, binder)) in psks.identities().zip(psks.binders()).enumerate() {
+        let (identity, obfuscated_age) = identity?;
+        let binder = binder?;
+        let Some(ticket) = ServerTicket::open(&config.tickets.key, identity) else {
+            continue;
+        };
+        if ticket.cipher_suite.hash_alg() != alg {
+            continue;
+        }
+        // a session without a client certificate can't be resumed if one is required
+        if config.client_auth == ClientAuth::Require && ticket.peer_cert.is_empty() {
+            continue;
+        }
+
+        // the binders cover ClientHello up to the binders themselves
+        let partial = &client_hello[..client_hello.len() - psks.binders_len()];
+        let transcript = state.rl.unenc_rl.transcript_with(alg, partial);
+        Finished::verify(binder, &key_schedule::binder_key(&ticket.psk), &transcript)?;
+
+        return Ok(Some(AcceptedPsk {
+            index: index as u16,
+            ticket,
+            obfuscated_age,
+            binder,
+        }));
+    }
+    Ok(None)
+}
+
+/// Sends EncryptedExtensions, CertificateRequest, Certificate, CertificateVerify, and Finished.
+///
+/// CertificateRequest is only sent if client authentication is enabled. Certificate and
+/// CertificateVerify aren't sent if the client authenticated with a ticket.
+fn send_ser_flight(
+    state: &mut State,
+    config: &Config,
+    negotiated: &Negotiated,
+    priv_key: &SigningKey,
+) -> Result<(), ShakeResult> {
+    let mut flight = EncExtRef {
+        server_name: negotiated.server_name,
+        early_data: matches!(negotiated.early_data, EarlyData::Accepted { .. }),
+        app_proto: Some(&*state.app_proto).filter(|proto| !proto.is_empty()),
+        record_size_limit: match negotiated.record_limit {
+            RecordLimit::Size(_) => Some(config.extensions.record_size_limit),
+            _ => None,
+        },
+        max_frag_len: match negotiated.record_limit {
+            RecordLimit::FragLen(code) => Some(code),
+            _ => None,
+        },
+    }
+    .to_vec();
+    state.rl.unenc_rl.update_transcript(&flight);
+
+    if negotiated.client_auth != ClientAuth::None {
+        // SAFETY: the caller of `turtls_server_handshake` guarantees that the config is valid.
+        let anchors = unsafe { config.trust_anchors() }
+            .iter()
+            .filter_map(|anchor| Certificate::parse(anchor).ok())
+            .collect::<Vec<_>>();
+        let cert_request = CertRequestRef {
+            sig_algs: config.extensions.sig_algs,
+            authorities: anchors.iter().map(|anchor| anchor.subject.raw).collect(),
+        }
+        .to_vec();
+        state.rl.unenc_rl.update_transcript(&cert_request);
+        flight.extend_from_slice(&cert_request);
+    }
+
+    if !negotiated.resumed {
+        // SAFETY: the creator of `Config` guarantees that the certificates are valid.
+        let certs = unsafe { config.identity.certs() };
+        let certificate = CertificateRef { certs }.to_vec();
+        state.rl.unenc_rl.update_transcript(&certificate);
+        flight.extend_from_slice(&certificate);
+
+        let signature = priv_key
+            .sign(certificate::SER_CONTEXT, &state.rl.unenc_rl.transcript())
+            .map_err(|_| ShakeResult::RngError)?;
+        let cert_verify = CertVerifyRef {
+            scheme: priv_key.scheme().to_be_bytes(),
+            signature: &signature,
+        }
+        .to_vec();
+        state.rl.unenc_rl.update_transcript(&cert_verify);
+        flight.extend_from_slice(&cert_verify);
+    }
+
+    let finished = Finished::new(
+        &negotiated.secrets.ser_shake_traf_secret,
+        &state.rl.unenc_rl.transcript(),
+    )
+    .to_vec();
+    state.rl.unenc_rl.update_transcript(&finished);
+    flight.extend_from_slice(&finished);
+
+    // the client's limit applies to every encrypted record
+    state.rl.max_write_len = negotiated.record_limit.max_write_len();
+    state.rl.write(ContentType::Handshake, &flight)?;
+    Ok(())
+}
+
+/// Switches to the application traffic write keys and returns the state for reading the
+/// client's messages after ClientHello.
+fn start_cli_flight(state: &mut State, config: &Config, negotiated: Negotiated) -> Flight {
+    let (cli_app_traf_secret, ser_app_traf_secret) = negotiated
+        .secrets
+        .app_secrets(&state.rl.unenc_rl.transcript());
+    negotiated.keylog.log(keylog::CLI_APP, &cli_app_traf_secret);
+    negotiated.keylog.log(keylog::SER_APP, &ser_app_traf_secret);
+    let exp_master = negotiated
+        .secrets
+        .exp_master(&state.rl.unenc_rl.transcript());
+    negotiated.keylog.log(keylog::EXPORTER, &exp_master);
+    let app_secrets = AppSecrets::new(
+        ser_app_traf_secret,
+        cli_app_traf_secret,
+        negotiated.cipher_suite,
+    );
+    // the server writes with the application traffic keys after its Finished message, so the
+    // client can decrypt any alerts
+    let mut app_aead = app_secrets.aead();
+    state.rl.aead.swap_writer(&mut app_aead);
+    state.app_secrets = Some(app_secrets);
+
+    let ser_state = match negotiated.early_data {
+        EarlyData::None => after_early_data(state, config, &negotiated),
+        EarlyData::Accepted { ref secret, max } => {
+            let mut shake_aead = Box::new(
+                TlsAead::new(secret, secret, negotiated.cipher_suite)
+                    .expect("cipher suite was already negotiated"),
+            );
+            state.rl.aead.swap_reader(&mut shake_aead);
+            SerState::ReadEarlyData {
+                remaining: max as usize,
+                shake_aead,
+            }
+        },
+        EarlyData::Rejected { max } => SerState::SkipEarlyData {
+            remaining: max as usize,
+        },
+    };
+    Flight {
+        ser_state,
+        negotiated,
+        app_aead,
+    }
+}
+
+/// Returns the state after early data, if any, has been received.
+fn after_early_data(state: &mut State, config: &Config, negotiated: &Negotiated) -> SerState {
+    // early data is sent before the client knows our limit
+    state.rl.max_read_len = negotiated
+        .record_limit
+        .max_read_len(config.extensions.record_size_limit);
+    if negotiated.client_auth != ClientAuth::None {
+        SerState::WaitCert
+    } else {
+        SerState::WaitFinished
+    }
+}
+
+/// Reads the client's early data, if any, certificate, if requested, and Finished message, then
+/// switches to the application traffic read keys.
+fn read_cli_flight(
+    state: &mut State,
+    config: &Config,
+    flight: &mut Flight,
+) -> Result<(), ShakeResult> {
+    loop {
+        flight.ser_state = match flight.ser_state {
+            SerState::ReadEarlyData {
+                ref mut remaining,
+                ref mut shake_aead,
+            } => {
+                read_early_data(state, remaining)?;
+                state.rl.aead.swap_reader(shake_aead);
+                after_early_data(state, config, &flight.negotiated)
+            },
+            SerState::SkipEarlyData { ref mut remaining } => {
+                skip_early_data(state, remaining)?;
+                after_early_data(state, config, &flight.negotiated)
+            },
+            SerState::WaitCert => read_cli_cert(state, config, flight.negotiated.client_auth)?,
+            SerState::WaitCertVerify => {
+                read_cli_cert_verify(state, config)?;
+                SerState::WaitFinished
+            },
+            SerState::WaitFinished => {
+                read_cli_finished(state, &flight.negotiated)?;
+                state.rl.aead.swap_reader(&mut flight.app_aead);
+                return Ok(());
+            },
+        };
+    }
+}
+
+/// Reads the client's Finished message.
+fn read_cli_finished(state: &mut State, negotiated: &Negotiated) -> Result<(), ShakeResult> {
+    let transcript = state.rl.unenc_rl.transcript();
+
+    let msg = state.shake_buf.read(&mut state.rl, state.record_timeout)?;
+    if msg[0] != ShakeType::Finished.to_byte() {
+        return Err(ShakeResult::SentAlert(Alert::UnexpectedMessage));
+    }
+    // Finished must end on a record boundary because the keys change after it
+    if !state.shake_buf.is_empty() {
+        return Err(ShakeResult::SentAlert(Alert::UnexpectedMessage));
+    }
+    Finished::verify(
+        &msg[SHAKE_HEADER_SIZE..],
+        &negotiated.secrets.cli_shake_traf_secret,
+        &transcript,
+    )
+    .map_err(TlsError::Sent)?;
+    state.rl.unenc_rl.update_transcript(&msg);
+    Ok(())
+}
+
+/// Reads the client's Certificate message, returning the next state.
+///
+/// The client's certificate is verified with the trust anchors, and CertificateVerify is expected
+/// next unless the certificate is empty. If `client_auth` is `ClientAuth::Require`, the client
+/// must send a certificate.
+fn read_cli_cert(
+    state: &mut State,
+    config: &Config,
+    client_auth: ClientAuth,
+) -> Result<SerState, ShakeResult> {
+    let msg = state.shake_buf.read(&mut state.rl, state.record_timeout)?;
+    if msg[0] != ShakeType::Certificate.to_byte() {
+        return Err(ShakeResult::SentAlert(Alert::UnexpectedMessage));
+    }
+    let certificate = CertificateRef::parse(&msg[SHAKE_HEADER_SIZE..]).map_err(TlsError::Sent)?;
+    if certificate.certs.is_empty() {
+        if client_auth == ClientAuth::Require {
+            return Err(ShakeResult::SentAlert(Alert::CertRequired));
+        }
+        state.rl.unenc_rl.update_transcript(&msg);
+        return Ok(SerState::WaitFinished);
+    }
+    // SAFETY: the caller of `turtls_server_handshake` guarantees that the config is valid.
+    let anchors = unsafe { config.trust_anchors() };
+    x509::verify_chain(&certificate.certs, anchors, x509::unix_time()).map_err(TlsError::Sent)?;
+    // the certificate is only used once CertificateVerify has been verified
+    state.peer_cert = certificate.certs[0].to_vec();
+    state.rl.unenc_rl.update_transcript(&msg);
+    Ok(SerState::WaitCertVerify)
+}
+
+/// Reads the client's CertificateVerify message, which must be signed by the key of its
+/// certificate.
+fn read_cli_cert_verify(state: &mut State, config: &Config) -> Result<(), ShakeResult> {
+    let transcript = state.rl.unenc_rl.transcript();
+
+    let msg = state.shake_buf.read(&mut state.rl, state.record_timeout)?;
+    if msg[0] != ShakeType::CertificateVerify.to_byte() {
+        return Err(ShakeResult::SentAlert(Alert::UnexpectedMessage));
+    }
+    let cert_verify = CertVerifyRef::parse(&msg[SHAKE_HEADER_SIZE..]).map_err(TlsError::Sent)?;
+    if SigAlgs::parse_singular(cert_verify.scheme).algorithms
+        & config.extensions.sig_algs.algorithms
+        == 0
+    {
+        return Err(ShakeResult::SentAlert(Alert::IllegalParam));
+    }
+    let leaf = Certificate::parse(&state.peer_cert).map_err(TlsError::Sent)?;
+    cert_verify
+        .verify(&leaf.public_key, certificate::CLI_CONTEXT, &transcript)
+        .map_err(TlsError::Sent)?;
+    state.rl.unenc_rl.update_transcript(&msg);
+    Ok(())
+}
+
+/// Reads early data into the application data buffer until EndOfEarlyData is received.
+///
+/// If more than `remaining` bytes of early data are received, the handshake fails. The early
+/// data read keys must be in use.
+fn read_early_data(state: &mut State, remaining: &mut usize) -> Result<(), ShakeResult> {
+    let msg = loop {
+        if let Some(msg) = state.shake_buf.next_msg().map_err(TlsError::Sent)? {
+            break msg;
+        }
+        let msg_type = state.rl.read(state.record_timeout)?;
+        if msg_type == ContentType::ApplicationData.to_byte() {
+            *remaining = remaining
+                .checked_sub(state.rl.buf().len())
+                .ok_or(ShakeResult::SentAlert(Alert::UnexpectedMessage))?;
+            state.app_data.extend_from_slice(state.rl.buf());
+        } else if msg_type == ContentType::Handshake.to_byte() {
+            state
+                .shake_buf
+                .push(state.rl.buf())
+                .map_err(TlsError::Sent)?;
+        } else {
+            return Err(ShakeResult::SentAlert(Alert::UnexpectedMessage));
+        }
+    };
+
+    if msg[0] != ShakeType::EndOfEarlyData.to_byte() {
+        return Err(ShakeResult::SentAlert(Alert::UnexpectedMessage));
+    }
+    if msg.len() != SHAKE_HEADER_SIZE {
+        return Err(ShakeResult::SentAlert(Alert::DecodeError));
+    }
+    // EndOfEarlyData must end on a record boundary because the keys change after it
+    if !state.shake_buf.is_empty() {
+        return Err(ShakeResult::SentAlert(Alert::UnexpectedMessage));
+    }
+    state.rl.unenc_rl.update_transcript(&msg);
+    Ok(())
+}
+
+/// Skips records that can't be decrypted, which contain rejected early data, until a handshake
+/// record is received.
+///
+/// The handshake record is buffered. If more than `remaining` bytes of early data are received,
+/// the handshake fails.
+fn skip_early_data(state: &mut State, remaining: &mut usize) -> Result<(), ShakeResult> {
+    loop {
+        let mut msg_type = state.rl.unenc_rl.read(state.record_timeout)?;
+        while msg_type == ContentType::ChangeCipherSpec.to_byte() {
+            msg_type = state.rl.unenc_rl.read(state.record_timeout)?;
+        }
+        if msg_type != ContentType::ApplicationData.to_byte() {
+            return Err(ShakeResult::SentAlert(Alert::UnexpectedMessage));
+        }
+        // the tag and inner content type aren't early data
+        let len = state
+            .rl
+            .buf()
+            .len()
+            .saturating_sub(state.rl.aead.tag_len() + size_of::<ContentType>());
+
+        match state.rl.decrypt() {
+            Ok(msg_type) if msg_type == ContentType::Handshake.to_byte() => {
+                state
+                    .shake_buf
+                    .push(state.rl.buf())
+                    .map_err(TlsError::Sent)?;
+                return Ok(());
+            },
+            Ok(_) => return Err(ShakeResult::SentAlert(Alert::UnexpectedMessage)),
+            Err(TlsError::Sent(Alert::BadRecordMac)) => {
+                *remaining = remaining
+                    .checked_sub(len)
+                    .ok_or(ShakeResult::SentAlert(Alert::UnexpectedMessage))?;
+            },
+            Err(err) => return Err(err.into()),
+        }
+    }
+}
+
+/// Issues a ticket to the client if tickets are enabled.
+///
+/// The transcript must include the client's Finished message.
+fn send_ticket(
+    state: &mut State,
+    config: &Config,
+    negotiated: &Negotiated,
+) -> Result<(), ShakeResult> {
+    // the client's certificate must fit in the ticket
+    if !config.tickets.enabled() || state.peer_cert.len() > u16::MAX as usize {
+        return Ok(());
+    }
+    let res_master = negotiated
+        .secrets
+        .res_master(&state.rl.unenc_rl.transcript());
+    let ticket = ServerTicket::new(
+        &config.tickets,
+        negotiated.cipher_suite,
+        &res_master,
+        &TICKET_NONCE,
+        &negotiated.client_server_name,
+        &state.app_proto,
+        &state.peer_cert,
+    )
+    .map_err(|_| ShakeResult::RngError)?;
+    let sealed = ticket
+        .seal(&config.tickets.key)
+        .map_err(|_| ShakeResult::RngError)?;
+
+    state.rl.write(
+        ContentType::Handshake,
+        &ticket.to_msg(&sealed, &TICKET_NONCE),
+    )?;
+    Ok(())
+}
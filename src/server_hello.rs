@@ -1,115 +1,182 @@
-use std::time::Duration;
-
 use crate::alert::Alert;
 use crate::cipher_suites::{CipherList, CipherSuite};
-use crate::error::TlsError;
-use crate::extensions::{Extensions, SerHelExtRef};
-use crate::handshake::{ShakeType, SHAKE_HEADER_SIZE};
-use crate::record::{ContentType, ReadError, RecordLayer};
-use crate::versions::ProtocolVersion;
+use crate::client_hello::ClientHello;
+use crate::dh::GroupKeys;
+use crate::extensions::{Extensions, KeyShare, PreSharedKey, SupGroups, SupVersions};
+use crate::handshake::ShakeType;
+use crate::record::{ContentType, IoError, RecordLayer};
+use crate::versions::{ProtocolVersion, LEGACY_PROTO_VERS};
+use getrandom::{getrandom, Error};
 
 pub(crate) struct ServerHello<'a> {
-    leg_sesion_id: &'a [u8],
-    cipher_suite: CipherList,
-    extensions: Extensions,
+    /// The legacy session ID sent by the client, which must be echoed.
+    pub(crate) leg_session_id: &'a [u8],
+    /// The negotiated cipher suite.
+    pub(crate) cipher_suite: CipherList,
+    /// The negotiated group.
+    pub(crate) group: SupGroups,
+    /// The index of the PSK chosen from the client's offered PSKs, if any.
+    pub(crate) psk: Option<u16>,
 }
 
 impl<'a> ServerHello<'a> {
     pub(crate) const RANDOM_BYTES_LEN: usize = 32;
     pub(crate) const LEG_SESS_ID_LEN_SIZE: usize = 1;
     pub(crate) const LEGACY_COMPRESSION_METHOD: u8 = 0;
+    /// The `random` value that identifies a HelloRetryRequest.
+    pub(crate) const RETRY_RANDOM: [u8; ServerHello::RANDOM_BYTES_LEN] = [
+        0xcf, 0x21, 0xad, 0x74, 0xe5, 0x9a, 0x61, 0x11, 0xbe, 0x1d, 0x8c, 0x02, 0x1e, 0x65, 0xb8,
+        0x91, 0xc2, 0xa2, 0x11, 0x16, 0x7a, 0xbb, 0x8c, 0x5e, 0x07, 0x9e, 0x09, 0xe2, 0xc8, 0xa8,
+        0x33, 0x9c,
+    ];
     pub(crate) const MIN_LEN: usize = size_of::<ProtocolVersion>()
         + Self::RANDOM_BYTES_LEN
         + Self::LEG_SESS_ID_LEN_SIZE
         + size_of::<CipherSuite>()
         + 1
         + Extensions::LEN_SIZE;
-}
 
-pub struct RecvdSerHello<'a> {
-    pub(crate) cipher_suite: CipherList,
-    pub(crate) extensions: SerHelExtRef<'a>,
-}
+    fn extensions_len(&self) -> usize {
+        let psk_len = match self.psk {
+            Some(_) => PreSharedKey::len_ser(),
+            None => 0,
+        };
+        SupVersions::len_ser() + KeyShare::len_ser(self.group) + psk_len
+    }
 
-impl<'a> RecvdSerHello<'a> {
-    /// Recieve and parse a ServerHello message.
-    ///
-    /// Note: this function makes the assumption that the ServerHello will be exactly one record.
-    /// If the server sends a ServerHello that is broken into multiple records, it will alert
-    /// `HandshakeFailed` and return an error.
-    pub(crate) fn read(
-        record_layer: &'a mut RecordLayer,
-        record_timeout: Duration,
-    ) -> Result<Self, ReadError> {
-        let msg_type = record_layer.read(record_timeout)?;
-        if msg_type != ContentType::Handshake.to_byte() {
-            return Err(ReadError::Alert(TlsError::Sent(Alert::UnexpectedMessage)));
+    pub(crate) fn write_to(
+        &self,
+        record_layer: &mut RecordLayer,
+        keys: &GroupKeys,
+    ) -> Result<(), SerHelError> {
+        let mut random_bytes = [0; Self::RANDOM_BYTES_LEN];
+        getrandom(&mut random_bytes)?;
+        self.write_start(record_layer, &random_bytes, self.extensions_len());
+
+        KeyShare::write_ser(record_layer, self.group, keys);
+        if let Some(selected) = self.psk {
+            PreSharedKey::write_ser(record_layer, selected);
         }
 
-        if record_layer.len() < SHAKE_HEADER_SIZE + ServerHello::MIN_LEN {
-            return Err(ReadError::Alert(TlsError::Sent(Alert::DecodeError)));
-        }
+        record_layer.finish_and_send()?;
+        Ok(())
+    }
 
-        if record_layer.buf()[0] != ShakeType::ServerHello.to_byte() {
-            return Err(ReadError::Alert(TlsError::Sent(Alert::UnexpectedMessage)));
-        }
+    /// Writes the message as a HelloRetryRequest, which asks for a key share for `self.group`.
+    ///
+    /// `self.psk` is ignored.
+    pub(crate) fn write_retry(&self, record_layer: &mut RecordLayer) -> Result<(), IoError> {
+        let extensions_len = SupVersions::len_ser() + KeyShare::RETRY_LEN;
+        self.write_start(record_layer, &Self::RETRY_RANDOM, extensions_len);
 
-        let len = u32::from_be_bytes([
-            0,
-            record_layer.buf()[1],
-            record_layer.buf()[2],
-            record_layer.buf()[3],
-        ]) as usize;
+        KeyShare::write_retry(record_layer, self.group);
 
-        // ServerHello must be the only message in the record
-        if len < record_layer.len() - SHAKE_HEADER_SIZE {
-            return Err(ReadError::Alert(TlsError::Sent(Alert::DecodeError)));
-        }
+        record_layer.finish_and_send()
+    }
 
-        // ServerHello must not be more than one record (implemntation detail)
-        if len > record_layer.len() - SHAKE_HEADER_SIZE {
-            return Err(ReadError::Alert(TlsError::Sent(Alert::HandshakeFailure)));
-        }
+    /// Writes the message up to and including the SupportedVersions extension.
+    fn write_start(
+        &self,
+        record_layer: &mut RecordLayer,
+        random: &[u8; ServerHello::RANDOM_BYTES_LEN],
+        extensions_len: usize,
+    ) {
+        record_layer.start_as(ContentType::Handshake);
+        record_layer.push(ShakeType::ServerHello.to_byte());
+        record_layer.push_u24((Self::MIN_LEN + self.leg_session_id.len() + extensions_len) as u32);
 
-        if record_layer.len() - SHAKE_HEADER_SIZE < ServerHello::MIN_LEN {
-            return Err(ReadError::Alert(TlsError::Sent(Alert::DecodeError)));
-        }
+        record_layer.push_u16(LEGACY_PROTO_VERS.as_int());
+        record_layer.extend_from_slice(random);
 
-        let mut pos =
-            size_of::<ProtocolVersion>() + ServerHello::RANDOM_BYTES_LEN + SHAKE_HEADER_SIZE;
+        record_layer.push(self.leg_session_id.len() as u8);
+        record_layer.extend_from_slice(self.leg_session_id);
 
-        let leg_session_id_len = record_layer.buf()[pos];
+        self.cipher_suite.write_to(record_layer);
+        record_layer.push(Self::LEGACY_COMPRESSION_METHOD);
 
-        if leg_session_id_len > 32 {
-            return Err(ReadError::Alert(TlsError::Sent(Alert::DecodeError)));
+        record_layer.push_u16(extensions_len as u16);
+        SupVersions::write_ser(record_layer);
+    }
+}
+
+pub(crate) enum SerHelError {
+    RngError,
+    IoError,
+}
+
+impl From<Error> for SerHelError {
+    fn from(_: Error) -> Self {
+        Self::RngError
+    }
+}
+
+impl From<IoError> for SerHelError {
+    fn from(_: IoError) -> Self {
+        Self::IoError
+    }
+}
+
+/// A received ServerHello or HelloRetryRequest message.
+pub(crate) struct RecvdSerHello<'a> {
+    /// Whether or not the message is a HelloRetryRequest.
+    pub(crate) is_retry: bool,
+    pub(crate) cipher_suite: CipherList,
+    /// The unparsed extensions.
+    pub(crate) extensions: &'a [u8],
+}
+
+impl<'a> RecvdSerHello<'a> {
+    const MAX_SESSION_ID_LEN: usize = 32;
+
+    /// Parses the body of a ServerHello or HelloRetryRequest message.
+    ///
+    /// The extensions are not parsed because their contents differ between the two.
+    pub(crate) fn parse(server_hello: &'a [u8]) -> Result<Self, Alert> {
+        if server_hello.len() < ServerHello::MIN_LEN {
+            return Err(Alert::DecodeError);
+        }
+
+        let mut pos = size_of::<ProtocolVersion>();
+        let is_retry =
+            server_hello[pos..][..ServerHello::RANDOM_BYTES_LEN] == ServerHello::RETRY_RANDOM;
+        pos += ServerHello::RANDOM_BYTES_LEN;
+
+        let leg_session_id_len = server_hello[pos] as usize;
+        if leg_session_id_len > Self::MAX_SESSION_ID_LEN
+            || server_hello.len() < ServerHello::MIN_LEN + leg_session_id_len
+        {
+            return Err(Alert::DecodeError);
+        }
+        // the server must echo the (empty) legacy session ID sent in ClientHello
+        if leg_session_id_len != ClientHello::LEGACY_SESSION_ID as usize {
+            return Err(Alert::IllegalParam);
         }
-        pos += size_of_val(&leg_session_id_len) + leg_session_id_len as usize;
+        pos += ServerHello::LEG_SESS_ID_LEN_SIZE + leg_session_id_len;
 
         let cipher_suite = CipherList::parse_singular(
-            record_layer.buf()[pos..][..size_of::<CipherSuite>()]
+            server_hello[pos..][..size_of::<CipherSuite>()]
                 .try_into()
                 .unwrap(),
         );
         pos += size_of::<CipherSuite>();
 
+        if server_hello[pos] != ServerHello::LEGACY_COMPRESSION_METHOD {
+            return Err(Alert::IllegalParam);
+        }
         pos += size_of_val(&ServerHello::LEGACY_COMPRESSION_METHOD);
 
         let extensions_len =
-            u16::from_be_bytes(record_layer.buf()[pos..][..2].try_into().unwrap()) as usize;
+            u16::from_be_bytes(server_hello[pos..][..2].try_into().unwrap()) as usize;
 
         pos += Extensions::LEN_SIZE;
-        if extensions_len != record_layer.buf()[pos..].len() {
-            return Err(ReadError::Alert(TlsError::Sent(Alert::DecodeError)));
+        if extensions_len != server_hello[pos..].len() {
+            return Err(Alert::DecodeError);
         }
 
-        let extensions = match SerHelExtRef::parse(&record_layer.buf()[pos..]) {
-            Ok(ext) => ext,
-            Err(err) => return Err(ReadError::Alert(TlsError::Sent(err))),
-        };
-
         Ok(Self {
+            is_retry,
             cipher_suite,
-            extensions,
+            extensions: &server_hello[pos..],
         })
     }
 }
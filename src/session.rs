@@ -0,0 +1,621 @@
+//! Session tickets and the sessions used to resume connections.
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crylib::aead::chacha::ChaCha20Poly1305;
+use crylib::aead::{Aead, IV_SIZE, TAG_SIZE};
+use getrandom::getrandom;
+
+use crate::alert::Alert;
+use crate::cipher_suites::CipherList;
+use crate::config::{Config, Tickets};
+use crate::extensions::EarlyData;
+use crate::handshake::{ShakeType, SHAKE_HEADER_SIZE};
+use crate::key_schedule::{self, Digest, HashAlg};
+
+/// The status of the early data (0-RTT) of a connection.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[repr(C)]
+pub enum EarlyDataStatus {
+    /// No early data was sent.
+    NotSent,
+    /// The early data was accepted by the server.
+    Accepted,
+    /// The early data was rejected by the server and must be sent again if it is still needed.
+    Rejected,
+}
+
+/// The secret a client needs to turn a ticket into a session, available once the handshake is
+/// complete.
+pub(crate) struct Resumption {
+    /// The resumption master secret.
+    pub(crate) res_master: Digest,
+    pub(crate) cipher_suite: CipherList,
+    /// The server name the connection was established with.
+    pub(crate) server_name: Box<[u8]>,
+    /// The application protocol negotiated by the connection.
+    pub(crate) app_proto: Box<[u8]>,
+}
+
+/// A session that can be resumed with a ticket from the server.
+pub(crate) struct Session {
+    /// The cipher suite of the original connection. A resumed connection must use a cipher suite
+    /// with the same hash function.
+    pub(crate) cipher_suite: CipherList,
+    /// The pre-shared key derived from the ticket.
+    pub(crate) psk: Digest,
+    pub(crate) ticket: Box<[u8]>,
+    /// The value added to the ticket's age to obfuscate it.
+    pub(crate) age_add: u32,
+    /// The lifetime of the ticket in seconds.
+    pub(crate) lifetime: u32,
+    /// The time the ticket was received, in milliseconds since the Unix epoch.
+    pub(crate) received: u64,
+    /// The server name the ticket was issued for.
+    pub(crate) server_name: Box<[u8]>,
+    /// The maximum number of bytes of early data the server accepts with the ticket.
+    pub(crate) max_early_data: u32,
+    /// The application protocol of the original connection, which early data is sent for.
+    pub(crate) app_proto: Box<[u8]>,
+}
+
+impl Session {
+    /// The version of the serialization format.
+    const VERSION: u8 = 3;
+    /// The maximum lifetime of a ticket in seconds.
+    const MAX_LIFETIME: u32 = 604_800;
+    /// The maximum length of a ticket that can be offered in ClientHello.
+    const MAX_TICKET_LEN: usize = 0x8000;
+    const NONCE_LEN_SIZE: usize = 1;
+    const TICKET_LEN_SIZE: usize = 2;
+    const EXTENSIONS_LEN_SIZE: usize = 2;
+    const SERVER_NAME_LEN_SIZE: usize = 2;
+    const APP_PROTO_LEN_SIZE: usize = 1;
+    const MIN_TICKET_MSG_LEN: usize = 2 * size_of::<u32>()
+        + Self::NONCE_LEN_SIZE
+        + Self::TICKET_LEN_SIZE
+        + Self::EXTENSIONS_LEN_SIZE;
+
+    /// Parses the body of a NewSessionTicket message.
+    ///
+    /// If the server says the ticket must not be used, `None` is returned.
+    pub(crate) fn from_ticket(msg: &[u8], resumption: &Resumption) -> Result<Option<Self>, Alert> {
+        if msg.len() < Self::MIN_TICKET_MSG_LEN {
+            return Err(Alert::DecodeError);
+        }
+        let lifetime = u32::from_be_bytes(msg[..4].try_into().unwrap());
+        let age_add = u32::from_be_bytes(msg[4..8].try_into().unwrap());
+        let mut msg = &msg[2 * size_of::<u32>()..];
+
+        let nonce_len = msg[0] as usize;
+        msg = &msg[Self::NONCE_LEN_SIZE..];
+        if msg.len() < nonce_len + Self::TICKET_LEN_SIZE + Self::EXTENSIONS_LEN_SIZE {
+            return Err(Alert::DecodeError);
+        }
+        let (nonce, mut msg) = msg.split_at(nonce_len);
+
+        let ticket_len = u16::from_be_bytes([msg[0], msg[1]]) as usize;
+        msg = &msg[Self::TICKET_LEN_SIZE..];
+        if ticket_len == 0 || msg.len() < ticket_len + Self::EXTENSIONS_LEN_SIZE {
+            return Err(Alert::DecodeError);
+        }
+        let (ticket, msg) = msg.split_at(ticket_len);
+
+        if u16::from_be_bytes([msg[0], msg[1]]) as usize != msg.len() - Self::EXTENSIONS_LEN_SIZE {
+            return Err(Alert::DecodeError);
+        }
+        let max_early_data = EarlyData::parse_ticket(&msg[Self::EXTENSIONS_LEN_SIZE..])?;
+
+        if lifetime > Self::MAX_LIFETIME {
+            return Err(Alert::IllegalParam);
+        }
+        if lifetime == 0 || ticket.len() > Self::MAX_TICKET_LEN {
+            return Ok(None);
+        }
+
+        Ok(Some(Self {
+            cipher_suite: resumption.cipher_suite,
+            psk: key_schedule::ticket_psk(&resumption.res_master, nonce),
+            ticket: Box::from(ticket),
+            age_add,
+            lifetime,
+            received: unix_millis(),
+            server_name: resumption.server_name.clone(),
+            max_early_data,
+            app_proto: resumption.app_proto.clone(),
+        }))
+    }
+
+    /// Returns whether or not the session can be offered with `config`.
+    ///
+    /// The ticket must not have expired, and the server name and cipher suites must match.
+    pub(crate) fn is_usable(&self, config: &Config) -> bool {
+        let age = unix_millis().saturating_sub(self.received);
+        age < u64::from(self.lifetime) * 1000
+            && *self.server_name == *config.extensions.server_name.as_bytes()
+            && config.cipher_suites.with_hash(self.hash_alg()).suites != 0
+    }
+
+    /// Returns whether or not `early_data` can be sent with the session when using `config`.
+    ///
+    /// The session must be usable, and the server must accept enough early data with the same
+    /// cipher suite and application protocol.
+    pub(crate) fn allows_early_data(&self, config: &Config, early_data: &[u8]) -> bool {
+        !early_data.is_empty()
+            && early_data.len() <= self.max_early_data as usize
+            && config.cipher_suites.suites & self.cipher_suite.suites != 0
+            && (self.app_proto.is_empty() || config.extensions.app_protos.contains(&self.app_proto))
+    }
+
+    /// The obfuscated age of the ticket, as sent in ClientHello.
+    pub(crate) fn obfuscated_age(&self) -> u32 {
+        let age = unix_millis().saturating_sub(self.received) as u32;
+        age.wrapping_add(self.age_add)
+    }
+
+    /// Serializes the session so it can be stored by the user.
+    pub(crate) fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(
+            size_of_val(&Self::VERSION)
+                + size_of::<u8>()
+                + size_of::<u64>()
+                + 3 * size_of::<u32>()
+                + size_of::<u8>()
+                + self.psk.len()
+                + Self::SERVER_NAME_LEN_SIZE
+                + self.server_name.len()
+                + Self::APP_PROTO_LEN_SIZE
+                + self.app_proto.len()
+                + Self::TICKET_LEN_SIZE
+                + self.ticket.len(),
+        );
+        bytes.push(Self::VERSION);
+        bytes.push(self.cipher_suite.suites);
+        bytes.extend_from_slice(&self.received.to_be_bytes());
+        bytes.extend_from_slice(&self.lifetime.to_be_bytes());
+        bytes.extend_from_slice(&self.age_add.to_be_bytes());
+        bytes.extend_from_slice(&self.max_early_data.to_be_bytes());
+        bytes.push(self.psk.len() as u8);
+        bytes.extend_from_slice(&self.psk);
+        bytes.extend_from_slice(&(self.server_name.len() as u16).to_be_bytes());
+        bytes.extend_from_slice(&self.server_name);
+        bytes.push(self.app_proto.len() as u8);
+        bytes.extend_from_slice(&self.app_proto);
+        bytes.extend_from_slice(&(self.ticket.len() as u16).to_be_bytes());
+        bytes.extend_from_slice(&self.ticket);
+        bytes
+    }
+
+    /// Parses a session serialized by [`Session::to_bytes`].
+    pub(crate) fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        let (&version, bytes) = bytes.split_first()?;
+        if version != Self::VERSION {
+            return None;
+        }
+        let (&suite, bytes) = bytes.split_first()?;
+        let cipher_suite = CipherList { suites: suite };
+        if suite.count_ones() != 1 || suite > CipherList::AES_128_CCM_8_SHA256 {
+            return None;
+        }
+
+        let (received, bytes) = bytes.split_first_chunk()?;
+        let (lifetime, bytes) = bytes.split_first_chunk()?;
+        let (age_add, bytes) = bytes.split_first_chunk()?;
+        let (max_early_data, bytes) = bytes.split_first_chunk()?;
+
+        let alg = cipher_suite.hash_alg();
+        let (&psk_len, bytes) = bytes.split_first()?;
+        if psk_len as usize != alg.size() || bytes.len() < alg.size() {
+            return None;
+        }
+        let (psk, bytes) = bytes.split_at(alg.size());
+
+        let (server_name, bytes) = split_vec(bytes)?;
+        let (app_proto, bytes) = split_short_vec(bytes)?;
+        let (ticket, bytes) = split_vec(bytes)?;
+        if !bytes.is_empty() || ticket.is_empty() || ticket.len() > Self::MAX_TICKET_LEN {
+            return None;
+        }
+
+        Some(Self {
+            cipher_suite,
+            psk: Digest::new(alg, psk),
+            ticket: Box::from(ticket),
+            age_add: u32::from_be_bytes(*age_add),
+            lifetime: u32::from_be_bytes(*lifetime),
+            received: u64::from_be_bytes(*received),
+            server_name: Box::from(server_name),
+            max_early_data: u32::from_be_bytes(*max_early_data),
+            app_proto: Box::from(app_proto),
+        })
+    }
+
+    /// The hash function of the session.
+    pub(crate) const fn hash_alg(&self) -> HashAlg {
+        self.psk.alg()
+    }
+}
+
+/// The contents of a ticket issued by the server.
+///
+/// Tickets are encrypted with the server's ticket key, so the server doesn't need to store them.
+pub(crate) struct ServerTicket {
+    pub(crate) cipher_suite: CipherList,
+    pub(crate) psk: Digest,
+    /// The time the ticket was issued, in milliseconds since the Unix epoch.
+    pub(crate) issued: u64,
+    /// The lifetime of the ticket in seconds.
+    pub(crate) lifetime: u32,
+    pub(crate) age_add: u32,
+    /// The maximum number of bytes of early data to accept with the ticket.
+    pub(crate) max_early_data: u32,
+    /// The server name the client sent, or an empty slice if it didn't send one.
+    pub(crate) server_name: Box<[u8]>,
+    /// The negotiated application protocol, or an empty slice if none was negotiated.
+    pub(crate) app_proto: Box<[u8]>,
+    /// The client's end-entity certificate, or an empty slice if it didn't authenticate.
+    pub(crate) peer_cert: Box<[u8]>,
+}
+
+impl ServerTicket {
+    /// The version of the ticket format.
+    const VERSION: u8 = 3;
+    /// The maximum difference in milliseconds between the ticket age the client reports and the
+    /// age the server expects for early data to be accepted.
+    const MAX_AGE_SKEW: u64 = 10_000;
+
+    /// Creates a ticket for a connection that will be issued now.
+    ///
+    /// `res_master` is the resumption master secret of the connection and `nonce` is the nonce
+    /// the ticket is sent with.
+    pub(crate) fn new(
+        tickets: &Tickets,
+        cipher_suite: CipherList,
+        res_master: &Digest,
+        nonce: &[u8],
+        server_name: &[u8],
+        app_proto: &[u8],
+        peer_cert: &[u8],
+    ) -> Result<Self, getrandom::Error> {
+        let mut age_add = [0; size_of::<u32>()];
+        getrandom(&mut age_add)?;
+        Ok(Self {
+            cipher_suite,
+            psk: key_schedule::ticket_psk(res_master, nonce),
+            issued: unix_millis(),
+            lifetime: tickets.lifetime.min(Session::MAX_LIFETIME),
+            age_add: u32::from_be_bytes(age_add),
+            max_early_data: if tickets.anti_replay.is_some() {
+                tickets.max_early_data
+            } else {
+                0
+            },
+            server_name: Box::from(server_name),
+            app_proto: Box::from(app_proto),
+            peer_cert: Box::from(peer_cert),
+        })
+    }
+
+    /// Encrypts the ticket with `key`, returning the ticket to send to the client.
+    pub(crate) fn seal(
+        &self,
+        key: &[u8; ChaCha20Poly1305::KEY_SIZE],
+    ) -> Result<Vec<u8>, getrandom::Error> {
+        let mut iv = [0; IV_SIZE];
+        getrandom(&mut iv)?;
+
+        let mut ticket = Vec::new();
+        ticket.extend_from_slice(&iv);
+        ticket.push(Self::VERSION);
+        ticket.push(self.cipher_suite.suites);
+        ticket.extend_from_slice(&self.issued.to_be_bytes());
+        ticket.extend_from_slice(&self.lifetime.to_be_bytes());
+        ticket.extend_from_slice(&self.age_add.to_be_bytes());
+        ticket.extend_from_slice(&self.max_early_data.to_be_bytes());
+        ticket.push(self.psk.len() as u8);
+        ticket.extend_from_slice(&self.psk);
+        ticket.extend_from_slice(&(self.server_name.len() as u16).to_be_bytes());
+        ticket.extend_from_slice(&self.server_name);
+        ticket.push(self.app_proto.len() as u8);
+        ticket.extend_from_slice(&self.app_proto);
+        ticket.extend_from_slice(&(self.peer_cert.len() as u16).to_be_bytes());
+        ticket.extend_from_slice(&self.peer_cert);
+
+        let tag = ChaCha20Poly1305::new(*key).encrypt_inline(&mut ticket[IV_SIZE..], &[], &iv);
+        ticket.extend_from_slice(&tag);
+        Ok(ticket)
+    }
+
+    /// Decrypts a ticket sent by the client.
+    ///
+    /// If the ticket wasn't issued with `key` or has expired, `None` is returned.
+    pub(crate) fn open(key: &[u8; ChaCha20Poly1305::KEY_SIZE], ticket: &[u8]) -> Option<Self> {
+        if ticket.len() < IV_SIZE + TAG_SIZE {
+            return None;
+        }
+        let (iv, ticket) = ticket.split_at(IV_SIZE);
+        let (ticket, tag) = ticket.split_at(ticket.len() - TAG_SIZE);
+        let mut plain = ticket.to_vec();
+        ChaCha20Poly1305::new(*key)
+            .decrypt_inline(
+                &mut plain,
+                &[],
+                iv.try_into().unwrap(),
+                tag.try_into().unwrap(),
+            )
+            .ok()?;
+
+        let (&version, bytes) = plain.split_first()?;
+        if version != Self::VERSION {
+            return None;
+        }
+        let (&suite, bytes) = bytes.split_first()?;
+        let cipher_suite = CipherList { suites: suite };
+        let (issued, bytes) = bytes.split_first_chunk()?;
+        let (lifetime, bytes) = bytes.split_first_chunk()?;
+        let (age_add, bytes) = bytes.split_first_chunk()?;
+        let (max_early_data, bytes) = bytes.split_first_chunk()?;
+
+        let alg = cipher_suite.hash_alg();
+        let (&psk_len, bytes) = bytes.split_first()?;
+        if psk_len as usize != alg.size() || bytes.len() < alg.size() {
+            return None;
+        }
+        let (psk, bytes) = bytes.split_at(alg.size());
+        let (server_name, bytes) = split_vec(bytes)?;
+        let (app_proto, bytes) = split_short_vec(bytes)?;
+        let (peer_cert, bytes) = split_vec(bytes)?;
+        if !bytes.is_empty() {
+            return None;
+        }
+
+        let ticket = Self {
+            cipher_suite,
+            psk: Digest::new(alg, psk),
+            issued: u64::from_be_bytes(*issued),
+            lifetime: u32::from_be_bytes(*lifetime),
+            age_add: u32::from_be_bytes(*age_add),
+            max_early_data: u32::from_be_bytes(*max_early_data),
+            server_name: Box::from(server_name),
+            app_proto: Box::from(app_proto),
+            peer_cert: Box::from(peer_cert),
+        };
+        if ticket.age() >= u64::from(ticket.lifetime) * 1000 {
+            return None;
+        }
+        Some(ticket)
+    }
+
+    /// The age of the ticket in milliseconds.
+    fn age(&self) -> u64 {
+        unix_millis().saturating_sub(self.issued)
+    }
+
+    /// Returns whether or not the ticket age reported by the client is close to the expected age.
+    ///
+    /// Early data from a ClientHello that arrives much later than expected may have been
+    /// replayed.
+    pub(crate) fn is_fresh(&self, obfuscated_age: u32) -> bool {
+        let client_age = u64::from(obfuscated_age.wrapping_sub(self.age_add));
+        self.age().abs_diff(client_age) <= Self::MAX_AGE_SKEW
+    }
+
+    /// Returns the NewSessionTicket message, including its header.
+    ///
+    /// `ticket` is the sealed ticket and `nonce` is the nonce it was created with.
+    pub(crate) fn to_msg(&self, ticket: &[u8], nonce: &[u8]) -> Vec<u8> {
+        let mut extensions = Vec::with_capacity(EarlyData::len_ticket());
+        if self.max_early_data != 0 {
+            EarlyData::write_ticket(&mut extensions, self.max_early_data);
+        }
+
+        let len = 2 * size_of::<u32>()
+            + Session::NONCE_LEN_SIZE
+            + nonce.len()
+            + Session::TICKET_LEN_SIZE
+            + ticket.len()
+            + Session::EXTENSIONS_LEN_SIZE
+            + extensions.len();
+        let mut msg = Vec::with_capacity(SHAKE_HEADER_SIZE + len);
+        msg.push(ShakeType::NewSessionTicket.to_byte());
+        msg.extend_from_slice(&(len as u32).to_be_bytes()[1..]);
+        msg.extend_from_slice(&self.lifetime.to_be_bytes());
+        msg.extend_from_slice(&self.age_add.to_be_bytes());
+        msg.push(nonce.len() as u8);
+        msg.extend_from_slice(nonce);
+        msg.extend_from_slice(&(ticket.len() as u16).to_be_bytes());
+        msg.extend_from_slice(ticket);
+        msg.extend_from_slice(&(extensions.len() as u16).to_be_bytes());
+        msg.extend_from_slice(&extensions);
+        msg
+    }
+}
+
+/// Splits a value with a two-byte length encoding from the front of `bytes`.
+fn split_vec(bytes: &[u8]) -> Option<(&[u8], &[u8])> {
+    let (len, bytes) = bytes.split_first_chunk::<2>()?;
+    let len = u16::from_be_bytes(*len) as usize;
+    if bytes.len() < len {
+        return None;
+    }
+    Some(bytes.split_at(len))
+}
+
+/// Splits a value with a one-byte length encoding from the front of `bytes`.
+fn split_short_vec(bytes: &[u8]) -> Option<(&[u8], &[u8])> {
+    let (&len, bytes) = bytes.split_first()?;
+    if bytes.len() < len as usize {
+        return None;
+    }
+    Some(bytes.split_at(len as usize))
+}
+
+/// The current time in milliseconds since the Unix epoch.
+fn unix_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |time| time.as_millis() as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use crylib::aead::chacha::ChaCha20Poly1305;
+    use crylib::aead::{Aead, IV_SIZE, TAG_SIZE};
+
+    use super::{unix_millis, ServerTicket, Session};
+    use crate::cipher_suites::CipherList;
+    use crate::key_schedule::{Digest, HashAlg};
+
+    const KEY: [u8; ChaCha20Poly1305::KEY_SIZE] = [0x42; ChaCha20Poly1305::KEY_SIZE];
+
+    fn session() -> Session {
+        Session {
+            cipher_suite: CipherList {
+                suites: CipherList::AES_256_GCM_SHA384,
+            },
+            psk: Digest::new(HashAlg::Sha384, &[0xaa; 48]),
+            ticket: Box::new([0xbb; 100]),
+            age_add: 0x12345678,
+            lifetime: 7200,
+            received: 1_700_000_000_000,
+            server_name: Box::from(b"example.com".as_slice()),
+            max_early_data: 0x4000,
+            app_proto: Box::from(b"h2".as_slice()),
+        }
+    }
+
+    fn server_ticket() -> ServerTicket {
+        ServerTicket {
+            cipher_suite: CipherList {
+                suites: CipherList::CHA_CHA_POLY1305_SHA256,
+            },
+            psk: Digest::new(HashAlg::Sha256, &[0xcc; 32]),
+            issued: unix_millis(),
+            lifetime: 7200,
+            age_add: 0xfffffff0,
+            max_early_data: 0x4000,
+            server_name: Box::from(b"example.com".as_slice()),
+            app_proto: Box::from(b"http/1.1".as_slice()),
+            peer_cert: Box::new([0xdd; 300]),
+        }
+    }
+
+    /// Decrypts a sealed ticket, modifies its contents with `edit`, and encrypts it again.
+    fn reseal(ticket: &[u8], edit: impl FnOnce(&mut Vec<u8>)) -> Vec<u8> {
+        let (iv, ticket) = ticket.split_at(IV_SIZE);
+        let (ticket, tag) = ticket.split_at(ticket.len() - TAG_SIZE);
+        let iv = iv.try_into().unwrap();
+        let mut plain = ticket.to_vec();
+        ChaCha20Poly1305::new(KEY)
+            .decrypt_inline(&mut plain, &[], iv, tag.try_into().unwrap())
+            .unwrap();
+        edit(&mut plain);
+        let tag = ChaCha20Poly1305::new(KEY).encrypt_inline(&mut plain, &[], iv);
+        [iv.as_slice(), &plain, &tag].concat()
+    }
+
+    #[test]
+    fn session_round_trip() {
+        let session = session();
+        let parsed = Session::from_bytes(&session.to_bytes()).unwrap();
+        assert_eq!(parsed.cipher_suite.suites, session.cipher_suite.suites);
+        assert!(parsed.hash_alg() == HashAlg::Sha384);
+        assert_eq!(*parsed.psk, *session.psk);
+        assert_eq!(parsed.ticket, session.ticket);
+        assert_eq!(parsed.age_add, session.age_add);
+        assert_eq!(parsed.lifetime, session.lifetime);
+        assert_eq!(parsed.received, session.received);
+        assert_eq!(parsed.server_name, session.server_name);
+        assert_eq!(parsed.max_early_data, session.max_early_data);
+        assert_eq!(parsed.app_proto, session.app_proto);
+    }
+
+    #[test]
+    fn bad_session() {
+        let bytes = session().to_bytes();
+        for len in 0..bytes.len() {
+            assert!(Session::from_bytes(&bytes[..len]).is_none());
+        }
+        assert!(Session::from_bytes(&[bytes.as_slice(), &[0]].concat()).is_none());
+
+        let mut wrong_version = bytes.clone();
+        wrong_version[0] = Session::VERSION + 1;
+        assert!(Session::from_bytes(&wrong_version).is_none());
+
+        // the PSK length must match the cipher suite's hash function
+        let mut wrong_suite = bytes.clone();
+        wrong_suite[1] = CipherList::AES_128_GCM_SHA256;
+        assert!(Session::from_bytes(&wrong_suite).is_none());
+        let mut two_suites = bytes;
+        two_suites[1] |= CipherList::AES_128_GCM_SHA256;
+        assert!(Session::from_bytes(&two_suites).is_none());
+    }
+
+    #[test]
+    fn ticket_round_trip() {
+        let ticket = server_ticket();
+        let sealed = ticket.seal(&KEY).unwrap();
+        let opened = ServerTicket::open(&KEY, &sealed).unwrap();
+        assert_eq!(opened.cipher_suite.suites, ticket.cipher_suite.suites);
+        assert!(opened.psk.alg() == HashAlg::Sha256);
+        assert_eq!(*opened.psk, *ticket.psk);
+        assert_eq!(opened.issued, ticket.issued);
+        assert_eq!(opened.lifetime, ticket.lifetime);
+        assert_eq!(opened.age_add, ticket.age_add);
+        assert_eq!(opened.max_early_data, ticket.max_early_data);
+        assert_eq!(opened.server_name, ticket.server_name);
+        assert_eq!(opened.app_proto, ticket.app_proto);
+        assert_eq!(opened.peer_cert, ticket.peer_cert);
+
+        // tickets are encrypted with a new IV each time
+        assert_ne!(sealed, ticket.seal(&KEY).unwrap());
+    }
+
+    #[test]
+    fn bad_ticket() {
+        let sealed = server_ticket().seal(&KEY).unwrap();
+        assert!(ServerTicket::open(&[0x43; ChaCha20Poly1305::KEY_SIZE], &sealed).is_none());
+        for len in 0..sealed.len() {
+            assert!(ServerTicket::open(&KEY, &sealed[..len]).is_none());
+        }
+        let mut modified = sealed.clone();
+        modified[IV_SIZE] ^= 1;
+        assert!(ServerTicket::open(&KEY, &modified).is_none());
+
+        let wrong_version = reseal(&sealed, |plain| plain[0] = ServerTicket::VERSION + 1);
+        assert!(ServerTicket::open(&KEY, &wrong_version).is_none());
+        let truncated = reseal(&sealed, |plain| plain.truncate(plain.len() - 1));
+        assert!(ServerTicket::open(&KEY, &truncated).is_none());
+        let trailing = reseal(&sealed, |plain| plain.push(0));
+        assert!(ServerTicket::open(&KEY, &trailing).is_none());
+        assert!(ServerTicket::open(&KEY, &reseal(&sealed, |_| ())).is_some());
+    }
+
+    #[test]
+    fn expired_ticket() {
+        let mut ticket = server_ticket();
+        ticket.issued -= u64::from(ticket.lifetime) * 1000;
+        assert!(ServerTicket::open(&KEY, &ticket.seal(&KEY).unwrap()).is_none());
+        ticket.issued += 60_000;
+        assert!(ServerTicket::open(&KEY, &ticket.seal(&KEY).unwrap()).is_some());
+    }
+
+    #[test]
+    fn freshness() {
+        let mut ticket = server_ticket();
+        // a ticket issued in the future always has an age of zero
+        ticket.issued = u64::MAX;
+        let obfuscate = |age: u32| age.wrapping_add(ticket.age_add);
+        assert!(ticket.is_fresh(obfuscate(0)));
+        assert!(ticket.is_fresh(obfuscate(ServerTicket::MAX_AGE_SKEW as u32)));
+        assert!(!ticket.is_fresh(obfuscate(ServerTicket::MAX_AGE_SKEW as u32 + 1)));
+        assert!(!ticket.is_fresh(obfuscate(u32::MAX)));
+
+        ticket.issued = unix_millis() - 60_000;
+        let obfuscate = |age: u32| age.wrapping_add(ticket.age_add);
+        assert!(ticket.is_fresh(obfuscate(60_000)));
+        assert!(ticket.is_fresh(obfuscate(55_000)));
+        assert!(!ticket.is_fresh(obfuscate(0)));
+        assert!(!ticket.is_fresh(obfuscate(120_000)));
+    }
+}
@@ -1,20 +1,101 @@
-use std::mem::MaybeUninit;
+use std::time::Duration;
 
-use crate::record::{EncryptedRecLayer, Io,};
+use crate::config::Config;
+use crate::error::ShakeResult;
+use crate::handshake::ShakeBuf;
+use crate::key_update::AppSecrets;
+use crate::record::{EncryptedRecLayer, Io};
+use crate::session::{EarlyDataStatus, Resumption, Session};
+use crate::{client, server};
 
 /// A TLS connection buffer.
 ///
 /// This connection buffer may be reused between multiple consecutive connections.
-pub struct Connection(pub(crate) Option<State>);
+pub struct Connection {
+    pub(crate) state: Option<State>,
+    /// The most recent session of a connection that has been closed.
+    pub(crate) session: Option<Session>,
+}
+
+impl Connection {
+    /// Starts a new connection, discarding the previous one and its session.
+    pub(crate) fn start(&mut self, state: State) -> &mut State {
+        self.session = None;
+        self.state.insert(state)
+    }
+
+    /// Drops the connection state.
+    ///
+    /// The most recent session is kept so it can still be retrieved.
+    pub(crate) fn end(&mut self) {
+        if let Some(session) = self.state.take().and_then(|state| state.session) {
+            self.session = Some(session);
+        }
+    }
+
+    /// The most recent session received from the server.
+    pub(crate) fn session(&self) -> Option<&Session> {
+        match self.state {
+            Some(ref state) => state.session.as_ref(),
+            None => self.session.as_ref(),
+        }
+    }
+}
+
+/// A handshake that would have blocked, which is resumed by calling the same handshake function.
+pub(crate) enum Shake {
+    Client(client::Progress),
+    Server(server::Progress),
+}
 
 pub(crate) struct State {
     pub(crate) rl: EncryptedRecLayer,
+    pub(crate) record_timeout: Duration,
+    /// Application data that has been decrypted but not yet read by the user.
+    pub(crate) app_data: Vec<u8>,
+    pub(crate) shake_buf: ShakeBuf,
+    /// The peer's end-entity certificate, once its chain has been validated.
+    pub(crate) peer_cert: Vec<u8>,
+    /// The secret used to accept tickets, once the client has completed the handshake.
+    pub(crate) resumption: Option<Resumption>,
+    /// The most recent session received from the server.
+    pub(crate) session: Option<Session>,
+    /// Whether or not early data was accepted during the handshake.
+    pub(crate) early_data: EarlyDataStatus,
+    /// The application traffic secrets, once they have been derived.
+    pub(crate) app_secrets: Option<AppSecrets>,
+    /// The negotiated application protocol, or an empty slice if none was negotiated.
+    pub(crate) app_proto: Box<[u8]>,
+    /// The handshake in progress, if it would have blocked.
+    pub(crate) shake: Option<Shake>,
 }
 
 impl State {
-    pub(crate) fn new(io: Io) -> Self {
+    pub(crate) fn new(io: Io, config: &Config) -> Self {
         Self {
-            rl: EncryptedRecLayer::new(io)
+            rl: EncryptedRecLayer::new(io, config.non_blocking),
+            record_timeout: Duration::from_millis(config.timeout_millis),
+            app_data: Vec::new(),
+            shake_buf: ShakeBuf::default(),
+            peer_cert: Vec::new(),
+            resumption: None,
+            session: None,
+            early_data: EarlyDataStatus::NotSent,
+            app_secrets: None,
+            app_proto: Box::default(),
+            shake: None,
+        }
+    }
+
+    /// Returns the result of an operation that would have blocked.
+    ///
+    /// Records that haven't been written yet take priority, since the peer may be waiting for
+    /// them before it sends anything.
+    pub(crate) fn want(&self) -> ShakeResult {
+        if self.rl.unenc_rl.has_pending() {
+            ShakeResult::WantWrite
+        } else {
+            ShakeResult::WantRead
         }
     }
 }
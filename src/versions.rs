@@ -1,7 +1,11 @@
+#[derive(Clone, Copy)]
 #[repr(u16)]
 pub(crate) enum ProtocolVersion {
+    #[expect(unused, reason = "only TLS 1.3 is supported")]
     SslThreeZero = 0x0300,
+    #[expect(unused, reason = "only TLS 1.3 is supported")]
     TlsOneZero = 0x0301,
+    #[expect(unused, reason = "only TLS 1.3 is supported")]
     TlsOneOne = 0x0302,
     TlsOneTwo = 0x0303,
     TlsOneThree = 0x0304,
@@ -0,0 +1,1398 @@
+//! X.509 v3 certificates and certificate chain validation.
+
+use std::net::IpAddr;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crylib::big_int::UBigInt;
+use crylib::ec::{ecdsa, ed25519, AffinePoint, Secp256r1, Secp384r1, Secp521r1};
+use crylib::finite_field::FieldElement;
+use crylib::hash::{Hasher, Sha256, Sha384, Sha512};
+use crylib::rsa;
+
+use crate::alert::Alert;
+use crate::der::{tag, DerError, Reader};
+use crate::dh::NamedGroup;
+
+/// Object identifiers, encoded as the contents of a DER OBJECT IDENTIFIER.
+mod oid {
+    pub(super) const EC_PUBLIC_KEY: &[u8] = &[0x2a, 0x86, 0x48, 0xce, 0x3d, 0x02, 0x01];
+    pub(super) const SECP256R1: &[u8] = &[0x2a, 0x86, 0x48, 0xce, 0x3d, 0x03, 0x01, 0x07];
+    pub(super) const SECP384R1: &[u8] = &[0x2b, 0x81, 0x04, 0x00, 0x22];
+    pub(super) const SECP521R1: &[u8] = &[0x2b, 0x81, 0x04, 0x00, 0x23];
+    pub(super) const RSA_ENCRYPTION: &[u8] =
+        &[0x2a, 0x86, 0x48, 0x86, 0xf7, 0x0d, 0x01, 0x01, 0x01];
+    pub(super) const ED25519: &[u8] = &[0x2b, 0x65, 0x70];
+
+    pub(super) const ECDSA_SHA256: &[u8] = &[0x2a, 0x86, 0x48, 0xce, 0x3d, 0x04, 0x03, 0x02];
+    pub(super) const ECDSA_SHA384: &[u8] = &[0x2a, 0x86, 0x48, 0xce, 0x3d, 0x04, 0x03, 0x03];
+    pub(super) const ECDSA_SHA512: &[u8] = &[0x2a, 0x86, 0x48, 0xce, 0x3d, 0x04, 0x03, 0x04];
+    pub(super) const RSA_PKCS1_SHA256: &[u8] =
+        &[0x2a, 0x86, 0x48, 0x86, 0xf7, 0x0d, 0x01, 0x01, 0x0b];
+    pub(super) const RSA_PKCS1_SHA384: &[u8] =
+        &[0x2a, 0x86, 0x48, 0x86, 0xf7, 0x0d, 0x01, 0x01, 0x0c];
+    pub(super) const RSA_PKCS1_SHA512: &[u8] =
+        &[0x2a, 0x86, 0x48, 0x86, 0xf7, 0x0d, 0x01, 0x01, 0x0d];
+    pub(super) const RSA_PSS: &[u8] = &[0x2a, 0x86, 0x48, 0x86, 0xf7, 0x0d, 0x01, 0x01, 0x0a];
+    pub(super) const MGF1: &[u8] = &[0x2a, 0x86, 0x48, 0x86, 0xf7, 0x0d, 0x01, 0x01, 0x08];
+
+    pub(super) const SHA256: &[u8] = &[0x60, 0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x02, 0x01];
+    pub(super) const SHA384: &[u8] = &[0x60, 0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x02, 0x02];
+    pub(super) const SHA512: &[u8] = &[0x60, 0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x02, 0x03];
+
+    pub(super) const KEY_USAGE: &[u8] = &[0x55, 0x1d, 0x0f];
+    pub(super) const SUBJECT_ALT_NAME: &[u8] = &[0x55, 0x1d, 0x11];
+    pub(super) const BASIC_CONSTRAINTS: &[u8] = &[0x55, 0x1d, 0x13];
+}
+
+/// DER is only used for certificates, so malformed DER means the certificate is bad.
+impl From<DerError> for Alert {
+    fn from(_: DerError) -> Self {
+        Alert::BadCert
+    }
+}
+
+/// A parsed X.509 certificate.
+///
+/// The certificate borrows from the DER-encoded buffer it was parsed from.
+pub(crate) struct Certificate<'a> {
+    /// The DER-encoded TBSCertificate, which is what the issuer signs.
+    pub(crate) tbs: &'a [u8],
+    pub(crate) issuer: Name<'a>,
+    pub(crate) validity: Validity,
+    pub(crate) subject: Name<'a>,
+    pub(crate) public_key: PublicKey<'a>,
+    pub(crate) key_usage: Option<KeyUsage>,
+    pub(crate) basic_constraints: Option<BasicConstraints>,
+    pub(crate) subject_alt_names: Option<SubjectAltNames<'a>>,
+    pub(crate) sig_alg: SigAlg,
+    pub(crate) signature: &'a [u8],
+}
+
+impl<'a> Certificate<'a> {
+    const V3: &'static [u8] = &[2];
+
+    /// Parses a DER-encoded certificate.
+    ///
+    /// Malformed certificates are rejected with [`Alert::BadCert`], and certificates that use
+    /// unsupported algorithms or critical extensions are rejected with
+    /// [`Alert::UnsupportedCert`].
+    pub(crate) fn parse(der: &'a [u8]) -> Result<Self, Alert> {
+        let mut outer = Reader::new(der);
+        let mut cert = outer.read_sequence()?;
+        if !outer.is_empty() {
+            return Err(Alert::BadCert);
+        }
+
+        let tbs = cert.read_any()?;
+        if tbs.tag != tag::SEQUENCE {
+            return Err(Alert::BadCert);
+        }
+        let sig_alg = SigAlg::parse(cert.read(tag::SEQUENCE)?)?;
+        let signature = cert.read_bit_string()?;
+        if !cert.is_empty() {
+            return Err(Alert::BadCert);
+        }
+
+        let mut tbs_reader = Reader::new(tbs.value);
+        let version = match tbs_reader.read_optional(tag::explicit(0))? {
+            Some(version) => Reader::new(version).read_uint()?,
+            // v1
+            None => &[0],
+        };
+        // the serial number isn't needed
+        tbs_reader.read_uint()?;
+        // the signature algorithm must be repeated inside the signed data
+        if SigAlg::parse(tbs_reader.read(tag::SEQUENCE)?)? != sig_alg {
+            return Err(Alert::BadCert);
+        }
+        let issuer = Name::parse(&mut tbs_reader)?;
+        let validity = Validity::parse(tbs_reader.read_sequence()?)?;
+        let subject = Name::parse(&mut tbs_reader)?;
+        let public_key = PublicKey::parse(tbs_reader.read_sequence()?)?;
+
+        // the unique identifiers are deprecated, but may be present
+        tbs_reader.read_optional(tag::implicit(1))?;
+        tbs_reader.read_optional(tag::implicit(2))?;
+
+        let mut cert = Self {
+            tbs: tbs.raw,
+            issuer,
+            validity,
+            subject,
+            public_key,
+            key_usage: None,
+            basic_constraints: None,
+            subject_alt_names: None,
+            sig_alg,
+            signature,
+        };
+
+        if let Some(extensions) = tbs_reader.read_optional(tag::explicit(3))? {
+            // only v3 certificates have extensions
+            if version != Self::V3 {
+                return Err(Alert::BadCert);
+            }
+            let mut extensions = Reader::new(extensions);
+            let extensions = extensions.read_sequence()?;
+            cert.parse_extensions(extensions)?;
+        }
+        if !tbs_reader.is_empty() {
+            return Err(Alert::BadCert);
+        }
+        Ok(cert)
+    }
+
+    fn parse_extensions(&mut self, mut extensions: Reader<'a>) -> Result<(), Alert> {
+        while !extensions.is_empty() {
+            let mut extension = extensions.read_sequence()?;
+            let id = extension.read(tag::OID)?;
+            let critical = match extension.peek_tag() {
+                Some(tag::BOOLEAN) => extension.read_bool()?,
+                _ => false,
+            };
+            let value = extension.read(tag::OCTET_STRING)?;
+            if !extension.is_empty() {
+                return Err(Alert::BadCert);
+            }
+
+            match id {
+                oid::KEY_USAGE if self.key_usage.is_none() => {
+                    self.key_usage = Some(KeyUsage::parse(value)?);
+                },
+                oid::BASIC_CONSTRAINTS if self.basic_constraints.is_none() => {
+                    self.basic_constraints = Some(BasicConstraints::parse(value)?);
+                },
+                oid::SUBJECT_ALT_NAME if self.subject_alt_names.is_none() => {
+                    self.subject_alt_names = Some(SubjectAltNames::parse(value)?);
+                },
+                // each extension may only appear once
+                oid::KEY_USAGE | oid::BASIC_CONSTRAINTS | oid::SUBJECT_ALT_NAME => {
+                    return Err(Alert::BadCert);
+                },
+                // unknown extensions must be rejected if they are critical
+                _ if critical => return Err(Alert::UnsupportedCert),
+                _ => (),
+            }
+        }
+        Ok(())
+    }
+
+    /// Checks that the certificate may issue certificates.
+    ///
+    /// `depth` is the number of intermediate certificates between this certificate and the
+    /// end-entity certificate.
+    fn check_issuer(&self, now: i64, depth: usize) -> Result<(), Alert> {
+        if !self.validity.contains(now) {
+            return Err(Alert::CertExpired);
+        }
+        match self.basic_constraints {
+            Some(BasicConstraints { ca: true, path_len }) => {
+                if path_len.is_some_and(|path_len| depth > path_len as usize) {
+                    return Err(Alert::BadCert);
+                }
+            },
+            _ => return Err(Alert::BadCert),
+        }
+        if self
+            .key_usage
+            .is_some_and(|key_usage| key_usage.usages & KeyUsage::KEY_CERT_SIGN == 0)
+        {
+            return Err(Alert::BadCert);
+        }
+        Ok(())
+    }
+
+    /// Verifies that the certificate was issued for `name`, which is a DNS name or an IP
+    /// address.
+    ///
+    /// Only the subject alternative names are checked, as described in RFC 6125.
+    pub(crate) fn verify_name(&self, name: &[u8]) -> Result<(), Alert> {
+        let Some(alt_names) = self.subject_alt_names else {
+            return Err(Alert::BadCert);
+        };
+        let ip_addr = str::from_utf8(name)
+            .ok()
+            .and_then(|name| name.parse::<IpAddr>().ok());
+
+        let matches = alt_names.iter().any(|alt_name| match (alt_name, ip_addr) {
+            (GeneralName::Ip(addr), Some(IpAddr::V4(ip_addr))) => addr == ip_addr.octets(),
+            (GeneralName::Ip(addr), Some(IpAddr::V6(ip_addr))) => addr == ip_addr.octets(),
+            (GeneralName::Dns(pattern), None) => dns_name_matches(pattern, name),
+            _ => false,
+        });
+        match matches {
+            true => Ok(()),
+            false => Err(Alert::BadCert),
+        }
+    }
+
+    /// Verifies that `cert` was signed with this certificate's public key.
+    fn verify_issued(&self, cert: &Certificate) -> Result<(), Alert> {
+        self.public_key
+            .verify(cert.sig_alg, cert.tbs, cert.signature)
+            .map_err(|err| match err {
+                Alert::DecryptErorr => Alert::BadCert,
+                err => err,
+            })
+    }
+}
+
+/// A distinguished name.
+#[derive(Clone, Copy)]
+pub(crate) struct Name<'a> {
+    /// The DER-encoded name, including its tag and length.
+    ///
+    /// Names are compared by their encoding.
+    pub(crate) raw: &'a [u8],
+}
+
+impl<'a> Name<'a> {
+    fn parse(reader: &mut Reader<'a>) -> Result<Self, DerError> {
+        let name = reader.read_any()?;
+        if name.tag != tag::SEQUENCE {
+            return Err(DerError);
+        }
+        // make sure every attribute is well-formed
+        let mut rdns = Reader::new(name.value);
+        while !rdns.is_empty() {
+            let mut rdn = Reader::new(rdns.read(tag::SET)?);
+            while !rdn.is_empty() {
+                let mut attribute = rdn.read_sequence()?;
+                attribute.read(tag::OID)?;
+                attribute.read_any()?;
+                if !attribute.is_empty() {
+                    return Err(DerError);
+                }
+            }
+        }
+        Ok(Self { raw: name.raw })
+    }
+}
+
+/// The period during which a certificate is valid, in seconds since the Unix epoch.
+#[derive(Clone, Copy)]
+pub(crate) struct Validity {
+    pub(crate) not_before: i64,
+    pub(crate) not_after: i64,
+}
+
+impl Validity {
+    fn parse(mut validity: Reader) -> Result<Self, DerError> {
+        let not_before = validity.read_time()?;
+        let not_after = validity.read_time()?;
+        if !validity.is_empty() {
+            return Err(DerError);
+        }
+        Ok(Self {
+            not_before,
+            not_after,
+        })
+    }
+
+    /// Returns whether `time`, in seconds since the Unix epoch, is within the validity period.
+    pub(crate) const fn contains(&self, time: i64) -> bool {
+        self.not_before <= time && time <= self.not_after
+    }
+}
+
+/// The public key of a certificate.
+#[derive(Clone, Copy)]
+pub(crate) enum PublicKey<'a> {
+    /// An elliptic curve key, which is an uncompressed point.
+    Ec { curve: NamedGroup, point: &'a [u8] },
+    /// An RSA key, with big-endian integers.
+    Rsa {
+        modulus: &'a [u8],
+        exponent: &'a [u8],
+    },
+    /// An Ed25519 key, in its RFC 8032 encoding.
+    Ed25519(&'a [u8; ed25519::KEY_SIZE]),
+}
+
+impl<'a> PublicKey<'a> {
+    /// Parses the contents of a SubjectPublicKeyInfo.
+    fn parse(mut spki: Reader<'a>) -> Result<Self, Alert> {
+        let mut alg = spki.read_sequence()?;
+        let key = spki.read_bit_string()?;
+        if !spki.is_empty() {
+            return Err(Alert::BadCert);
+        }
+
+        match alg.read(tag::OID)? {
+            oid::EC_PUBLIC_KEY => {
+                // only named curves are allowed
+                let curve = match alg.read(tag::OID)? {
+                    oid::SECP256R1 => NamedGroup::Secp256r1,
+                    oid::SECP384R1 => NamedGroup::Secp384r1,
+                    oid::SECP521R1 => NamedGroup::Secp521r1,
+                    _ => return Err(Alert::UnsupportedCert),
+                };
+                if !alg.is_empty() {
+                    return Err(Alert::BadCert);
+                }
+                Ok(Self::Ec { curve, point: key })
+            },
+            oid::RSA_ENCRYPTION => {
+                // the parameters must be NULL
+                if !alg.read(tag::NULL)?.is_empty() || !alg.is_empty() {
+                    return Err(Alert::BadCert);
+                }
+                let mut outer = Reader::new(key);
+                let mut key = outer.read_sequence()?;
+                let modulus = key.read_uint()?;
+                let exponent = key.read_uint()?;
+                if !key.is_empty() || !outer.is_empty() {
+                    return Err(Alert::BadCert);
+                }
+                Ok(Self::Rsa { modulus, exponent })
+            },
+            oid::ED25519 => {
+                // there are no parameters
+                if !alg.is_empty() {
+                    return Err(Alert::BadCert);
+                }
+                let key = key.try_into().map_err(|_| Alert::BadCert)?;
+                Ok(Self::Ed25519(key))
+            },
+            _ => Err(Alert::UnsupportedCert),
+        }
+    }
+
+    /// Verifies a signature of `msg` that was made with this key.
+    ///
+    /// Invalid signatures are rejected with [`Alert::DecryptErorr`].
+    pub(crate) fn verify(
+        &self,
+        sig_alg: SigAlg,
+        msg: &[u8],
+        signature: &[u8],
+    ) -> Result<(), Alert> {
+        match (*self, sig_alg) {
+            (Self::Ec { curve, point }, SigAlg::Ecdsa(hash)) => match curve {
+                NamedGroup::Secp256r1 => verify_secp256r1(point, msg, hash, signature),
+                NamedGroup::Secp384r1 => verify_secp384r1(point, msg, hash, signature),
+                NamedGroup::Secp521r1 => verify_secp521r1(point, msg, hash, signature),
+                _ => Err(Alert::UnsupportedCert),
+            },
+            (Self::Rsa { modulus, exponent }, SigAlg::RsaPkcs1(hash) | SigAlg::RsaPss(hash)) => {
+                let key =
+                    rsa::PublicKey::new(modulus, exponent).map_err(|_| Alert::UnsupportedCert)?;
+                let pss = matches!(sig_alg, SigAlg::RsaPss(_));
+                let verified = match (hash, pss) {
+                    (SigHash::Sha256, false) => {
+                        key.verify_pkcs1_v15::<{ Sha256::HASH_SIZE }, Sha256>(msg, signature)
+                    },
+                    (SigHash::Sha384, false) => {
+                        key.verify_pkcs1_v15::<{ Sha384::HASH_SIZE }, Sha384>(msg, signature)
+                    },
+                    (SigHash::Sha512, false) => {
+                        key.verify_pkcs1_v15::<{ Sha512::HASH_SIZE }, Sha512>(msg, signature)
+                    },
+                    (SigHash::Sha256, true) => {
+                        key.verify_pss::<{ Sha256::HASH_SIZE }, Sha256>(msg, signature)
+                    },
+                    (SigHash::Sha384, true) => {
+                        key.verify_pss::<{ Sha384::HASH_SIZE }, Sha384>(msg, signature)
+                    },
+                    (SigHash::Sha512, true) => {
+                        key.verify_pss::<{ Sha512::HASH_SIZE }, Sha512>(msg, signature)
+                    },
+                };
+                verified.map(|_| ()).map_err(|_| Alert::DecryptErorr)
+            },
+            (Self::Ed25519(key), SigAlg::Ed25519) => {
+                let signature = signature.try_into().map_err(|_| Alert::DecryptErorr)?;
+                ed25519::verify_signature(key, msg, signature)
+                    .map(|_| ())
+                    .map_err(|_| Alert::DecryptErorr)
+            },
+            // the signature algorithm doesn't match the type of key
+            _ => Err(Alert::BadCert),
+        }
+    }
+}
+
+/// Implements ECDSA signature verification for a NIST curve.
+///
+/// `$n` is the number of limbs in the curve's field elements, and `$coord_len` is the number of
+/// bytes in each encoded coordinate.
+macro_rules! impl_ecdsa_verify {
+    ($n:literal, $curve:ident, $coord_len:literal, $verify:ident) => {
+        #[doc = concat!("Verifies a DER-encoded ", stringify!($curve), " ECDSA signature.")]
+        ///
+        /// `point` is the uncompressed public key.
+        fn $verify(point: &[u8], msg: &[u8], hash: SigHash, signature: &[u8]) -> Result<(), Alert> {
+            const INT_SIZE: usize = $n * size_of::<u64>();
+            let to_int = |bytes: &[u8]| {
+                let mut buf = [0; INT_SIZE];
+                buf[INT_SIZE - bytes.len()..].copy_from_slice(bytes);
+                UBigInt::<$n>::from_be_bytes(buf)
+            };
+
+            // only uncompressed points are allowed
+            let [0x04, coords @ ..] = point else {
+                return Err(Alert::BadCert);
+            };
+            if coords.len() != 2 * $coord_len {
+                return Err(Alert::BadCert);
+            }
+            let (raw_x, raw_y) = coords.split_at($coord_len);
+            let x = FieldElement::try_from(to_int(raw_x)).map_err(|_| Alert::BadCert)?;
+            let y = FieldElement::try_from(to_int(raw_y)).map_err(|_| Alert::BadCert)?;
+            let pub_key = AffinePoint::<$n, $curve>::new(x, y)
+                .ok_or(Alert::BadCert)?
+                .as_projective();
+
+            let sig = ecdsa::Signature::decode_der(signature).map_err(|_| Alert::DecryptErorr)?;
+            ecdsa::verify_signature::<$n, $curve, _>(msg, &pub_key, |msg| hash.hash(msg), &sig)
+                .map_err(|_| Alert::DecryptErorr)?;
+            Ok(())
+        }
+    };
+}
+
+impl_ecdsa_verify!(4, Secp256r1, 32, verify_secp256r1);
+impl_ecdsa_verify!(6, Secp384r1, 48, verify_secp384r1);
+impl_ecdsa_verify!(9, Secp521r1, 66, verify_secp521r1);
+
+/// A hash function used in a certificate signature.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub(crate) enum SigHash {
+    Sha256,
+    Sha384,
+    Sha512,
+}
+
+impl SigHash {
+    fn hash(self, msg: &[u8]) -> Vec<u8> {
+        match self {
+            Self::Sha256 => Sha256::hash(msg).to_vec(),
+            Self::Sha384 => Sha384::hash(msg).to_vec(),
+            Self::Sha512 => Sha512::hash(msg).to_vec(),
+        }
+    }
+
+    const fn size(self) -> u32 {
+        match self {
+            Self::Sha256 => 32,
+            Self::Sha384 => 48,
+            Self::Sha512 => 64,
+        }
+    }
+
+    /// Parses an AlgorithmIdentifier of a hash function.
+    fn parse(mut alg: Reader) -> Result<Self, Alert> {
+        let hash = match alg.read(tag::OID)? {
+            oid::SHA256 => Self::Sha256,
+            oid::SHA384 => Self::Sha384,
+            oid::SHA512 => Self::Sha512,
+            _ => return Err(Alert::UnsupportedCert),
+        };
+        // the parameters may be absent or NULL
+        if alg
+            .read_optional(tag::NULL)?
+            .is_some_and(|null| !null.is_empty())
+            || !alg.is_empty()
+        {
+            return Err(Alert::BadCert);
+        }
+        Ok(hash)
+    }
+}
+
+/// The algorithm used to sign a certificate.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub(crate) enum SigAlg {
+    Ecdsa(SigHash),
+    RsaPkcs1(SigHash),
+    /// RSASSA-PSS with MGF1 using the same hash function and a salt as long as the hash.
+    RsaPss(SigHash),
+    /// Ed25519, which signs the message itself rather than a hash of it.
+    Ed25519,
+}
+
+impl SigAlg {
+    /// Parses the contents of an AlgorithmIdentifier.
+    fn parse(alg: &[u8]) -> Result<Self, Alert> {
+        let mut alg = Reader::new(alg);
+        let id = alg.read(tag::OID)?;
+        let sig_alg = match id {
+            oid::ECDSA_SHA256 => Self::Ecdsa(SigHash::Sha256),
+            oid::ECDSA_SHA384 => Self::Ecdsa(SigHash::Sha384),
+            oid::ECDSA_SHA512 => Self::Ecdsa(SigHash::Sha512),
+            oid::RSA_PKCS1_SHA256 => Self::RsaPkcs1(SigHash::Sha256),
+            oid::RSA_PKCS1_SHA384 => Self::RsaPkcs1(SigHash::Sha384),
+            oid::RSA_PKCS1_SHA512 => Self::RsaPkcs1(SigHash::Sha512),
+            oid::RSA_PSS => return Self::parse_pss_params(alg.read_sequence()?),
+            oid::ED25519 => Self::Ed25519,
+            _ => return Err(Alert::UnsupportedCert),
+        };
+        match sig_alg {
+            // ECDSA and Ed25519 have no parameters
+            Self::Ecdsa(_) | Self::Ed25519 if !alg.is_empty() => return Err(Alert::BadCert),
+            // PKCS #1 v1.5 has NULL parameters
+            Self::RsaPkcs1(_) if !alg.read(tag::NULL)?.is_empty() || !alg.is_empty() => {
+                return Err(Alert::BadCert)
+            },
+            _ => (),
+        }
+        Ok(sig_alg)
+    }
+
+    /// Parses the parameters of RSASSA-PSS.
+    ///
+    /// Only parameters that match the TLS 1.3 `rsa_pss_*` signature schemes are supported.
+    fn parse_pss_params(mut params: Reader) -> Result<Self, Alert> {
+        // the defaults (SHA-1) aren't supported, so the hash functions must be present
+        let hash = SigHash::parse(Reader::new(params.read(tag::explicit(0))?).read_sequence()?)?;
+
+        let mut mask_gen = Reader::new(params.read(tag::explicit(1))?).read_sequence()?;
+        if mask_gen.read(tag::OID)? != oid::MGF1
+            || SigHash::parse(mask_gen.read_sequence()?)? != hash
+            || !mask_gen.is_empty()
+        {
+            return Err(Alert::UnsupportedCert);
+        }
+
+        let salt_len = Reader::new(params.read(tag::explicit(2))?).read_uint()?;
+        if salt_len != [hash.size() as u8] {
+            return Err(Alert::UnsupportedCert);
+        }
+
+        // the trailer field must be the default (1)
+        if let Some(trailer) = params.read_optional(tag::explicit(3))? {
+            if Reader::new(trailer).read_uint()? != [1] {
+                return Err(Alert::UnsupportedCert);
+            }
+        }
+        if !params.is_empty() {
+            return Err(Alert::BadCert);
+        }
+        Ok(Self::RsaPss(hash))
+    }
+}
+
+/// The key usage extension.
+#[derive(Clone, Copy)]
+pub(crate) struct KeyUsage {
+    pub(crate) usages: u16,
+}
+
+impl KeyUsage {
+    pub(crate) const DIGITAL_SIGNATURE: u16 = 1 << 0;
+    pub(crate) const KEY_CERT_SIGN: u16 = 1 << 5;
+
+    fn parse(value: &[u8]) -> Result<Self, DerError> {
+        let mut reader = Reader::new(value);
+        let bit_string = reader.read(tag::BIT_STRING)?;
+        if !reader.is_empty() {
+            return Err(DerError);
+        }
+        // bit 0 is the most significant bit of the first byte
+        let usages = match *bit_string {
+            [unused, first] if unused < 8 => first.reverse_bits() as u16,
+            [unused, first, second] if unused < 8 => {
+                first.reverse_bits() as u16 | (second.reverse_bits() as u16) << 8
+            },
+            _ => return Err(DerError),
+        };
+        Ok(Self { usages })
+    }
+}
+
+/// The basic constraints extension.
+#[derive(Clone, Copy)]
+pub(crate) struct BasicConstraints {
+    /// Whether the certificate belongs to a certificate authority.
+    pub(crate) ca: bool,
+    /// The maximum number of intermediate certificates that may follow this one.
+    pub(crate) path_len: Option<u8>,
+}
+
+impl BasicConstraints {
+    fn parse(value: &[u8]) -> Result<Self, DerError> {
+        let mut outer = Reader::new(value);
+        let mut constraints = outer.read_sequence()?;
+        if !outer.is_empty() {
+            return Err(DerError);
+        }
+
+        let ca = match constraints.peek_tag() {
+            Some(tag::BOOLEAN) => {
+                // DER requires the default value to be omitted
+                if !constraints.read_bool()? {
+                    return Err(DerError);
+                }
+                true
+            },
+            _ => false,
+        };
+        let path_len = match constraints.peek_tag() {
+            Some(tag::INTEGER) => match constraints.read_uint()? {
+                [len] => Some(*len),
+                _ => return Err(DerError),
+            },
+            _ => None,
+        };
+        if !constraints.is_empty() {
+            return Err(DerError);
+        }
+        Ok(Self { ca, path_len })
+    }
+}
+
+/// A name in the subject alternative name extension.
+#[derive(Clone, Copy)]
+pub(crate) enum GeneralName<'a> {
+    Dns(&'a [u8]),
+    Ip(&'a [u8]),
+    /// A type of name that isn't supported.
+    Other,
+}
+
+/// The subject alternative name extension.
+#[derive(Clone, Copy)]
+pub(crate) struct SubjectAltNames<'a> {
+    names: &'a [u8],
+}
+
+impl<'a> SubjectAltNames<'a> {
+    const DNS_NAME: u8 = tag::implicit(2);
+    const IP_ADDRESS: u8 = tag::implicit(7);
+
+    fn parse(value: &'a [u8]) -> Result<Self, DerError> {
+        let mut outer = Reader::new(value);
+        let names = outer.read(tag::SEQUENCE)?;
+        if !outer.is_empty() || names.is_empty() {
+            return Err(DerError);
+        }
+        // make sure every name is well-formed
+        let mut reader = Reader::new(names);
+        while !reader.is_empty() {
+            reader.read_any()?;
+        }
+        Ok(Self { names })
+    }
+
+    /// Returns an iterator over the names.
+    pub(crate) fn iter(&self) -> impl Iterator<Item = GeneralName<'a>> {
+        let mut reader = Reader::new(self.names);
+        core::iter::from_fn(move || {
+            let name = reader.read_any().ok()?;
+            Some(match name.tag {
+                Self::DNS_NAME => GeneralName::Dns(name.value),
+                Self::IP_ADDRESS => GeneralName::Ip(name.value),
+                _ => GeneralName::Other,
+            })
+        })
+    }
+}
+
+/// Returns whether a DNS name from a certificate matches the name of the server.
+///
+/// Names are compared case-insensitively and a trailing dot in `name` is ignored. A wildcard is
+/// only allowed as the entire leftmost label of `pattern`, where it matches exactly one label.
+fn dns_name_matches(pattern: &[u8], name: &[u8]) -> bool {
+    let name = name.strip_suffix(b".").unwrap_or(name);
+    if !is_dns_name(name) || !pattern.is_ascii() {
+        return false;
+    }
+    match pattern.strip_prefix(b"*.") {
+        Some(suffix) => {
+            // the wildcard can't cover a top-level domain, such as in `*.com`
+            if !suffix.contains(&b'.') || suffix.contains(&b'*') {
+                return false;
+            }
+            match name.iter().position(|&byte| byte == b'.') {
+                Some(dot) => name[dot + 1..].eq_ignore_ascii_case(suffix),
+                None => false,
+            }
+        },
+        // partial wildcards, such as in `w*.example.com`, aren't supported
+        None if pattern.contains(&b'*') => false,
+        None => name.eq_ignore_ascii_case(pattern),
+    }
+}
+
+/// Returns whether `name` is a syntactically valid DNS name without a trailing dot.
+fn is_dns_name(name: &[u8]) -> bool {
+    const MAX_LEN: usize = 253;
+    const MAX_LABEL_LEN: usize = 63;
+
+    name.len() <= MAX_LEN
+        && name.split(|&byte| byte == b'.').all(|label| {
+            !label.is_empty()
+                && label.len() <= MAX_LABEL_LEN
+                && !label.starts_with(b"-")
+                && !label.ends_with(b"-")
+                && label
+                    .iter()
+                    .all(|&byte| byte.is_ascii_alphanumeric() || byte == b'-' || byte == b'_')
+        })
+}
+
+/// The maximum number of certificates in a path, including the trust anchor.
+const MAX_PATH_LEN: usize = 8;
+
+/// Returns the current time in seconds since the Unix epoch.
+pub(crate) fn unix_time() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |time| time.as_secs() as i64)
+}
+
+/// Validates a certificate chain against the trust anchors, returning the end-entity
+/// certificate.
+///
+/// `certs` starts with the end-entity certificate, but the other certificates may be in any
+/// order and may include certificates that aren't part of the path. Only the subject and public
+/// key of a trust anchor are used. `now` is the current time in seconds since the Unix epoch.
+pub(crate) fn verify_chain<'a>(
+    certs: &[&'a [u8]],
+    anchors: &[Box<[u8]>],
+    now: i64,
+) -> Result<Certificate<'a>, Alert> {
+    let mut chain = certs
+        .iter()
+        .map(|cert| Certificate::parse(cert))
+        .collect::<Result<Vec<_>, _>>()?;
+    if chain.is_empty() {
+        return Err(Alert::DecodeError);
+    }
+    let leaf = chain.remove(0);
+
+    if !leaf.validity.contains(now) {
+        return Err(Alert::CertExpired);
+    }
+    if leaf
+        .key_usage
+        .is_some_and(|key_usage| key_usage.usages & KeyUsage::DIGITAL_SIGNATURE == 0)
+    {
+        return Err(Alert::BadCert);
+    }
+
+    // the end-entity certificate may be trusted directly
+    if anchors.iter().any(|anchor| **anchor == *certs[0]) {
+        return Ok(leaf);
+    }
+    let anchors = anchors
+        .iter()
+        .filter_map(|anchor| Certificate::parse(anchor).ok())
+        .collect::<Vec<_>>();
+    PathBuilder::new(&chain, &anchors, now).build(&leaf, 0)?;
+    Ok(leaf)
+}
+
+/// Searches for a path from a certificate to a trust anchor.
+struct PathBuilder<'a, 'b> {
+    intermediates: &'b [Certificate<'a>],
+    anchors: &'b [Certificate<'b>],
+    now: i64,
+    /// Whether or not each intermediate certificate is already on the current path.
+    on_path: Vec<bool>,
+    /// The number of signatures checked so far.
+    sig_checks: usize,
+}
+
+impl<'a, 'b> PathBuilder<'a, 'b> {
+    /// The maximum number of signatures checked while building a path.
+    ///
+    /// Without a limit, a peer could send many certificates that all issue each other and make
+    /// the search take exponential time.
+    const MAX_SIG_CHECKS: usize = 64;
+
+    fn new(intermediates: &'b [Certificate<'a>], anchors: &'b [Certificate<'b>], now: i64) -> Self {
+        Self {
+            intermediates,
+            anchors,
+            now,
+            on_path: vec![false; intermediates.len()],
+            sig_checks: 0,
+        }
+    }
+
+    /// Finds a path from `cert` to a trust anchor.
+    ///
+    /// `depth` is the number of intermediate certificates between `cert` and the end-entity
+    /// certificate, including `cert` itself if it is an intermediate.
+    fn build(&mut self, cert: &Certificate, depth: usize) -> Result<(), Alert> {
+        let mut err = Alert::UnknownCa;
+        for anchor in self.anchors {
+            if anchor.subject.raw != cert.issuer.raw {
+                continue;
+            }
+            match self.verify_issued(anchor, cert) {
+                Ok(()) => return Ok(()),
+                Err(anchor_err) => err = anchor_err,
+            }
+        }
+
+        // the end-entity certificate and trust anchor take up two places in the path
+        if depth + 2 >= MAX_PATH_LEN {
+            return Err(err);
+        }
+        for (index, issuer) in self.intermediates.iter().enumerate() {
+            // a certificate can't appear twice in a path
+            if self.on_path[index] || issuer.subject.raw != cert.issuer.raw {
+                continue;
+            }
+            let result = issuer
+                .check_issuer(self.now, depth)
+                .and_then(|()| self.verify_issued(issuer, cert))
+                .and_then(|()| {
+                    self.on_path[index] = true;
+                    let result = self.build(issuer, depth + 1);
+                    self.on_path[index] = false;
+                    result
+                });
+            match result {
+                Ok(()) => return Ok(()),
+                Err(issuer_err) => err = issuer_err,
+            }
+        }
+        Err(err)
+    }
+
+    /// Verifies that `cert` was signed by `issuer`, failing once too many signatures have been
+    /// checked.
+    fn verify_issued(&mut self, issuer: &Certificate, cert: &Certificate) -> Result<(), Alert> {
+        if self.sig_checks >= Self::MAX_SIG_CHECKS {
+            return Err(Alert::BadCert);
+        }
+        self.sig_checks += 1;
+        issuer.verify_issued(cert)
+    }
+}
+
+/// Decodes a DER-encoded private key into the format of [`Identity::priv_key`].
+///
+/// Both PKCS #8 and SEC 1 (`EC PRIVATE KEY`) encodings are accepted, but only for secp256r1 and
+/// Ed25519 keys.
+///
+/// [`Identity::priv_key`]: crate::config::Identity::priv_key
+pub(crate) fn parse_priv_key(der: &[u8]) -> Result<[u8; 32], DerError> {
+    let mut outer = Reader::new(der);
+    let mut key = outer.read_sequence()?;
+    if !outer.is_empty() {
+        return Err(DerError);
+    }
+    let version = key.read(tag::INTEGER)?;
+    // a SEC 1 key holds the key directly, and PKCS #8 starts with the algorithm
+    if key.peek_tag() == Some(tag::OCTET_STRING) {
+        if version != [1] {
+            return Err(DerError);
+        }
+        return parse_ec_priv_key(key);
+    }
+    // version 2 of PKCS #8 only adds optional fields
+    if version != [0] && version != [1] {
+        return Err(DerError);
+    }
+    let mut alg = key.read_sequence()?;
+    let inner = key.read(tag::OCTET_STRING)?;
+    // attributes and the public key may follow, but they aren't needed
+
+    let mut inner = Reader::new(inner);
+    let priv_key = match alg.read(tag::OID)? {
+        oid::EC_PUBLIC_KEY => {
+            if alg.read(tag::OID)? != oid::SECP256R1 {
+                return Err(DerError);
+            }
+            let mut ec_key = inner.read_sequence()?;
+            if ec_key.read(tag::INTEGER)? != [1] {
+                return Err(DerError);
+            }
+            parse_ec_priv_key(ec_key)?
+        },
+        oid::ED25519 => inner
+            .read(tag::OCTET_STRING)?
+            .try_into()
+            .map_err(|_| DerError)?,
+        _ => return Err(DerError),
+    };
+    if !alg.is_empty() || !inner.is_empty() {
+        return Err(DerError);
+    }
+    Ok(priv_key)
+}
+
+/// Decodes the contents of a SEC 1 ECPrivateKey after its version, which must use secp256r1.
+fn parse_ec_priv_key(mut key: Reader) -> Result<[u8; 32], DerError> {
+    let scalar = key.read(tag::OCTET_STRING)?;
+    // the curve may be left out if it is known from elsewhere
+    if let Some(params) = key.read_optional(tag::explicit(0))? {
+        let mut params = Reader::new(params);
+        if params.read(tag::OID)? != oid::SECP256R1 || !params.is_empty() {
+            return Err(DerError);
+        }
+    }
+    // the public key may follow, but it isn't needed
+
+    // the scalar should be 32 bytes, but some encoders strip leading zeros
+    if scalar.is_empty() || scalar.len() > 32 {
+        return Err(DerError);
+    }
+    let mut priv_key = [0; 32];
+    priv_key[32 - scalar.len()..].copy_from_slice(scalar);
+    Ok(priv_key)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        dns_name_matches, verify_chain, Certificate, PathBuilder, PublicKey, SigAlg, SigHash,
+    };
+    use crate::alert::Alert;
+    use crate::dh::NamedGroup;
+
+    // self-signed ECDSA P-256 and RSA-2048 certificates, generated with OpenSSL
+    const P256_LEAF: [u8; 419] = [
+        0x30, 0x82, 0x01, 0x9f, 0x30, 0x82, 0x01, 0x45, 0xa0, 0x03, 0x02, 0x01, 0x02, 0x02, 0x14,
+        0x7e, 0xf0, 0x44, 0x76, 0x89, 0xda, 0x5f, 0x3b, 0x44, 0x60, 0x7e, 0xdc, 0x4c, 0xdb, 0x15,
+        0xbd, 0xcf, 0x86, 0xf3, 0x47, 0x30, 0x0a, 0x06, 0x08, 0x2a, 0x86, 0x48, 0xce, 0x3d, 0x04,
+        0x03, 0x02, 0x30, 0x19, 0x31, 0x17, 0x30, 0x15, 0x06, 0x03, 0x55, 0x04, 0x03, 0x0c, 0x0e,
+        0x65, 0x63, 0x2e, 0x65, 0x78, 0x61, 0x6d, 0x70, 0x6c, 0x65, 0x2e, 0x63, 0x6f, 0x6d, 0x30,
+        0x1e, 0x17, 0x0d, 0x32, 0x34, 0x30, 0x31, 0x30, 0x31, 0x30, 0x30, 0x30, 0x30, 0x30, 0x30,
+        0x5a, 0x17, 0x0d, 0x33, 0x34, 0x30, 0x31, 0x30, 0x31, 0x30, 0x30, 0x30, 0x30, 0x30, 0x30,
+        0x5a, 0x30, 0x19, 0x31, 0x17, 0x30, 0x15, 0x06, 0x03, 0x55, 0x04, 0x03, 0x0c, 0x0e, 0x65,
+        0x63, 0x2e, 0x65, 0x78, 0x61, 0x6d, 0x70, 0x6c, 0x65, 0x2e, 0x63, 0x6f, 0x6d, 0x30, 0x59,
+        0x30, 0x13, 0x06, 0x07, 0x2a, 0x86, 0x48, 0xce, 0x3d, 0x02, 0x01, 0x06, 0x08, 0x2a, 0x86,
+        0x48, 0xce, 0x3d, 0x03, 0x01, 0x07, 0x03, 0x42, 0x00, 0x04, 0xf5, 0xc8, 0x2d, 0x5b, 0xa0,
+        0xae, 0xd4, 0x6b, 0x53, 0xff, 0xa8, 0x65, 0x36, 0xc0, 0xb0, 0x87, 0x8b, 0x68, 0x2d, 0x7b,
+        0xdb, 0xac, 0x5b, 0xab, 0xb3, 0xc8, 0x1f, 0xb5, 0xf4, 0x7c, 0x5a, 0x60, 0x1e, 0xa4, 0x30,
+        0x36, 0xd5, 0x78, 0x39, 0x79, 0x24, 0xd1, 0xae, 0x37, 0xbf, 0x3b, 0x0d, 0x75, 0x46, 0x74,
+        0x81, 0xf9, 0x6d, 0x80, 0x05, 0xc6, 0x66, 0xeb, 0x78, 0x4d, 0x7a, 0x01, 0x04, 0x0b, 0xa3,
+        0x6b, 0x30, 0x69, 0x30, 0x1d, 0x06, 0x03, 0x55, 0x1d, 0x0e, 0x04, 0x16, 0x04, 0x14, 0x8e,
+        0x12, 0xc0, 0x34, 0x93, 0x1a, 0xa3, 0xbe, 0x9d, 0xc0, 0x93, 0x92, 0x6c, 0xc7, 0x92, 0xf9,
+        0xcd, 0x81, 0x1a, 0xd3, 0x30, 0x1f, 0x06, 0x03, 0x55, 0x1d, 0x23, 0x04, 0x18, 0x30, 0x16,
+        0x80, 0x14, 0x8e, 0x12, 0xc0, 0x34, 0x93, 0x1a, 0xa3, 0xbe, 0x9d, 0xc0, 0x93, 0x92, 0x6c,
+        0xc7, 0x92, 0xf9, 0xcd, 0x81, 0x1a, 0xd3, 0x30, 0x19, 0x06, 0x03, 0x55, 0x1d, 0x11, 0x04,
+        0x12, 0x30, 0x10, 0x82, 0x0e, 0x65, 0x63, 0x2e, 0x65, 0x78, 0x61, 0x6d, 0x70, 0x6c, 0x65,
+        0x2e, 0x63, 0x6f, 0x6d, 0x30, 0x0c, 0x06, 0x03, 0x55, 0x1d, 0x13, 0x01, 0x01, 0xff, 0x04,
+        0x02, 0x30, 0x00, 0x30, 0x0a, 0x06, 0x08, 0x2a, 0x86, 0x48, 0xce, 0x3d, 0x04, 0x03, 0x02,
+        0x03, 0x48, 0x00, 0x30, 0x45, 0x02, 0x20, 0x06, 0x48, 0x42, 0xa0, 0x72, 0xac, 0x1d, 0x0c,
+        0x97, 0x20, 0x33, 0x59, 0xb7, 0x40, 0x23, 0xbd, 0xd6, 0xae, 0x2a, 0xe0, 0x29, 0xc6, 0x69,
+        0x7d, 0x92, 0x89, 0xe3, 0x5b, 0xb2, 0x79, 0x5f, 0x12, 0x02, 0x21, 0x00, 0xc3, 0x9d, 0xc1,
+        0x50, 0x95, 0x13, 0xa9, 0x83, 0xb0, 0xd9, 0x99, 0xfc, 0x0c, 0x4c, 0xfc, 0x31, 0x43, 0xe4,
+        0x21, 0xf5, 0xaa, 0x5a, 0x22, 0x2a, 0x20, 0x81, 0x05, 0x74, 0x5c, 0xd9, 0x14, 0xda,
+    ];
+
+    const RSA_LEAF: [u8; 818] = [
+        0x30, 0x82, 0x03, 0x2e, 0x30, 0x82, 0x02, 0x16, 0xa0, 0x03, 0x02, 0x01, 0x02, 0x02, 0x14,
+        0x1a, 0x22, 0x86, 0x31, 0x7e, 0x5c, 0x1e, 0x5c, 0x2e, 0x46, 0x99, 0x11, 0x7f, 0xad, 0xc6,
+        0xef, 0x08, 0xe3, 0xd0, 0x6b, 0x30, 0x0d, 0x06, 0x09, 0x2a, 0x86, 0x48, 0x86, 0xf7, 0x0d,
+        0x01, 0x01, 0x0b, 0x05, 0x00, 0x30, 0x1a, 0x31, 0x18, 0x30, 0x16, 0x06, 0x03, 0x55, 0x04,
+        0x03, 0x0c, 0x0f, 0x72, 0x73, 0x61, 0x2e, 0x65, 0x78, 0x61, 0x6d, 0x70, 0x6c, 0x65, 0x2e,
+        0x63, 0x6f, 0x6d, 0x30, 0x1e, 0x17, 0x0d, 0x32, 0x34, 0x30, 0x31, 0x30, 0x31, 0x30, 0x30,
+        0x30, 0x30, 0x30, 0x30, 0x5a, 0x17, 0x0d, 0x33, 0x34, 0x30, 0x31, 0x30, 0x31, 0x30, 0x30,
+        0x30, 0x30, 0x30, 0x30, 0x5a, 0x30, 0x1a, 0x31, 0x18, 0x30, 0x16, 0x06, 0x03, 0x55, 0x04,
+        0x03, 0x0c, 0x0f, 0x72, 0x73, 0x61, 0x2e, 0x65, 0x78, 0x61, 0x6d, 0x70, 0x6c, 0x65, 0x2e,
+        0x63, 0x6f, 0x6d, 0x30, 0x82, 0x01, 0x22, 0x30, 0x0d, 0x06, 0x09, 0x2a, 0x86, 0x48, 0x86,
+        0xf7, 0x0d, 0x01, 0x01, 0x01, 0x05, 0x00, 0x03, 0x82, 0x01, 0x0f, 0x00, 0x30, 0x82, 0x01,
+        0x0a, 0x02, 0x82, 0x01, 0x01, 0x00, 0xbb, 0xd6, 0xd8, 0x35, 0xa1, 0x9c, 0x37, 0x62, 0xee,
+        0xd8, 0x77, 0xbc, 0x0e, 0x23, 0xc6, 0x62, 0x3e, 0x35, 0x3d, 0x2b, 0xd9, 0xb3, 0xa1, 0x5e,
+        0x34, 0x17, 0x85, 0x3b, 0xf8, 0xe6, 0x18, 0xc2, 0xc8, 0x58, 0x20, 0x0e, 0x03, 0x6a, 0x35,
+        0x5c, 0x1c, 0x6c, 0x6a, 0xbc, 0x46, 0xc2, 0x53, 0x6d, 0x19, 0x58, 0x3a, 0x02, 0xc6, 0x30,
+        0x94, 0x5c, 0x7f, 0x8f, 0x3d, 0x11, 0x69, 0xf8, 0x2d, 0xef, 0x46, 0xf8, 0x8b, 0x80, 0x5a,
+        0xa4, 0xef, 0x57, 0xca, 0x8a, 0xd5, 0x61, 0x18, 0x66, 0xf1, 0xea, 0x81, 0x1e, 0x93, 0xb9,
+        0x82, 0xfa, 0x5e, 0xea, 0x62, 0xf9, 0x18, 0xda, 0xea, 0x47, 0x4b, 0xeb, 0x41, 0xef, 0xc5,
+        0x75, 0x35, 0x1e, 0x1d, 0xda, 0x70, 0x44, 0x28, 0x88, 0x8a, 0xfa, 0x55, 0xc7, 0x27, 0x06,
+        0x78, 0x5e, 0xa0, 0xa0, 0xe0, 0x6d, 0xb4, 0x45, 0x0a, 0xce, 0x55, 0xc7, 0x61, 0x91, 0x7f,
+        0x0d, 0xb0, 0x5d, 0xf9, 0x99, 0x66, 0x8a, 0x31, 0x0e, 0x70, 0x1e, 0xb4, 0xc5, 0x31, 0x8c,
+        0xf2, 0xd8, 0x35, 0x60, 0x56, 0x8e, 0x72, 0x42, 0x77, 0x1e, 0x47, 0x57, 0x66, 0x9e, 0x2f,
+        0x10, 0xcb, 0x11, 0x66, 0x37, 0x92, 0xdb, 0x27, 0x88, 0x79, 0x06, 0x9b, 0xc0, 0xaa, 0x8a,
+        0xd3, 0x88, 0x22, 0xce, 0x58, 0x47, 0x3a, 0xa7, 0x42, 0xa9, 0x7a, 0xcd, 0x6b, 0xd9, 0xe0,
+        0xc5, 0x69, 0x2f, 0x17, 0xdd, 0xe1, 0x61, 0xac, 0xff, 0x1b, 0xa9, 0x0d, 0xd6, 0xe2, 0xb5,
+        0xcb, 0x98, 0xd4, 0xbb, 0xf7, 0x80, 0x05, 0x7b, 0xac, 0x87, 0xad, 0x73, 0x1d, 0x69, 0x1d,
+        0xec, 0x96, 0xe7, 0x1b, 0xc1, 0x61, 0x2f, 0x36, 0x8e, 0x39, 0x0e, 0x80, 0xe3, 0x1e, 0x9c,
+        0x58, 0xd3, 0xb4, 0x06, 0x92, 0x14, 0xab, 0xac, 0x4a, 0x06, 0x13, 0x95, 0x01, 0x55, 0xb9,
+        0x4c, 0xa0, 0x57, 0x28, 0x05, 0xd6, 0xc9, 0x02, 0x03, 0x01, 0x00, 0x01, 0xa3, 0x6c, 0x30,
+        0x6a, 0x30, 0x1d, 0x06, 0x03, 0x55, 0x1d, 0x0e, 0x04, 0x16, 0x04, 0x14, 0xaa, 0xf5, 0xb9,
+        0xbc, 0xfc, 0xb8, 0x3d, 0xbf, 0x2e, 0x91, 0x57, 0xde, 0xb9, 0x85, 0xa0, 0xfe, 0x18, 0x51,
+        0xc0, 0xd9, 0x30, 0x1f, 0x06, 0x03, 0x55, 0x1d, 0x23, 0x04, 0x18, 0x30, 0x16, 0x80, 0x14,
+        0xaa, 0xf5, 0xb9, 0xbc, 0xfc, 0xb8, 0x3d, 0xbf, 0x2e, 0x91, 0x57, 0xde, 0xb9, 0x85, 0xa0,
+        0xfe, 0x18, 0x51, 0xc0, 0xd9, 0x30, 0x1a, 0x06, 0x03, 0x55, 0x1d, 0x11, 0x04, 0x13, 0x30,
+        0x11, 0x82, 0x0f, 0x72, 0x73, 0x61, 0x2e, 0x65, 0x78, 0x61, 0x6d, 0x70, 0x6c, 0x65, 0x2e,
+        0x63, 0x6f, 0x6d, 0x30, 0x0c, 0x06, 0x03, 0x55, 0x1d, 0x13, 0x01, 0x01, 0xff, 0x04, 0x02,
+        0x30, 0x00, 0x30, 0x0d, 0x06, 0x09, 0x2a, 0x86, 0x48, 0x86, 0xf7, 0x0d, 0x01, 0x01, 0x0b,
+        0x05, 0x00, 0x03, 0x82, 0x01, 0x01, 0x00, 0x3c, 0x83, 0xa6, 0xaa, 0x1d, 0xc0, 0x89, 0x4d,
+        0xf1, 0x36, 0x88, 0xc5, 0x4b, 0xe8, 0x14, 0x09, 0x57, 0x28, 0x1e, 0x3e, 0x4d, 0xf3, 0xef,
+        0x7d, 0xb2, 0xa9, 0xdb, 0xc9, 0x5d, 0x58, 0x52, 0x92, 0x47, 0xae, 0x8a, 0x45, 0x09, 0x06,
+        0x9e, 0x85, 0xe8, 0x40, 0x86, 0x9f, 0x0f, 0x6b, 0xf2, 0x40, 0xf2, 0x3e, 0x81, 0xf4, 0xf8,
+        0x9b, 0x8a, 0x82, 0xd7, 0x96, 0x11, 0xb3, 0xa1, 0xf8, 0x18, 0x8d, 0x2b, 0x15, 0x6e, 0x3c,
+        0x78, 0xd1, 0xfd, 0xe3, 0x2d, 0x80, 0x69, 0x40, 0xcb, 0xcf, 0xfe, 0xde, 0x93, 0x3c, 0x69,
+        0x3e, 0xcc, 0x34, 0xdb, 0x53, 0x63, 0x19, 0x43, 0x8e, 0x71, 0x8a, 0x4f, 0x40, 0x56, 0x2d,
+        0x2b, 0x41, 0x52, 0x07, 0xcd, 0x0d, 0x50, 0x40, 0x73, 0xfd, 0x45, 0xcc, 0x34, 0xcb, 0x1a,
+        0x84, 0xe5, 0xf3, 0xb9, 0xa8, 0xff, 0xc7, 0xee, 0x11, 0x5e, 0xce, 0xc6, 0x5a, 0xbe, 0x29,
+        0x73, 0xc9, 0x5a, 0x17, 0x59, 0x78, 0xed, 0xa5, 0x1d, 0x60, 0x0d, 0x45, 0x01, 0xed, 0xef,
+        0x9e, 0x29, 0x3b, 0x25, 0x60, 0x04, 0x49, 0xa8, 0x58, 0x93, 0x2d, 0xb4, 0x1b, 0x62, 0x06,
+        0xe6, 0x93, 0xc6, 0x85, 0x2f, 0x86, 0xfa, 0xda, 0x9e, 0x6b, 0xce, 0x92, 0xa2, 0x2e, 0x65,
+        0x46, 0xeb, 0x88, 0x6f, 0x3d, 0x48, 0xa6, 0xeb, 0x96, 0xad, 0x79, 0x87, 0x28, 0xbe, 0x5a,
+        0x30, 0x76, 0x21, 0x00, 0x07, 0x2b, 0xfe, 0x4a, 0x46, 0xff, 0x88, 0x23, 0x6b, 0xa6, 0x4e,
+        0xa5, 0xef, 0xe8, 0xe7, 0xbc, 0xe6, 0x3c, 0x2c, 0x28, 0x59, 0x3d, 0x88, 0x6f, 0x17, 0x0e,
+        0x6e, 0xd1, 0xa2, 0x1f, 0xf3, 0x00, 0x2b, 0xc9, 0x4d, 0xd1, 0x3b, 0xbb, 0x0f, 0x7b, 0xf6,
+        0x70, 0xa8, 0x6d, 0x88, 0x63, 0x54, 0x03, 0x33, 0xdf, 0x7e, 0xb5, 0x07, 0x6c, 0xb6, 0x6c,
+        0x6d, 0x4d, 0x14, 0x7f, 0xd1, 0x59, 0xc6, 0xbe,
+    ];
+
+    /// A time within the validity period of the certificates below.
+    const NOW: i64 = 1_700_000_000;
+
+    // a P-256 chain of `Root`, `Int` and a leaf with several subject alternative names
+    const ROOT: [u8; 291] = [
+        0x30, 0x82, 0x01, 0x1f, 0x30, 0x81, 0xc6, 0xa0, 0x03, 0x02, 0x01, 0x02, 0x02, 0x01, 0x01,
+        0x30, 0x0a, 0x06, 0x08, 0x2a, 0x86, 0x48, 0xce, 0x3d, 0x04, 0x03, 0x02, 0x30, 0x0f, 0x31,
+        0x0d, 0x30, 0x0b, 0x06, 0x03, 0x55, 0x04, 0x03, 0x0c, 0x04, 0x52, 0x6f, 0x6f, 0x74, 0x30,
+        0x1e, 0x17, 0x0d, 0x32, 0x30, 0x30, 0x31, 0x30, 0x31, 0x30, 0x30, 0x30, 0x30, 0x30, 0x30,
+        0x5a, 0x17, 0x0d, 0x34, 0x39, 0x31, 0x32, 0x33, 0x31, 0x32, 0x33, 0x35, 0x39, 0x35, 0x39,
+        0x5a, 0x30, 0x0f, 0x31, 0x0d, 0x30, 0x0b, 0x06, 0x03, 0x55, 0x04, 0x03, 0x0c, 0x04, 0x52,
+        0x6f, 0x6f, 0x74, 0x30, 0x59, 0x30, 0x13, 0x06, 0x07, 0x2a, 0x86, 0x48, 0xce, 0x3d, 0x02,
+        0x01, 0x06, 0x08, 0x2a, 0x86, 0x48, 0xce, 0x3d, 0x03, 0x01, 0x07, 0x03, 0x42, 0x00, 0x04,
+        0x0a, 0xa9, 0x9c, 0xb1, 0x78, 0xb8, 0x00, 0xd2, 0xd4, 0x06, 0x18, 0xeb, 0xcf, 0x69, 0xdb,
+        0xac, 0x4a, 0x09, 0x75, 0x92, 0xc2, 0xfa, 0xc6, 0xcd, 0x81, 0xf2, 0x08, 0x4a, 0xb4, 0x52,
+        0x97, 0xc8, 0x06, 0xd0, 0xa2, 0x6e, 0xa7, 0xc8, 0xb0, 0x4f, 0x60, 0xf3, 0xa5, 0x7e, 0x09,
+        0xbb, 0x26, 0x10, 0x96, 0xc7, 0x24, 0x31, 0x48, 0x44, 0x7f, 0xed, 0x16, 0x16, 0xf4, 0xd4,
+        0x53, 0x0a, 0xe6, 0xd9, 0xa3, 0x13, 0x30, 0x11, 0x30, 0x0f, 0x06, 0x03, 0x55, 0x1d, 0x13,
+        0x01, 0x01, 0xff, 0x04, 0x05, 0x30, 0x03, 0x01, 0x01, 0xff, 0x30, 0x0a, 0x06, 0x08, 0x2a,
+        0x86, 0x48, 0xce, 0x3d, 0x04, 0x03, 0x02, 0x03, 0x48, 0x00, 0x30, 0x45, 0x02, 0x20, 0x28,
+        0x4c, 0x30, 0x35, 0x31, 0x7f, 0x97, 0x49, 0x41, 0xdd, 0xa6, 0x15, 0x88, 0x12, 0x05, 0x97,
+        0xf8, 0x65, 0xf3, 0xc8, 0xf2, 0x72, 0xfd, 0x70, 0x89, 0xe2, 0xc4, 0x32, 0x62, 0x4f, 0x93,
+        0x45, 0x02, 0x21, 0x00, 0xb4, 0x5f, 0x1d, 0x43, 0xd0, 0x21, 0xe1, 0xf3, 0x02, 0xfe, 0x24,
+        0x71, 0x95, 0x46, 0x55, 0x57, 0x24, 0x55, 0x4b, 0x79, 0x66, 0x2f, 0xa9, 0xbb, 0xba, 0x02,
+        0x64, 0x23, 0x09, 0x5d, 0xf8, 0x98,
+    ];
+
+    const INT: [u8; 291] = [
+        0x30, 0x82, 0x01, 0x1f, 0x30, 0x81, 0xc5, 0xa0, 0x03, 0x02, 0x01, 0x02, 0x02, 0x01, 0x02,
+        0x30, 0x0a, 0x06, 0x08, 0x2a, 0x86, 0x48, 0xce, 0x3d, 0x04, 0x03, 0x02, 0x30, 0x0f, 0x31,
+        0x0d, 0x30, 0x0b, 0x06, 0x03, 0x55, 0x04, 0x03, 0x0c, 0x04, 0x52, 0x6f, 0x6f, 0x74, 0x30,
+        0x1e, 0x17, 0x0d, 0x32, 0x30, 0x30, 0x31, 0x30, 0x31, 0x30, 0x30, 0x30, 0x30, 0x30, 0x30,
+        0x5a, 0x17, 0x0d, 0x34, 0x39, 0x31, 0x32, 0x33, 0x31, 0x32, 0x33, 0x35, 0x39, 0x35, 0x39,
+        0x5a, 0x30, 0x0e, 0x31, 0x0c, 0x30, 0x0a, 0x06, 0x03, 0x55, 0x04, 0x03, 0x0c, 0x03, 0x49,
+        0x6e, 0x74, 0x30, 0x59, 0x30, 0x13, 0x06, 0x07, 0x2a, 0x86, 0x48, 0xce, 0x3d, 0x02, 0x01,
+        0x06, 0x08, 0x2a, 0x86, 0x48, 0xce, 0x3d, 0x03, 0x01, 0x07, 0x03, 0x42, 0x00, 0x04, 0x79,
+        0x81, 0xc6, 0xa3, 0x34, 0xe4, 0xb6, 0xd7, 0xca, 0x37, 0x60, 0xef, 0xa3, 0x56, 0x30, 0xf7,
+        0x00, 0x66, 0x5b, 0x12, 0x3d, 0xa3, 0x61, 0xc8, 0x1d, 0x96, 0x76, 0x82, 0x1f, 0x9d, 0xc0,
+        0x6d, 0x02, 0xc2, 0x9b, 0x16, 0xcd, 0x7f, 0x2a, 0xa7, 0xfb, 0xb6, 0x08, 0x59, 0x58, 0x35,
+        0x58, 0x74, 0x62, 0x60, 0xad, 0x8e, 0xec, 0x39, 0x54, 0xcc, 0x06, 0xec, 0x6a, 0x7d, 0x4e,
+        0xdc, 0xb8, 0x9b, 0xa3, 0x13, 0x30, 0x11, 0x30, 0x0f, 0x06, 0x03, 0x55, 0x1d, 0x13, 0x01,
+        0x01, 0xff, 0x04, 0x05, 0x30, 0x03, 0x01, 0x01, 0xff, 0x30, 0x0a, 0x06, 0x08, 0x2a, 0x86,
+        0x48, 0xce, 0x3d, 0x04, 0x03, 0x02, 0x03, 0x49, 0x00, 0x30, 0x46, 0x02, 0x21, 0x00, 0xb9,
+        0x0c, 0xff, 0x38, 0x45, 0xbb, 0xa4, 0xfd, 0x76, 0xfe, 0x6f, 0x24, 0x9d, 0x94, 0x3b, 0x33,
+        0x30, 0x2b, 0xb1, 0x50, 0x01, 0x87, 0x18, 0x26, 0xd2, 0x0b, 0xc1, 0x2b, 0x9a, 0xc6, 0x8f,
+        0x05, 0x02, 0x21, 0x00, 0xe1, 0xed, 0x6b, 0x07, 0xdd, 0xb7, 0x6d, 0xc1, 0x4c, 0xdf, 0xe3,
+        0x72, 0x4c, 0x22, 0x49, 0x81, 0xba, 0xca, 0xac, 0x9a, 0xee, 0x74, 0x4a, 0xa4, 0x1c, 0x6b,
+        0x16, 0x6c, 0xcd, 0x36, 0x2c, 0xc4,
+    ];
+
+    // `Int` without the basic constraints extension
+    const INT_NOT_CA: [u8; 270] = [
+        0x30, 0x82, 0x01, 0x0a, 0x30, 0x81, 0xb0, 0xa0, 0x03, 0x02, 0x01, 0x02, 0x02, 0x01, 0x03,
+        0x30, 0x0a, 0x06, 0x08, 0x2a, 0x86, 0x48, 0xce, 0x3d, 0x04, 0x03, 0x02, 0x30, 0x0f, 0x31,
+        0x0d, 0x30, 0x0b, 0x06, 0x03, 0x55, 0x04, 0x03, 0x0c, 0x04, 0x52, 0x6f, 0x6f, 0x74, 0x30,
+        0x1e, 0x17, 0x0d, 0x32, 0x30, 0x30, 0x31, 0x30, 0x31, 0x30, 0x30, 0x30, 0x30, 0x30, 0x30,
+        0x5a, 0x17, 0x0d, 0x34, 0x39, 0x31, 0x32, 0x33, 0x31, 0x32, 0x33, 0x35, 0x39, 0x35, 0x39,
+        0x5a, 0x30, 0x0e, 0x31, 0x0c, 0x30, 0x0a, 0x06, 0x03, 0x55, 0x04, 0x03, 0x0c, 0x03, 0x49,
+        0x6e, 0x74, 0x30, 0x59, 0x30, 0x13, 0x06, 0x07, 0x2a, 0x86, 0x48, 0xce, 0x3d, 0x02, 0x01,
+        0x06, 0x08, 0x2a, 0x86, 0x48, 0xce, 0x3d, 0x03, 0x01, 0x07, 0x03, 0x42, 0x00, 0x04, 0x79,
+        0x81, 0xc6, 0xa3, 0x34, 0xe4, 0xb6, 0xd7, 0xca, 0x37, 0x60, 0xef, 0xa3, 0x56, 0x30, 0xf7,
+        0x00, 0x66, 0x5b, 0x12, 0x3d, 0xa3, 0x61, 0xc8, 0x1d, 0x96, 0x76, 0x82, 0x1f, 0x9d, 0xc0,
+        0x6d, 0x02, 0xc2, 0x9b, 0x16, 0xcd, 0x7f, 0x2a, 0xa7, 0xfb, 0xb6, 0x08, 0x59, 0x58, 0x35,
+        0x58, 0x74, 0x62, 0x60, 0xad, 0x8e, 0xec, 0x39, 0x54, 0xcc, 0x06, 0xec, 0x6a, 0x7d, 0x4e,
+        0xdc, 0xb8, 0x9b, 0x30, 0x0a, 0x06, 0x08, 0x2a, 0x86, 0x48, 0xce, 0x3d, 0x04, 0x03, 0x02,
+        0x03, 0x49, 0x00, 0x30, 0x46, 0x02, 0x21, 0x00, 0x9a, 0x40, 0xcf, 0x61, 0xe8, 0x58, 0xab,
+        0xa5, 0x47, 0x63, 0x0c, 0xa2, 0x58, 0xe3, 0xca, 0x38, 0x77, 0x2d, 0x53, 0x7e, 0x45, 0xd9,
+        0x2b, 0x52, 0xd3, 0x9d, 0xf8, 0xea, 0xb5, 0x60, 0x27, 0xe4, 0x02, 0x21, 0x00, 0xf7, 0xa5,
+        0x40, 0xed, 0xea, 0xd2, 0xf7, 0xca, 0xcd, 0xb1, 0x5a, 0x72, 0x52, 0x36, 0xef, 0xe0, 0x7d,
+        0x1b, 0x59, 0xbd, 0x49, 0x4c, 0x45, 0x21, 0xaa, 0xf3, 0xa6, 0x27, 0x1e, 0xf4, 0x48, 0xb0,
+    ];
+
+    const LEAF: [u8; 368] = [
+        0x30, 0x82, 0x01, 0x6c, 0x30, 0x82, 0x01, 0x12, 0xa0, 0x03, 0x02, 0x01, 0x02, 0x02, 0x01,
+        0x04, 0x30, 0x0a, 0x06, 0x08, 0x2a, 0x86, 0x48, 0xce, 0x3d, 0x04, 0x03, 0x02, 0x30, 0x0e,
+        0x31, 0x0c, 0x30, 0x0a, 0x06, 0x03, 0x55, 0x04, 0x03, 0x0c, 0x03, 0x49, 0x6e, 0x74, 0x30,
+        0x1e, 0x17, 0x0d, 0x32, 0x30, 0x30, 0x31, 0x30, 0x31, 0x30, 0x30, 0x30, 0x30, 0x30, 0x30,
+        0x5a, 0x17, 0x0d, 0x34, 0x39, 0x31, 0x32, 0x33, 0x31, 0x32, 0x33, 0x35, 0x39, 0x35, 0x39,
+        0x5a, 0x30, 0x1a, 0x31, 0x18, 0x30, 0x16, 0x06, 0x03, 0x55, 0x04, 0x03, 0x0c, 0x0f, 0x77,
+        0x77, 0x77, 0x2e, 0x65, 0x78, 0x61, 0x6d, 0x70, 0x6c, 0x65, 0x2e, 0x6e, 0x65, 0x74, 0x30,
+        0x59, 0x30, 0x13, 0x06, 0x07, 0x2a, 0x86, 0x48, 0xce, 0x3d, 0x02, 0x01, 0x06, 0x08, 0x2a,
+        0x86, 0x48, 0xce, 0x3d, 0x03, 0x01, 0x07, 0x03, 0x42, 0x00, 0x04, 0x81, 0x86, 0x8a, 0x8b,
+        0xc9, 0xa7, 0xcb, 0x0a, 0x7a, 0xdd, 0x02, 0x77, 0xdd, 0x07, 0x38, 0xf4, 0xd7, 0x4c, 0x61,
+        0x91, 0xcb, 0xa7, 0x35, 0x6c, 0x0d, 0x68, 0xb1, 0xae, 0xcb, 0xc9, 0xe4, 0x8b, 0x35, 0xe8,
+        0xad, 0xfd, 0x58, 0xbf, 0x6b, 0xd1, 0xa2, 0x95, 0x9a, 0x3b, 0xfe, 0x76, 0xf0, 0xc0, 0x04,
+        0xc6, 0xd6, 0x7d, 0x7c, 0xf8, 0x2d, 0xd3, 0xe8, 0x76, 0x40, 0xe6, 0x9c, 0x45, 0x43, 0x18,
+        0xa3, 0x55, 0x30, 0x53, 0x30, 0x51, 0x06, 0x03, 0x55, 0x1d, 0x11, 0x04, 0x4a, 0x30, 0x48,
+        0x82, 0x0d, 0x2a, 0x2e, 0x65, 0x78, 0x61, 0x6d, 0x70, 0x6c, 0x65, 0x2e, 0x63, 0x6f, 0x6d,
+        0x82, 0x0b, 0x65, 0x78, 0x61, 0x6d, 0x70, 0x6c, 0x65, 0x2e, 0x6f, 0x72, 0x67, 0x82, 0x0c,
+        0x31, 0x39, 0x38, 0x2e, 0x35, 0x31, 0x2e, 0x31, 0x30, 0x30, 0x2e, 0x31, 0x87, 0x04, 0xc0,
+        0x00, 0x02, 0x01, 0x87, 0x10, 0x20, 0x01, 0x0d, 0xb8, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x01, 0x87, 0x04, 0x61, 0x62, 0x63, 0x64, 0x30, 0x0a, 0x06,
+        0x08, 0x2a, 0x86, 0x48, 0xce, 0x3d, 0x04, 0x03, 0x02, 0x03, 0x48, 0x00, 0x30, 0x45, 0x02,
+        0x20, 0x25, 0x8b, 0x54, 0x9d, 0x0d, 0xdd, 0xe1, 0x47, 0x82, 0xd0, 0xc5, 0x05, 0xe2, 0x29,
+        0xff, 0xda, 0x4c, 0x56, 0x5c, 0xbb, 0xbb, 0xf2, 0xed, 0xe5, 0x57, 0xbc, 0x16, 0xed, 0x06,
+        0x55, 0x9d, 0x52, 0x02, 0x21, 0x00, 0xcb, 0xc7, 0x4c, 0x10, 0x0c, 0xe5, 0x02, 0xf2, 0xde,
+        0x2f, 0xff, 0x92, 0x09, 0xfa, 0x08, 0xf9, 0x3b, 0x24, 0x9c, 0x28, 0xcd, 0x37, 0x43, 0xc4,
+        0x02, 0xc1, 0x32, 0x09, 0xcc, 0x29, 0x22, 0xec,
+    ];
+
+    // CA certificates for `Int`, each issued by `Int` with the same key
+    const LOOP: [[u8; 289]; 5] = [
+        [
+            0x30, 0x82, 0x01, 0x1d, 0x30, 0x81, 0xc4, 0xa0, 0x03, 0x02, 0x01, 0x02, 0x02, 0x01,
+            0x0b, 0x30, 0x0a, 0x06, 0x08, 0x2a, 0x86, 0x48, 0xce, 0x3d, 0x04, 0x03, 0x02, 0x30,
+            0x0e, 0x31, 0x0c, 0x30, 0x0a, 0x06, 0x03, 0x55, 0x04, 0x03, 0x0c, 0x03, 0x49, 0x6e,
+            0x74, 0x30, 0x1e, 0x17, 0x0d, 0x32, 0x30, 0x30, 0x31, 0x30, 0x31, 0x30, 0x30, 0x30,
+            0x30, 0x30, 0x30, 0x5a, 0x17, 0x0d, 0x34, 0x39, 0x31, 0x32, 0x33, 0x31, 0x32, 0x33,
+            0x35, 0x39, 0x35, 0x39, 0x5a, 0x30, 0x0e, 0x31, 0x0c, 0x30, 0x0a, 0x06, 0x03, 0x55,
+            0x04, 0x03, 0x0c, 0x03, 0x49, 0x6e, 0x74, 0x30, 0x59, 0x30, 0x13, 0x06, 0x07, 0x2a,
+            0x86, 0x48, 0xce, 0x3d, 0x02, 0x01, 0x06, 0x08, 0x2a, 0x86, 0x48, 0xce, 0x3d, 0x03,
+            0x01, 0x07, 0x03, 0x42, 0x00, 0x04, 0x79, 0x81, 0xc6, 0xa3, 0x34, 0xe4, 0xb6, 0xd7,
+            0xca, 0x37, 0x60, 0xef, 0xa3, 0x56, 0x30, 0xf7, 0x00, 0x66, 0x5b, 0x12, 0x3d, 0xa3,
+            0x61, 0xc8, 0x1d, 0x96, 0x76, 0x82, 0x1f, 0x9d, 0xc0, 0x6d, 0x02, 0xc2, 0x9b, 0x16,
+            0xcd, 0x7f, 0x2a, 0xa7, 0xfb, 0xb6, 0x08, 0x59, 0x58, 0x35, 0x58, 0x74, 0x62, 0x60,
+            0xad, 0x8e, 0xec, 0x39, 0x54, 0xcc, 0x06, 0xec, 0x6a, 0x7d, 0x4e, 0xdc, 0xb8, 0x9b,
+            0xa3, 0x13, 0x30, 0x11, 0x30, 0x0f, 0x06, 0x03, 0x55, 0x1d, 0x13, 0x01, 0x01, 0xff,
+            0x04, 0x05, 0x30, 0x03, 0x01, 0x01, 0xff, 0x30, 0x0a, 0x06, 0x08, 0x2a, 0x86, 0x48,
+            0xce, 0x3d, 0x04, 0x03, 0x02, 0x03, 0x48, 0x00, 0x30, 0x45, 0x02, 0x20, 0x2f, 0x07,
+            0xf3, 0x82, 0x8f, 0x4b, 0xa6, 0x13, 0x72, 0xa1, 0x7c, 0xb5, 0xa9, 0x4e, 0xec, 0x4c,
+            0xfa, 0xdb, 0xbb, 0x10, 0x89, 0xc1, 0x48, 0x8c, 0x11, 0x2b, 0x0b, 0x48, 0x13, 0x50,
+            0xa0, 0x67, 0x02, 0x21, 0x00, 0xc7, 0x8f, 0x75, 0xc5, 0x8d, 0x2e, 0x40, 0xe6, 0xfa,
+            0x91, 0x8a, 0x96, 0xf0, 0xe3, 0x25, 0x8c, 0x27, 0x62, 0x56, 0x94, 0xe7, 0x6b, 0x54,
+            0x1e, 0x0a, 0x11, 0xc5, 0xe0, 0xad, 0xec, 0x3e, 0x40,
+        ],
+        [
+            0x30, 0x82, 0x01, 0x1d, 0x30, 0x81, 0xc4, 0xa0, 0x03, 0x02, 0x01, 0x02, 0x02, 0x01,
+            0x0c, 0x30, 0x0a, 0x06, 0x08, 0x2a, 0x86, 0x48, 0xce, 0x3d, 0x04, 0x03, 0x02, 0x30,
+            0x0e, 0x31, 0x0c, 0x30, 0x0a, 0x06, 0x03, 0x55, 0x04, 0x03, 0x0c, 0x03, 0x49, 0x6e,
+            0x74, 0x30, 0x1e, 0x17, 0x0d, 0x32, 0x30, 0x30, 0x31, 0x30, 0x31, 0x30, 0x30, 0x30,
+            0x30, 0x30, 0x30, 0x5a, 0x17, 0x0d, 0x34, 0x39, 0x31, 0x32, 0x33, 0x31, 0x32, 0x33,
+            0x35, 0x39, 0x35, 0x39, 0x5a, 0x30, 0x0e, 0x31, 0x0c, 0x30, 0x0a, 0x06, 0x03, 0x55,
+            0x04, 0x03, 0x0c, 0x03, 0x49, 0x6e, 0x74, 0x30, 0x59, 0x30, 0x13, 0x06, 0x07, 0x2a,
+            0x86, 0x48, 0xce, 0x3d, 0x02, 0x01, 0x06, 0x08, 0x2a, 0x86, 0x48, 0xce, 0x3d, 0x03,
+            0x01, 0x07, 0x03, 0x42, 0x00, 0x04, 0x79, 0x81, 0xc6, 0xa3, 0x34, 0xe4, 0xb6, 0xd7,
+            0xca, 0x37, 0x60, 0xef, 0xa3, 0x56, 0x30, 0xf7, 0x00, 0x66, 0x5b, 0x12, 0x3d, 0xa3,
+            0x61, 0xc8, 0x1d, 0x96, 0x76, 0x82, 0x1f, 0x9d, 0xc0, 0x6d, 0x02, 0xc2, 0x9b, 0x16,
+            0xcd, 0x7f, 0x2a, 0xa7, 0xfb, 0xb6, 0x08, 0x59, 0x58, 0x35, 0x58, 0x74, 0x62, 0x60,
+            0xad, 0x8e, 0xec, 0x39, 0x54, 0xcc, 0x06, 0xec, 0x6a, 0x7d, 0x4e, 0xdc, 0xb8, 0x9b,
+            0xa3, 0x13, 0x30, 0x11, 0x30, 0x0f, 0x06, 0x03, 0x55, 0x1d, 0x13, 0x01, 0x01, 0xff,
+            0x04, 0x05, 0x30, 0x03, 0x01, 0x01, 0xff, 0x30, 0x0a, 0x06, 0x08, 0x2a, 0x86, 0x48,
+            0xce, 0x3d, 0x04, 0x03, 0x02, 0x03, 0x48, 0x00, 0x30, 0x45, 0x02, 0x21, 0x00, 0xe7,
+            0xbc, 0x48, 0x8b, 0x8a, 0xa4, 0xb2, 0x3e, 0x1f, 0xf6, 0xb7, 0xcf, 0xf2, 0x6e, 0x91,
+            0x4f, 0x87, 0x28, 0x78, 0x85, 0x2e, 0xd1, 0xf8, 0xd4, 0x8f, 0xba, 0x69, 0x43, 0xac,
+            0x77, 0xd8, 0xc4, 0x02, 0x20, 0x50, 0xf9, 0xff, 0x70, 0x8a, 0x12, 0x62, 0x8f, 0xa7,
+            0x43, 0x97, 0xee, 0x0a, 0xc5, 0xd7, 0x38, 0x26, 0x9e, 0x7c, 0xe8, 0xec, 0x64, 0x86,
+            0x63, 0x78, 0x14, 0xc1, 0x81, 0xa6, 0x9c, 0xa1, 0x88,
+        ],
+        [
+            0x30, 0x82, 0x01, 0x1d, 0x30, 0x81, 0xc4, 0xa0, 0x03, 0x02, 0x01, 0x02, 0x02, 0x01,
+            0x0d, 0x30, 0x0a, 0x06, 0x08, 0x2a, 0x86, 0x48, 0xce, 0x3d, 0x04, 0x03, 0x02, 0x30,
+            0x0e, 0x31, 0x0c, 0x30, 0x0a, 0x06, 0x03, 0x55, 0x04, 0x03, 0x0c, 0x03, 0x49, 0x6e,
+            0x74, 0x30, 0x1e, 0x17, 0x0d, 0x32, 0x30, 0x30, 0x31, 0x30, 0x31, 0x30, 0x30, 0x30,
+            0x30, 0x30, 0x30, 0x5a, 0x17, 0x0d, 0x34, 0x39, 0x31, 0x32, 0x33, 0x31, 0x32, 0x33,
+            0x35, 0x39, 0x35, 0x39, 0x5a, 0x30, 0x0e, 0x31, 0x0c, 0x30, 0x0a, 0x06, 0x03, 0x55,
+            0x04, 0x03, 0x0c, 0x03, 0x49, 0x6e, 0x74, 0x30, 0x59, 0x30, 0x13, 0x06, 0x07, 0x2a,
+            0x86, 0x48, 0xce, 0x3d, 0x02, 0x01, 0x06, 0x08, 0x2a, 0x86, 0x48, 0xce, 0x3d, 0x03,
+            0x01, 0x07, 0x03, 0x42, 0x00, 0x04, 0x79, 0x81, 0xc6, 0xa3, 0x34, 0xe4, 0xb6, 0xd7,
+            0xca, 0x37, 0x60, 0xef, 0xa3, 0x56, 0x30, 0xf7, 0x00, 0x66, 0x5b, 0x12, 0x3d, 0xa3,
+            0x61, 0xc8, 0x1d, 0x96, 0x76, 0x82, 0x1f, 0x9d, 0xc0, 0x6d, 0x02, 0xc2, 0x9b, 0x16,
+            0xcd, 0x7f, 0x2a, 0xa7, 0xfb, 0xb6, 0x08, 0x59, 0x58, 0x35, 0x58, 0x74, 0x62, 0x60,
+            0xad, 0x8e, 0xec, 0x39, 0x54, 0xcc, 0x06, 0xec, 0x6a, 0x7d, 0x4e, 0xdc, 0xb8, 0x9b,
+            0xa3, 0x13, 0x30, 0x11, 0x30, 0x0f, 0x06, 0x03, 0x55, 0x1d, 0x13, 0x01, 0x01, 0xff,
+            0x04, 0x05, 0x30, 0x03, 0x01, 0x01, 0xff, 0x30, 0x0a, 0x06, 0x08, 0x2a, 0x86, 0x48,
+            0xce, 0x3d, 0x04, 0x03, 0x02, 0x03, 0x48, 0x00, 0x30, 0x45, 0x02, 0x21, 0x00, 0xd7,
+            0x46, 0xf8, 0x70, 0x9e, 0x93, 0x78, 0x13, 0x49, 0x5d, 0x13, 0xfa, 0x51, 0x42, 0xbb,
+            0xae, 0x73, 0x97, 0x47, 0x36, 0x6f, 0x06, 0x7e, 0x1e, 0x77, 0xed, 0x8d, 0x72, 0x85,
+            0xac, 0xc8, 0xe0, 0x02, 0x20, 0x33, 0x28, 0x55, 0xef, 0x20, 0xa0, 0xb0, 0xa4, 0xcf,
+            0x3d, 0xf7, 0xdf, 0xca, 0xa7, 0x8d, 0xff, 0x18, 0xee, 0xdf, 0x73, 0x0d, 0x42, 0x02,
+            0x0a, 0x29, 0xc0, 0x76, 0xd8, 0x31, 0x92, 0xf0, 0x23,
+        ],
+        [
+            0x30, 0x82, 0x01, 0x1d, 0x30, 0x81, 0xc4, 0xa0, 0x03, 0x02, 0x01, 0x02, 0x02, 0x01,
+            0x0e, 0x30, 0x0a, 0x06, 0x08, 0x2a, 0x86, 0x48, 0xce, 0x3d, 0x04, 0x03, 0x02, 0x30,
+            0x0e, 0x31, 0x0c, 0x30, 0x0a, 0x06, 0x03, 0x55, 0x04, 0x03, 0x0c, 0x03, 0x49, 0x6e,
+            0x74, 0x30, 0x1e, 0x17, 0x0d, 0x32, 0x30, 0x30, 0x31, 0x30, 0x31, 0x30, 0x30, 0x30,
+            0x30, 0x30, 0x30, 0x5a, 0x17, 0x0d, 0x34, 0x39, 0x31, 0x32, 0x33, 0x31, 0x32, 0x33,
+            0x35, 0x39, 0x35, 0x39, 0x5a, 0x30, 0x0e, 0x31, 0x0c, 0x30, 0x0a, 0x06, 0x03, 0x55,
+            0x04, 0x03, 0x0c, 0x03, 0x49, 0x6e, 0x74, 0x30, 0x59, 0x30, 0x13, 0x06, 0x07, 0x2a,
+            0x86, 0x48, 0xce, 0x3d, 0x02, 0x01, 0x06, 0x08, 0x2a, 0x86, 0x48, 0xce, 0x3d, 0x03,
+            0x01, 0x07, 0x03, 0x42, 0x00, 0x04, 0x79, 0x81, 0xc6, 0xa3, 0x34, 0xe4, 0xb6, 0xd7,
+            0xca, 0x37, 0x60, 0xef, 0xa3, 0x56, 0x30, 0xf7, 0x00, 0x66, 0x5b, 0x12, 0x3d, 0xa3,
+            0x61, 0xc8, 0x1d, 0x96, 0x76, 0x82, 0x1f, 0x9d, 0xc0, 0x6d, 0x02, 0xc2, 0x9b, 0x16,
+            0xcd, 0x7f, 0x2a, 0xa7, 0xfb, 0xb6, 0x08, 0x59, 0x58, 0x35, 0x58, 0x74, 0x62, 0x60,
+            0xad, 0x8e, 0xec, 0x39, 0x54, 0xcc, 0x06, 0xec, 0x6a, 0x7d, 0x4e, 0xdc, 0xb8, 0x9b,
+            0xa3, 0x13, 0x30, 0x11, 0x30, 0x0f, 0x06, 0x03, 0x55, 0x1d, 0x13, 0x01, 0x01, 0xff,
+            0x04, 0x05, 0x30, 0x03, 0x01, 0x01, 0xff, 0x30, 0x0a, 0x06, 0x08, 0x2a, 0x86, 0x48,
+            0xce, 0x3d, 0x04, 0x03, 0x02, 0x03, 0x48, 0x00, 0x30, 0x45, 0x02, 0x21, 0x00, 0xf5,
+            0x2e, 0xf8, 0xd8, 0xe7, 0xd5, 0x55, 0xa6, 0xf6, 0xc7, 0xd2, 0x58, 0x43, 0x1e, 0xf4,
+            0x62, 0x52, 0x40, 0xa2, 0x4e, 0xc8, 0xae, 0x7e, 0xcd, 0x79, 0x33, 0xf1, 0xc1, 0x44,
+            0x0d, 0x2a, 0xfa, 0x02, 0x20, 0x34, 0xb6, 0x1e, 0xe9, 0xe5, 0x43, 0xe6, 0x71, 0xda,
+            0xcf, 0x98, 0x7b, 0x28, 0x39, 0x89, 0x0a, 0x2f, 0x3d, 0x9d, 0x5c, 0xc4, 0xbf, 0x2d,
+            0xd5, 0xb2, 0x5c, 0xb5, 0x63, 0xa3, 0x8d, 0xc9, 0x9e,
+        ],
+        [
+            0x30, 0x82, 0x01, 0x1d, 0x30, 0x81, 0xc4, 0xa0, 0x03, 0x02, 0x01, 0x02, 0x02, 0x01,
+            0x0f, 0x30, 0x0a, 0x06, 0x08, 0x2a, 0x86, 0x48, 0xce, 0x3d, 0x04, 0x03, 0x02, 0x30,
+            0x0e, 0x31, 0x0c, 0x30, 0x0a, 0x06, 0x03, 0x55, 0x04, 0x03, 0x0c, 0x03, 0x49, 0x6e,
+            0x74, 0x30, 0x1e, 0x17, 0x0d, 0x32, 0x30, 0x30, 0x31, 0x30, 0x31, 0x30, 0x30, 0x30,
+            0x30, 0x30, 0x30, 0x5a, 0x17, 0x0d, 0x34, 0x39, 0x31, 0x32, 0x33, 0x31, 0x32, 0x33,
+            0x35, 0x39, 0x35, 0x39, 0x5a, 0x30, 0x0e, 0x31, 0x0c, 0x30, 0x0a, 0x06, 0x03, 0x55,
+            0x04, 0x03, 0x0c, 0x03, 0x49, 0x6e, 0x74, 0x30, 0x59, 0x30, 0x13, 0x06, 0x07, 0x2a,
+            0x86, 0x48, 0xce, 0x3d, 0x02, 0x01, 0x06, 0x08, 0x2a, 0x86, 0x48, 0xce, 0x3d, 0x03,
+            0x01, 0x07, 0x03, 0x42, 0x00, 0x04, 0x79, 0x81, 0xc6, 0xa3, 0x34, 0xe4, 0xb6, 0xd7,
+            0xca, 0x37, 0x60, 0xef, 0xa3, 0x56, 0x30, 0xf7, 0x00, 0x66, 0x5b, 0x12, 0x3d, 0xa3,
+            0x61, 0xc8, 0x1d, 0x96, 0x76, 0x82, 0x1f, 0x9d, 0xc0, 0x6d, 0x02, 0xc2, 0x9b, 0x16,
+            0xcd, 0x7f, 0x2a, 0xa7, 0xfb, 0xb6, 0x08, 0x59, 0x58, 0x35, 0x58, 0x74, 0x62, 0x60,
+            0xad, 0x8e, 0xec, 0x39, 0x54, 0xcc, 0x06, 0xec, 0x6a, 0x7d, 0x4e, 0xdc, 0xb8, 0x9b,
+            0xa3, 0x13, 0x30, 0x11, 0x30, 0x0f, 0x06, 0x03, 0x55, 0x1d, 0x13, 0x01, 0x01, 0xff,
+            0x04, 0x05, 0x30, 0x03, 0x01, 0x01, 0xff, 0x30, 0x0a, 0x06, 0x08, 0x2a, 0x86, 0x48,
+            0xce, 0x3d, 0x04, 0x03, 0x02, 0x03, 0x48, 0x00, 0x30, 0x45, 0x02, 0x21, 0x00, 0xb8,
+            0xb1, 0x40, 0x53, 0x1c, 0x44, 0x4b, 0x5b, 0x16, 0x75, 0x50, 0x4c, 0xbe, 0x59, 0x02,
+            0x5d, 0x5f, 0xff, 0x5f, 0xa0, 0x49, 0x45, 0xd8, 0xd9, 0xf0, 0x70, 0x42, 0xd8, 0x15,
+            0xc6, 0xf6, 0xd2, 0x02, 0x20, 0x4d, 0xcf, 0x16, 0xa1, 0x4c, 0xcc, 0x1d, 0xe0, 0xfd,
+            0x2d, 0xaf, 0x74, 0x02, 0xf1, 0x52, 0x23, 0xe3, 0x9b, 0x62, 0x77, 0xa5, 0xf2, 0x68,
+            0x2c, 0x31, 0xfc, 0xb3, 0xcf, 0xf6, 0xec, 0x2e, 0x9d,
+        ],
+    ];
+
+    #[test]
+    fn parse_p256() {
+        let cert = Certificate::parse(&P256_LEAF).unwrap();
+        assert!(matches!(
+            cert.public_key,
+            PublicKey::Ec { curve: NamedGroup::Secp256r1, point } if point.len() == 65
+        ));
+        assert!(cert.sig_alg == SigAlg::Ecdsa(SigHash::Sha256));
+        // 2024-01-01 to 2034-01-01
+        assert_eq!(cert.validity.not_before, 1_704_067_200);
+        assert_eq!(cert.validity.not_after, 2_019_686_400);
+        assert!(cert
+            .basic_constraints
+            .is_some_and(|constraints| !constraints.ca));
+        assert!(cert.subject_alt_names.is_some());
+    }
+
+    #[test]
+    fn parse_rsa() {
+        let cert = Certificate::parse(&RSA_LEAF).unwrap();
+        assert!(matches!(
+            cert.public_key,
+            PublicKey::Rsa { modulus, exponent: [0x01, 0x00, 0x01] } if modulus.len() == 256
+        ));
+        assert!(cert.sig_alg == SigAlg::RsaPkcs1(SigHash::Sha256));
+        assert!(cert.subject_alt_names.is_some());
+    }
+
+    #[test]
+    fn parse_malformed() {
+        for len in 0..P256_LEAF.len() {
+            assert!(Certificate::parse(&P256_LEAF[..len]).is_err());
+        }
+        for len in 0..RSA_LEAF.len() {
+            assert!(Certificate::parse(&RSA_LEAF[..len]).is_err());
+        }
+        // nothing may follow the certificate
+        let trailing = [P256_LEAF.as_slice(), &[0]].concat();
+        assert_eq!(Certificate::parse(&trailing).err(), Some(Alert::BadCert));
+        let two_certs = [P256_LEAF.as_slice(), &RSA_LEAF].concat();
+        assert_eq!(Certificate::parse(&two_certs).err(), Some(Alert::BadCert));
+    }
+
+    #[test]
+    fn chain() {
+        let anchors = [Box::from(ROOT.as_slice())];
+        assert!(verify_chain(&[&LEAF, &INT], &anchors, NOW).is_ok());
+        assert_eq!(
+            verify_chain(&[&LEAF], &anchors, NOW).err(),
+            Some(Alert::UnknownCa)
+        );
+        // the end-entity certificate may be trusted directly
+        assert!(verify_chain(&[&LEAF], &[Box::from(LEAF.as_slice())], NOW).is_ok());
+        // only CAs may issue certificates
+        assert_eq!(
+            verify_chain(&[&LEAF, &INT_NOT_CA], &anchors, NOW).err(),
+            Some(Alert::BadCert)
+        );
+        // the certificates are valid from 2020 through 2049
+        assert_eq!(
+            verify_chain(&[&LEAF, &INT], &anchors, 2_524_608_000).err(),
+            Some(Alert::CertExpired)
+        );
+    }
+
+    #[test]
+    fn self_issued_loop() {
+        let mut certs = vec![LEAF.as_slice()];
+        certs.extend(LOOP.iter().map(<[u8; _]>::as_slice));
+        assert!(verify_chain(&certs, &[Box::from(ROOT.as_slice())], NOW).is_err());
+
+        let leaf = Certificate::parse(&LEAF).unwrap();
+        let intermediates = LOOP
+            .iter()
+            .map(|cert| Certificate::parse(cert).unwrap())
+            .collect::<Vec<_>>();
+        let anchors = [Certificate::parse(&ROOT).unwrap()];
+
+        // every certificate issues every other one
+        let mut builder = PathBuilder::new(&intermediates, &anchors, NOW);
+        assert_eq!(builder.build(&leaf, 0), Err(Alert::BadCert));
+        assert_eq!(builder.sig_checks, PathBuilder::MAX_SIG_CHECKS);
+
+        // a certificate can't issue itself in the same path
+        let mut builder = PathBuilder::new(&intermediates[..1], &anchors, NOW);
+        assert!(builder.build(&leaf, 0).is_err());
+        assert_eq!(builder.sig_checks, 1);
+    }
+
+    #[test]
+    fn wildcards() {
+        assert!(dns_name_matches(b"*.example.com", b"a.example.com"));
+        assert!(!dns_name_matches(b"*.example.com", b"a.b.example.com"));
+        assert!(!dns_name_matches(b"*.example.com", b"example.com"));
+        assert!(!dns_name_matches(b"*.com", b"example.com"));
+        assert!(!dns_name_matches(b"w*.example.com", b"www.example.com"));
+        assert!(!dns_name_matches(b"*.*.example.com", b"a.b.example.com"));
+    }
+
+    #[test]
+    fn dns_names() {
+        assert!(dns_name_matches(b"www.example.com", b"www.example.com"));
+        assert!(dns_name_matches(b"WWW.Example.COM", b"www.example.com"));
+        assert!(dns_name_matches(b"*.EXAMPLE.com", b"A.example.COM"));
+        assert!(dns_name_matches(b"www.example.com", b"www.example.com."));
+        assert!(!dns_name_matches(b"www.example.com", b"www.example.com.."));
+        assert!(!dns_name_matches(b"www.example.com", b"example.com"));
+        assert!(!dns_name_matches(b"www.example.com", b""));
+        assert!(!dns_name_matches(b"", b""));
+    }
+
+    #[test]
+    fn verify_name() {
+        // the leaf is for `*.example.com`, `example.org`, `198.51.100.1`, 192.0.2.1, 2001:db8::1
+        // and 97.98.99.100
+        let cert = Certificate::parse(&LEAF).unwrap();
+        assert_eq!(cert.verify_name(b"a.example.com"), Ok(()));
+        assert_eq!(cert.verify_name(b"A.EXAMPLE.COM."), Ok(()));
+        assert_eq!(cert.verify_name(b"example.org"), Ok(()));
+        assert_eq!(cert.verify_name(b"192.0.2.1"), Ok(()));
+        assert_eq!(cert.verify_name(b"2001:db8::1"), Ok(()));
+        assert_eq!(cert.verify_name(b"2001:DB8:0:0:0:0:0:1"), Ok(()));
+        assert_eq!(cert.verify_name(b"a.b.example.com"), Err(Alert::BadCert));
+        assert_eq!(cert.verify_name(b"example.com"), Err(Alert::BadCert));
+        assert_eq!(cert.verify_name(b"192.0.2.2"), Err(Alert::BadCert));
+        assert_eq!(cert.verify_name(b"2001:db8::2"), Err(Alert::BadCert));
+        // an IPv4 address doesn't match the same octets in an IPv6 address
+        assert_eq!(cert.verify_name(b"::ffff:192.0.2.1"), Err(Alert::BadCert));
+        assert_eq!(cert.verify_name(b""), Err(Alert::BadCert));
+
+        // a DNS name never matches an IP address, and an IP address never matches a DNS name
+        assert_eq!(cert.verify_name(b"198.51.100.1"), Err(Alert::BadCert));
+        assert_eq!(cert.verify_name(b"97.98.99.100"), Ok(()));
+        assert_eq!(cert.verify_name(b"abcd"), Err(Alert::BadCert));
+        // the common name is never used
+        assert_eq!(cert.verify_name(b"www.example.net"), Err(Alert::BadCert));
+
+        // the root has no subject alternative names
+        let cert = Certificate::parse(&ROOT).unwrap();
+        assert_eq!(cert.verify_name(b"Root"), Err(Alert::BadCert));
+    }
+}